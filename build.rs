@@ -0,0 +1,24 @@
+// Puts the Swagger UI and ReDoc bundles of `assets_mode="embedded"` in OUT_DIR, where
+// src/openapi/assets.rs includes them. The build never goes to the network: the bundles are
+// copied from src/openapi/bundles, written by scripts/fetch_docs_assets.py, and left empty when
+// that has not been run.
+use std::{env, fs, path::Path};
+
+const BUNDLES: [&str; 3] = [
+    "swagger-ui.css",
+    "swagger-ui-bundle.js",
+    "redoc.standalone.js",
+];
+
+fn main() {
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let vendored = Path::new("src/openapi/bundles");
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed={}", vendored.display());
+
+    for name in BUNDLES {
+        // assets_mode="embedded" reports the bundles missing
+        let content = fs::read(vendored.join(name)).unwrap_or_default();
+        fs::write(Path::new(&out_dir).join(name), content).unwrap();
+    }
+}
@@ -3,7 +3,7 @@ from __future__ import annotations
 
 import asyncio
 from dataclasses import dataclass
-from typing import Any, Callable, List, TypeVar
+from typing import Any, Callable, Dict, List, Optional, TypeVar
 
 import orjson
 import psutil
@@ -11,14 +11,15 @@ from typing_extensions import Annotated, Doc
 
 from hypern.args_parser import ArgsConfig
 from hypern.datastructures import Contact, HTTPMethod, Info, License
-from hypern.hypern import DatabaseConfig, FunctionInfo, MiddlewareConfig, Router, Server, WebsocketRouter
+from hypern.hypern import CsrfConfig, DatabaseConfig, FunctionInfo, IpFilterConfig, MiddlewareConfig, ResponseCache, Router, Server, ServerHandle, SocketHeld, TestClient, WebsocketRouter
 from hypern.hypern import Route as InternalRoute
 from hypern.logging import logger
 from hypern.middleware import Middleware
-from hypern.openapi import SchemaGenerator, SwaggerUI
+from hypern.openapi import ReDocUI, SchemaGenerator, SwaggerUI
 from hypern.processpool import run_processes
 from hypern.response import HTMLResponse, JSONResponse
 from hypern.routing import Route
+from hypern.routing.dispatcher import get_parameter_names
 from hypern.scheduler import Scheduler
 from hypern.ws import WebsocketRoute
 
@@ -170,6 +171,45 @@ class Hypern:
             """
             ),
         ] = "/docs",
+        redoc_url: Annotated[
+            str | None,
+            Doc(
+                """
+                The path to the alternative API documentation rendered by ReDoc.
+
+                Disabled by default. If `openapi_url` is set to `None`, this will be automatically disabled.
+            """
+            ),
+        ] = None,
+        docs_assets_mode: Annotated[
+            str,
+            Doc(
+                """
+                Where the documentation pages load their JS/CSS bundles from.
+
+                * `cdn`: jsdelivr, the default.
+                * `static_url`: `docs_assets_url`, a copy of the bundles hosted by you.
+                * `embedded`: the bundles shipped in the package, served by the server itself.
+                    Use it for air-gapped deployments.
+            """
+            ),
+        ] = "cdn",
+        docs_assets_url: Annotated[
+            str | None,
+            Doc(
+                """
+                The base URL of the bundles when `docs_assets_mode` is `static_url`.
+            """
+            ),
+        ] = None,
+        swagger_oauth2_redirect_url: Annotated[
+            str | None,
+            Doc(
+                """
+                The path of the OAuth2 redirect page of Swagger UI, served when set.
+            """
+            ),
+        ] = None,
         license_info: Annotated[
             License | None,
             Doc(
@@ -238,6 +278,7 @@ class Hypern:
     ) -> None:
         super().__init__(*args, **kwargs)
         self.router = Router(path="/")
+        self.server = None
         self.websocket_router = WebsocketRouter(path="/")
         self.scheduler = scheduler
         self.injectables = default_injectables or {}
@@ -247,8 +288,36 @@ class Hypern:
         self.args = ArgsConfig()
         self.start_up_handler = None
         self.shutdown_handler = None
+        self.not_found_handler = None
+        self.method_not_allowed_handler = None
         self.auto_compression = auto_compression
+        self.compression_config = None
         self.database_config = database_config
+        self.health_config = None
+        self.readiness_checks = []
+        self.metrics_path = None
+        self.openapi_config = None
+        self.openapi_docs = bool(openapi_url and docs_url)
+        self.openapi_components = {"schemas": {}, "securitySchemes": {}}
+        self.title = title
+        self.version = version
+        self.tracing_endpoint = None
+        self.logging_config = None
+        self.http_options = None
+        self.session_config = None
+        self.rate_limit_config = None
+        self.security_headers_config = None
+        self.static_dirs = []
+        self.docs_assets = False
+        self.max_form_fields = None
+        self.upload_spool_size = None
+        self.max_upload_size = None
+        self.csrf_config = None
+        self.ip_filter_config = None
+        self.trusted_proxies = None
+        self.auto_etag_max_size = None
+        self.response_cache = None
+        self.debug_headers = False
         self.thread_config = ThreadConfigurator().get_config()
 
         for route in routes or []:
@@ -269,6 +338,10 @@ class Hypern:
                 ),
                 openapi_url=openapi_url,
                 docs_url=docs_url,
+                redoc_url=redoc_url,
+                assets_mode=docs_assets_mode,
+                assets_url=docs_assets_url,
+                oauth2_redirect_url=swagger_oauth2_redirect_url,
             )
 
     def __add_openapi(
@@ -276,6 +349,10 @@ class Hypern:
         info: Info,
         openapi_url: str,
         docs_url: str,
+        redoc_url: str | None = None,
+        assets_mode: str = "cdn",
+        assets_url: str | None = None,
+        oauth2_redirect_url: str | None = None,
     ):
         """
         Adds OpenAPI schema and documentation routes to the application.
@@ -284,6 +361,10 @@ class Hypern:
             info (Info): An instance of the Info class containing metadata about the API.
             openapi_url (str): The URL path where the OpenAPI schema will be served.
             docs_url (str): The URL path where the Swagger UI documentation will be served.
+            redoc_url (str | None): The URL path where the ReDoc documentation will be served.
+            assets_mode (str): `cdn`, `static_url` or `embedded`, where the pages load their bundles from.
+            assets_url (str | None): The base URL of the bundles for `static_url`.
+            oauth2_redirect_url (str | None): The URL path of the Swagger UI OAuth2 redirect page.
 
         The method defines two internal functions:
             - schema: Generates and returns the OpenAPI schema as a JSON response.
@@ -300,18 +381,40 @@ class Hypern:
                     "components": {"securitySchemes": {}},
                 }
             )
+            for name, component in self.openapi_components["schemas"].items():
+                schemas.add_component(name, component)
+            for name, scheme in self.openapi_components["securitySchemes"].items():
+                schemas.add_security_scheme(name, scheme)
             return JSONResponse(content=orjson.dumps(schemas.get_schema(self)))
 
+        # built here so an invalid assets_mode fails at startup
+        swagger = SwaggerUI(
+            title="Swagger",
+            openapi_url=openapi_url,
+            oauth2_redirect_url=oauth2_redirect_url,
+            assets_mode=assets_mode,
+            assets_url=assets_url,
+        )
+        redoc = ReDocUI(title="ReDoc", openapi_url=openapi_url, assets_mode=assets_mode, assets_url=assets_url)
+
         def template_render(*args, **kwargs):
-            swagger = SwaggerUI(
-                title="Swagger",
-                openapi_url=openapi_url,
-            )
-            template = swagger.get_html_content()
-            return HTMLResponse(template)
+            return HTMLResponse(swagger.get_html_content())
+
+        def redoc_render(*args, **kwargs):
+            return HTMLResponse(redoc.get_html_content())
+
+        def oauth2_redirect(*args, **kwargs):
+            return HTMLResponse(swagger.get_oauth2_redirect_html())
+
+        if assets_mode == "embedded":
+            self.docs_assets = True
 
-        self.add_route(HTTPMethod.GET, openapi_url, schema)
-        self.add_route(HTTPMethod.GET, docs_url, template_render)
+        self.add_route(HTTPMethod.GET, openapi_url, schema, include_in_schema=False)
+        self.add_route(HTTPMethod.GET, docs_url, template_render, include_in_schema=False)
+        if redoc_url:
+            self.add_route(HTTPMethod.GET, redoc_url, redoc_render, include_in_schema=False)
+        if oauth2_redirect_url:
+            self.add_route(HTTPMethod.GET, oauth2_redirect_url, oauth2_redirect, include_in_schema=False)
 
     def add_response_header(self, key: str, value: str):
         """
@@ -414,6 +517,447 @@ class Hypern:
         """
         self.database_config = config
 
+    def set_health_check(self, path: str):
+        """
+        Serves the readiness probe alone at `path`, the same as `enable_health(liveness=None, readiness=path)`.
+        Prefer `enable_health`, which also serves a liveness probe.
+
+        Args:
+            path (str): The URL path of the readiness probe (e.g. `/healthz`).
+
+        Raises:
+            ValueError: If the path does not start with "/".
+        """
+        self.enable_health(liveness=None, readiness=path)
+
+    def enable_health(self, liveness: str | None = "/healthz", readiness: str | None = "/readyz"):
+        """
+        Serves liveness and readiness probes directly by the server, without running Python per probe.
+
+        Args:
+            liveness (str | None): Always answers 200 once the server is running. None leaves it out.
+            readiness (str | None): Answers 503 until startup finished, then reports the database,
+                the redis response cache and the readiness checks. None leaves it out.
+
+        Raises:
+            ValueError: If a path does not start with "/", or both are the same path.
+        """
+        for path in (liveness, readiness):
+            if path is not None and not path.startswith("/"):
+                raise ValueError(f"Health probe path must start with '/': {path!r}")
+        if liveness is not None and liveness == readiness:
+            raise ValueError(f"Liveness and readiness paths must differ: {liveness!r}")
+        self.health_config = {"liveness": liveness, "readiness": readiness}
+
+    def add_readiness_check(self, name: str, check: Callable[[], Any], interval: float = 5.0):
+        """
+        Registers a readiness check, polled in the background. Probes read its last result.
+
+        Args:
+            name (str): The name reported in the readiness body.
+            check (Callable[[], Any]): A function or coroutine function, ready while it returns a truthy value.
+            interval (float): Seconds between two polls.
+        """
+        self.readiness_checks.append({"name": name, "check": check, "interval": interval})
+
+    def set_metrics_path(self, path: str):
+        """
+        Exposes request metrics in the Prometheus text format, served directly by the server.
+
+        Args:
+            path (str): The URL path of the metrics endpoint (e.g. `/metrics`).
+
+        Raises:
+            ValueError: If the path does not start with "/".
+        """
+        if not path.startswith("/"):
+            raise ValueError(f"Metrics path must start with '/': {path!r}")
+        self.metrics_path = path
+
+    def set_compression_config(
+        self,
+        algorithms: List[str] | None = None,
+        level: int | Dict[str, int] | None = None,
+        min_size: int = 1024,
+        content_types: List[str] | None = None,
+    ):
+        """
+        Chooses how responses are compressed while `auto_compression` is enabled.
+
+        Args:
+            algorithms (List[str] | None): The encodings offered to clients in order of preference, among `br`, `gzip`,
+                `zstd` and `deflate`. `["br", "gzip", "zstd"]` when None.
+            level (int | Dict[str, int] | None): The compression level of every algorithm, or a level per algorithm such as
+                `{"br": 5, "gzip": 6}`. Up to 9 for gzip and deflate, 11 for br and 22 for zstd.
+                The default level of each algorithm when None.
+            min_size (int): Smaller bodies are sent uncompressed.
+            content_types (List[str] | None): The content types that are compressed, `type/*` matches a whole type.
+                `["application/json", "text/*"]` when None.
+        """
+        self.compression_config = {
+            "algorithms": list(algorithms) if algorithms is not None else None,
+            "level": level,
+            "min_size": min_size,
+            "content_types": list(content_types) if content_types is not None else None,
+        }
+
+    def enable_metrics(self, path: str = "/metrics"):
+        """
+        Exposes request, memory pool and database session metrics in the Prometheus text format,
+        served directly by the server and left out of the metrics it reports.
+
+        Args:
+            path (str): The URL path of the metrics endpoint.
+        """
+        self.set_metrics_path(path)
+
+    def enable_openapi(
+        self,
+        path: str = "/openapi.json",
+        title: str | None = None,
+        version: str | None = None,
+        servers: List[Dict[str, Any]] | None = None,
+    ):
+        """
+        Serves the OpenAPI document of the routes directly by the server, built once when it starts.
+
+        Operations come from the YAML after `---` in handler docstrings, path parameters and methods
+        from the routes. Routes with `include_in_schema=False` are left out.
+
+        Args:
+            path (str): The URL path of the document, it replaces the python one at the same path.
+            title (str | None): The title of the API, the application title when None.
+            version (str | None): The version of the API, the application version when None.
+            servers (List[Dict[str, Any]] | None): The `servers` of the document, e.g. `[{"url": "https://api.example.com"}]`.
+
+        Raises:
+            ValueError: If the path does not start with "/".
+        """
+        if not path.startswith("/"):
+            raise ValueError(f"OpenAPI path must start with '/': {path!r}")
+        self.openapi_config = {
+            "path": path,
+            "title": title or self.title,
+            "version": version or self.version,
+            "servers": servers,
+        }
+
+    def add_schema_component(self, name: str, schema: Dict[str, Any]):
+        """
+        Registers a model under `components.schemas` of the OpenAPI documents.
+
+        Docstrings reference it with `$ref: '#/components/schemas/<name>'`, or just `$ref: <name>`.
+
+        Args:
+            name (str): The name of the model.
+            schema (Dict[str, Any]): The JSON schema of the model.
+        """
+        self.openapi_components["schemas"][name] = schema
+
+    def add_security_scheme(self, name: str, scheme: Dict[str, Any]):
+        """
+        Registers an authentication scheme under `components.securitySchemes` of the OpenAPI documents.
+
+        Args:
+            name (str): The name operations refer to in `security`.
+            scheme (Dict[str, Any]): The scheme, e.g. `{"type": "http", "scheme": "bearer"}`.
+        """
+        self.openapi_components["securitySchemes"][name] = scheme
+
+    def set_tracing_endpoint(self, url: str):
+        """
+        Exports an OpenTelemetry span for every request to an OTLP collector.
+
+        Args:
+            url (str): The OTLP gRPC endpoint of the collector (e.g. `http://localhost:4317`).
+        """
+        self.tracing_endpoint = url
+
+    def set_logging(self, level: str = "info", json: bool = False, access_log: bool = True):
+        """
+        Configures the server logs.
+
+        Args:
+            level (str): The default filter, a level or `EnvFilter` directives such as `info,hypern=debug`.
+                The `RUST_LOG` environment variable takes precedence when set.
+            json (bool): Write logs as JSON lines.
+            access_log (bool): Log one line per response with its status and latency.
+        """
+        self.logging_config = {"level": level, "json": json, "access_log": access_log}
+
+    def set_http_options(
+        self,
+        keep_alive_secs: int = 30,
+        max_header_bytes: int = 65536,
+        h2_max_concurrent_streams: int = 200,
+        h2_enabled: bool = True,
+        tcp_nodelay: bool = False,
+        backlog: int = 1024,
+    ):
+        """
+        Tunes how the server handles connections.
+
+        Args:
+            keep_alive_secs (int): How long an idle HTTP/1 connection is kept open, 0 closes it after each response.
+            max_header_bytes (int): The largest request head accepted, larger ones get 431. At least 8192.
+            h2_max_concurrent_streams (int): The maximum number of concurrent streams per HTTP/2 connection.
+            h2_enabled (bool): Accept HTTP/2 connections.
+            tcp_nodelay (bool): Set TCP_NODELAY on accepted connections.
+            backlog (int): The listen backlog of the socket.
+        """
+        self.http_options = {
+            "keep_alive_secs": keep_alive_secs,
+            "max_header_bytes": max_header_bytes,
+            "h2_max_concurrent_streams": h2_max_concurrent_streams,
+            "h2_enabled": h2_enabled,
+            "tcp_nodelay": tcp_nodelay,
+            "backlog": backlog,
+        }
+
+    def set_csrf_config(self, config: CsrfConfig):
+        """
+        Enables CSRF protection: POST, PUT, PATCH and DELETE requests must send the token of the
+        CSRF cookie in a header or form field, otherwise they are rejected with 403.
+
+        Args:
+            config (CsrfConfig): The secret, cookie, header and form field names.
+        """
+        self.csrf_config = config
+
+    def set_ip_filter(
+        self,
+        config: Optional[IpFilterConfig] = None,
+        allow: Optional[List[str]] = None,
+        deny: Optional[List[str]] = None,
+        status: int = 403,
+    ):
+        """
+        Restricts which client addresses can reach the routes, others are rejected before any python code runs.
+
+        Routes can replace it with their own `ip_filter`.
+
+        Args:
+            config (IpFilterConfig | None): The CIDR ranges and whether they are an allowlist or a blocklist,
+                built from `allow`, `deny` and `status` when None.
+            allow (List[str] | None): Only these CIDR ranges are served, everyone when empty.
+            deny (List[str] | None): These CIDR ranges are rejected, even when they are also allowed.
+            status (int): The status of rejected requests.
+        """
+        self.ip_filter_config = config or IpFilterConfig(allow=allow or [], deny=deny or [], status=status)
+
+    def set_trusted_proxies(self, cidrs: List[str]):
+        """
+        Sets the proxies whose X-Forwarded-For and Forwarded headers are used for request.client_ip.
+
+        Args:
+            cidrs (List[str]): The address ranges of the proxies, e.g. ["10.0.0.0/8"].
+        """
+        self.trusted_proxies = cidrs
+
+    def set_auto_etag(self, max_size: int = 1024 * 1024):
+        """
+        Adds an ETag to GET and HEAD responses and answers 304 Not Modified when the client already has them.
+
+        ETags and Last-Modified headers set by a handler are compared with If-None-Match and
+        If-Modified-Since as they are; otherwise an ETag is computed from the body of responses up to `max_size`.
+        A route can opt in or out with its `etag` argument.
+
+        Args:
+            max_size (int): The largest body in bytes an ETag is computed for, 1 MiB by default.
+        """
+        self.auto_etag_max_size = max_size
+
+    def set_response_cache(self, cache: ResponseCache):
+        """
+        Sets where the responses of routes configured with a cache are stored.
+
+        Cached responses are served without running middlewares or handlers. Keep a reference to
+        `cache` to invalidate entries, e.g. `cache.invalidate_prefix("/products")`.
+
+        Args:
+            cache (ResponseCache): The in-memory or redis backed response cache.
+        """
+        self.response_cache = cache
+
+    def set_debug_headers(self, enabled: bool = True):
+        """
+        Adds how long each middleware and the handler took to every response.
+
+        The nth middleware that ran sets `X-Middleware-<n>-Time-Ms`, counting before hooks then
+        after hooks in execution order, and the handler sets `X-Handler-Time-Ms`. Meant for
+        troubleshooting slow requests, it can be enabled in production.
+
+        Args:
+            enabled (bool): Whether the timing headers are sent.
+        """
+        self.debug_headers = enabled
+
+    def set_max_form_fields(self, max_fields: int):
+        """
+        Limits how many fields of an urlencoded form body are parsed, the rest are ignored.
+
+        Args:
+            max_fields (int): The maximum number of fields, 1000 by default.
+        """
+        self.max_form_fields = max_fields
+
+    def set_upload_spool_size(self, max_size: int):
+        """
+        Sets the size above which uploaded files are written to a temporary file instead of kept in memory.
+
+        Args:
+            max_size (int): The size in bytes, 1 MiB by default.
+        """
+        self.upload_spool_size = max_size
+
+    def set_max_upload_size(self, max_size: int):
+        """
+        Sets the largest `multipart/form-data` body read, a larger body stops being read at the limit.
+
+        Args:
+            max_size (int): The size in bytes, 1 GiB by default.
+        """
+        self.max_upload_size = max_size
+
+    def set_static_dir(self, url_prefix: str, directory: str, cache_control: str = "public, max-age=3600"):
+        """
+        Serves the files of a directory, ahead of routes and without running middlewares.
+
+        Args:
+            url_prefix (str): The URL prefix the files are served under (e.g. `/static`).
+            directory (str): The directory holding the files.
+            cache_control (str): The Cache-Control header sent with every file.
+        """
+        self.static_dirs.append((url_prefix, directory, cache_control))
+
+    def enable_sessions(
+        self,
+        secret_key: str,
+        cookie_name: str = "session",
+        max_age: int = 86400,
+        same_site: str = "lax",
+        backend: Any = "cookie",
+        old_keys: Optional[List[str]] = None,
+        secure: bool = False,
+        redis_url: str = "redis://localhost:6379",
+    ):
+        """
+        Enables `request.session`, a dict loaded before the handler and saved after it when changed.
+
+        Args:
+            secret_key (str): The key used to sign the session cookie.
+            cookie_name (str): The name of the session cookie.
+            max_age (int): The session lifetime in seconds.
+            same_site (str): The SameSite attribute of the cookie: `strict`, `lax` or `none`.
+            backend (str | BaseBackend): `cookie` keeps the session in the signed cookie, `redis` keeps it in redis
+                and only stores a signed session id in the cookie. A `hypern.caching` backend such as
+                `MemoryBackend()` stores the data the same way as `redis`.
+            old_keys (List[str] | None): Previous secret keys, still accepted when verifying cookies.
+            secure (bool): Whether the cookie is only sent over HTTPS.
+            redis_url (str): The redis server used by the `redis` backend.
+        """
+        self.session_config = {
+            "secret_key": secret_key,
+            "cookie_name": cookie_name,
+            "max_age": max_age,
+            "same_site": same_site,
+            "backend": backend,
+            "old_keys": old_keys,
+            "secure": secure,
+            "redis_url": redis_url,
+        }
+
+    def enable_rate_limit(
+        self,
+        limit: int,
+        window_secs: int,
+        key: str = "ip",
+        backend: Optional[str] = None,
+        exempt_paths: Optional[List[str]] = None,
+    ):
+        """
+        Limits how many requests each client can send, answering 429 Too Many Requests past the limit.
+
+        Requests are counted in a sliding window before any python code runs. Responses carry
+        `X-RateLimit-Limit` and `X-RateLimit-Remaining`, rejections also carry `Retry-After`.
+        Routes can set their own limit with `rate_limit`.
+
+        Args:
+            limit (int): The number of requests allowed per window.
+            window_secs (int): The window length in seconds.
+            key (str): `ip` counts per client address, `header:<name>` per value of a header such as
+                `header:X-Api-Key`, falling back to the address when the header is missing.
+            backend (str | None): A redis url to share the counters between workers and servers,
+                None keeps them in the process.
+            exempt_paths (List[str] | None): Paths or route patterns that are never limited, e.g. `/public/*`.
+        """
+        self.rate_limit_config = {
+            "limit": limit,
+            "window_secs": window_secs,
+            "key": key,
+            "backend": backend,
+            "exempt_paths": exempt_paths,
+        }
+
+    def enable_security_headers(
+        self,
+        hsts_max_age: Optional[int] = 31536000,
+        csp: Optional[str] = None,
+        frame_options: Optional[str] = "DENY",
+        nosniff: bool = True,
+        referrer_policy: Optional[str] = "strict-origin-when-cross-origin",
+        force_hsts: bool = False,
+    ):
+        """
+        Adds standard security headers to every response, including static files and error responses.
+
+        A header already set by the application is kept. Passing None for a value leaves its header out.
+
+        Args:
+            hsts_max_age (int | None): `Strict-Transport-Security` max-age in seconds, only sent on
+                https requests, as reported by trusted proxies when behind one.
+            csp (str | None): The `Content-Security-Policy` value.
+            frame_options (str | None): `X-Frame-Options`, `DENY` or `SAMEORIGIN`.
+            nosniff (bool): Whether to send `X-Content-Type-Options: nosniff`.
+            referrer_policy (str | None): The `Referrer-Policy` value.
+            force_hsts (bool): Send `Strict-Transport-Security` on plain http requests too.
+        """
+        self.security_headers_config = {
+            "hsts_max_age": hsts_max_age,
+            "csp": csp,
+            "frame_options": frame_options,
+            "nosniff": nosniff,
+            "referrer_policy": referrer_policy,
+            "force_hsts": force_hsts,
+        }
+
+    def set_trailing_slash(self, policy: str):
+        """
+        Sets how a request that differs from a route only by a trailing slash is handled.
+
+        Args:
+            policy (str): `strict` (default) treats `/items` and `/items/` as different routes,
+                `redirect` redirects to the registered form and `merge` serves the registered route.
+                Routes can override it with `Route(..., trailing_slash=...)`.
+        """
+        self.router.trailing_slash = policy
+
+    def test_client(self, database_config: DatabaseConfig | None = None) -> TestClient:
+        """
+        Creates a client sending requests through the routes, middlewares and dependencies of the app,
+        without starting the server.
+
+        Args:
+            database_config (DatabaseConfig | None): Opens the database, and the automatic
+                transaction of each request, like `database_config` does for the server.
+        """
+        return TestClient(
+            self.router,
+            middlewares=(self.middleware_before_request, self.middleware_after_request),
+            injected=self.injectables,
+            database_config=database_config,
+        )
+
     def start(
         self,
     ):
@@ -426,7 +970,60 @@ class Hypern:
         if self.scheduler:
             self.scheduler.start()
 
+        server = self.build_server()
+
+        if self.args.auto_workers:
+            self.args.workers = self.thread_config.workers
+            self.args.max_blocking_threads = self.thread_config.max_blocking_threads
+
+        run_processes(
+            server=server,
+            host=self.args.host,
+            port=self.args.port,
+            workers=self.args.workers,
+            processes=self.args.processes,
+            max_blocking_threads=self.args.max_blocking_threads,
+            reload=self.args.reload,
+            unix_socket=self.args.unix_socket,
+            unix_socket_permissions=self.args.unix_socket_permissions,
+        )
+
+    def serve(
+        self,
+        host: str = "127.0.0.1",
+        port: int = 5000,
+        workers: int = 1,
+        max_blocking_threads: int = 32,
+        unix_socket: str | None = None,
+    ) -> ServerHandle:
+        """
+        Starts the server in the background of the current process, without blocking.
+
+        Args:
+            host (str): The address to listen on.
+            port (int): The port to listen on.
+            workers (int): The number of runtime worker threads.
+            max_blocking_threads (int): The maximum number of threads running sync handlers.
+            unix_socket (str | None): Listen on this unix socket path instead of host and port.
+
+        Returns:
+            ServerHandle: Stops the server with `stop()`, after which another one can be served.
+        """
+        server = self.build_server()
+        socket = SocketHeld.unix(unix_socket) if unix_socket else SocketHeld(host, port)
+        return server.serve(socket, workers, max_blocking_threads)
+
+    def build_server(self) -> Server:
+        """
+        Creates the server with the routes and configuration of the app.
+        """
+        if self.openapi_docs or self.openapi_config:
+            problems = SchemaGenerator({}).validate_all(self.router.routes)
+            if problems:
+                logger.warning("Invalid OpenAPI docstrings, these operations are left out:\n" + "\n".join(problems))
+
         server = Server()
+        self.server = server
         server.set_router(router=self.router)
         server.set_websocket_router(websocket_router=self.websocket_router)
         server.set_injected(injected=self.injectables)
@@ -434,30 +1031,86 @@ class Hypern:
         server.set_after_hooks(hooks=self.middleware_after_request)
         server.set_response_headers(headers=self.response_headers)
         server.set_auto_compression(enabled=self.auto_compression)
+        if self.compression_config:
+            server.set_compression_config(**self.compression_config)
         server.set_mem_pool_capacity(min_capacity=self.args.min_capacity, max_capacity=self.args.max_capacity)
 
         if self.database_config:
             server.set_database_config(config=self.database_config)
+        if self.health_config:
+            server.enable_health(**self.health_config)
+        for check in self.readiness_checks:
+            server.add_readiness_check(**check)
+        if self.metrics_path:
+            server.set_metrics_path(path=self.metrics_path)
+        if self.openapi_config:
+            config = dict(self.openapi_config)
+            base_schema = {"components": self.openapi_components}
+            if servers := config.pop("servers"):
+                base_schema["servers"] = servers
+            server.enable_openapi(**config, base_schema=base_schema)
+        if self.tracing_endpoint:
+            server.set_tracing_endpoint(url=self.tracing_endpoint)
+        if self.logging_config:
+            server.set_logging(**self.logging_config)
+        if self.http_options:
+            server.set_http_options(**self.http_options)
+        if self.session_config:
+            server.enable_sessions(**self.session_config)
+        if self.rate_limit_config:
+            server.enable_rate_limit(**self.rate_limit_config)
+        if self.security_headers_config:
+            server.enable_security_headers(**self.security_headers_config)
+        if self.csrf_config:
+            server.set_csrf_config(self.csrf_config)
+        if self.ip_filter_config:
+            server.set_ip_filter(self.ip_filter_config)
+        if self.trusted_proxies:
+            server.set_trusted_proxies(self.trusted_proxies)
+        if self.response_cache:
+            server.set_response_cache(self.response_cache)
+        if self.auto_etag_max_size is not None:
+            server.set_auto_etag(max_size=self.auto_etag_max_size)
+        if self.debug_headers:
+            server.set_debug_headers(enabled=True)
+        if self.max_form_fields is not None:
+            server.set_max_form_fields(max_fields=self.max_form_fields)
+        if self.upload_spool_size is not None:
+            server.set_upload_spool_size(max_size=self.upload_spool_size)
+        if self.max_upload_size is not None:
+            server.set_max_upload_size(max_size=self.max_upload_size)
+        if self.docs_assets:
+            server.enable_docs_assets()
+        for url_prefix, directory, cache_control in self.static_dirs:
+            server.set_static_dir(url_prefix=url_prefix, directory=directory, cache_control=cache_control)
         if self.start_up_handler:
             server.set_startup_handler(self.start_up_handler)
         if self.shutdown_handler:
             server.set_shutdown_handler(self.shutdown_handler)
+        if self.not_found_handler:
+            server.set_404_handler(self.not_found_handler)
+        if self.method_not_allowed_handler:
+            server.set_405_handler(self.method_not_allowed_handler)
+        return server
 
-        if self.args.auto_workers:
-            self.args.workers = self.thread_config.workers
-            self.args.max_blocking_threads = self.thread_config.max_blocking_threads
-
-        run_processes(
-            server=server,
-            host=self.args.host,
-            port=self.args.port,
-            workers=self.args.workers,
-            processes=self.args.processes,
-            max_blocking_threads=self.args.max_blocking_threads,
-            reload=self.args.reload,
-        )
-
-    def add_route(self, method: HTTPMethod, endpoint: str, handler: Callable[..., Any]):
+    def add_route(
+        self,
+        method: HTTPMethod,
+        endpoint: str,
+        handler: Callable[..., Any],
+        deprecated: bool = False,
+        sunset_date: str | None = None,
+        etag: bool | None = None,
+        cache: Dict[str, Any] | None = None,
+        rate_limit: Dict[str, int] | None = None,
+        ip_filter: Dict[str, Any] | None = None,
+        include_in_schema: bool = True,
+        tags: List[str] | None = None,
+        summary: str | None = None,
+        description: str | None = None,
+        operation_id: str | None = None,
+        stream_body: bool = False,
+    ):
         """
         Adds a route to the router.
 
@@ -465,12 +1118,61 @@ class Hypern:
             method (HTTPMethod): The HTTP method for the route (e.g., GET, POST).
             endpoint (str): The endpoint path for the route.
             handler (Callable[..., Any]): The function that handles requests to the route.
+            deprecated (bool): Whether responses carry a `Deprecation` header and the route is marked deprecated in the OpenAPI schema.
+            sunset_date (str | None): HTTP date sent as the `Sunset` header of a deprecated route.
+            etag (bool | None): Whether ETags and 304 responses are handled for this route, None follows `set_auto_etag`.
+            cache (Dict[str, Any] | None): Arguments of `Route.cache`, e.g. {"ttl": 60, "vary_headers": ["accept-language"]}.
+            rate_limit (Dict[str, int] | None): Arguments of `Route.rate_limit`, e.g. {"limit": 10, "window_secs": 60}.
+            ip_filter (Dict[str, Any] | None): Arguments of `Route.ip_filter`, e.g. {"allow": ["10.0.0.0/8"]}.
+            include_in_schema (bool): Whether the route is listed in the OpenAPI document.
+            tags (List[str] | None): Groups the operation in the OpenAPI document, see also `Router.get_routes_by_tag`.
+            summary (str | None): The OpenAPI summary, replacing the one of the handler docstring.
+            description (str | None): The OpenAPI description, replacing the one of the handler docstring.
+            operation_id (str | None): The OpenAPI operationId.
+            stream_body (bool): Leave the body unread, the handler reads it with `request.streaming_body()`.
 
         """
         is_async = asyncio.iscoroutinefunction(handler)
-        func_info = FunctionInfo(handler=handler, is_async=is_async)
-        route = InternalRoute(path=endpoint, function=func_info, method=method.name)
+        func_info = FunctionInfo(handler=handler, is_async=is_async, parameter_names=get_parameter_names(handler))
+        route = InternalRoute(
+            path=endpoint,
+            function=func_info,
+            method=method.name,
+            deprecated=deprecated,
+            sunset_date=sunset_date,
+            etag=etag,
+            include_in_schema=include_in_schema,
+            tags=tags,
+            summary=summary,
+            description=description,
+            operation_id=operation_id,
+        )
+        if cache is not None:
+            route.cache(**cache)
+        if rate_limit is not None:
+            route.rate_limit(**rate_limit)
+        if ip_filter is not None:
+            route.ip_filter(**ip_filter)
+        route.stream_body = stream_body
         self.router.add_route(route=route)
+        if self.server:
+            self.server.add_route(route=route)
+
+    def remove_route(self, method: HTTPMethod, endpoint: str) -> bool:
+        """
+        Removes a route from the router.
+
+        Args:
+            method (HTTPMethod): The HTTP method of the route.
+            endpoint (str): The endpoint path of the route.
+
+        Returns:
+            bool: True if the route existed and was removed.
+        """
+        removed = self.router.remove_route(path=endpoint, method=method.name)
+        if self.server:
+            removed = self.server.remove_route(path=endpoint, method=method.name)
+        return removed
 
     def add_websocket(self, ws_route: WebsocketRoute):
         """
@@ -500,3 +1202,27 @@ class Hypern:
             handler (Callable[..., Any]): The function to be executed on application shutdown.
         """
         self.shutdown_handler = FunctionInfo(handler=handler, is_async=asyncio.iscoroutinefunction(handler))
+
+    def set_404_handler(self, handler: Callable[..., Any]):
+        """
+        Registers the function rendering responses to requests that match no route.
+
+        The function receives the request. A returned `Response` is sent as is, any other body is
+        sent with status 404. Global after hooks run on the response.
+
+        Args:
+            handler (Callable[..., Any]): The function building the 404 response.
+        """
+        self.not_found_handler = FunctionInfo(handler=handler, is_async=asyncio.iscoroutinefunction(handler))
+
+    def set_405_handler(self, handler: Callable[..., Any]):
+        """
+        Registers the function rendering responses to requests whose path only has routes for other methods.
+
+        The function receives the request. A returned `Response` is sent as is, any other body is
+        sent with status 405. Global after hooks run on the response.
+
+        Args:
+            handler (Callable[..., Any]): The function building the 405 response.
+        """
+        self.method_not_allowed_handler = FunctionInfo(handler=handler, is_async=asyncio.iscoroutinefunction(handler))
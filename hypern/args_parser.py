@@ -21,6 +21,22 @@ class ArgsConfig:
             help="Choose the port. [Defaults to `5000`]",
         )
 
+        parser.add_argument(
+            "--unix-socket",
+            type=str,
+            default=None,
+            required=False,
+            help="Listen on a unix domain socket at this path instead of host and port, `@name` for an abstract socket.",
+        )
+
+        parser.add_argument(
+            "--unix-socket-permissions",
+            type=lambda value: int(value, 8),
+            default=None,
+            required=False,
+            help="Octal permissions of the unix socket file, e.g. `660`.",
+        )
+
         parser.add_argument(
             "--processes",
             type=int,
@@ -82,6 +98,8 @@ class ArgsConfig:
 
         self.host = args.host or "127.0.0.1"
         self.port = args.port or 5000
+        self.unix_socket = args.unix_socket
+        self.unix_socket_permissions = args.unix_socket_permissions
         self.max_blocking_threads = args.max_blocking_threads or 32
         self.processes = args.processes or 1
         self.workers = args.workers or 1
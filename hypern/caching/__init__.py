@@ -1,6 +1,8 @@
 from .backend import BaseBackend
 from .redis_backend import RedisBackend
+from .memory_backend import MemoryBackend
+from hypern.hypern import ResponseCache
 
 from .strategies import CacheAsideStrategy, CacheEntry, CacheStrategy, StaleWhileRevalidateStrategy, cache_with_strategy
 
-__all__ = ["BaseBackend", "RedisBackend", "CacheAsideStrategy", "CacheEntry", "CacheStrategy", "StaleWhileRevalidateStrategy", "cache_with_strategy"]
+__all__ = ["BaseBackend", "RedisBackend", "MemoryBackend", "ResponseCache", "CacheAsideStrategy", "CacheEntry", "CacheStrategy", "StaleWhileRevalidateStrategy", "cache_with_strategy"]
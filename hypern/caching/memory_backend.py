@@ -0,0 +1,151 @@
+import fnmatch
+from typing import Any, Optional
+
+from hypern.hypern import MemoryStore
+
+from .backend import BaseBackend
+
+
+class MemoryBackend(BaseBackend):
+    def __init__(self, max_entries: int = 10000, default_ttl: Optional[int] = None):
+        """
+        Initialize the in-process backend, interchangeable with RedisBackend
+
+        Values are kept as python objects, they are not copied or serialized.
+        Expired entries are dropped when they are read and by a periodic task.
+
+        Args:
+            max_entries: Entries kept before the least recently used ones are evicted
+            default_ttl: Time to live in seconds of entries set without a ttl, None keeps them forever
+        """
+        self.store = MemoryStore(max_entries, default_ttl)
+
+    async def get(self, key: str) -> Optional[Any]:
+        """
+        Get a value
+
+        Args:
+            key: Cache key
+
+        Returns:
+            The stored object or None if key doesn't exist or has expired
+        """
+        return self.store.get(key)
+
+    async def set(self, key: str, value: Any, ttl: Optional[int] = None) -> bool:
+        """
+        Set a value with optional TTL
+
+        Args:
+            key: Cache key
+            value: Python object to store
+            ttl: Time to live in seconds, the backend default_ttl when None
+
+        Returns:
+            bool: Always True
+        """
+        self.store.set(key, value, ttl)
+        return True
+
+    async def delete(self, key: str) -> bool:
+        """
+        Delete a key
+
+        Args:
+            key: Cache key to delete
+
+        Returns:
+            bool: True if key was deleted, False otherwise
+        """
+        return self.store.delete(key)
+
+    async def delete_pattern(self, pattern: str) -> int:
+        """
+        Delete all keys matching a Redis style glob pattern
+
+        Args:
+            pattern: Key pattern to match
+
+        Returns:
+            int: Number of keys deleted
+        """
+        prefix = pattern[:-1]
+        if pattern.endswith("*") and not any(c in prefix for c in "*?[\\"):
+            return self.store.delete_startswith(prefix)
+        return sum(self.store.delete(key) for key in self.store.keys() if fnmatch.fnmatchcase(key, pattern))
+
+    async def delete_startswith(self, prefix: str) -> int:
+        """
+        Delete all keys starting with prefix
+
+        Args:
+            prefix: Key prefix to match
+
+        Returns:
+            int: Number of keys deleted
+        """
+        return self.store.delete_startswith(prefix)
+
+    async def exists(self, key: str) -> bool:
+        """
+        Check if key exists
+
+        Args:
+            key: Cache key to check
+
+        Returns:
+            bool: True if key exists, False otherwise
+        """
+        return self.store.exists(key)
+
+    async def ttl(self, key: str) -> int:
+        """
+        Get TTL of key in seconds
+
+        Args:
+            key: Cache key
+
+        Returns:
+            int: TTL in seconds, -2 if key doesn't exist, -1 if key has no TTL
+        """
+        return self.store.get_ttl(key)
+
+    async def incr(self, key: str, amount: int = 1) -> Optional[int]:
+        """
+        Increment value by amount
+
+        Args:
+            key: Cache key
+            amount: Amount to increment by
+
+        Returns:
+            int: New value after increment or None if the value is not an integer
+        """
+        try:
+            return self.store.incr(key, amount)
+        except TypeError:
+            return None
+
+    async def set_nx(self, key: str, value: Any, ttl: Optional[int] = None) -> bool:
+        """
+        Set key only if it doesn't exist (SET NX operation)
+
+        Args:
+            key: Cache key
+            value: Value to set
+            ttl: Optional TTL in seconds
+
+        Returns:
+            bool: True if key was set, False otherwise
+        """
+        return self.store.set_nx(key, value, ttl)
+
+    async def clear(self) -> bool:
+        """
+        Remove every key
+
+        Returns:
+            bool: Always True
+        """
+        self.store.clear()
+        return True
@@ -92,10 +92,17 @@ class RedisBackend(BaseBackend):
             int: Number of keys deleted
         """
         try:
-            keys = await self.redis.keys(pattern)
-            if keys:
-                return await self.redis.delete(*keys)
-            return 0
+            # SCAN walks the keyspace in batches instead of blocking Redis like KEYS
+            deleted = 0
+            batch = []
+            async for key in self.redis.scan_iter(match=pattern, count=500):
+                batch.append(key)
+                if len(batch) >= 500:
+                    deleted += await self.redis.delete(*batch)
+                    batch = []
+            if batch:
+                deleted += await self.redis.delete(*batch)
+            return deleted
         except Exception as e:
             logger.error(f"Error deleting keys matching {pattern}: {e}")
             return 0
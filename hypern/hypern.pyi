@@ -1,19 +1,56 @@
 from __future__ import annotations
 
-from dataclasses import dataclass
-from typing import Any, Callable, Dict, List, Tuple
+import os
+from dataclasses import dataclass, field
+from datetime import datetime
+from typing import Any, Callable, Dict, Iterator, List, Tuple
 from enum import Enum
 
 @dataclass
 class BaseSchemaGenerator:
-    remove_converter: Callable[[str], str]
-    parse_docstring: Callable[[Callable[..., Any]], str]
+    base_schema: Dict[str, Any]
+    strict: bool
+
+    def __init__(self, base_schema: Dict[str, Any], strict: bool = False) -> None: ...
+    def remove_converter(self, path: str) -> str: ...
+    def parse_docstring(self, func_or_method: Callable[..., Any]) -> str: ...
+    def validate_all(self, routes: List[Route]) -> List[str]: ...
+
+    def add_component(self, name: str, schema: Dict[str, Any]) -> None: ...
+    def add_security_scheme(self, name: str, scheme: Dict[str, Any]) -> None: ...
+    def missing_refs(self, schema: Dict[str, Any]) -> List[str]: ...
 
 @dataclass
 class SwaggerUI:
+    EMBEDDED_ASSETS_URL: str
+
+    title: str
+    openapi_url: str
+    oauth2_redirect_url: str | None
+    assets_mode: str
+    assets_url: str | None
+
+    def __init__(
+        self,
+        title: str,
+        openapi_url: str,
+        oauth2_redirect_url: str | None = None,
+        assets_mode: str = "cdn",
+        assets_url: str | None = None,
+    ) -> None: ...
+    def get_html_content(self) -> str: ...
+    def get_oauth2_redirect_html(self) -> str: ...
+
+@dataclass
+class ReDocUI:
+    EMBEDDED_ASSETS_URL: str
+
     title: str
     openapi_url: str
+    assets_mode: str
+    assets_url: str | None
 
+    def __init__(self, title: str, openapi_url: str, assets_mode: str = "cdn", assets_url: str | None = None) -> None: ...
     def get_html_content(self) -> str: ...
 
 @dataclass
@@ -93,7 +130,44 @@ class BackgroundTasks:
         """
         pass
 
+    def get_task_result(self, task_id: str) -> Any | None:
+        """
+        Get the result of a finished task, None while it is running
+        """
+        pass
+
+    def get_all_results(self) -> Dict[str, Dict[str, Any]]:
+        """
+        Get the results of all finished tasks as {task_id: {"success": bool, "result": Any, "error": str | None}}.
+        Collected tasks are removed, unfinished tasks are left running and not included
+        """
+        pass
+
+    def is_task_complete(self, task_id: str) -> bool:
+        """
+        Whether a task has finished running
+        """
+        pass
+
 class Scheduler:
+    def __init__(self, persist_path: str | None = None) -> None:
+        """
+        persist_path: str | None: Path of a SQLite file where job metadata is persisted and restored from on start
+        """
+        pass
+
+    def register_task(self, name: str, task: Callable[..., Any]) -> None:
+        """
+        Register a task or callback under a name so persisted jobs using it can be restored after a restart
+        """
+        pass
+
+    def get_unrestored_jobs(self) -> List[Tuple[str, str]]:
+        """
+        Get the (job_id, name) of persisted jobs that were not restored because the task or callback of that name is not registered
+        """
+        pass
+
     def add_job(
         self,
         job_type: str,
@@ -101,14 +175,21 @@ class Scheduler:
         task: Callable[..., Any],
         timezone: str,
         dependencies: List[str],
-        retry_policy: Tuple[int, int, bool] | None = None,
+        retry_policy: Tuple[int, int, bool] | Tuple[int, int, bool, float | None] | Tuple[int, int, bool, float | None, int | None] | None = None,
+        max_concurrency: int = 1,
+        misfire_policy: str = "skip",
+        timeout_secs: int | None = None,
+        on_success: Callable[[str, Any], None] | None = None,
+        on_failure: Callable[[str, str], None] | None = None,
+        skip_if_running: bool = False,
     ) -> str:
         """
         Add a job to the scheduler
         params:
-        job_type: str: The type of the job (e.g. "cron", "interval")
+        job_type: str: The type of the job (e.g. "cron", "interval", "date")
 
-        schedule_param: str: The schedule parameter of the job. interval in seconds for interval jobs, cron expression for cron jobs
+        schedule_param: str: The schedule parameter of the job. interval in seconds for interval jobs, cron expression for cron jobs,
+        local datetime in the job timezone for one-shot date jobs (e.g. "2025-07-01T09:00:00")
 
         Exmaple:
         //           sec  min   hour   day of month   month   day of week   year
@@ -120,7 +201,20 @@ class Scheduler:
 
         dependencies: List[str]: The IDs of the jobs this job depends on
 
-        retry_policy: Tuple[int, int, bool] | None: The retry policy of the job. (max_retries, retry_delay_secs, exponential_backoff)
+        retry_policy: Tuple | None: The retry policy of the job. (max_retries, retry_delay_secs, exponential_backoff[, jitter_factor[, max_delay_secs]])
+
+        max_concurrency: int: The maximum number of runs of this job allowed in flight at once
+
+        misfire_policy: str: What to do when the job is due while max_concurrency runs are in flight.
+        "skip" drops the run, "queue" runs it as soon as the previous run finishes
+
+        timeout_secs: int | None: Maximum time in seconds a single run may take before it is marked failed, a sync task then gets TimeoutError raised in its thread
+
+        on_success: Callable[[str, Any], None] | None: Called with the job ID and the task return value after a successful run
+
+        on_failure: Callable[[str, str], None] | None: Called with the job ID and the error message after a failed run, including when retries are exhausted
+
+        skip_if_running: bool: Skip a run that becomes due while a previous run is still executing, even with max_concurrency > 1 or misfire_policy "queue"
 
         return:
         str: The ID of the job
@@ -145,15 +239,22 @@ class Scheduler:
         """
         pass
 
-    def get_job_status(self, job_id: str) -> Tuple[float, float, List[str], int]:
+    def get_job_status(self, job_id: str) -> Tuple[float, float, List[str], int, bool, int]:
         """
         Get the status of a job
+        (last_run, last_success, failed_dependencies, current_retry, is_running, skipped_runs)
         """
         pass
 
-    def get_next_run(self, job_id: str) -> float:
+    def get_last_error(self, job_id: str) -> str | None:
         """
-        Get the next run time of a job
+        Get the error message of the last failed run of a job, or None if it last succeeded
+        """
+        pass
+
+    def get_next_run(self, job_id: str) -> float | None:
+        """
+        Get the next run time of a job, None once a date job has run
         """
         pass
 
@@ -165,10 +266,60 @@ class FunctionInfo:
     Attributes:
         handler (Callable): The function to be called
         is_async (bool): Whether the function is async or not
+        parameter_names (List[str] | None): Handler parameters after the request, used to inject
+            dependencies by name. When None the whole dependency dict is passed as `inject`.
     """
 
     handler: Callable
     is_async: bool
+    parameter_names: List[str] | None = None
+
+class TestResponse:
+    status_code: int
+    headers: Header
+    content: bytes
+    text: str
+    ok: bool
+    cookies: Dict[str, str]
+
+    def json(self) -> Any: ...
+    def assert_status(self, expected: int) -> None: ...
+
+class TestClient:
+    cookies: Dict[str, str]
+
+    def __init__(
+        self,
+        router: Router,
+        middlewares: Tuple[List[Tuple[FunctionInfo, MiddlewareConfig]], List[Tuple[FunctionInfo, MiddlewareConfig]]] | None = None,
+        injected: Dict[str, Any] | None = None,
+        database_config: DatabaseConfig | None = None,
+    ) -> None: ...
+    def request(
+        self,
+        method: str,
+        path: str,
+        headers: Dict[str, str] | None = None,
+        params: Dict[str, str] | None = None,
+        body: bytes | str | None = None,
+        json: Any = None,
+        data: Dict[str, str] | None = None,
+        files: Dict[str, bytes | Tuple[str, bytes] | Tuple[str, bytes, str]] | None = None,
+        cookies: Dict[str, str] | None = None,
+    ) -> TestResponse: ...
+    def get(self, path: str, **kwargs: Any) -> TestResponse: ...
+    def post(self, path: str, **kwargs: Any) -> TestResponse: ...
+    def put(self, path: str, **kwargs: Any) -> TestResponse: ...
+    def patch(self, path: str, **kwargs: Any) -> TestResponse: ...
+    def delete(self, path: str, **kwargs: Any) -> TestResponse: ...
+    def close(self) -> None: ...
+    def __enter__(self) -> TestClient: ...
+    def __exit__(self, _exc_type, _exc_value, _traceback) -> bool: ...
+
+class ServerHandle:
+    def stop(self, graceful: bool = True) -> None: ...
+    def is_running(self) -> bool: ...
+    def wait(self, timeout: float | None = None) -> bool: ...
 
 @dataclass
 class Server:
@@ -181,6 +332,7 @@ class Server:
     def set_router(self, router: Router) -> None: ...
     def set_websocket_router(self, websocket_router: WebsocketRouter) -> None: ...
     def start(self, socket: SocketHeld, worker: int, max_blocking_threads: int) -> None: ...
+    def serve(self, socket: SocketHeld, worker: int, max_blocking_threads: int) -> ServerHandle: ...
     def inject(self, key: str, value: Any) -> None: ...
     def set_injected(self, injected: Dict[str, Any]) -> None: ...
     def set_before_hooks(self, hooks: List[FunctionInfo]) -> None: ...
@@ -189,14 +341,133 @@ class Server:
     def set_startup_handler(self, on_startup: FunctionInfo) -> None: ...
     def set_shutdown_handler(self, on_shutdown: FunctionInfo) -> None: ...
     def set_auto_compression(self, enabled: bool) -> None: ...
+    def set_compression_config(
+        self,
+        algorithms: List[str] | None = None,
+        level: int | Dict[str, int] | None = None,
+        min_size: int = 1024,
+        content_types: List[str] | None = None,
+    ) -> None: ...
     def set_database_config(self, config: DatabaseConfig) -> None: ...
     def set_mem_pool_capacity(self, min_capacity: int, max_capacity: int) -> None: ...
+    def set_health_check(self, path: str) -> None: ...
+    def enable_health(self, liveness: str | None = "/healthz", readiness: str | None = "/readyz") -> None: ...
+    def add_readiness_check(self, name: str, check: Callable[[], Any], interval: float = 5.0) -> None: ...
+    def set_metrics_path(self, path: str) -> None: ...
+    def enable_metrics(self, path: str = "/metrics") -> None: ...
+    def set_tracing_endpoint(self, url: str) -> None: ...
+    def set_logging(self, level: str = "info", json: bool = False, access_log: bool = True) -> None: ...
+    def set_http_options(
+        self,
+        keep_alive_secs: int = 30,
+        max_header_bytes: int = 65536,
+        h2_max_concurrent_streams: int = 200,
+        h2_enabled: bool = True,
+        tcp_nodelay: bool = False,
+        backlog: int = 1024,
+    ) -> None: ...
+    def enable_openapi(
+        self, path: str = "/openapi.json", title: str = "Hypern", version: str = "0.1.0", base_schema: Dict[str, Any] | None = None
+    ) -> None: ...
+    def set_max_form_fields(self, max_fields: int) -> None: ...
+    def set_upload_spool_size(self, max_size: int) -> None: ...
+    def set_max_upload_size(self, max_size: int) -> None: ...
+    def enable_docs_assets(self) -> None: ...
+    def set_csrf_config(self, config: CsrfConfig) -> None: ...
+    def set_ip_filter(
+        self,
+        config: IpFilterConfig | None = None,
+        allow: List[str] | None = None,
+        deny: List[str] | None = None,
+        status: int = 403,
+    ) -> None: ...
+    def set_trusted_proxies(self, cidrs: List[str]) -> None: ...
+    def set_auto_etag(self, max_size: int = 1048576) -> None: ...
+    def set_response_cache(self, cache: ResponseCache) -> None: ...
+    def set_debug_headers(self, enabled: bool) -> None: ...
+    def set_404_handler(self, handler: FunctionInfo) -> None: ...
+    def set_405_handler(self, handler: FunctionInfo) -> None: ...
+    def set_static_dir(self, url_prefix: str, directory: str, cache_control: str = "public, max-age=3600") -> None: ...
+    def enable_sessions(
+        self,
+        secret_key: str,
+        cookie_name: str = "session",
+        max_age: int = 86400,
+        same_site: str = "lax",
+        backend: Any = None,
+        old_keys: List[str] | None = None,
+        secure: bool = False,
+        redis_url: str = "redis://localhost:6379",
+    ) -> None: ...
+    def enable_rate_limit(
+        self,
+        limit: int,
+        window_secs: int,
+        key: str = "ip",
+        backend: str | None = None,
+        exempt_paths: List[str] | None = None,
+    ) -> None: ...
+    def enable_security_headers(
+        self,
+        hsts_max_age: int | None = 31536000,
+        csp: str | None = None,
+        frame_options: str | None = "DENY",
+        nosniff: bool = True,
+        referrer_policy: str | None = "strict-origin-when-cross-origin",
+        force_hsts: bool = False,
+    ) -> None: ...
+    def remove_route(self, path: str, method: str) -> bool: ...
+
+class ResponseCache:
+    def __init__(self, backend: str = "memory", redis_url: str = "redis://localhost:6379", max_entries: int = 10000) -> None: ...
+    def invalidate_prefix(self, prefix: str) -> int: ...
+
+class MemoryStore:
+    max_entries: int
+    default_ttl: int | None
+
+    def __init__(self, max_entries: int = 10000, default_ttl: int | None = None) -> None: ...
+    def get(self, key: str) -> Any | None: ...
+    def set(self, key: str, value: Any, ttl: int | None = None) -> None: ...
+    def set_nx(self, key: str, value: Any, ttl: int | None = None) -> bool: ...
+    def delete(self, key: str) -> bool: ...
+    def delete_startswith(self, prefix: str) -> int: ...
+    def exists(self, key: str) -> bool: ...
+    def get_ttl(self, key: str) -> int: ...
+    def incr(self, key: str, amount: int = 1) -> int: ...
+    def keys(self) -> List[str]: ...
+    def clear(self) -> None: ...
+    def __len__(self) -> int: ...
+
+class BodyStream:
+    """
+    Async iterator over the chunks of a request body, as they arrive.
+    """
+
+    def __aiter__(self) -> BodyStream: ...
+    async def __anext__(self) -> bytes: ...
 
 class Route:
     path: str
     function: FunctionInfo
     method: str
+    trailing_slash: str | None
+    deprecated: bool
+    sunset_date: str | None
+    etag: bool | None
+    include_in_schema: bool
+    tags: List[str]
+    summary: str | None
+    description: str | None
+    operation_id: str | None
+    stream_body: bool
 
+    @staticmethod
+    def new_deprecated(path: str, function: FunctionInfo, method: str, sunset: str | None = None) -> Route: ...
+    def deprecation_headers(self) -> List[Tuple[str, str]]: ...
+    def cache(self, ttl: int = 60, key: str = "method+path+query", vary_headers: List[str] | None = None) -> None: ...
+    def rate_limit(self, limit: int, window_secs: int) -> None: ...
+    def ip_filter(self, allow: List[str] | None = None, deny: List[str] | None = None, status: int = 403) -> None: ...
     def matches(self, path: str, method: str) -> str: ...
     def clone_route(self) -> Route: ...
     def update_path(self, new_path: str) -> None: ...
@@ -204,23 +475,38 @@ class Route:
     def is_valid(self) -> bool: ...
     def get_path_parans(self) -> List[str]: ...
     def has_parameters(self) -> bool: ...
+    def has_wildcard(self) -> bool: ...
+    def openapi_path(self) -> str: ...
     def normalized_path(self) -> str: ...
     def same_handler(self, other: Route) -> bool: ...
 
 class Router:
     routes: List[Route]
+    trailing_slash: str
 
     def add_route(self, route: Route) -> None: ...
     def remove_route(self, path: str, method: str) -> bool: ...
     def get_route(self, path: str, method) -> Route | None: ...
     def get_routes_by_path(self, path: str) -> List[Route]: ...
     def get_routes_by_method(self, method: str) -> List[Route]: ...
+    def get_routes_by_tag(self, tag: str) -> List[Route]: ...
     def extend_route(self, routes: List[Route]) -> None: ...
+    def include_router(self, other: Router, prefix: str) -> None: ...
+    def update_base_path(self, new_path: str) -> None: ...
+    def find_matching_route(self, path: str, method: str) -> Route | None: ...
+    def cache_stats(self) -> Dict[str, int]: ...
+    def print_routes(self) -> str: ...
+    def route_summary(self) -> List[Tuple[str, str, bool]]: ...
 
 @dataclass
 class SocketHeld:
     socket: Any
 
+    def __init__(self, ip: str, port: int) -> None: ...
+    @staticmethod
+    def unix(path: str, permissions: int | None = None) -> SocketHeld: ...
+    def try_clone(self) -> SocketHeld: ...
+
 @dataclass
 class WebSocketSession:
     sender: Callable[[str], None]
@@ -228,11 +514,26 @@ class WebSocketSession:
     is_closed: bool
 
     def send(self, message: str) -> None: ...
+    def send_json(self, data: Any) -> None: ...
+    async def receive(self, timeout_secs: float | None = None) -> str | bytes | None: ...
+    def set_metadata(self, key: str, value: str) -> None: ...
+    def get_metadata(self, key: str) -> str | None: ...
+    def get_all_metadata(self) -> Dict[str, str]: ...
 
 @dataclass
 class WebsocketRoute:
     path: str
     handler: Callable[[WebSocketSession], None]
+    rate_limit: Tuple[int, float] | None
+    close_on_rate_limit: bool
+
+    def __init__(
+        self,
+        path: str,
+        handler: Callable[[WebSocketSession], None],
+        rate_limit: Tuple[int, float] | None = None,
+        close_on_rate_limit: bool = False,
+    ) -> None: ...
 
 @dataclass
 class WebsocketRouter:
@@ -250,10 +551,21 @@ class Header:
     headers: Dict[str, str]
 
     def get(self, key: str) -> str | None: ...
+    def get_all(self, key: str) -> List[str]: ...
     def set(self, key: str, value: str) -> None: ...
+    def set_all(self, key: str, values: List[str]) -> None: ...
     def append(self, key: str, value: str) -> None: ...
     def update(self, headers: Dict[str, str]) -> None: ...
     def get_headers(self) -> Dict[str, str]: ...
+    def update_from_dict(self, headers: Dict[str, str]) -> None: ...
+    def merge(self, other: Header) -> Header: ...
+    def merge_in_place(self, other: Header) -> None: ...
+    def keys(self) -> List[str]: ...
+    def values(self) -> List[str]: ...
+    def items(self) -> List[Tuple[str, str]]: ...
+    def __iter__(self) -> Iterator[str]: ...
+    def __len__(self) -> int: ...
+    def __delitem__(self, key: str) -> None: ...
 
 @dataclass
 class Response:
@@ -263,46 +575,142 @@ class Response:
     description: str
     file_path: str | None
     context_id: str
+    state: Dict[str, Any] | None
+
+    def __init__(self, status_code: int, headers: Header | Dict[str, str], description: str | bytes, response_type: str | None = None) -> None: ...
+    @staticmethod
+    def json(obj: Any, status: int = 200, decimal_as_float: bool = False) -> Response: ...
+    @staticmethod
+    def from_json(data: Any, status_code: int = 200) -> Response: ...
+    @staticmethod
+    def file(path: str, status_code: int = 200, content_type: str | None = None) -> Response: ...
+    @staticmethod
+    def redirect(url: str, status: int | None = None, permanent: bool = False) -> Response: ...
+    def set_cookie(
+        self,
+        key: str,
+        value: str,
+        max_age: int | None = None,
+        expires: str | int | datetime | None = None,
+        path: str | None = "/",
+        domain: str | None = None,
+        secure: bool = False,
+        httponly: bool = False,
+        samesite: str | None = None,
+    ) -> None: ...
+    def delete_cookie(self, key: str, path: str | None = "/", domain: str | None = None) -> None: ...
 
 @dataclass
 class QueryParams:
     queries: Dict[str, List[str]]
 
-    def to_dict(self) -> Dict[str, str]: ...
+    def get(self, key: str, default: str | None = None) -> str | None: ...
+    def get_first(self, key: str) -> str | None: ...
+    def get_all(self, key: str) -> List[str]: ...
+    def get_int(self, key: str, default: int | None = None) -> int | None: ...
+    def get_float(self, key: str, default: float | None = None) -> float | None: ...
+    def get_bool(self, key: str, default: bool | None = None) -> bool | None: ...
+    def get_list(self, key: str, sep: str = ",") -> List[str]: ...
+    def keys(self) -> List[str]: ...
+    def values(self) -> List[str]: ...
+    def items(self) -> List[Tuple[str, str]]: ...
+    def __iter__(self) -> Iterator[str]: ...
+    def __len__(self) -> int: ...
+    def __contains__(self, key: str) -> bool: ...
+    def to_dict(self, flat: bool = False) -> Dict[str, List[str]] | Dict[str, str]: ...
 
 @dataclass
 class UploadedFile:
     name: str
     content_type: str
-    path: str
+    path: str | None
     size: int
     content: bytes
     filename: str
 
+    def read(self, size: int = -1) -> bytes: ...
+    def save(self, dest: str | os.PathLike) -> None: ...
+
 @dataclass
 class BodyData:
     json: bytes
     files: List[UploadedFile]
+    form: List[Tuple[str, str]]
+    raw: bytes
+
+@dataclass
+class Url:
+    scheme: str
+    host: str
+    port: int | None
+    path: str
+    query: str
+    fragment: str
+
+    def __init__(self, scheme: str, host: str, path: str, port: int | None = None, query: str = "", fragment: str = "") -> None: ...
+    @staticmethod
+    def from_request(request: Request) -> Url: ...
+    @property
+    def netloc(self) -> str: ...
+    def to_string(self) -> str: ...
 
 @dataclass
 class Request:
     path: str
+    url: Url
     query_params: QueryParams
     headers: Header
     path_params: Dict[str, str]
     body: BodyData
     method: str
     remote_addr: str
+    client_ip: str
     timestamp: float
     context_id: str
-
+    session: Dict[str, Any] | None
+    csrf_token: str | None
+    state: Dict[str, Any]
+
+    @property
+    def accepts_json(self) -> bool: ...
+    @property
+    def accepts_html(self) -> bool: ...
+    def accepts(self, *content_types: str) -> str | None: ...
+    def accept_language(self, best_of: List[str] | None = None) -> str | None: ...
+    @property
+    def base_url(self) -> str: ...
+    @property
+    def full_url(self) -> str: ...
+    @property
+    def cookies(self) -> Dict[str, str]: ...
+    def form(self) -> Dict[str, str | List[str]]: ...
+    def raw_body(self) -> bytes: ...
+    def streaming_body(self) -> BodyStream: ...
     def json(self) -> Dict[str, Any]: ...
     def set_body(self, body: BodyData) -> None: ...
 
+@dataclass
+class CsrfConfig:
+    secret: str
+    cookie_name: str = "csrftoken"
+    header_name: str = "X-CSRF-Token"
+    form_field: str = "csrf_token"
+
+@dataclass
+class IpFilterConfig:
+    cidrs: List[str] = field(default_factory=list)
+    mode: str = "allowlist"
+    allow: List[str] = field(default_factory=list)
+    deny: List[str] = field(default_factory=list)
+    status: int = 403
+
 @dataclass
 class MiddlewareConfig:
     priority: int = 0
     is_conditional: bool = True
+    path_patterns: List[str] = field(default_factory=list)
+    exclude_patterns: List[str] = field(default_factory=list)
+    timeout_ms: int | None = None
 
     @staticmethod
     def default(self) -> MiddlewareConfig: ...
@@ -321,17 +729,60 @@ class DatabaseConfig:
     idle_timeout: int = 30
 
     options: Dict[str, Any] = {}
+    isolation_level: str | None = None
+    slow_query_threshold_ms: int | None = None
+    log_params: bool = False
+    sqlite_pragmas: Dict[str, str] | None = None
+    allow_unknown_pragmas: bool = False
+    warmup: bool = False
 
 @dataclass
 class DatabaseTransaction:
+    isolation_level: str | None
+
     def execute(self, query: str, params: List[Any]) -> int: ...
     def fetch_all(self, query: str, params: List[Any]) -> List[Dict[str, Any]]: ...
+    def execute_many(self, queries: List[Tuple[str, List[Any]]]) -> List[int]: ...
+    def fetch_page(self, query: str, params: List[Any], page: int, page_size: int) -> Dict[str, Any]: ...
+    def fetch_one(self, query: str, params: List[Any]) -> Dict[str, Any]: ...
+    def fetch_optional(self, query: str, params: List[Any]) -> Dict[str, Any] | None: ...
     def stream_data(self, query: str, params: List[Any], chunk_size: int) -> Dict[str, Any]: ...
     def bulk_change(self, query: str, params: List[List[Any]], batch_size: int) -> int | None: ...
     def commit(self) -> None: ...
     def rollback(self) -> None: ...
     def __del__(self) -> None: ...
-    def __enter__(self) -> None: ...
-    def __exit__(self, _exc_type, _exc_value, _traceback) -> None: ...
+    def __enter__(self) -> DatabaseTransaction: ...
+    def __exit__(self, _exc_type, _exc_value, _traceback) -> bool: ...
 
 def get_session_database(context_id: str) -> DatabaseTransaction: ...
+def check_database_health() -> bool: ...
+def database_pool_status() -> Dict[str, int]: ...
+
+class PostgresNotifier:
+    def listen(self, channel: str, callback: Callable[[str], None]) -> None: ...
+    def unlisten(self, channel: str) -> bool: ...
+
+def hash_password(plain: str, algorithm: str = "argon2", cost: int | None = None) -> str: ...
+def verify_password(plain: str, hashed: str) -> bool: ...
+def create_token(claims: Dict[str, Any], secret: str, algorithm: str = "HS256", expires_in_secs: int | None = None) -> str: ...
+def verify_token(token: str, secret: str, algorithm: str = "HS256", verify_exp: bool = True, leeway: int = 0) -> Dict[str, Any]: ...
+
+class ExpiredSignatureError(ValueError): ...
+class InvalidTokenError(RuntimeError): ...
+class InvalidSignatureError(InvalidTokenError): ...
+def encrypt_aes_gcm(plaintext: bytes, key: bytes) -> bytes: ...
+def decrypt_aes_gcm(ciphertext: bytes, key: bytes) -> bytes: ...
+def generate_token(length: int = 32, alphabet: str | None = None) -> str: ...
+def generate_hex_token(bytes: int = 32) -> str: ...
+
+class MetricsCounter:
+    """
+    A counter reported by the metrics endpoint next to the request metrics.
+    """
+
+    name: str
+    labels: List[str]
+
+    def __init__(self, name: str, help: str, labels: List[str] = []) -> None: ...
+    def inc(self, amount: float = 1.0, labels: Dict[str, str] | None = None) -> None: ...
+    def get(self, labels: Dict[str, str] | None = None) -> float: ...
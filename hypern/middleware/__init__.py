@@ -3,6 +3,8 @@ from .cors import CORSMiddleware
 from .limit import RateLimitMiddleware, StorageBackend, RedisBackend, InMemoryBackend
 from .compress import CompressionMiddleware
 from .cache import EdgeCacheMiddleware
+from .jwt import JWTAuthMiddleware
+from hypern.hypern import CsrfConfig, IpFilterConfig
 
 __all__ = [
     "Middleware",
@@ -13,5 +15,8 @@ __all__ = [
     "InMemoryBackend",
     "CompressionMiddleware",
     "EdgeCacheMiddleware",
+    "JWTAuthMiddleware",
     "MiddlewareConfig",
+    "CsrfConfig",
+    "IpFilterConfig",
 ]
@@ -0,0 +1,48 @@
+from typing import Optional
+
+from hypern.hypern import InvalidTokenError, MiddlewareConfig, Request, Response, verify_token
+
+from .base import Middleware
+
+
+class JWTAuthMiddleware(Middleware):
+    """
+    The `JWTAuthMiddleware` class checks the `Authorization: Bearer <token>` header of requests and
+    stores the verified claims in `request.state`. Requests without a valid token get 401.
+    """
+
+    def __init__(
+        self,
+        secret: str,
+        algorithm: str = "HS256",
+        leeway: int = 0,
+        state_key: str = "claims",
+        config: Optional[MiddlewareConfig] = None,
+    ) -> None:
+        super().__init__(config)
+        self.secret = secret
+        self.algorithm = algorithm
+        self.leeway = leeway
+        self.state_key = state_key
+
+    def _unauthorized(self, error: str) -> Response:
+        return Response(
+            status_code=401,
+            headers={"WWW-Authenticate": f'Bearer error="{error}"'},
+            description="Unauthorized",
+        )
+
+    def before_request(self, request: Request):
+        scheme, _, token = (request.headers.get("authorization") or "").partition(" ")
+        if scheme.lower() != "bearer" or not token.strip():
+            return self._unauthorized("invalid_request")
+        try:
+            claims = verify_token(token.strip(), self.secret, self.algorithm, leeway=self.leeway)
+        except (ValueError, InvalidTokenError):
+            # expired tokens raise a ValueError subclass
+            return self._unauthorized("invalid_token")
+        request.state[self.state_key] = claims
+        return request
+
+    def after_request(self, response):
+        return response
@@ -110,7 +110,7 @@ class RateLimitMiddleware(Middleware):
         self.window_size = window_size
 
     def get_request_identifier(self, request: Request):
-        return request.remote_addr
+        return request.client_ip
 
     def before_request(self, request: Request):
         """
@@ -148,7 +148,7 @@ class ConcurrentRequestMiddleware(Middleware):
         self.lock = Lock()
 
     def get_request_identifier(self, request):
-        return request.remote_addr
+        return request.client_ip
 
     def before_request(self, request):
         """
@@ -1,5 +1,6 @@
 # -*- coding: utf-8 -*-
+from .redoc import ReDocUI
 from .schemas import SchemaGenerator
 from .swagger import SwaggerUI
 
-__all__ = ["SchemaGenerator", "SwaggerUI"]
+__all__ = ["SchemaGenerator", "SwaggerUI", "ReDocUI"]
@@ -0,0 +1,3 @@
+from hypern.hypern import ReDocUI
+
+__all__ = ["ReDocUI"]
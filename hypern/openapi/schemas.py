@@ -2,6 +2,7 @@
 from __future__ import annotations
 
 from hypern.hypern import BaseSchemaGenerator, Route as InternalRoute
+from hypern.logging import logger
 import typing
 import orjson
 
@@ -10,28 +11,53 @@ class EndpointInfo(typing.NamedTuple):
     path: str
     http_method: str
     func: typing.Callable[..., typing.Any]
+    deprecated: bool = False
+    # OpenAPI fields set on the route, they replace the docstring ones
+    metadata: dict[str, typing.Any] = {}
 
 
 class SchemaGenerator(BaseSchemaGenerator):
-    def __init__(self, base_schema: dict[str, typing.Any]) -> None:
+    def __init__(self, base_schema: dict[str, typing.Any], strict: bool = False) -> None:
         self.base_schema = base_schema
+        self.strict = strict
 
     def get_endpoints(self, routes: list[InternalRoute]) -> list[EndpointInfo]:
         """
         Given the routes, yields the following information:
 
         - path
-            eg: /users/
+            eg: /users/{id}
         - http_method
             one of 'get', 'post', 'put', 'patch', 'delete', 'options'
         - func
             method ready to extract the docstring
+        - deprecated
+            whether the route is marked as deprecated
         """
         endpoints_info: list[EndpointInfo] = []
 
         for route in routes:
+            if not route.include_in_schema:
+                continue
             method = route.method.lower()
-            endpoints_info.append(EndpointInfo(path=route.path, http_method=method, func=route.function.handler))
+            endpoints_info.append(
+                EndpointInfo(
+                    path=route.openapi_path(),
+                    http_method=method,
+                    func=route.function.handler,
+                    deprecated=route.deprecated,
+                    metadata={
+                        key: value
+                        for key, value in (
+                            ("tags", route.tags),
+                            ("summary", route.summary),
+                            ("description", route.description),
+                            ("operationId", route.operation_id),
+                        )
+                        if value
+                    },
+                )
+            )
         return endpoints_info
 
     def get_schema(self, app) -> dict[str, typing.Any]:
@@ -48,6 +74,13 @@ class SchemaGenerator(BaseSchemaGenerator):
             if endpoint.path not in schema["paths"]:
                 schema["paths"][endpoint.path] = {}
 
-            schema["paths"][endpoint.path][endpoint.http_method] = orjson.loads(parsed)
+            operation = orjson.loads(parsed)
+            operation.update(endpoint.metadata)
+            if endpoint.deprecated:
+                operation["deprecated"] = True
+            schema["paths"][endpoint.path][endpoint.http_method] = operation
 
+        missing = self.missing_refs(schema)
+        if missing:
+            logger.warning(f"OpenAPI document references missing components: {', '.join(missing)}")
         return schema
@@ -20,8 +20,13 @@ def run_processes(
     processes: int,
     max_blocking_threads: int,
     reload: bool = True,
+    unix_socket: str | None = None,
+    unix_socket_permissions: int | None = None,
 ) -> List[Process]:
-    socket = SocketHeld(host, port)
+    if unix_socket:
+        socket = SocketHeld.unix(unix_socket, permissions=unix_socket_permissions)
+    else:
+        socket = SocketHeld(host, port)
 
     process_pool = init_processpool(
         server,
@@ -50,7 +55,10 @@ def run_processes(
 
         observer.start()
 
-    logger.info(f"Server started at http://{host}:{port}")
+    if unix_socket:
+        logger.info(f"Server started at unix:{unix_socket}")
+    else:
+        logger.info(f"Server started at http://{host}:{port}")
     logger.info("Press Ctrl + C to stop")
 
     try:
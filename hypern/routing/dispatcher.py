@@ -24,6 +24,21 @@ def is_async_callable(obj: typing.Any) -> bool:
     return asyncio.iscoroutinefunction(obj) or (callable(obj) and asyncio.iscoroutinefunction(obj.__call__))
 
 
+def get_parameter_names(handler: typing.Callable) -> typing.List[str]:
+    """
+    Names of the handler parameters after the request, used to inject dependencies by name.
+    A **kwargs parameter is reported as "inject" so the handler still receives every dependency.
+    """
+    names = []
+    parameters = list(inspect.signature(handler).parameters.values())[1:]
+    for parameter in parameters:
+        if parameter.kind == inspect.Parameter.VAR_KEYWORD:
+            names.append("inject")
+        elif parameter.kind != inspect.Parameter.VAR_POSITIONAL:
+            names.append(parameter.name)
+    return names
+
+
 async def run_in_threadpool(func: typing.Callable, *args, **kwargs):
     if kwargs:  # pragma: no cover
         # run_sync doesn't accept 'kwargs', so bind them in here
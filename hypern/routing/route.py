@@ -13,7 +13,7 @@ from hypern.datastructures import HTTPMethod
 from hypern.hypern import FunctionInfo, Request, Router
 from hypern.hypern import Route as InternalRoute
 
-from .dispatcher import dispatch
+from .dispatcher import dispatch, get_parameter_names
 
 
 def get_field_type(field):
@@ -147,11 +147,35 @@ class Route:
         *,
         name: str | None = None,
         tags: List[str] | None = None,
+        trailing_slash: str | None = None,
+        deprecated: bool = False,
+        sunset_date: str | None = None,
+        etag: bool | None = None,
+        cache: Dict[str, Any] | None = None,
+        rate_limit: Dict[str, int] | None = None,
+        ip_filter: Dict[str, Any] | None = None,
+        include_in_schema: bool = True,
+        summary: str | None = None,
+        description: str | None = None,
+        operation_id: str | None = None,
+        stream_body: bool = False,
     ) -> None:
         self.path = path
         self.endpoint = endpoint
         self.tags = tags or ["Default"]
         self.name = name
+        self.trailing_slash = trailing_slash
+        self.deprecated = deprecated
+        self.sunset_date = sunset_date
+        self.etag = etag
+        self.cache = cache
+        self.rate_limit = rate_limit
+        self.ip_filter = ip_filter
+        self.include_in_schema = include_in_schema
+        self.summary = summary
+        self.description = description
+        self.operation_id = operation_id
+        self.stream_body = stream_body
 
         self.http_methods = {
             "GET": HTTPMethod.GET,
@@ -213,8 +237,29 @@ class Route:
 
     def make_internal_route(self, path, handler, method) -> InternalRoute:
         is_async = asyncio.iscoroutinefunction(handler)
-        func_info = FunctionInfo(handler=handler, is_async=is_async)
-        return InternalRoute(path=path, function=func_info, method=method)
+        func_info = FunctionInfo(handler=handler, is_async=is_async, parameter_names=get_parameter_names(handler))
+        route = InternalRoute(
+            path=path,
+            function=func_info,
+            method=method,
+            trailing_slash=self.trailing_slash,
+            deprecated=self.deprecated,
+            sunset_date=self.sunset_date,
+            etag=self.etag,
+            include_in_schema=self.include_in_schema,
+            tags=self.tags,
+            summary=self.summary,
+            description=self.description,
+            operation_id=self.operation_id,
+        )
+        if self.cache is not None:
+            route.cache(**self.cache)
+        if self.rate_limit is not None:
+            route.rate_limit(**self.rate_limit)
+        if self.ip_filter is not None:
+            route.ip_filter(**self.ip_filter)
+        route.stream_body = self.stream_body
+        return route
 
     def __call__(self, app, *args: Any, **kwds: Any) -> Any:
         router = Router(self.path)
@@ -6,6 +6,36 @@ from base64 import b64encode, b64decode
 
 import typing
 
+from hypern.hypern import (
+    ExpiredSignatureError,
+    InvalidSignatureError,
+    InvalidTokenError,
+    create_token,
+    decrypt_aes_gcm,
+    encrypt_aes_gcm,
+    generate_hex_token,
+    generate_token,
+    hash_password,
+    verify_password,
+    verify_token,
+)
+
+__all__ = [
+    "EDEngine",
+    "AESEngine",
+    "hash_password",
+    "verify_password",
+    "create_token",
+    "verify_token",
+    "ExpiredSignatureError",
+    "InvalidSignatureError",
+    "InvalidTokenError",
+    "encrypt_aes_gcm",
+    "decrypt_aes_gcm",
+    "generate_token",
+    "generate_hex_token",
+]
+
 
 class EDEngine(ABC):
     @abstractmethod
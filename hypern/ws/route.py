@@ -1,4 +1,4 @@
-from typing import Callable, Optional
+from typing import Callable, Optional, Tuple
 
 from hypern.hypern import WebsocketRoute as WebsocketRouteInternal, WebSocketSession
 
@@ -8,9 +8,16 @@ class WebsocketRoute:
         self.routes = []
         self._disconnect_handler: Optional[Callable] = None
 
-    def on(self, path):
+    def on(self, path, rate_limit: Optional[Tuple[int, float]] = None, close_on_rate_limit: bool = False):
+        """
+        Register a handler for `path`.
+
+        `rate_limit` is `(max_messages, window_secs)` per connection, messages over it are dropped
+        after an error message, or the connection is closed with `close_on_rate_limit`.
+        """
+
         def wrapper(func):
-            self.routes.append(WebsocketRouteInternal(path, func))
+            self.routes.append(WebsocketRouteInternal(path, func, rate_limit=rate_limit, close_on_rate_limit=close_on_rate_limit))
             return func
 
         return wrapper
@@ -0,0 +1,42 @@
+"""Compare Response.json with json.dumps on a ~1MB nested payload.
+
+Usage: python scripts/bench_json.py [rounds]
+"""
+
+import datetime
+import json
+import sys
+import timeit
+import uuid
+from decimal import Decimal
+
+from hypern.hypern import Response
+
+
+def build_payload(size: int = 1024 * 1024):
+    item = {
+        "id": str(uuid.uuid4()),
+        "created_at": datetime.datetime(2024, 1, 1, 12, 30),
+        "price": Decimal("19.99"),
+        "tags": ["a", "b", "c"],
+        "owner": {"name": "user", "active": True, "score": 4.5},
+    }
+    item_size = len(json.dumps(item, default=str))
+    return {"items": [dict(item) for _ in range(size // item_size)]}
+
+
+def main():
+    rounds = int(sys.argv[1]) if len(sys.argv) > 1 else 20
+    payload = build_payload()
+    print(f"payload: {len(json.dumps(payload, default=str)) / 1024:.0f} KB, {rounds} rounds")
+
+    python_time = timeit.timeit(lambda: json.dumps(payload, default=str), number=rounds)
+    rust_time = timeit.timeit(lambda: Response.json(payload), number=rounds)
+
+    print(f"json.dumps:    {python_time / rounds * 1000:.2f} ms")
+    print(f"Response.json: {rust_time / rounds * 1000:.2f} ms")
+    print(f"speedup:       {python_time / rust_time:.2f}x")
+
+
+if __name__ == "__main__":
+    main()
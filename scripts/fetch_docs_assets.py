@@ -0,0 +1,29 @@
+"""Download the Swagger UI and ReDoc bundles served by `docs_assets_mode="embedded"`.
+
+The build itself never downloads anything, run this once before building to embed them.
+The files land in src/openapi/bundles, where build.rs copies them from.
+
+Usage: python scripts/fetch_docs_assets.py
+"""
+
+import pathlib
+import urllib.request
+
+ASSETS = {
+    "swagger-ui.css": "https://cdn.jsdelivr.net/npm/swagger-ui-dist@5.9.0/swagger-ui.css",
+    "swagger-ui-bundle.js": "https://cdn.jsdelivr.net/npm/swagger-ui-dist@5.9.0/swagger-ui-bundle.js",
+    "redoc.standalone.js": "https://cdn.jsdelivr.net/npm/redoc@2.1.3/bundles/redoc.standalone.js",
+}
+
+
+def main():
+    directory = pathlib.Path(__file__).resolve().parent.parent / "src" / "openapi" / "bundles"
+    directory.mkdir(parents=True, exist_ok=True)
+    for name, url in ASSETS.items():
+        with urllib.request.urlopen(url) as response:
+            (directory / name).write_bytes(response.read())
+        print(f"{name} <- {url}")
+
+
+if __name__ == "__main__":
+    main()
@@ -1,5 +1,5 @@
 use super::background_task::BackgroundTask;
-use pyo3::prelude::*;
+use pyo3::{prelude::*, types::PyDict};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use tokio::task::JoinHandle;
@@ -141,6 +141,36 @@ impl BackgroundTasks {
         }
     }
 
+    // Results of every finished task by id, as dicts. Collected tasks are forgotten and
+    // unfinished ones are left running
+    fn get_all_results(&self, py: Python) -> PyResult<Py<PyDict>> {
+        let mut running_tasks = self.running_tasks.lock().unwrap();
+        let runtime = get_runtime();
+        let results = PyDict::new(py);
+
+        let finished: Vec<String> = running_tasks
+            .iter()
+            .filter(|(_, handle)| handle.is_finished())
+            .map(|(task_id, _)| task_id.clone())
+            .collect();
+        for task_id in finished {
+            let Some(handle) = running_tasks.remove(&task_id) else {
+                continue;
+            };
+            let result = runtime.block_on(handle).unwrap_or_else(|_| TaskResult {
+                success: false,
+                result: None,
+                error: Some("Task was cancelled".to_string()),
+            });
+            let entry = PyDict::new(py);
+            entry.set_item("success", result.success)?;
+            entry.set_item("result", result.result)?;
+            entry.set_item("error", result.error)?;
+            results.set_item(task_id, entry)?;
+        }
+        Ok(results.into())
+    }
+
     fn is_task_complete(&self, task_id: &str) -> PyResult<bool> {
         let running_tasks = self.running_tasks.lock().unwrap();
         if let Some(handle) = running_tasks.get(task_id) {
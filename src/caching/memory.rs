@@ -0,0 +1,268 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use dashmap::{mapref::entry::Entry as MapEntry, DashMap};
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::instants::get_runtime;
+
+// how often the background task drops expired entries
+const PURGE_INTERVAL: Duration = Duration::from_secs(30);
+
+struct Entry {
+    value: PyObject,
+    expires: Option<Instant>,
+    // clock tick of the last read or write, the smallest one is evicted first
+    last_access: AtomicU64,
+}
+
+impl Entry {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+}
+
+struct Store {
+    entries: DashMap<String, Entry>,
+    clock: AtomicU64,
+    max_entries: usize,
+    default_ttl: Option<u64>,
+}
+
+impl Store {
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn entry(&self, value: PyObject, ttl: Option<u64>) -> Entry {
+        Entry {
+            value,
+            expires: ttl
+                .or(self.default_ttl)
+                .map(|ttl| Instant::now() + Duration::from_secs(ttl)),
+            last_access: AtomicU64::new(self.tick()),
+        }
+    }
+
+    fn purge_expired(&self) {
+        let now = Instant::now();
+        self.entries.retain(|_, entry| !entry.is_expired(now));
+    }
+
+    // Drop expired entries, then the least recently used ones, until the store fits
+    fn evict(&self) {
+        if self.entries.len() <= self.max_entries {
+            return;
+        }
+        self.purge_expired();
+        while self.entries.len() > self.max_entries {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|entry| entry.last_access.load(Ordering::Relaxed))
+                .map(|entry| entry.key().clone());
+            match oldest {
+                Some(key) => self.entries.remove(&key),
+                None => break,
+            };
+        }
+    }
+
+    // Remove `key` if it is still expired, a concurrent set may have replaced it meanwhile
+    fn remove_expired(&self, key: &str) {
+        let now = Instant::now();
+        self.entries.remove_if(key, |_, entry| entry.is_expired(now));
+    }
+}
+
+/// In-process key/value store with per-entry expiry and LRU eviction
+#[pyclass]
+#[derive(Clone)]
+pub struct MemoryStore {
+    // shared with the purge task, which stops once the store is dropped
+    store: Arc<Store>,
+}
+
+#[pymethods]
+impl MemoryStore {
+    #[new]
+    #[pyo3(signature = (max_entries=10_000, default_ttl=None))]
+    pub fn new(max_entries: usize, default_ttl: Option<u64>) -> PyResult<Self> {
+        if max_entries == 0 {
+            return Err(PyValueError::new_err("max_entries must be greater than 0"));
+        }
+        if default_ttl == Some(0) {
+            return Err(PyValueError::new_err("default_ttl must be greater than 0"));
+        }
+        let store = Arc::new(Store {
+            entries: DashMap::new(),
+            clock: AtomicU64::new(0),
+            max_entries,
+            default_ttl,
+        });
+
+        let weak = Arc::downgrade(&store);
+        get_runtime().spawn(async move {
+            let mut interval = tokio::time::interval(PURGE_INTERVAL);
+            // the first tick completes immediately
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                match weak.upgrade() {
+                    Some(store) => store.purge_expired(),
+                    None => break,
+                }
+            }
+        });
+
+        Ok(Self { store })
+    }
+
+    #[getter]
+    pub fn max_entries(&self) -> usize {
+        self.store.max_entries
+    }
+
+    #[getter]
+    pub fn default_ttl(&self) -> Option<u64> {
+        self.store.default_ttl
+    }
+
+    pub fn get(&self, py: Python, key: &str) -> Option<PyObject> {
+        let value = {
+            let entry = self.store.entries.get(key)?;
+            if entry.is_expired(Instant::now()) {
+                None
+            } else {
+                entry.last_access.store(self.store.tick(), Ordering::Relaxed);
+                Some(entry.value.clone_ref(py))
+            }
+        };
+        if value.is_none() {
+            self.store.remove_expired(key);
+        }
+        value
+    }
+
+    #[pyo3(signature = (key, value, ttl=None))]
+    pub fn set(&self, key: String, value: PyObject, ttl: Option<u64>) {
+        let entry = self.store.entry(value, ttl);
+        self.store.entries.insert(key, entry);
+        self.store.evict();
+    }
+
+    /// Set `key` only when it is missing or expired, returns whether it was set
+    #[pyo3(signature = (key, value, ttl=None))]
+    pub fn set_nx(&self, key: String, value: PyObject, ttl: Option<u64>) -> bool {
+        let inserted = match self.store.entries.entry(key) {
+            MapEntry::Occupied(mut occupied) => {
+                if occupied.get().is_expired(Instant::now()) {
+                    occupied.insert(self.store.entry(value, ttl));
+                    true
+                } else {
+                    false
+                }
+            }
+            MapEntry::Vacant(vacant) => {
+                vacant.insert(self.store.entry(value, ttl));
+                true
+            }
+        };
+        if inserted {
+            self.store.evict();
+        }
+        inserted
+    }
+
+    pub fn delete(&self, key: &str) -> bool {
+        self.store
+            .entries
+            .remove(key)
+            .is_some_and(|(_, entry)| !entry.is_expired(Instant::now()))
+    }
+
+    /// Remove every key starting with `prefix`, returns how many were removed
+    pub fn delete_startswith(&self, prefix: &str) -> usize {
+        let now = Instant::now();
+        let mut removed = 0;
+        self.store.entries.retain(|key, entry| {
+            let matched = key.starts_with(prefix);
+            removed += (matched && !entry.is_expired(now)) as usize;
+            !matched
+        });
+        removed
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.store
+            .entries
+            .get(key)
+            .is_some_and(|entry| !entry.is_expired(Instant::now()))
+    }
+
+    /// Seconds left before `key` expires, -1 when it never expires and -2 when it is missing
+    pub fn get_ttl(&self, key: &str) -> i64 {
+        let now = Instant::now();
+        match self.store.entries.get(key) {
+            Some(entry) if !entry.is_expired(now) => match entry.expires {
+                Some(expires) => (expires - now).as_secs_f64().ceil() as i64,
+                None => -1,
+            },
+            _ => -2,
+        }
+    }
+
+    /// Add `amount` to an integer value, a missing key starts from 0
+    #[pyo3(signature = (key, amount=1))]
+    pub fn incr(&self, py: Python, key: String, amount: i64) -> PyResult<i64> {
+        let now = Instant::now();
+        let value = match self.store.entries.entry(key) {
+            MapEntry::Occupied(mut occupied) if !occupied.get().is_expired(now) => {
+                let entry = occupied.get_mut();
+                let value = entry.value.extract::<i64>(py)? + amount;
+                entry.value = value.into_py(py);
+                entry.last_access.store(self.store.tick(), Ordering::Relaxed);
+                value
+            }
+            MapEntry::Occupied(mut occupied) => {
+                occupied.insert(self.store.entry(amount.into_py(py), None));
+                amount
+            }
+            MapEntry::Vacant(vacant) => {
+                vacant.insert(self.store.entry(amount.into_py(py), None));
+                amount
+            }
+        };
+        self.store.evict();
+        Ok(value)
+    }
+
+    /// Keys that have not expired, in no particular order
+    pub fn keys(&self) -> Vec<String> {
+        let now = Instant::now();
+        self.store
+            .entries
+            .iter()
+            .filter(|entry| !entry.is_expired(now))
+            .map(|entry| entry.key().clone())
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        self.store.entries.clear();
+    }
+
+    pub fn __len__(&self) -> usize {
+        let now = Instant::now();
+        self.store
+            .entries
+            .iter()
+            .filter(|entry| !entry.is_expired(now))
+            .count()
+    }
+}
@@ -0,0 +1,53 @@
+use std::fmt;
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+use redis::{aio::ConnectionManager, Client, RedisResult};
+use tokio::sync::OnceCell;
+
+// A redis client sharing one multiplexed connection, which reconnects by itself after failures
+pub struct RedisPool {
+    client: Client,
+    manager: OnceCell<ConnectionManager>,
+}
+
+impl fmt::Debug for RedisPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedisPool")
+            .field("client", &self.client)
+            .finish()
+    }
+}
+
+impl RedisPool {
+    pub fn open(url: &str) -> PyResult<Self> {
+        let client = Client::open(url)
+            .map_err(|e| PyValueError::new_err(format!("Invalid redis url: {}", e)))?;
+        Ok(Self {
+            client,
+            manager: OnceCell::new(),
+        })
+    }
+
+    // For blocking calls made from python, outside of the server runtime
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    // The shared connection, opened on first use so it belongs to the runtime serving requests
+    pub async fn connection(&self) -> RedisResult<ConnectionManager> {
+        self.manager
+            .get_or_try_init(|| ConnectionManager::new(self.client.clone()))
+            .await
+            .cloned()
+    }
+
+    pub async fn ping(&self) -> bool {
+        match self.connection().await {
+            Ok(mut connection) => redis::cmd("PING")
+                .query_async::<_, String>(&mut connection)
+                .await
+                .is_ok(),
+            Err(_) => false,
+        }
+    }
+}
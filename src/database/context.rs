@@ -1,7 +1,7 @@
 use dashmap::DashMap;
 use lazy_static::lazy_static;
 use once_cell::sync::OnceCell;
-use pyo3::prelude::*;
+use pyo3::{exceptions::PyRuntimeError, prelude::*, types::PyDict};
 
 use super::sql::{connection::DatabaseConnection, transaction::DatabaseTransaction};
 
@@ -36,3 +36,23 @@ pub fn get_sql_connect() -> Option<&'static DatabaseConnection> {
 pub fn set_sql_connect(connection: DatabaseConnection) {
     let _ = SQL_DATABASE_CONNECTION.set(connection);
 }
+
+/// Whether the configured database answers `SELECT 1` within 2 seconds
+#[pyfunction]
+pub fn check_database_health(py: Python) -> PyResult<bool> {
+    let connection = get_sql_connect()
+        .ok_or_else(|| PyRuntimeError::new_err("Database connection is not configured"))?;
+    connection.check_health(py)
+}
+
+/// Connections open in the database pool, as `{"size": ..., "idle": ...}`
+#[pyfunction]
+pub fn database_pool_status(py: Python) -> PyResult<Py<PyDict>> {
+    let connection = get_sql_connect()
+        .ok_or_else(|| PyRuntimeError::new_err("Database connection is not configured"))?;
+    let (size, idle) = connection.pool_status();
+    let status = PyDict::new(py);
+    status.set_item("size", size)?;
+    status.set_item("idle", idle)?;
+    Ok(status.into())
+}
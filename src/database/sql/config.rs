@@ -1,9 +1,9 @@
-use pyo3::prelude::*;
+use pyo3::{exceptions::PyValueError, prelude::*};
 use sqlx::{
     mysql::{MySqlConnectOptions, MySqlPoolOptions},
     postgres::{PgConnectOptions, PgPoolOptions},
     sqlite::{SqliteConnectOptions, SqlitePoolOptions},
-    ConnectOptions, Pool,
+    ConnectOptions, Executor, Pool,
 };
 use std::collections::HashMap;
 use std::time::Duration;
@@ -38,11 +38,105 @@ pub struct DatabaseConfig {
 
     // Additional database-specific options
     pub options: Option<HashMap<String, String>>,
+
+    // Default isolation level for every transaction opened from this connection
+    pub isolation_level: Option<String>,
+
+    // Queries slower than this are logged as warnings
+    pub slow_query_threshold_ms: Option<u64>,
+
+    // Include parameter values in slow query logs
+    pub log_params: bool,
+
+    // PRAGMA statements run on every new SQLite connection
+    pub sqlite_pragmas: HashMap<String, String>,
+
+    // Accept pragma names outside of SQLITE_PRAGMAS
+    pub allow_unknown_pragmas: bool,
+
+    // Open min_connections before the server takes traffic
+    pub warmup: bool,
+}
+
+const SQL_ISOLATION_LEVELS: [&str; 3] = ["READ COMMITTED", "REPEATABLE READ", "SERIALIZABLE"];
+const SQLITE_ISOLATION_LEVELS: [&str; 3] = ["DEFERRED", "IMMEDIATE", "EXCLUSIVE"];
+
+// Normalize an isolation level and check it is supported by the driver
+pub fn validate_isolation_level(driver: &DatabaseType, level: &str) -> PyResult<String> {
+    let level = level
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_uppercase();
+    let allowed = match driver {
+        DatabaseType::Postgres | DatabaseType::Mysql => SQL_ISOLATION_LEVELS,
+        DatabaseType::Sqlite => SQLITE_ISOLATION_LEVELS,
+    };
+    if !allowed.contains(&level.as_str()) {
+        return Err(PyValueError::new_err(format!(
+            "Invalid isolation level '{}' for {:?}, expected one of: {}",
+            level,
+            driver,
+            allowed.join(", ")
+        )));
+    }
+    Ok(level)
+}
+
+const SQLITE_PRAGMAS: [&str; 5] = [
+    "journal_mode",
+    "synchronous",
+    "cache_size",
+    "foreign_keys",
+    "busy_timeout",
+];
+
+// Normalize pragma names and reject anything that can't be spliced into a PRAGMA statement
+pub fn validate_sqlite_pragmas(
+    driver: &DatabaseType,
+    pragmas: HashMap<String, String>,
+    allow_unknown: bool,
+) -> PyResult<HashMap<String, String>> {
+    if !pragmas.is_empty() && !matches!(driver, DatabaseType::Sqlite) {
+        return Err(PyValueError::new_err(format!(
+            "sqlite_pragmas can't be used with {:?}",
+            driver
+        )));
+    }
+    let mut validated = HashMap::new();
+    for (name, value) in pragmas {
+        let name = name.trim().to_lowercase();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(PyValueError::new_err(format!("Invalid pragma name '{}'", name)));
+        }
+        if !allow_unknown && !SQLITE_PRAGMAS.contains(&name.as_str()) {
+            return Err(PyValueError::new_err(format!(
+                "Unknown pragma '{}', expected one of: {} (set allow_unknown_pragmas=True to use it anyway)",
+                name,
+                SQLITE_PRAGMAS.join(", ")
+            )));
+        }
+        let value = value.trim().to_string();
+        if value.is_empty()
+            || !value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        {
+            return Err(PyValueError::new_err(format!(
+                "Invalid value '{}' for pragma '{}'",
+                value, name
+            )));
+        }
+        validated.insert(name, value);
+    }
+    Ok(validated)
 }
 
 #[pymethods]
 impl DatabaseConfig {
     #[new]
+    #[pyo3(signature = (driver, url, max_connections, min_connections, idle_timeout, options=None, isolation_level=None, slow_query_threshold_ms=None, log_params=false, sqlite_pragmas=None, allow_unknown_pragmas=false, warmup=false))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
         driver: DatabaseType,
         url: &str,
@@ -50,15 +144,35 @@ impl DatabaseConfig {
         min_connections: u32,
         idle_timeout: u64,
         options: Option<HashMap<String, String>>,
-    ) -> Self {
-        DatabaseConfig {
+        isolation_level: Option<&str>,
+        slow_query_threshold_ms: Option<u64>,
+        log_params: bool,
+        sqlite_pragmas: Option<HashMap<String, String>>,
+        allow_unknown_pragmas: bool,
+        warmup: bool,
+    ) -> PyResult<Self> {
+        let isolation_level = isolation_level
+            .map(|level| validate_isolation_level(&driver, level))
+            .transpose()?;
+        let sqlite_pragmas = validate_sqlite_pragmas(
+            &driver,
+            sqlite_pragmas.unwrap_or_default(),
+            allow_unknown_pragmas,
+        )?;
+        Ok(DatabaseConfig {
             driver,
             url: url.to_string(),
             max_connections,
             min_connections,
             idle_timeout,
             options,
-        }
+            isolation_level,
+            slow_query_threshold_ms,
+            log_params,
+            sqlite_pragmas,
+            allow_unknown_pragmas,
+            warmup,
+        })
     }
 }
 
@@ -84,7 +198,21 @@ impl DatabaseConfig {
     pub async fn create_mysql_pool(&self) -> Result<Pool<sqlx::MySql>, sqlx::Error> {
         let connect_options = self.url.parse::<MySqlConnectOptions>()?;
 
+        // MySQL rejects SET TRANSACTION once BEGIN has run, so the level is set per session
+        let isolation_level = self.isolation_level.clone();
         MySqlPoolOptions::new()
+            .after_connect(move |conn, _meta| {
+                let isolation_level = isolation_level.clone();
+                Box::pin(async move {
+                    if let Some(level) = isolation_level {
+                        conn.execute(
+                            format!("SET SESSION TRANSACTION ISOLATION LEVEL {}", level).as_str(),
+                        )
+                        .await?;
+                    }
+                    Ok(())
+                })
+            })
             .max_connections(self.max_connections)
             .min_connections(self.min_connections)
             .idle_timeout(Some(Duration::from_secs(self.idle_timeout)))
@@ -97,7 +225,19 @@ impl DatabaseConfig {
     pub async fn create_sqlite_pool(&self) -> Result<Pool<sqlx::Sqlite>, sqlx::Error> {
         let connect_options = self.url.parse::<SqliteConnectOptions>()?;
 
+        // most pragmas only apply to the connection that runs them
+        let pragmas = self.sqlite_pragmas.clone();
         SqlitePoolOptions::new()
+            .after_connect(move |conn, _meta| {
+                let pragmas = pragmas.clone();
+                Box::pin(async move {
+                    for (name, value) in pragmas {
+                        conn.execute(format!("PRAGMA {} = {}", name, value).as_str())
+                            .await?;
+                    }
+                    Ok(())
+                })
+            })
             .max_connections(self.max_connections)
             .min_connections(self.min_connections)
             .idle_timeout(Some(Duration::from_secs(self.idle_timeout)))
@@ -132,6 +272,12 @@ impl DatabaseConfig {
             min_connections: 1,
             idle_timeout: 600,
             options: None,
+            isolation_level: None,
+            slow_query_threshold_ms: None,
+            log_params: false,
+            sqlite_pragmas: HashMap::new(),
+            allow_unknown_pragmas: false,
+            warmup: false,
         }
     }
 }
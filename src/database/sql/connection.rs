@@ -1,16 +1,35 @@
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use futures::future::join_all;
+use pyo3::{
+    exceptions::{PyNotImplementedError, PyRuntimeError},
+    prelude::*,
+};
+use sqlx::postgres::PgListener;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+use crate::instants::get_runtime;
 
 use super::{
     config::DatabaseConfig,
+    db_trait::SlowQueryLog,
     postgresql::PostgresDatabase,
     mysql::MySqlDatabase,
     sqlite::SqliteDatabase,
     transaction::{DatabaseTransaction, DatabaseTransactionType},
 };
-use sqlx::{Error as SqlxError, Pool};
+use sqlx::{Connection, Error as SqlxError, Pool};
 use sqlx::{MySql, Postgres, Sqlite};
 use tokio::sync::Mutex;
 
+// a ping slower than this counts as a failed health check
+const PING_TIMEOUT: Duration = Duration::from_secs(2);
+
 #[derive(Clone)]
 enum DatabaseType {
     Postgres(Arc<Pool<sqlx::Postgres>>),
@@ -22,10 +41,41 @@ enum DatabaseType {
 #[derive(Clone)]
 pub struct DatabaseConnection {
     connection: DatabaseType,
+    isolation_level: Option<String>,
+    slow_query: SlowQueryLog,
+    // LISTEN tasks by channel, each owns a dedicated connection
+    listeners: Arc<DashMap<String, JoinHandle<()>>>,
+    // driver and url of the config the pool was opened with
+    target: String,
+}
+
+fn config_target(config: &DatabaseConfig) -> String {
+    format!("{:?} {}", config.driver, config.url)
+}
+
+// ping `count` connections concurrently, each held until all are done so the pool has to open
+// that many
+async fn warmup_pool<DB>(pool: &Pool<DB>, count: u32) -> usize
+where
+    DB: sqlx::Database,
+{
+    let connections = join_all((0..count).map(|_| async move {
+        let mut conn = pool.acquire().await?;
+        conn.ping().await?;
+        Ok::<_, SqlxError>(conn)
+    }))
+    .await;
+    connections.iter().filter(|conn| conn.is_ok()).count()
 }
 
 impl DatabaseConnection {
     pub async fn new(config: DatabaseConfig) -> Self {
+        let isolation_level = config.isolation_level.clone();
+        let target = config_target(&config);
+        let slow_query = SlowQueryLog {
+            threshold_ms: config.slow_query_threshold_ms,
+            log_params: config.log_params,
+        };
         let connection = match config.driver {
             super::config::DatabaseType::Postgres => {
                 let pool = config.create_postgres_pool().await.unwrap();
@@ -42,60 +92,220 @@ impl DatabaseConnection {
         }
         .unwrap();
 
-        Self { connection }
+        let database = Self {
+            connection,
+            isolation_level,
+            slow_query,
+            listeners: Arc::new(DashMap::new()),
+            target,
+        };
+        if config.warmup {
+            let started = Instant::now();
+            let opened = database.warmup(config.min_connections).await;
+            info!(
+                "Warmed up {} of {} database connections in {:?}",
+                opened,
+                config.min_connections,
+                started.elapsed()
+            );
+        }
+        database
+    }
+
+    // Whether the pool was opened for the same driver and database as `config`
+    pub fn opened_with(&self, config: &DatabaseConfig) -> bool {
+        self.target == config_target(config)
+    }
+
+    // open `count` connections up front, see `warmup_pool`. Returns how many succeeded
+    async fn warmup(&self, count: u32) -> usize {
+        match &self.connection {
+            DatabaseType::Postgres(pool) => warmup_pool(pool, count).await,
+            DatabaseType::MySql(pool) => warmup_pool(pool, count).await,
+            DatabaseType::Sqlite(pool) => warmup_pool(pool, count).await,
+        }
+    }
+
+    // connections open in the pool and how many of them are idle
+    pub fn pool_status(&self) -> (u32, usize) {
+        match &self.connection {
+            DatabaseType::Postgres(pool) => (pool.size(), pool.num_idle()),
+            DatabaseType::MySql(pool) => (pool.size(), pool.num_idle()),
+            DatabaseType::Sqlite(pool) => (pool.size(), pool.num_idle()),
+        }
     }
 
     // get transaction
     pub async fn transaction(&self) -> DatabaseTransaction {
         match &self.connection {
             DatabaseType::Postgres(pool) => {
-                let transaction = pool
-                    .begin()
-                    .await
-                    .map_err(|e| SqlxError::Configuration(e.to_string().into()));
+                let transaction = self.begin_postgres(pool).await;
                 DatabaseTransaction::from_transaction(DatabaseTransactionType::Postgres(
-                    PostgresDatabase,
+                    PostgresDatabase {
+                        slow_query: self.slow_query.clone(),
+                    },
                     Arc::new(Mutex::new(Some(transaction.unwrap()))),
+                    self.isolation_level.clone(),
                 ))
             }
             DatabaseType::MySql(pool) => {
-                let transaction = pool
-                    .begin()
-                    .await
-                    .map_err(|e| SqlxError::Configuration(e.to_string().into()));
+                let transaction = self.begin_mysql(pool).await;
                 DatabaseTransaction::from_transaction(DatabaseTransactionType::MySql(
-                    MySqlDatabase,
+                    MySqlDatabase {
+                        slow_query: self.slow_query.clone(),
+                    },
                     Arc::new(Mutex::new(Some(transaction.unwrap()))),
+                    self.isolation_level.clone(),
                 ))
             }
             DatabaseType::Sqlite(pool) => {
-                let transaction = pool
-                    .begin()
-                    .await
-                    .map_err(|e| SqlxError::Configuration(e.to_string().into()));
+                let transaction = self.begin_sqlite(pool).await;
                 DatabaseTransaction::from_transaction(DatabaseTransactionType::SQLite(
-                    SqliteDatabase,
+                    SqliteDatabase {
+                        slow_query: self.slow_query.clone(),
+                    },
                     Arc::new(Mutex::new(Some(transaction.unwrap()))),
+                    self.isolation_level.clone(),
                 ))
             }
         }
     }
 
+    // lightweight round trip used by the health check endpoint, must run inside the tokio runtime
+    pub async fn ping(&self) -> bool {
+        let query = async {
+            match &self.connection {
+                DatabaseType::Postgres(pool) => sqlx::query("SELECT 1").execute(pool.as_ref()).await.is_ok(),
+                DatabaseType::MySql(pool) => sqlx::query("SELECT 1").execute(pool.as_ref()).await.is_ok(),
+                DatabaseType::Sqlite(pool) => sqlx::query("SELECT 1").execute(pool.as_ref()).await.is_ok(),
+            }
+        };
+        tokio::time::timeout(PING_TIMEOUT, query).await.unwrap_or(false)
+    }
+
+    // blocking ping for python callers, run on the shared runtime with the GIL released
+    pub fn check_health(&self, py: Python) -> PyResult<bool> {
+        let connection = self.clone();
+        let handle = get_runtime().spawn(async move { connection.ping().await });
+        py.allow_threads(|| futures::executor::block_on(handle))
+            .map_err(|e| PyRuntimeError::new_err(format!("Health check failed: {}", e)))
+    }
+
+    // subscribe to a postgres channel and call `callback(payload)` for every notification
+    pub async fn listen(&self, channel: &str, callback: PyObject) -> PyResult<()> {
+        let pool = match &self.connection {
+            DatabaseType::Postgres(pool) => pool.clone(),
+            _ => {
+                return Err(PyNotImplementedError::new_err(
+                    "LISTEN/NOTIFY is only supported for Postgres",
+                ))
+            }
+        };
+
+        let mut listener = PgListener::connect_with(&pool)
+            .await
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        listener
+            .listen(channel)
+            .await
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+
+        let channel_name = channel.to_string();
+        let handle = get_runtime().spawn(async move {
+            loop {
+                match listener.recv().await {
+                    Ok(notification) => {
+                        let payload = notification.payload().to_string();
+                        Python::with_gil(|py| {
+                            if let Err(e) = callback.call1(py, (payload,)) {
+                                error!("Notification callback for {} failed: {}", channel_name, e);
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        error!("Listener for {} stopped: {}", channel_name, e);
+                        break;
+                    }
+                }
+            }
+        });
+
+        // listening again on a channel replaces the previous callback
+        if let Some(previous) = self.listeners.insert(channel.to_string(), handle) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    // stop listening on a channel, dropping its dedicated connection
+    pub fn unlisten(&self, channel: &str) -> PyResult<bool> {
+        if !matches!(self.connection, DatabaseType::Postgres(_)) {
+            return Err(PyNotImplementedError::new_err(
+                "LISTEN/NOTIFY is only supported for Postgres",
+            ));
+        }
+        match self.listeners.remove(channel) {
+            Some((_, handle)) => {
+                handle.abort();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
     pub async fn begin_transaction(&self) -> Option<Box<dyn std::any::Any + Send>> {
-        
         match &self.connection {
             DatabaseType::Postgres(pool) => {
-                let transaction: sqlx::Transaction<Postgres> = pool.begin().await.ok()?;
+                let transaction = self.begin_postgres(pool).await.ok()?;
                 Some(Box::new(transaction))
             }
             DatabaseType::MySql(pool) => {
-                let transaction: sqlx::Transaction<MySql> = pool.begin().await.ok()?;
+                let transaction = self.begin_mysql(pool).await.ok()?;
                 Some(Box::new(transaction))
             }
             DatabaseType::Sqlite(pool) => {
-                let transaction: sqlx::Transaction<Sqlite> = pool.begin().await.ok()?;
+                let transaction = self.begin_sqlite(pool).await.ok()?;
                 Some(Box::new(transaction))
             }
         }
     }
+
+    async fn begin_postgres(
+        &self,
+        pool: &Pool<Postgres>,
+    ) -> Result<sqlx::Transaction<'static, Postgres>, SqlxError> {
+        let mut transaction = pool.begin().await?;
+        if let Some(level) = &self.isolation_level {
+            sqlx::query(&format!("SET TRANSACTION ISOLATION LEVEL {}", level))
+                .execute(&mut *transaction)
+                .await?;
+        }
+        Ok(transaction)
+    }
+
+    // the isolation level is applied per session when the pool connects
+    async fn begin_mysql(
+        &self,
+        pool: &Pool<MySql>,
+    ) -> Result<sqlx::Transaction<'static, MySql>, SqlxError> {
+        pool.begin().await
+    }
+
+    async fn begin_sqlite(
+        &self,
+        pool: &Pool<Sqlite>,
+    ) -> Result<sqlx::Transaction<'static, Sqlite>, SqlxError> {
+        let mut transaction = pool.begin().await?;
+        // sqlx always issues a deferred BEGIN, so IMMEDIATE and EXCLUSIVE take the
+        // write lock up front by rewriting user_version with its current value
+        if matches!(self.isolation_level.as_deref(), Some("IMMEDIATE" | "EXCLUSIVE")) {
+            let version: i64 = sqlx::query_scalar("PRAGMA user_version")
+                .fetch_one(&mut *transaction)
+                .await?;
+            sqlx::query(&format!("PRAGMA user_version = {}", version))
+                .execute(&mut *transaction)
+                .await?;
+        }
+        Ok(transaction)
+    }
 }
@@ -1,8 +1,51 @@
 use std::sync::Arc;
+use std::time::Instant;
 
 use pyo3::prelude::*;
 use sqlx::Database;
 use tokio::sync::Mutex;
+use tracing::warn;
+
+// Slow query logging settings taken from DatabaseConfig
+#[derive(Debug, Clone, Default)]
+pub struct SlowQueryLog {
+    pub threshold_ms: Option<u64>,
+    // parameter values may contain personal data, so only their count is logged by default
+    pub log_params: bool,
+}
+
+impl SlowQueryLog {
+    pub fn log(&self, started: Instant, query: &str, params: &[&PyAny]) {
+        let threshold = match self.threshold_ms {
+            Some(threshold) => threshold,
+            None => return,
+        };
+        let elapsed = started.elapsed().as_millis() as u64;
+        if elapsed <= threshold {
+            return;
+        }
+
+        if self.log_params {
+            let values: Vec<String> = params
+                .iter()
+                .map(|p| p.repr().map(|r| r.to_string()).unwrap_or_default())
+                .collect();
+            warn!(
+                "Slow query ({} ms): {} params: [{}]",
+                elapsed,
+                query,
+                values.join(", ")
+            );
+        } else {
+            warn!(
+                "Slow query ({} ms): {} ({} params)",
+                elapsed,
+                query,
+                params.len()
+            );
+        }
+    }
+}
 
 // Trait for dynamic parameter binding
 pub trait DynamicParameterBinder {
@@ -50,6 +93,30 @@ pub trait DatabaseOperations {
         params: Vec<&PyAny>,
     ) -> Result<Vec<PyObject>, PyErr>;
 
+    // runs each statement with the GIL released while it executes
+    fn execute_many(
+        &mut self,
+        py: Python<'_>,
+        transaction: Arc<Mutex<Option<sqlx::Transaction<'static, Self::DatabaseType>>>>,
+        queries: Vec<(String, Vec<&PyAny>)>,
+    ) -> Result<Vec<u64>, PyErr>;
+
+    async fn fetch_one(
+        &mut self,
+        py: Python<'_>,
+        transaction: Arc<Mutex<Option<sqlx::Transaction<'static, Self::DatabaseType>>>>,
+        query: &str,
+        params: Vec<&PyAny>,
+    ) -> Result<PyObject, PyErr>;
+
+    async fn fetch_optional(
+        &mut self,
+        py: Python<'_>,
+        transaction: Arc<Mutex<Option<sqlx::Transaction<'static, Self::DatabaseType>>>>,
+        query: &str,
+        params: Vec<&PyAny>,
+    ) -> Result<Option<PyObject>, PyErr>;
+
     async fn stream_data(
         &mut self,
         py: Python<'_>,
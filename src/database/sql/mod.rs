@@ -4,4 +4,5 @@ pub mod postgresql;
 pub mod sqlite;
 pub mod mysql;
 pub mod config;
+pub mod notifier;
 pub mod transaction;
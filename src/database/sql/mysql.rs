@@ -1,21 +1,41 @@
 use std::sync::Arc;
+use std::time::Instant;
 use regex::Regex;
 use tokio::sync::Mutex;
 
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
 use futures::StreamExt;
 use pyo3::{
     prelude::*,
-    types::PyDict,
+    types::{PyBytes, PyDate, PyDateAccess, PyDateTime, PyDict, PyList, PyTime, PyTimeAccess},
 };
 use sqlx::{
     mysql::{MySqlArguments, MySqlRow},
-    Column, Row, ValueRef,
+    types::{Decimal, Json, JsonValue},
+    Column, Row, TypeInfo, ValueRef,
 };
 
-use super::db_trait::{DatabaseOperations, DynamicParameterBinder};
+use crate::types::json::{to_json_value, to_py_object, JsonOptions};
+
+use super::db_trait::{DatabaseOperations, DynamicParameterBinder, SlowQueryLog};
 // Similarly implement for other database types...
 pub struct MySqlParameterBinder;
 
+fn naive_date(date: &impl PyDateAccess) -> PyResult<NaiveDate> {
+    NaiveDate::from_ymd_opt(date.get_year(), date.get_month() as u32, date.get_day() as u32)
+        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid date"))
+}
+
+fn naive_time(time: &impl PyTimeAccess) -> PyResult<NaiveTime> {
+    NaiveTime::from_hms_micro_opt(
+        time.get_hour() as u32,
+        time.get_minute() as u32,
+        time.get_second() as u32,
+        time.get_microsecond(),
+    )
+    .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid time"))
+}
+
 impl DynamicParameterBinder for MySqlParameterBinder {
     type Arguments = MySqlArguments;
     type Database = sqlx::MySql;
@@ -57,6 +77,28 @@ impl DynamicParameterBinder for MySqlParameterBinder {
 
         // Bind parameters with lifetime preservation
         for param in params_converted {
+            // datetime is a subclass of date so it is checked first
+            if let Ok(dt) = param.downcast::<PyDateTime>() {
+                query_builder = query_builder.bind(NaiveDateTime::new(naive_date(dt)?, naive_time(dt)?));
+                continue;
+            }
+            if let Ok(date) = param.downcast::<PyDate>() {
+                query_builder = query_builder.bind(naive_date(date)?);
+                continue;
+            }
+            if let Ok(time) = param.downcast::<PyTime>() {
+                query_builder = query_builder.bind(naive_time(time)?);
+                continue;
+            }
+            if let Ok(bytes) = param.downcast::<PyBytes>() {
+                query_builder = query_builder.bind(bytes.as_bytes().to_vec());
+                continue;
+            }
+            if param.is_instance_of::<PyDict>() || param.is_instance_of::<PyList>() {
+                let json_value = to_json_value(param, &JsonOptions::default())?;
+                query_builder = query_builder.bind(Json(json_value));
+                continue;
+            }
             query_builder = match param.extract::<String>() {
                 // Use String instead of &str
                 Ok(s) => query_builder.bind(s),
@@ -90,8 +132,17 @@ impl DynamicParameterBinder for MySqlParameterBinder {
                 Ok(val) => {
                     if val.is_null() {
                         dict.set_item(column_name, py.None()).unwrap();
+                    }
+                    // JSON columns also decode as strings, and text columns as JSON, so the type is checked
+                    else if column.type_info().name() == "JSON" {
+                        let json_val = row.try_get::<Json<JsonValue>, _>(i).map_err(|e| {
+                            PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string())
+                        })?;
+                        dict.set_item(column_name, to_py_object(py, &json_val.0)?)?;
                     } else if let Ok(int_val) = row.try_get::<i32, _>(i) {
                         dict.set_item(column_name, int_val).unwrap();
+                    } else if let Ok(bigint_val) = row.try_get::<i64, _>(i) {
+                        dict.set_item(column_name, bigint_val).unwrap();
                     } else if let Ok(str_val) = row.try_get::<String, _>(i) {
                         dict.set_item(column_name, str_val).unwrap();
                     } else if let Ok(float_val) = row.try_get::<f64, _>(i) {
@@ -99,6 +150,51 @@ impl DynamicParameterBinder for MySqlParameterBinder {
                     } else if let Ok(bool_val) = row.try_get::<bool, _>(i) {
                         dict.set_item(column_name, bool_val).unwrap();
                     }
+                    // DECIMAL is returned as a string to keep its exact value
+                    else if let Ok(decimal_val) = row.try_get::<Decimal, _>(i) {
+                        dict.set_item(column_name, decimal_val.to_string())?;
+                    }
+                    // DATETIME and TIMESTAMP
+                    else if let Ok(datetime_val) = row.try_get::<NaiveDateTime, _>(i) {
+                        let py_datetime = PyDateTime::new(
+                            py,
+                            datetime_val.year(),
+                            datetime_val.month() as u8,
+                            datetime_val.day() as u8,
+                            datetime_val.hour() as u8,
+                            datetime_val.minute() as u8,
+                            datetime_val.second() as u8,
+                            (datetime_val.nanosecond() / 1000) as u32,
+                            None,
+                        )?;
+                        dict.set_item(column_name, py_datetime)?;
+                    } else if let Ok(date_val) = row.try_get::<NaiveDate, _>(i) {
+                        let py_date = PyDate::new(
+                            py,
+                            date_val.year(),
+                            date_val.month() as u8,
+                            date_val.day() as u8,
+                        )?;
+                        dict.set_item(column_name, py_date)?;
+                    } else if let Ok(time_val) = row.try_get::<NaiveTime, _>(i) {
+                        let py_time = PyTime::new(
+                            py,
+                            time_val.hour() as u8,
+                            time_val.minute() as u8,
+                            time_val.second() as u8,
+                            (time_val.nanosecond() / 1000) as u32,
+                            None,
+                        )?;
+                        dict.set_item(column_name, py_time)?;
+                    }
+                    // BLOB and BINARY columns
+                    else if let Ok(bytes_val) = row.try_get::<Vec<u8>, _>(i) {
+                        dict.set_item(column_name, PyBytes::new(py, &bytes_val))?;
+                    }
+                    // Fallback for unknown types
+                    else {
+                        dict.set_item(column_name, py.None())?;
+                    }
                 }
                 Err(_) => {
                     // Handle unsupported types or log an error
@@ -112,7 +208,9 @@ impl DynamicParameterBinder for MySqlParameterBinder {
 }
 
 #[derive(Debug, Clone, Default)]
-pub struct MySqlDatabase;
+pub struct MySqlDatabase {
+    pub slow_query: SlowQueryLog,
+}
 
 impl DatabaseOperations for MySqlDatabase {
     type Row = MySqlRow;
@@ -126,13 +224,16 @@ impl DatabaseOperations for MySqlDatabase {
         query: &str,
         params: Vec<&PyAny>,
     ) -> Result<u64, PyErr> {
+        let logged_params = params.clone();
         let query_builder = MySqlParameterBinder.bind_parameters(query, params)?;
         let mut guard = transaction.lock().await;
         let transaction = guard.as_mut().unwrap();
+        let started = Instant::now();
         let result = query_builder
             .execute(&mut **transaction)
             .await
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+        self.slow_query.log(started, query, &logged_params);
 
         Ok(result.rows_affected())
     }
@@ -144,13 +245,16 @@ impl DatabaseOperations for MySqlDatabase {
         query: &str,
         params: Vec<&PyAny>,
     ) -> Result<Vec<PyObject>, PyErr> {
+        let logged_params = params.clone();
         let query_builder = MySqlParameterBinder.bind_parameters(query, params)?;
         let mut guard = transaction.lock().await;
         let transaction = guard.as_mut().unwrap();
+        let started = Instant::now();
         let rows = query_builder
             .fetch_all(&mut **transaction)
             .await
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+        self.slow_query.log(started, query, &logged_params);
 
         let result: Vec<PyObject> = rows
             .iter()
@@ -160,6 +264,79 @@ impl DatabaseOperations for MySqlDatabase {
         Ok(result)
     }
 
+    fn execute_many(
+        &mut self,
+        py: Python<'_>,
+        transaction: Arc<Mutex<Option<sqlx::Transaction<'static, Self::DatabaseType>>>>,
+        queries: Vec<(String, Vec<&PyAny>)>,
+    ) -> Result<Vec<u64>, PyErr> {
+        let mut results = Vec::with_capacity(queries.len());
+        for (index, (query, params)) in queries.iter().enumerate() {
+            let logged_params = params.clone();
+            let query_builder = MySqlParameterBinder.bind_parameters(query, params.clone())?;
+            let transaction = transaction.clone();
+            let started = Instant::now();
+            let result = py
+                .allow_threads(move || {
+                    futures::executor::block_on(async move {
+                        let mut guard = transaction.lock().await;
+                        let transaction = guard.as_mut().unwrap();
+                        query_builder.execute(&mut **transaction).await
+                    })
+                })
+                .map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+                        "Statement {} failed: {}",
+                        index, e
+                    ))
+                })?;
+            self.slow_query.log(started, query, &logged_params);
+            results.push(result.rows_affected());
+        }
+        Ok(results)
+    }
+
+    async fn fetch_one(
+        &mut self,
+        py: Python<'_>,
+        transaction: Arc<Mutex<Option<sqlx::Transaction<'static, Self::DatabaseType>>>>,
+        query: &str,
+        params: Vec<&PyAny>,
+    ) -> Result<PyObject, PyErr> {
+        let query_builder = MySqlParameterBinder.bind_parameters(query, params)?;
+        let mut guard = transaction.lock().await;
+        let transaction = guard.as_mut().unwrap();
+        let row = query_builder
+            .fetch_one(&mut **transaction)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => {
+                    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("No rows returned")
+                }
+                e => PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()),
+            })?;
+
+        MySqlParameterBinder.bind_result(py, &row)
+    }
+
+    async fn fetch_optional(
+        &mut self,
+        py: Python<'_>,
+        transaction: Arc<Mutex<Option<sqlx::Transaction<'static, Self::DatabaseType>>>>,
+        query: &str,
+        params: Vec<&PyAny>,
+    ) -> Result<Option<PyObject>, PyErr> {
+        let query_builder = MySqlParameterBinder.bind_parameters(query, params)?;
+        let mut guard = transaction.lock().await;
+        let transaction = guard.as_mut().unwrap();
+        let row = query_builder
+            .fetch_optional(&mut **transaction)
+            .await
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        row.map(|row| MySqlParameterBinder.bind_result(py, &row)).transpose()
+    }
+
     async fn stream_data(
         &mut self,
         py: Python<'_>,
@@ -0,0 +1,28 @@
+use pyo3::{exceptions::PyRuntimeError, prelude::*};
+
+use crate::database::context::get_sql_connect;
+
+/// Postgres LISTEN/NOTIFY subscriptions on the application's database connection
+#[pyclass]
+#[derive(Debug, Default, Clone)]
+pub struct PostgresNotifier {}
+
+#[pymethods]
+impl PostgresNotifier {
+    #[new]
+    fn new() -> Self {
+        Self {}
+    }
+
+    fn listen(&self, channel: &str, callback: PyObject) -> PyResult<()> {
+        let connection = get_sql_connect()
+            .ok_or_else(|| PyRuntimeError::new_err("Database connection is not configured"))?;
+        futures::executor::block_on(connection.listen(channel, callback))
+    }
+
+    fn unlisten(&self, channel: &str) -> PyResult<bool> {
+        let connection = get_sql_connect()
+            .ok_or_else(|| PyRuntimeError::new_err("Database connection is not configured"))?;
+        connection.unlisten(channel)
+    }
+}
@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Instant;
 
 use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
 use futures::StreamExt;
@@ -17,7 +18,7 @@ use sqlx::{
 };
 use tokio::sync::Mutex;
 
-use super::db_trait::{DatabaseOperations, DynamicParameterBinder};
+use super::db_trait::{DatabaseOperations, DynamicParameterBinder, SlowQueryLog};
 // Similarly implement for other database types...
 pub struct PostgresParameterBinder;
 
@@ -216,7 +217,9 @@ impl DynamicParameterBinder for PostgresParameterBinder {
 }
 
 #[derive(Debug, Clone, Default)]
-pub struct PostgresDatabase;
+pub struct PostgresDatabase {
+    pub slow_query: SlowQueryLog,
+}
 
 impl DatabaseOperations for PostgresDatabase {
     type Row = PgRow;
@@ -230,13 +233,16 @@ impl DatabaseOperations for PostgresDatabase {
         query: &str,
         params: Vec<&PyAny>,
     ) -> Result<u64, PyErr> {
+        let logged_params = params.clone();
         let query_builder = PostgresParameterBinder.bind_parameters(query, params)?;
         let mut guard = transaction.lock().await;
         let transaction = guard.as_mut().unwrap();
+        let started = Instant::now();
         let result = query_builder
             .execute(&mut **transaction)
             .await
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+        self.slow_query.log(started, query, &logged_params);
 
         std::mem::drop(guard);
         Ok(result.rows_affected())
@@ -250,13 +256,16 @@ impl DatabaseOperations for PostgresDatabase {
         params: Vec<&PyAny>,
     ) -> Result<Vec<PyObject>, PyErr> {
 
+        let logged_params = params.clone();
         let query_builder = PostgresParameterBinder.bind_parameters(query, params)?;
         let mut guard = transaction.lock().await;
         let transaction = guard.as_mut().unwrap();
+        let started = Instant::now();
         let rows = query_builder
             .fetch_all(&mut **transaction)
             .await
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+        self.slow_query.log(started, query, &logged_params);
 
         let result: Vec<PyObject> = rows
             .iter()
@@ -265,6 +274,79 @@ impl DatabaseOperations for PostgresDatabase {
         Ok(result)
     }
 
+    fn execute_many(
+        &mut self,
+        py: Python<'_>,
+        transaction: Arc<Mutex<Option<sqlx::Transaction<'static, Self::DatabaseType>>>>,
+        queries: Vec<(String, Vec<&PyAny>)>,
+    ) -> Result<Vec<u64>, PyErr> {
+        let mut results = Vec::with_capacity(queries.len());
+        for (index, (query, params)) in queries.iter().enumerate() {
+            let logged_params = params.clone();
+            let query_builder = PostgresParameterBinder.bind_parameters(query, params.clone())?;
+            let transaction = transaction.clone();
+            let started = Instant::now();
+            let result = py
+                .allow_threads(move || {
+                    futures::executor::block_on(async move {
+                        let mut guard = transaction.lock().await;
+                        let transaction = guard.as_mut().unwrap();
+                        query_builder.execute(&mut **transaction).await
+                    })
+                })
+                .map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+                        "Statement {} failed: {}",
+                        index, e
+                    ))
+                })?;
+            self.slow_query.log(started, query, &logged_params);
+            results.push(result.rows_affected());
+        }
+        Ok(results)
+    }
+
+    async fn fetch_one(
+        &mut self,
+        py: Python<'_>,
+        transaction: Arc<Mutex<Option<sqlx::Transaction<'static, Self::DatabaseType>>>>,
+        query: &str,
+        params: Vec<&PyAny>,
+    ) -> Result<PyObject, PyErr> {
+        let query_builder = PostgresParameterBinder.bind_parameters(query, params)?;
+        let mut guard = transaction.lock().await;
+        let transaction = guard.as_mut().unwrap();
+        let row = query_builder
+            .fetch_one(&mut **transaction)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => {
+                    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("No rows returned")
+                }
+                e => PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()),
+            })?;
+
+        PostgresParameterBinder.bind_result(py, &row)
+    }
+
+    async fn fetch_optional(
+        &mut self,
+        py: Python<'_>,
+        transaction: Arc<Mutex<Option<sqlx::Transaction<'static, Self::DatabaseType>>>>,
+        query: &str,
+        params: Vec<&PyAny>,
+    ) -> Result<Option<PyObject>, PyErr> {
+        let query_builder = PostgresParameterBinder.bind_parameters(query, params)?;
+        let mut guard = transaction.lock().await;
+        let transaction = guard.as_mut().unwrap();
+        let row = query_builder
+            .fetch_optional(&mut **transaction)
+            .await
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        row.map(|row| PostgresParameterBinder.bind_result(py, &row)).transpose()
+    }
+
     async fn stream_data(
         &mut self,
         py: Python<'_>,
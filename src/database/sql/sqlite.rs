@@ -1,8 +1,12 @@
 use std::sync::Arc;
+use std::time::Instant;
 
-use super::db_trait::{DatabaseOperations, DynamicParameterBinder};
+use super::db_trait::{DatabaseOperations, DynamicParameterBinder, SlowQueryLog};
 use futures::StreamExt;
-use pyo3::{prelude::*, types::PyDict};
+use pyo3::{
+    prelude::*,
+    types::{PyBytes, PyDict, PyList},
+};
 use regex::Regex;
 use sqlx::{
     query::Query,
@@ -11,8 +15,18 @@ use sqlx::{
 };
 use tokio::sync::Mutex;
 
+use crate::types::json::{from_json_slice, to_json_value, JsonOptions};
+
 pub struct SqliteParameterBinder;
 
+// SQLite has no JSON type, so TEXT holding a JSON object or array is returned parsed
+fn parse_json_text(py: Python<'_>, text: &str) -> Option<PyObject> {
+    if !(text.starts_with('{') || text.starts_with('[')) {
+        return None;
+    }
+    from_json_slice(py, text.as_bytes(), "Invalid JSON").ok()
+}
+
 impl DynamicParameterBinder for SqliteParameterBinder {
     type Arguments = SqliteArguments<'static>;
     type Database = Sqlite;
@@ -56,7 +70,13 @@ impl DynamicParameterBinder for SqliteParameterBinder {
 
         // Bind parameters dynamically
         for param in params_converted {
-            query = if let Ok(s) = param.extract::<String>() {
+            query = if let Ok(bytes) = param.downcast::<PyBytes>() {
+                query.bind(bytes.as_bytes().to_vec())
+            } else if param.is_instance_of::<PyDict>() || param.is_instance_of::<PyList>() {
+                // stored as TEXT
+                let json_value = to_json_value(param, &JsonOptions::default())?;
+                query.bind(json_value.to_string())
+            } else if let Ok(s) = param.extract::<String>() {
                 query.bind(s)
             } else if let Ok(i) = param.extract::<i64>() {
                 query.bind(i)
@@ -95,12 +115,15 @@ impl DynamicParameterBinder for SqliteParameterBinder {
                     } else if let Ok(bool_val) = row.try_get::<bool, _>(i) {
                         dict.set_item(column_name, bool_val)?;
                     } else if let Ok(string_val) = row.try_get::<String, _>(i) {
-                        dict.set_item(column_name, string_val)?;
+                        match parse_json_text(py, &string_val) {
+                            Some(json_val) => dict.set_item(column_name, json_val)?,
+                            None => dict.set_item(column_name, string_val)?,
+                        }
+                    } else if let Ok(bytes_val) = row.try_get::<Vec<u8>, _>(i) {
+                        dict.set_item(column_name, PyBytes::new(py, &bytes_val))?;
                     } else {
-                        return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
-                            "Unsupported column type: {:?}",
-                            val.type_info()
-                        )));
+                        // Fallback for unknown types
+                        dict.set_item(column_name, py.None())?;
                     }
                 }
                 Err(e) => {
@@ -117,7 +140,9 @@ impl DynamicParameterBinder for SqliteParameterBinder {
 
 #[derive(Debug, Clone, Default)]
 #[pyclass]
-pub struct SqliteDatabase;
+pub struct SqliteDatabase {
+    pub slow_query: SlowQueryLog,
+}
 
 impl DatabaseOperations for SqliteDatabase {
     type Row = SqliteRow;
@@ -131,13 +156,16 @@ impl DatabaseOperations for SqliteDatabase {
         query: &str,
         params: Vec<&PyAny>,
     ) -> Result<u64, PyErr> {
+        let logged_params = params.clone();
         let query_builder = SqliteParameterBinder.bind_parameters(query, params)?;
         let mut guard = transaction.lock().await;
         let transaction = guard.as_mut().unwrap();
+        let started = Instant::now();
         let result = query_builder
             .execute(&mut **transaction)
             .await
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+        self.slow_query.log(started, query, &logged_params);
 
         Ok(result.rows_affected())
     }
@@ -149,13 +177,16 @@ impl DatabaseOperations for SqliteDatabase {
         query: &str,
         params: Vec<&PyAny>,
     ) -> Result<Vec<PyObject>, PyErr> {
+        let logged_params = params.clone();
         let query_builder = SqliteParameterBinder.bind_parameters(query, params)?;
         let mut guard = transaction.lock().await;
         let transaction = guard.as_mut().unwrap();
+        let started = Instant::now();
         let rows = query_builder
             .fetch_all(&mut **transaction)
             .await
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+        self.slow_query.log(started, query, &logged_params);
 
         let result: Vec<PyObject> = rows
             .iter()
@@ -165,6 +196,79 @@ impl DatabaseOperations for SqliteDatabase {
         Ok(result)
     }
 
+    fn execute_many(
+        &mut self,
+        py: Python<'_>,
+        transaction: Arc<Mutex<Option<sqlx::Transaction<'static, Self::DatabaseType>>>>,
+        queries: Vec<(String, Vec<&PyAny>)>,
+    ) -> Result<Vec<u64>, PyErr> {
+        let mut results = Vec::with_capacity(queries.len());
+        for (index, (query, params)) in queries.iter().enumerate() {
+            let logged_params = params.clone();
+            let query_builder = SqliteParameterBinder.bind_parameters(query, params.clone())?;
+            let transaction = transaction.clone();
+            let started = Instant::now();
+            let result = py
+                .allow_threads(move || {
+                    futures::executor::block_on(async move {
+                        let mut guard = transaction.lock().await;
+                        let transaction = guard.as_mut().unwrap();
+                        query_builder.execute(&mut **transaction).await
+                    })
+                })
+                .map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+                        "Statement {} failed: {}",
+                        index, e
+                    ))
+                })?;
+            self.slow_query.log(started, query, &logged_params);
+            results.push(result.rows_affected());
+        }
+        Ok(results)
+    }
+
+    async fn fetch_one(
+        &mut self,
+        py: Python<'_>,
+        transaction: Arc<Mutex<Option<sqlx::Transaction<'static, Self::DatabaseType>>>>,
+        query: &str,
+        params: Vec<&PyAny>,
+    ) -> Result<PyObject, PyErr> {
+        let query_builder = SqliteParameterBinder.bind_parameters(query, params)?;
+        let mut guard = transaction.lock().await;
+        let transaction = guard.as_mut().unwrap();
+        let row = query_builder
+            .fetch_one(&mut **transaction)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => {
+                    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("No rows returned")
+                }
+                e => PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()),
+            })?;
+
+        SqliteParameterBinder.bind_result(py, &row)
+    }
+
+    async fn fetch_optional(
+        &mut self,
+        py: Python<'_>,
+        transaction: Arc<Mutex<Option<sqlx::Transaction<'static, Self::DatabaseType>>>>,
+        query: &str,
+        params: Vec<&PyAny>,
+    ) -> Result<Option<PyObject>, PyErr> {
+        let query_builder = SqliteParameterBinder.bind_parameters(query, params)?;
+        let mut guard = transaction.lock().await;
+        let transaction = guard.as_mut().unwrap();
+        let row = query_builder
+            .fetch_optional(&mut **transaction)
+            .await
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        row.map(|row| SqliteParameterBinder.bind_result(py, &row)).transpose()
+    }
+
     async fn stream_data(
         &mut self,
         py: Python<'_>,
@@ -1,4 +1,4 @@
-use pyo3::prelude::*;
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyDict};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::error;
@@ -15,30 +15,41 @@ pub enum DatabaseTransactionType {
     Postgres(
         PostgresDatabase,
         Arc<Mutex<Option<sqlx::Transaction<'static, sqlx::Postgres>>>>,
+        Option<String>,
     ),
     MySql(
         MySqlDatabase,
         Arc<Mutex<Option<sqlx::Transaction<'static, sqlx::MySql>>>>,
+        Option<String>,
     ),
     SQLite(
         SqliteDatabase,
         Arc<Mutex<Option<sqlx::Transaction<'static, sqlx::Sqlite>>>>,
+        Option<String>,
     ),
 }
 
+// The row query and the count query of one page, the same LIMIT/OFFSET syntax works on every driver
+fn page_queries(query: &str, page: u64, page_size: u64) -> PyResult<(String, String)> {
+    let offset = page
+        .checked_mul(page_size)
+        .ok_or_else(|| PyValueError::new_err("page * page_size is too large"))?;
+    let query = query.trim().trim_end_matches(';');
+    Ok((
+        format!("{} LIMIT {} OFFSET {}", query, page_size, offset),
+        format!("SELECT COUNT(*) AS total FROM ({}) AS hypern_page", query),
+    ))
+}
+
 #[pyclass]
 #[derive(Clone, Debug)]
 pub struct DatabaseTransaction {
     transaction: DatabaseTransactionType,
-    do_commit: bool,
 }
 
 impl DatabaseTransaction {
     pub fn from_transaction(transaction: DatabaseTransactionType) -> Self {
-        Self {
-            transaction,
-            do_commit: false,
-        }
+        Self { transaction }
     }
 
     async fn renew_transaction<T>(
@@ -67,21 +78,22 @@ impl DatabaseTransaction {
         T: sqlx::Database,
     {
         let mut guard = transaction.lock().await;
-        let transaction = guard.take().unwrap();
-        transaction.commit().await.ok();
+        if let Some(transaction) = guard.take() {
+            transaction.commit().await.ok();
+        }
 
         self.renew_transaction(guard).await;
     }
 
     pub async fn commit_internal(&mut self) {
         match self.transaction.clone() {
-            DatabaseTransactionType::Postgres(_, transaction) => {
+            DatabaseTransactionType::Postgres(_, transaction, _) => {
                 self.commit_with_type(transaction).await
             }
-            DatabaseTransactionType::MySql(_, transaction) => {
+            DatabaseTransactionType::MySql(_, transaction, _) => {
                 self.commit_with_type(transaction).await
             }
-            DatabaseTransactionType::SQLite(_, transaction) => {
+            DatabaseTransactionType::SQLite(_, transaction, _) => {
                 self.commit_with_type(transaction).await
             }
         }
@@ -94,23 +106,22 @@ impl DatabaseTransaction {
         T: sqlx::Database,
     {
         let mut guard = transaction.lock().await;
-        let transaction = guard.take().unwrap();
-        transaction.rollback().await.ok();
+        if let Some(transaction) = guard.take() {
+            transaction.rollback().await.ok();
+        }
         self.renew_transaction(guard).await;
     }
 
-    async fn rollback_internal(&mut self) {
-        if !self.do_commit {
-            return;
-        }
+    // discard everything run on the transaction since the last commit, then start a new one
+    pub async fn rollback_internal(&mut self) {
         match self.transaction.clone() {
-            DatabaseTransactionType::Postgres(_, transaction) => {
+            DatabaseTransactionType::Postgres(_, transaction, _) => {
                 self.rollback_with_type(transaction).await
             }
-            DatabaseTransactionType::MySql(_, transaction) => {
+            DatabaseTransactionType::MySql(_, transaction, _) => {
                 self.rollback_with_type(transaction).await
             }
-            DatabaseTransactionType::SQLite(_, transaction) => {
+            DatabaseTransactionType::SQLite(_, transaction, _) => {
                 self.rollback_with_type(transaction).await
             }
         }
@@ -119,17 +130,26 @@ impl DatabaseTransaction {
 
 #[pymethods]
 impl DatabaseTransaction {
+    #[getter]
+    fn isolation_level(&self) -> Option<String> {
+        match &self.transaction {
+            DatabaseTransactionType::Postgres(_, _, level)
+            | DatabaseTransactionType::MySql(_, _, level)
+            | DatabaseTransactionType::SQLite(_, _, level) => level.clone(),
+        }
+    }
+
     fn execute(&self, query: &str, params: Vec<&PyAny>) -> PyResult<u64> {
         let transaction = self.transaction.clone();
         let result = futures::executor::block_on(async move {
             match transaction {
-                DatabaseTransactionType::Postgres(mut db, transaction) => {
+                DatabaseTransactionType::Postgres(mut db, transaction, _) => {
                     db.execute(transaction, query, params).await
                 }
-                DatabaseTransactionType::MySql(mut db, transaction) => {
+                DatabaseTransactionType::MySql(mut db, transaction, _) => {
                     db.execute(transaction, query, params).await
                 }
-                DatabaseTransactionType::SQLite(mut db, transaction) => {
+                DatabaseTransactionType::SQLite(mut db, transaction, _) => {
                     db.execute(transaction, query, params).await
                 }
             }
@@ -137,6 +157,24 @@ impl DatabaseTransaction {
         Ok(result)
     }
 
+    fn execute_many(
+        &self,
+        py: Python<'_>,
+        queries: Vec<(String, Vec<&PyAny>)>,
+    ) -> PyResult<Vec<u64>> {
+        match self.transaction.clone() {
+            DatabaseTransactionType::Postgres(mut db, transaction, _) => {
+                db.execute_many(py, transaction, queries)
+            }
+            DatabaseTransactionType::MySql(mut db, transaction, _) => {
+                db.execute_many(py, transaction, queries)
+            }
+            DatabaseTransactionType::SQLite(mut db, transaction, _) => {
+                db.execute_many(py, transaction, queries)
+            }
+        }
+    }
+
     fn fetch_all(
         &self,
         py: Python<'_>,
@@ -145,13 +183,13 @@ impl DatabaseTransaction {
     ) -> Result<Vec<PyObject>, PyErr> {
         let result = futures::executor::block_on(async move {
             match self.transaction.clone() {
-                DatabaseTransactionType::Postgres(mut db, transaction) => {
+                DatabaseTransactionType::Postgres(mut db, transaction, _) => {
                     db.fetch_all(py, transaction, query, params).await
                 }
-                DatabaseTransactionType::MySql(mut db, transaction) => {
+                DatabaseTransactionType::MySql(mut db, transaction, _) => {
                     db.fetch_all(py, transaction, query, params).await
                 }
-                DatabaseTransactionType::SQLite(mut db, transaction) => {
+                DatabaseTransactionType::SQLite(mut db, transaction, _) => {
                     db.fetch_all(py, transaction, query, params).await
                 }
             }
@@ -160,6 +198,77 @@ impl DatabaseTransaction {
         Ok(result)
     }
 
+    // `page` starts at 0, the count query runs the original query as a subquery with the same params
+    fn fetch_page(
+        &self,
+        py: Python<'_>,
+        query: &str,
+        params: Vec<&PyAny>,
+        page: i64,
+        page_size: i64,
+    ) -> PyResult<Py<PyDict>> {
+        if page_size <= 0 {
+            return Err(PyValueError::new_err("page_size must be greater than 0"));
+        }
+        if page < 0 {
+            return Err(PyValueError::new_err("page must be 0 or greater"));
+        }
+        let (page, page_size) = (page as u64, page_size as u64);
+        let (items_query, count_query) = page_queries(query, page, page_size)?;
+
+        let items = self.fetch_all(py, &items_query, params.clone())?;
+        let total: u64 = self
+            .fetch_one(py, &count_query, params)?
+            .as_ref(py)
+            .get_item("total")?
+            .extract()?;
+
+        let result = PyDict::new(py);
+        result.set_item("items", items)?;
+        result.set_item("total", total)?;
+        result.set_item("page", page)?;
+        result.set_item("page_size", page_size)?;
+        result.set_item("total_pages", total.div_ceil(page_size))?;
+        Ok(result.into())
+    }
+
+    fn fetch_one(&self, py: Python<'_>, query: &str, params: Vec<&PyAny>) -> PyResult<PyObject> {
+        futures::executor::block_on(async move {
+            match self.transaction.clone() {
+                DatabaseTransactionType::Postgres(mut db, transaction, _) => {
+                    db.fetch_one(py, transaction, query, params).await
+                }
+                DatabaseTransactionType::MySql(mut db, transaction, _) => {
+                    db.fetch_one(py, transaction, query, params).await
+                }
+                DatabaseTransactionType::SQLite(mut db, transaction, _) => {
+                    db.fetch_one(py, transaction, query, params).await
+                }
+            }
+        })
+    }
+
+    fn fetch_optional(
+        &self,
+        py: Python<'_>,
+        query: &str,
+        params: Vec<&PyAny>,
+    ) -> PyResult<Option<PyObject>> {
+        futures::executor::block_on(async move {
+            match self.transaction.clone() {
+                DatabaseTransactionType::Postgres(mut db, transaction, _) => {
+                    db.fetch_optional(py, transaction, query, params).await
+                }
+                DatabaseTransactionType::MySql(mut db, transaction, _) => {
+                    db.fetch_optional(py, transaction, query, params).await
+                }
+                DatabaseTransactionType::SQLite(mut db, transaction, _) => {
+                    db.fetch_optional(py, transaction, query, params).await
+                }
+            }
+        })
+    }
+
     fn stream_data(
         &self,
         py: Python<'_>,
@@ -169,15 +278,15 @@ impl DatabaseTransaction {
     ) -> PyResult<Vec<Vec<PyObject>>> {
         let result = futures::executor::block_on(async move {
             match self.transaction.clone() {
-                DatabaseTransactionType::Postgres(mut db, transaction) => {
+                DatabaseTransactionType::Postgres(mut db, transaction, _) => {
                     db.stream_data(py, transaction, query, params, chunk_size)
                         .await
                 }
-                DatabaseTransactionType::MySql(mut db, transaction) => {
+                DatabaseTransactionType::MySql(mut db, transaction, _) => {
                     db.stream_data(py, transaction, query, params, chunk_size)
                         .await
                 }
-                DatabaseTransactionType::SQLite(mut db, transaction) => {
+                DatabaseTransactionType::SQLite(mut db, transaction, _) => {
                     db.stream_data(py, transaction, query, params, chunk_size)
                         .await
                 }
@@ -196,21 +305,18 @@ impl DatabaseTransaction {
         let transaction = self.transaction.clone();
         let result = futures::executor::block_on(async move {
             let row_effect = match transaction {
-                DatabaseTransactionType::Postgres(mut db, transaction) => {
+                DatabaseTransactionType::Postgres(mut db, transaction, _) => {
                     db.bulk_change(transaction, query, params, batch_size).await
                 }
-                DatabaseTransactionType::MySql(mut db, transaction) => {
+                DatabaseTransactionType::MySql(mut db, transaction, _) => {
                     db.bulk_change(transaction, query, params, batch_size).await
                 }
-                DatabaseTransactionType::SQLite(mut db, transaction) => {
+                DatabaseTransactionType::SQLite(mut db, transaction, _) => {
                     db.bulk_change(transaction, query, params, batch_size).await
                 }
             };
             Ok(match row_effect {
-                Ok(row) => {
-                    self.do_commit = true;
-                    row
-                }
+                Ok(row) => row,
                 Err(e) => {
                     self.rollback_internal().await;
                     error!("Error in bulk_change: {:?}", e);
@@ -237,4 +343,25 @@ impl DatabaseTransaction {
         });
         Ok(())
     }
+
+    fn __enter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    // commit when the block succeeds, rollback when it raised; exceptions always propagate
+    fn __exit__(
+        mut slf: PyRefMut<Self>,
+        exc_type: &PyAny,
+        _exc_val: &PyAny,
+        _exc_tb: &PyAny,
+    ) -> PyResult<bool> {
+        futures::executor::block_on(async move {
+            if exc_type.is_none() {
+                slf.commit_internal().await;
+            } else {
+                slf.rollback_internal().await;
+            }
+        });
+        Ok(false)
+    }
 }
@@ -1,10 +1,18 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
-use pyo3::{prelude::*, types::PyDict};
+use pyo3::{
+    exceptions::PyTypeError,
+    prelude::*,
+    types::{PyBytes, PyDict, PyList, PyString, PyTuple},
+};
 
 use crate::{
     di::DependencyInjection, instants::get_mem_pool, types::{
-        function_info::FunctionInfo, middleware::MiddlewareReturn, request::Request,
+        function_info::FunctionInfo,
+        header::Header,
+        json::{to_json_vec, JsonOptions},
+        middleware::MiddlewareReturn,
+        request::Request,
         response::Response,
     }
 };
@@ -30,15 +38,24 @@ where
 
     // Add dependencies to kwargs if provided
     if let Some(dependency_injection) = deps {
+        let kwargs = kwargs.as_ref(py).downcast::<PyDict>()?;
+        let dependencies = dependency_injection
+            .to_object(py)
+            .into_ref(py)
+            .downcast::<PyDict>()?
+            .to_owned();
+
+        if function.wants_inject_dict() {
+            kwargs.set_item("inject", dependencies)?;
+        }
 
-        kwargs.as_ref(py).set_item(
-            "inject",
-            dependency_injection
-                .to_object(py)
-                .into_ref(py)
-                .downcast::<PyDict>()?
-                .to_owned(),
-        )?;
+        // each dependency named by a handler parameter is passed as its own keyword argument
+        let names = function.parameter_names.iter().flatten();
+        for name in names.filter(|name| *name != "inject") {
+            if let Some(dependency) = dependencies.get_item(name.as_str())? {
+                kwargs.set_item(name, dependency)?;
+            }
+        }
     }
 
     let result = handler.call(
@@ -66,11 +83,91 @@ pub async fn execute_http_function(
         })?
         .await?;
 
-        return Python::with_gil(|py| -> PyResult<Response> { output.extract(py) });
+        return Python::with_gil(|py| -> PyResult<Response> {
+            response_from_output(output.as_ref(py))
+        });
+    };
+
+    Python::with_gil(|py| -> PyResult<Response> {
+        response_from_output(get_function_output(function, py, request, deps)?)
+    })
+}
+
+// Error page handlers may return a Response, or a plain body sent with `status_code`
+pub async fn execute_error_handler(
+    request: &Request,
+    function: &FunctionInfo,
+    status_code: u16,
+) -> PyResult<Response> {
+    let output: Py<PyAny> = if function.is_async {
+        Python::with_gil(|py| {
+            pyo3_asyncio::tokio::into_future(get_function_output(function, py, request, None)?)
+        })?
+        .await?
+    } else {
+        Python::with_gil(|py| get_function_output(function, py, request, None).map(Into::into))?
     };
 
     Python::with_gil(|py| -> PyResult<Response> {
-        get_function_output(function, py, request, deps)?.extract()
+        let output = output.as_ref(py);
+        if output.extract::<Response>().is_ok() || output.downcast::<PyTuple>().is_ok() {
+            return response_from_output(output);
+        }
+        let mut response = response_from_body(output)?;
+        response.status_code = status_code;
+        Ok(response)
+    })
+}
+
+// Handlers may return a Response, a plain body, or (body, status) / (body, status, headers)
+fn response_from_output(output: &PyAny) -> PyResult<Response> {
+    if let Ok(response) = output.extract::<Response>() {
+        return Ok(response);
+    }
+
+    if let Ok(tuple) = output.downcast::<PyTuple>() {
+        if tuple.len() == 2 || tuple.len() == 3 {
+            let mut response = response_from_body(tuple.get_item(0)?)?;
+            response.status_code = tuple.get_item(1)?.extract()?;
+            if tuple.len() == 3 {
+                let headers: HashMap<String, String> = tuple.get_item(2)?.extract()?;
+                for (key, value) in headers {
+                    response.headers.set(key, value);
+                }
+            }
+            return Ok(response);
+        }
+    }
+
+    response_from_body(output)
+}
+
+fn response_from_body(body: &PyAny) -> PyResult<Response> {
+    let (content_type, description) = if body.is_instance_of::<PyDict>()
+        || body.is_instance_of::<PyList>()
+    {
+        ("application/json", to_json_vec(body, &JsonOptions::default())?)
+    } else if let Ok(text) = body.downcast::<PyString>() {
+        ("text/plain", text.to_str()?.as_bytes().to_vec())
+    } else if let Ok(bytes) = body.downcast::<PyBytes>() {
+        ("application/octet-stream", bytes.as_bytes().to_vec())
+    } else {
+        return Err(PyTypeError::new_err(format!(
+            "Handler returned unsupported type {}",
+            body.get_type().name()?
+        )));
+    };
+
+    let mut headers = Header::default();
+    headers.set("content-type".to_string(), content_type.to_string());
+    Ok(Response {
+        status_code: 200,
+        response_type: "text".to_string(),
+        headers,
+        description,
+        file_path: None,
+        context_id: "".to_string(),
+        state: None,
     })
 }
 
@@ -0,0 +1,131 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use axum::{http::StatusCode, response::IntoResponse, Json};
+use dashmap::DashMap;
+use pyo3::prelude::*;
+use serde_json::{json, Map, Value};
+use tracing::warn;
+
+use crate::{database::context::get_sql_connect, middlewares::cache::ResponseCache};
+
+// set once the startup handler has run and the database is connected, cleared when the server stops
+static READY: AtomicBool = AtomicBool::new(false);
+
+pub fn set_ready(ready: bool) {
+    READY.store(ready, Ordering::Relaxed);
+}
+
+// Paths of the probes, None leaves that probe out
+#[derive(Clone, Debug)]
+pub struct HealthEndpoints {
+    pub liveness: Option<String>,
+    pub readiness: Option<String>,
+}
+
+// A python callable polled in the background, probes only read its last result
+#[derive(Clone, Debug)]
+pub struct ReadinessCheck {
+    pub name: String,
+    pub check: PyObject,
+    pub interval: Duration,
+}
+
+impl ReadinessCheck {
+    // truthy result is ready, a coroutine is run to completion first, exceptions are not ready
+    fn run(&self) -> bool {
+        Python::with_gil(|py| -> PyResult<bool> {
+            let result = self.check.call0(py)?;
+            let asyncio = py.import("asyncio")?;
+            let is_coroutine = asyncio
+                .call_method1("iscoroutine", (result.as_ref(py),))?
+                .is_true()?;
+            let result = match is_coroutine {
+                true => asyncio.call_method1("run", (result,))?,
+                false => result.into_ref(py),
+            };
+            result.is_true()
+        })
+        .unwrap_or_else(|e| {
+            warn!("Readiness check {} failed: {}", self.name, e);
+            false
+        })
+    }
+}
+
+pub struct Readiness {
+    checks: Vec<ReadinessCheck>,
+    results: DashMap<String, bool>,
+    response_cache: Option<Arc<ResponseCache>>,
+}
+
+impl Readiness {
+    pub fn new(checks: Vec<ReadinessCheck>, response_cache: Option<Arc<ResponseCache>>) -> Arc<Self> {
+        Arc::new(Self {
+            checks,
+            results: DashMap::new(),
+            response_cache,
+        })
+    }
+
+    // one task per check, must run inside the tokio runtime
+    pub fn spawn_checks(self: &Arc<Self>) {
+        for check in self.checks.iter().cloned() {
+            let readiness = self.clone();
+            tokio::spawn(async move {
+                loop {
+                    let polled = check.clone();
+                    let ready = tokio::task::spawn_blocking(move || polled.run())
+                        .await
+                        .unwrap_or(false);
+                    readiness.results.insert(check.name.clone(), ready);
+                    tokio::time::sleep(check.interval).await;
+                }
+            });
+        }
+    }
+}
+
+pub async fn liveness() -> impl IntoResponse {
+    Json(json!({"status": "ok"}))
+}
+
+// 503 until the server finished starting and while any component is down
+pub async fn readiness(state: Arc<Readiness>) -> impl IntoResponse {
+    if !READY.load(Ordering::Relaxed) {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"status": "starting"})),
+        );
+    }
+
+    let mut checks: Vec<(String, bool)> = Vec::new();
+    if let Some(database) = get_sql_connect() {
+        checks.push(("db".to_string(), database.ping().await));
+    }
+    if let Some(response_cache) = &state.response_cache {
+        if let Some(ready) = response_cache.ping().await {
+            checks.push(("redis".to_string(), ready));
+        }
+    }
+    for check in &state.checks {
+        // not polled yet counts as not ready
+        let ready = state.results.get(&check.name).map(|ready| *ready).unwrap_or(false);
+        checks.push((check.name.clone(), ready));
+    }
+
+    let (status, label) = match checks.iter().all(|(_, ready)| *ready) {
+        true => (StatusCode::OK, "ok"),
+        false => (StatusCode::SERVICE_UNAVAILABLE, "unavailable"),
+    };
+    let checks: Map<String, Value> = checks
+        .into_iter()
+        .map(|(name, ready)| (name, json!(if ready { "ok" } else { "error" })))
+        .collect();
+    (status, Json(json!({"status": label, "checks": checks})))
+}
@@ -0,0 +1,179 @@
+use std::{future::Future, time::Duration};
+
+use axum::{extract::ConnectInfo, Extension, Router};
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo, TokioTimer},
+    server::{conn::auto::Builder, graceful::GracefulShutdown},
+    service::TowerToHyperService,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpListener,
+};
+use tower::Layer;
+use tracing::{debug, error};
+
+// hyper refuses a read buffer smaller than this
+const MIN_HEADER_BYTES: usize = 8192;
+
+// Connection limits set with `Server.set_http_options`
+#[derive(Clone, Debug)]
+pub struct HttpOptions {
+    // 0 closes http/1 connections after each response
+    pub keep_alive_secs: u64,
+    pub max_header_bytes: usize,
+    pub h2_max_concurrent_streams: u32,
+    pub h2_enabled: bool,
+    pub tcp_nodelay: bool,
+    pub backlog: i32,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            keep_alive_secs: 30,
+            max_header_bytes: 65536,
+            h2_max_concurrent_streams: 200,
+            h2_enabled: true,
+            tcp_nodelay: false,
+            backlog: 1024,
+        }
+    }
+}
+
+impl HttpOptions {
+    pub fn new(
+        keep_alive_secs: u64,
+        max_header_bytes: usize,
+        h2_max_concurrent_streams: u32,
+        h2_enabled: bool,
+        tcp_nodelay: bool,
+        backlog: i32,
+    ) -> PyResult<Self> {
+        if max_header_bytes < MIN_HEADER_BYTES {
+            return Err(PyValueError::new_err(format!(
+                "max_header_bytes must be at least {}",
+                MIN_HEADER_BYTES
+            )));
+        }
+        if h2_max_concurrent_streams == 0 {
+            return Err(PyValueError::new_err(
+                "h2_max_concurrent_streams must be greater than 0",
+            ));
+        }
+        if backlog <= 0 {
+            return Err(PyValueError::new_err("backlog must be greater than 0"));
+        }
+        Ok(Self {
+            keep_alive_secs,
+            max_header_bytes,
+            h2_max_concurrent_streams,
+            h2_enabled,
+            tcp_nodelay,
+            backlog,
+        })
+    }
+
+    fn builder(&self) -> Builder<TokioExecutor> {
+        let mut builder = Builder::new(TokioExecutor::new());
+        if !self.h2_enabled {
+            builder = builder.http1_only();
+        }
+        // headers above the read buffer are answered with 431
+        let mut http1 = builder.http1();
+        http1.max_buf_size(self.max_header_bytes);
+        match self.keep_alive_secs {
+            0 => {
+                http1.keep_alive(false);
+            }
+            secs => {
+                // an idle connection is waiting for the headers of its next request
+                http1
+                    .timer(TokioTimer::new())
+                    .header_read_timeout(Duration::from_secs(secs));
+            }
+        }
+        builder
+            .http2()
+            .max_concurrent_streams(self.h2_max_concurrent_streams)
+            .max_header_list_size(u32::try_from(self.max_header_bytes).unwrap_or(u32::MAX));
+        builder
+    }
+}
+
+// In-flight connections of a listener, finished gracefully on shutdown
+pub struct Connections {
+    builder: Builder<TokioExecutor>,
+    graceful: GracefulShutdown,
+}
+
+impl Connections {
+    pub fn new(options: &HttpOptions) -> Self {
+        Self {
+            builder: options.builder(),
+            graceful: GracefulShutdown::new(),
+        }
+    }
+
+    // serve one connection in its own task, `peer` is exposed to handlers as ConnectInfo
+    pub fn spawn<I, T>(&self, io: I, app: &Router, peer: T)
+    where
+        I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+        T: Clone + Send + Sync + 'static,
+    {
+        let service = Extension(ConnectInfo(peer)).layer(app.clone());
+        let connection = self
+            .builder
+            .serve_connection_with_upgrades(TokioIo::new(io), TowerToHyperService::new(service))
+            .into_owned();
+        let connection = self.graceful.watch(connection);
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                debug!("Connection closed: {}", e);
+            }
+        });
+    }
+
+    pub async fn shutdown(self) {
+        self.graceful.shutdown().await;
+    }
+}
+
+// Accept connections until `shutdown` resolves, then wait for the in-flight ones
+pub async fn serve_tcp<F>(
+    listener: TcpListener,
+    app: Router,
+    options: HttpOptions,
+    shutdown: F,
+) -> std::io::Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let connections = Connections::new(&options);
+    tokio::pin!(shutdown);
+
+    loop {
+        let (stream, peer) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    // e.g. out of file descriptors, give in-flight connections time to close
+                    error!("Failed to accept a connection: {}", e);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+        if options.tcp_nodelay {
+            if let Err(e) = stream.set_nodelay(true) {
+                debug!("Failed to set TCP_NODELAY: {}", e);
+            }
+        }
+        connections.spawn(stream, &app, peer);
+    }
+
+    connections.shutdown().await;
+    Ok(())
+}
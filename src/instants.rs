@@ -10,12 +10,9 @@ pub fn get_runtime() -> &'static Runtime {
     RUNTIME.get_or_init(|| Runtime::new().unwrap())
 }
 
+// A server started again in the same process keeps the pool of the first one
 pub fn create_mem_pool(min_capacity: usize, max_capacity: usize) {
-    let pool = AdaptiveMemoryPool::new(min_capacity, max_capacity);
-    match MEM_POOL.set(pool) {
-        Ok(_) => (),
-        Err(_) => panic!("Memory pool already initialized"),
-    };
+    let _ = MEM_POOL.set(AdaptiveMemoryPool::new(min_capacity, max_capacity));
 }
 
 pub fn get_mem_pool() -> &'static AdaptiveMemoryPool {
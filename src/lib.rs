@@ -13,12 +13,24 @@ mod di;
 mod middlewares;
 mod database;
 mod mem_pool;
+mod metrics;
+mod telemetry;
+mod logging;
+mod health;
+mod testing;
+mod http_server;
+#[cfg(unix)]
+mod unix_socket;
+mod static_files;
+mod security;
+mod caching;
 
 #[pymodule]
-fn hypern(_py: Python<'_>, m: &PyModule) -> PyResult<()>  {
+fn hypern(py: Python<'_>, m: &PyModule) -> PyResult<()>  {
 
     m.add_class::<openapi::schemas::BaseSchemaGenerator>()?;
     m.add_class::<openapi::swagger::SwaggerUI>()?;    
+    m.add_class::<openapi::redoc::ReDocUI>()?;
 
     m.add_class::<background::background_task::BackgroundTask>()?;
     m.add_class::<background::background_tasks::BackgroundTasks>()?;
@@ -26,6 +38,9 @@ fn hypern(_py: Python<'_>, m: &PyModule) -> PyResult<()>  {
     m.add_class::<scheduler::scheduler::Scheduler>()?;
     
     m.add_class::<server::Server>()?;
+    m.add_class::<server::ServerHandle>()?;
+    m.add_class::<testing::TestClient>()?;
+    m.add_class::<testing::TestResponse>()?;
     m.add_class::<router::route::Route>()?;
     m.add_class::<router::router::Router>()?;
     m.add_class::<types::http::HttpMethod>()?;
@@ -34,9 +49,15 @@ fn hypern(_py: Python<'_>, m: &PyModule) -> PyResult<()>  {
     m.add_class::<types::header::Header>()?;
     m.add_class::<types::request::PyRequest>()?;
     m.add_class::<types::request::PyBodyData>()?;
-    m.add_class::<types::request::PyUploadedFile>()?;
+    m.add_class::<types::upload::PyUploadedFile>()?;
+    m.add_class::<types::url::Url>()?;
     m.add_class::<types::query::QueryParams>()?;
     m.add_class::<middlewares::base::MiddlewareConfig>()?;
+    m.add_class::<middlewares::csrf::CsrfConfig>()?;
+    m.add_class::<middlewares::ip_filter::IpFilterConfig>()?;
+    m.add_class::<middlewares::cache::ResponseCache>()?;
+    m.add_class::<caching::memory::MemoryStore>()?;
+    m.add_class::<metrics::PyMetricsCounter>()?;
     
     m.add_class::<ws::socket::SocketHeld>()?;
     m.add_class::<ws::websocket::WebSocketSession>()?;
@@ -46,8 +67,22 @@ fn hypern(_py: Python<'_>, m: &PyModule) -> PyResult<()>  {
     m.add_class::<database::sql::config::DatabaseConfig>()?;
     m.add_class::<database::sql::config::DatabaseType>()?;
     m.add_class::<database::sql::transaction::DatabaseTransaction>()?;
+    m.add_class::<database::sql::notifier::PostgresNotifier>()?;
 
     m.add_function(wrap_pyfunction!(database::context::get_session_database, m)?)?;
+    m.add_function(wrap_pyfunction!(database::context::check_database_health, m)?)?;
+    m.add_function(wrap_pyfunction!(database::context::database_pool_status, m)?)?;
+    m.add_function(wrap_pyfunction!(security::password::hash_password, m)?)?;
+    m.add_function(wrap_pyfunction!(security::password::verify_password, m)?)?;
+    m.add_function(wrap_pyfunction!(security::jwt::create_token, m)?)?;
+    m.add_function(wrap_pyfunction!(security::jwt::verify_token, m)?)?;
+    m.add("ExpiredSignatureError", py.get_type::<security::jwt::ExpiredSignatureError>())?;
+    m.add("InvalidTokenError", py.get_type::<security::jwt::InvalidTokenError>())?;
+    m.add("InvalidSignatureError", py.get_type::<security::jwt::InvalidSignatureError>())?;
+    m.add_function(wrap_pyfunction!(security::aes::py_encrypt_aes_gcm, m)?)?;
+    m.add_function(wrap_pyfunction!(security::aes::py_decrypt_aes_gcm, m)?)?;
+    m.add_function(wrap_pyfunction!(security::token::generate_token, m)?)?;
+    m.add_function(wrap_pyfunction!(security::token::generate_hex_token, m)?)?;
 
     pyo3::prepare_freethreaded_python();
     Ok(())
@@ -0,0 +1,47 @@
+use pyo3::{exceptions::PyValueError, prelude::*};
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+// Log output of the server, `level` is the filter used when RUST_LOG is not set
+#[derive(Clone, Debug)]
+pub struct LoggingConfig {
+    pub level: String,
+    pub json: bool,
+    pub access_log: bool,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            json: false,
+            access_log: true,
+        }
+    }
+}
+
+impl LoggingConfig {
+    pub fn new(level: &str, json: bool, access_log: bool) -> PyResult<Self> {
+        EnvFilter::try_new(level)
+            .map_err(|e| PyValueError::new_err(format!("Invalid log level {:?}: {}", level, e)))?;
+        Ok(Self {
+            level: level.to_string(),
+            json,
+            access_log,
+        })
+    }
+
+    // The global subscriber can only be set once per process, later calls keep the first one
+    pub fn init(&self) {
+        let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&self.level));
+        let registry = tracing_subscriber::registry().with(filter);
+        let _ = if self.json {
+            registry
+                .with(fmt::layer().json().with_target(false).with_level(true))
+                .try_init()
+        } else {
+            registry
+                .with(fmt::layer().with_target(false).with_level(true))
+                .try_init()
+        };
+    }
+}
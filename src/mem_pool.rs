@@ -5,6 +5,9 @@ use std::{
     sync::Arc,
     time::{Duration, Instant},
 };
+
+use crate::metrics::record_mem_pool;
+
 struct PoolMetrics {
     last_access: Instant,
     hit_count: u64,
@@ -58,12 +61,14 @@ impl AdaptiveMemoryPool {
         while let Some(item) = pool.pop_front() {
             if item.last_used.elapsed() < self.retention_period {
                 metrics.hit_count += 1;
+                record_mem_pool(true);
                 return Ok(item.object.as_ref(py).downcast::<PyDict>()?.into());
             }
         }
 
         // If no reusable dict found, create new one
         metrics.miss_count += 1;
+        record_mem_pool(false);
         Ok(PyDict::new(py).into())
     }
 
@@ -0,0 +1,251 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use axum::{
+    extract::{Request as HttpRequest, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response as ServerResponse},
+};
+use lazy_static::lazy_static;
+use prometheus::{
+    CounterVec, Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    database::context::get_sql_session_mapping,
+    router::router::{RouteMatch, Router},
+};
+
+lazy_static! {
+    static ref REGISTRY: Registry = Registry::new();
+    static ref REQUESTS_TOTAL: IntCounterVec = register(IntCounterVec::new(
+        Opts::new("hypern_requests_total", "Number of handled requests"),
+        &["method", "path", "status"],
+    ));
+    static ref REQUEST_DURATION: HistogramVec = register(HistogramVec::new(
+        HistogramOpts::new(
+            "hypern_request_duration_milliseconds",
+            "Response latency in milliseconds",
+        )
+        .buckets(vec![
+            1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0,
+        ]),
+        &["method", "path"],
+    ));
+    static ref ACTIVE_CONNECTIONS: IntGauge = register(IntGauge::new(
+        "hypern_active_connections",
+        "Number of requests currently being handled",
+    ));
+    static ref REQUESTS_IN_FLIGHT: IntGaugeVec = register(IntGaugeVec::new(
+        Opts::new(
+            "hypern_requests_in_flight",
+            "Number of requests currently being handled per route",
+        ),
+        &["method", "path"],
+    ));
+    static ref RESPONSES_TOTAL: IntCounterVec = register(IntCounterVec::new(
+        Opts::new("hypern_responses_total", "Number of responses by status class"),
+        &["method", "path", "class"],
+    ));
+    static ref MEM_POOL_HITS: IntCounter = register(IntCounter::new(
+        "hypern_mem_pool_hits_total",
+        "Number of dicts reused from the memory pool",
+    ));
+    static ref MEM_POOL_MISSES: IntCounter = register(IntCounter::new(
+        "hypern_mem_pool_misses_total",
+        "Number of dicts the memory pool had to create",
+    ));
+    static ref DB_SESSIONS: IntGauge = register(IntGauge::new(
+        "hypern_db_sessions",
+        "Number of open database sessions",
+    ));
+    static ref IP_FILTER_REJECTIONS: IntCounterVec = register(IntCounterVec::new(
+        Opts::new(
+            "hypern_ip_filter_rejections_total",
+            "Number of requests rejected by the ip filter",
+        ),
+        &["path"],
+    ));
+}
+
+fn register<T>(metric: prometheus::Result<T>) -> T
+where
+    T: prometheus::core::Collector + Clone + 'static,
+{
+    let metric = metric.unwrap();
+    REGISTRY.register(Box::new(metric.clone())).unwrap();
+    metric
+}
+
+// Tracks one request from start to response, the active gauge drops even if the handler panics
+pub struct RequestMetrics {
+    method: String,
+    path: String,
+    started: std::time::Instant,
+}
+
+impl RequestMetrics {
+    // `path` is the route pattern, raw paths would give one series per id
+    pub fn start(method: &str, path: &str) -> Self {
+        ACTIVE_CONNECTIONS.inc();
+        REQUESTS_IN_FLIGHT.with_label_values(&[method, path]).inc();
+        Self {
+            method: method.to_string(),
+            path: path.to_string(),
+            started: std::time::Instant::now(),
+        }
+    }
+
+    pub fn finish(self, status: StatusCode) {
+        let elapsed = self.started.elapsed().as_secs_f64() * 1000.0;
+        REQUESTS_TOTAL
+            .with_label_values(&[&self.method, &self.path, status.as_str()])
+            .inc();
+        let class = format!("{}xx", status.as_u16() / 100);
+        RESPONSES_TOTAL
+            .with_label_values(&[&self.method, &self.path, &class])
+            .inc();
+        REQUEST_DURATION
+            .with_label_values(&[&self.method, &self.path])
+            .observe(elapsed);
+    }
+}
+
+impl Drop for RequestMetrics {
+    fn drop(&mut self) {
+        ACTIVE_CONNECTIONS.dec();
+        REQUESTS_IN_FLIGHT
+            .with_label_values(&[&self.method, &self.path])
+            .dec();
+    }
+}
+
+// What the metrics layer needs to label requests
+#[derive(Clone)]
+pub struct MetricsState {
+    pub router: Arc<RwLock<Router>>,
+    // the scrape endpoint itself is not counted
+    pub scrape_path: String,
+}
+
+// Layer around the whole app, so cached responses, rejections and unmatched requests are counted
+// like handled ones. `path` is the route pattern, or empty for requests matching no route
+pub async fn track_requests(
+    State(state): State<MetricsState>,
+    req: HttpRequest,
+    next: Next,
+) -> ServerResponse {
+    if req.uri().path() == state.scrape_path {
+        return next.run(req).await;
+    }
+    let method = req.method().as_str().to_uppercase();
+    let path = match state.router.read().unwrap().resolve(req.uri().path(), &method) {
+        RouteMatch::Found(route, _) => route.path.clone(),
+        _ => String::new(),
+    };
+    let metrics = RequestMetrics::start(&method, &path);
+    let response = next.run(req).await;
+    metrics.finish(response.status());
+    response
+}
+
+// `path` is the route pattern, or empty for requests matching no route
+pub fn record_ip_rejection(path: &str) {
+    IP_FILTER_REJECTIONS.with_label_values(&[path]).inc();
+}
+
+pub fn record_mem_pool(hit: bool) {
+    if hit {
+        MEM_POOL_HITS.inc();
+    } else {
+        MEM_POOL_MISSES.inc();
+    }
+}
+
+// Prometheus text format of every collected metric
+pub async fn metrics_handler() -> ServerResponse {
+    // read when scraped, the map is not tracked on every change
+    DB_SESSIONS.set(get_sql_session_mapping().len() as i64);
+
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    if let Err(e) = encoder.encode(&REGISTRY.gather(), &mut buffer) {
+        return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+    ([(header::CONTENT_TYPE, encoder.format_type().to_string())], buffer).into_response()
+}
+
+/// A counter of the application, reported by the metrics endpoint next to the request metrics
+#[pyclass(name = "MetricsCounter")]
+pub struct PyMetricsCounter {
+    counter: CounterVec,
+    #[pyo3(get)]
+    name: String,
+    #[pyo3(get)]
+    labels: Vec<String>,
+}
+
+#[pymethods]
+impl PyMetricsCounter {
+    #[new]
+    #[pyo3(signature = (name, help, labels=Vec::new()))]
+    fn new(name: &str, help: &str, labels: Vec<String>) -> PyResult<Self> {
+        let label_names: Vec<&str> = labels.iter().map(String::as_str).collect();
+        let counter = CounterVec::new(Opts::new(name, help), &label_names)
+            .map_err(|e| PyValueError::new_err(format!("Invalid counter {}: {}", name, e)))?;
+        REGISTRY
+            .register(Box::new(counter.clone()))
+            .map_err(|e| PyValueError::new_err(format!("Cannot register counter {}: {}", name, e)))?;
+        Ok(Self {
+            counter,
+            name: name.to_string(),
+            labels,
+        })
+    }
+
+    #[pyo3(signature = (amount=1.0, labels=None))]
+    fn inc(&self, amount: f64, labels: Option<HashMap<String, String>>) -> PyResult<()> {
+        if amount < 0.0 {
+            return Err(PyValueError::new_err("Counters can only increase"));
+        }
+        self.counter_for(labels)?.inc_by(amount);
+        Ok(())
+    }
+
+    #[pyo3(signature = (labels=None))]
+    fn get(&self, labels: Option<HashMap<String, String>>) -> PyResult<f64> {
+        Ok(self.counter_for(labels)?.get())
+    }
+}
+
+impl PyMetricsCounter {
+    fn counter_for(
+        &self,
+        labels: Option<HashMap<String, String>>,
+    ) -> PyResult<prometheus::Counter> {
+        let labels = labels.unwrap_or_default();
+        let values = self
+            .labels
+            .iter()
+            .map(|name| {
+                labels.get(name).map(String::as_str).ok_or_else(|| {
+                    PyValueError::new_err(format!("Missing label {} for {}", name, self.name))
+                })
+            })
+            .collect::<PyResult<Vec<&str>>>()?;
+        if labels.len() != values.len() {
+            return Err(PyValueError::new_err(format!(
+                "Unknown labels for {}, expected {:?}",
+                self.name, self.labels
+            )));
+        }
+        self.counter
+            .get_metric_with_label_values(&values)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
@@ -1,6 +1,6 @@
 use pyo3::prelude::*;
 
-use crate::types::function_info::FunctionInfo;
+use crate::{router::router::Router, types::function_info::FunctionInfo};
 
 #[pyclass]
 #[derive(Clone)]
@@ -10,15 +10,36 @@ pub struct MiddlewareConfig {
 
     #[pyo3(get)]
     pub is_conditional: bool,
+
+    // route-style patterns (`/api/*`, `/users/:id`), empty means every path
+    #[pyo3(get)]
+    pub path_patterns: Vec<String>,
+
+    #[pyo3(get)]
+    pub exclude_patterns: Vec<String>,
+
+    // async middlewares taking longer are abandoned, None waits for them
+    #[pyo3(get)]
+    pub timeout_ms: Option<u64>,
 }
 
 #[pymethods]
 impl MiddlewareConfig {
     #[new]
-    pub fn new(priority: i32, is_conditional: bool) -> Self {
+    #[pyo3(signature = (priority=0, is_conditional=true, path_patterns=Vec::new(), exclude_patterns=Vec::new(), timeout_ms=None))]
+    pub fn new(
+        priority: i32,
+        is_conditional: bool,
+        path_patterns: Vec<String>,
+        exclude_patterns: Vec<String>,
+        timeout_ms: Option<u64>,
+    ) -> Self {
         Self {
             priority,
             is_conditional,
+            path_patterns,
+            exclude_patterns,
+            timeout_ms,
         }
     }
 
@@ -27,8 +48,18 @@ impl MiddlewareConfig {
         Self {
             priority: 0,
             is_conditional: true,
+            path_patterns: Vec::new(),
+            exclude_patterns: Vec::new(),
+            timeout_ms: None,
         }
     }
+
+    // Whether the middleware should run for a request path
+    pub fn applies_to(&self, path: &str) -> bool {
+        let matches = |pattern: &String| Router::path_matches_pattern(path, pattern);
+        (self.path_patterns.is_empty() || self.path_patterns.iter().any(matches))
+            && !self.exclude_patterns.iter().any(matches)
+    }
 }
 
 #[derive(Clone)]
@@ -0,0 +1,342 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{to_bytes, Body, Bytes, HttpBody},
+    extract::Request as HttpRequest,
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response as ServerResponse},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+};
+use redis::Commands;
+use serde_json::{json, Value};
+use tracing::error;
+
+use crate::caching::{memory::MemoryStore, redis_pool::RedisPool};
+
+const CACHE_BACKENDS: [&str; 2] = ["memory", "redis"];
+const KEY_PARTS: [&str; 3] = ["method", "path", "query"];
+const REDIS_KEY_PREFIX: &str = "response:";
+// requests carrying credentials get a response meant for them alone, unless the key varies on them
+const CREDENTIAL_HEADERS: [header::HeaderName; 2] = [header::COOKIE, header::AUTHORIZATION];
+
+// How the responses of a route are cached, set with `Route.cache`
+#[derive(Debug, Clone)]
+pub struct CacheRule {
+    pub ttl: u64,
+    key_parts: Vec<String>,
+    vary_headers: Vec<String>,
+}
+
+impl CacheRule {
+    pub fn new(ttl: u64, key: &str, vary_headers: Vec<String>) -> PyResult<Self> {
+        if ttl == 0 {
+            return Err(PyValueError::new_err("Cache ttl must be greater than 0"));
+        }
+        let key_parts: Vec<String> = key.split('+').map(|part| part.trim().to_lowercase()).collect();
+        if let Some(part) = key_parts.iter().find(|part| !KEY_PARTS.contains(&part.as_str())) {
+            return Err(PyValueError::new_err(format!(
+                "Invalid cache key part '{}', expected a combination of: {}",
+                part,
+                KEY_PARTS.join(", ")
+            )));
+        }
+        // invalidation matches keys by path prefix
+        if !key_parts.iter().any(|part| part == "path") {
+            return Err(PyValueError::new_err("The cache key must include the path"));
+        }
+        Ok(Self {
+            ttl,
+            key_parts,
+            vary_headers: vary_headers.iter().map(|name| name.to_lowercase()).collect(),
+        })
+    }
+
+    // Key of the request, None when its response can't be cached. The path always comes first
+    pub fn key(&self, req: &HttpRequest) -> Option<String> {
+        if !matches!(req.method().as_str(), "GET" | "HEAD") {
+            return None;
+        }
+        let personal = CREDENTIAL_HEADERS.iter().any(|name| {
+            req.headers().contains_key(name) && !self.vary_headers.iter().any(|vary| vary == name.as_str())
+        });
+        if personal {
+            return None;
+        }
+        let mut key = req.uri().path().to_string();
+        for part in &self.key_parts {
+            match part.as_str() {
+                // HEAD is answered from the GET entry
+                "method" => key.push_str("|GET"),
+                "query" => {
+                    key.push('?');
+                    key.push_str(req.uri().query().unwrap_or(""));
+                }
+                _ => {}
+            }
+        }
+        for name in &self.vary_headers {
+            let values = req
+                .headers()
+                .get_all(name.as_str())
+                .iter()
+                .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+                .collect::<Vec<_>>()
+                .join(",");
+            key.push_str(&format!("|{}={}", name, values));
+        }
+        Some(key)
+    }
+}
+
+fn has_directive(headers: &HeaderMap, directives: &[&str]) -> bool {
+    headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|directive| {
+            directives
+                .iter()
+                .any(|expected| directive.trim().eq_ignore_ascii_case(expected))
+        })
+}
+
+// `Cache-Control: no-cache` from the client skips the lookup, the fresh response is still stored
+pub fn bypasses_cache(req: &HttpRequest) -> bool {
+    has_directive(req.headers(), &["no-cache"])
+}
+
+// Kept as is by the memory store, serialized to JSON for redis
+#[pyclass]
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Bytes,
+}
+
+impl CachedResponse {
+    fn to_response(&self) -> ServerResponse {
+        let mut response = Body::from(self.body.clone()).into_response();
+        *response.status_mut() = StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK);
+        let headers = response.headers_mut();
+        headers.clear();
+        for (name, value) in &self.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.append(name, value);
+            }
+        }
+        response
+    }
+
+    fn to_json(&self) -> String {
+        json!({
+            "status": self.status,
+            "headers": self.headers,
+            "body": STANDARD.encode(&self.body),
+        })
+        .to_string()
+    }
+
+    fn from_json(data: &str) -> Option<Self> {
+        let value: Value = serde_json::from_str(data).ok()?;
+        let headers = value["headers"]
+            .as_array()?
+            .iter()
+            .filter_map(|pair| Some((pair[0].as_str()?.to_string(), pair[1].as_str()?.to_string())))
+            .collect();
+        Some(Self {
+            status: value["status"].as_u64()? as u16,
+            headers,
+            body: STANDARD.decode(value["body"].as_str()?).ok()?.into(),
+        })
+    }
+}
+
+enum CacheBackend {
+    // the store behind `hypern.caching.MemoryBackend`, with its expiry and LRU eviction
+    Memory(MemoryStore),
+    Redis(RedisPool),
+}
+
+impl std::fmt::Debug for CacheBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CacheBackend::Memory(store) => f.debug_tuple("Memory").field(&store.max_entries()).finish(),
+            CacheBackend::Redis(pool) => f.debug_tuple("Redis").field(pool).finish(),
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    // shared with the server, so invalidation from python applies to the running app
+    backend: Arc<CacheBackend>,
+}
+
+#[pymethods]
+impl ResponseCache {
+    #[new]
+    #[pyo3(signature = (backend="memory", redis_url="redis://localhost:6379", max_entries=10_000))]
+    pub fn new(backend: &str, redis_url: &str, max_entries: usize) -> PyResult<Self> {
+        let backend = match backend {
+            "memory" => CacheBackend::Memory(MemoryStore::new(max_entries, None)?),
+            "redis" => CacheBackend::Redis(RedisPool::open(redis_url)?),
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Invalid cache backend '{}', expected one of: {}",
+                    backend,
+                    CACHE_BACKENDS.join(", ")
+                )))
+            }
+        };
+        Ok(Self {
+            backend: Arc::new(backend),
+        })
+    }
+
+    /// Drop every cached response whose path starts with `prefix`, returns how many were removed
+    pub fn invalidate_prefix(&self, py: Python, prefix: &str) -> PyResult<usize> {
+        match self.backend.as_ref() {
+            CacheBackend::Memory(store) => Ok(store.delete_startswith(prefix)),
+            CacheBackend::Redis(pool) => py
+                .allow_threads(|| -> redis::RedisResult<usize> {
+                    let mut connection = pool.client().get_connection()?;
+                    let pattern = format!("{}{}*", REDIS_KEY_PREFIX, escape_pattern(prefix));
+                    let keys: Vec<String> = connection.scan_match(&pattern)?.collect();
+                    if keys.is_empty() {
+                        return Ok(0);
+                    }
+                    redis::cmd("DEL").arg(&keys).query(&mut connection)
+                })
+                .map_err(|e| PyRuntimeError::new_err(format!("Failed to invalidate cache: {}", e))),
+        }
+    }
+}
+
+// glob characters of a redis MATCH pattern
+fn escape_pattern(prefix: &str) -> String {
+    let mut escaped = String::with_capacity(prefix.len());
+    for c in prefix.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+impl ResponseCache {
+    // Reachability of the redis backend for the readiness endpoint, None with the memory backend
+    pub async fn ping(&self) -> Option<bool> {
+        match self.backend.as_ref() {
+            CacheBackend::Memory(_) => None,
+            CacheBackend::Redis(pool) => Some(pool.ping().await),
+        }
+    }
+
+    // The stored response for `key`, None on a miss or when the backend fails
+    pub async fn lookup(&self, key: &str) -> Option<ServerResponse> {
+        let cached = match self.backend.as_ref() {
+            CacheBackend::Memory(store) => Python::with_gil(|py| {
+                store
+                    .get(py, key)
+                    .and_then(|cached| cached.extract::<CachedResponse>(py).ok())
+            }),
+            CacheBackend::Redis(pool) => {
+                let key = format!("{}{}", REDIS_KEY_PREFIX, key);
+                let data = match pool.connection().await {
+                    Ok(mut connection) => {
+                        redis::cmd("GET")
+                            .arg(&key)
+                            .query_async::<_, Option<String>>(&mut connection)
+                            .await
+                    }
+                    Err(e) => Err(e),
+                };
+                match data {
+                    Ok(data) => data.and_then(|data| CachedResponse::from_json(&data)),
+                    Err(e) => {
+                        error!("Failed to read cached response: {}", e);
+                        None
+                    }
+                }
+            }
+        };
+        cached.map(|cached| cached.to_response())
+    }
+
+    // Store a 200 response and hand it back. Responses that set cookies, opt out with
+    // `Cache-Control: no-store` or `private`, or stream their body are not stored
+    pub async fn store(&self, key: String, ttl: u64, response: ServerResponse) -> ServerResponse {
+        if response.status() != StatusCode::OK
+            || response.headers().contains_key(header::SET_COOKIE)
+            || has_directive(response.headers(), &["no-store", "private"])
+            || response.body().size_hint().exact().is_none()
+        {
+            return response;
+        }
+
+        let (parts, body) = response.into_parts();
+        let body = match to_bytes(body, usize::MAX).await {
+            Ok(body) => body,
+            Err(e) => {
+                error!("Failed to read response body for the cache: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+        let cached = CachedResponse {
+            status: parts.status.as_u16(),
+            headers: parts
+                .headers
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.to_string(),
+                        String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                    )
+                })
+                .collect(),
+            body: body.clone(),
+        };
+
+        match self.backend.as_ref() {
+            CacheBackend::Memory(store) => {
+                let stored = Python::with_gil(|py| -> PyResult<()> {
+                    store.set(key, Py::new(py, cached)?.into_py(py), Some(ttl));
+                    Ok(())
+                });
+                if let Err(e) = stored {
+                    error!("Failed to store cached response: {}", e);
+                }
+            }
+            CacheBackend::Redis(pool) => {
+                let key = format!("{}{}", REDIS_KEY_PREFIX, key);
+                let result = match pool.connection().await {
+                    Ok(mut connection) => {
+                        redis::cmd("SET")
+                            .arg(&key)
+                            .arg(cached.to_json())
+                            .arg("EX")
+                            .arg(ttl)
+                            .query_async::<_, ()>(&mut connection)
+                            .await
+                    }
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    error!("Failed to store cached response: {}", e);
+                }
+            }
+        }
+        ServerResponse::from_parts(parts, Body::from(body))
+    }
+}
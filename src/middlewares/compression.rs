@@ -0,0 +1,236 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    body::HttpBody,
+    http::{header, Response},
+    Router,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use tower_http::{
+    compression::{predicate::Predicate, CompressionLayer},
+    CompressionLevel,
+};
+
+const ALGORITHMS: [&str; 4] = ["gzip", "br", "deflate", "zstd"];
+pub const DEFAULT_ALGORITHMS: [&str; 3] = ["br", "gzip", "zstd"];
+pub const DEFAULT_CONTENT_TYPES: [&str; 2] = ["application/json", "text/*"];
+pub const DEFAULT_MIN_SIZE: u64 = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Algorithm {
+    Gzip,
+    Br,
+    Deflate,
+    Zstd,
+}
+
+impl Algorithm {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_lowercase().as_str() {
+            "gzip" => Ok(Self::Gzip),
+            "br" | "brotli" => Ok(Self::Br),
+            "deflate" => Ok(Self::Deflate),
+            "zstd" => Ok(Self::Zstd),
+            name => Err(PyValueError::new_err(format!(
+                "Unsupported compression algorithm: {}, expected one of {:?}",
+                name, ALGORITHMS
+            ))),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Br => "br",
+            Self::Deflate => "deflate",
+            Self::Zstd => "zstd",
+        }
+    }
+
+    fn max_level(&self) -> u8 {
+        match self {
+            Self::Gzip | Self::Deflate => 9,
+            Self::Br => 11,
+            Self::Zstd => 22,
+        }
+    }
+
+    // a layer that only offers this algorithm
+    fn layer(&self, level: Option<u8>, predicate: CompressWhen) -> CompressionLayer<CompressWhen> {
+        let quality = match level {
+            Some(level) => CompressionLevel::Precise(level as i32),
+            None => CompressionLevel::Default,
+        };
+        CompressionLayer::new()
+            .gzip(*self == Self::Gzip)
+            .br(*self == Self::Br)
+            .deflate(*self == Self::Deflate)
+            .zstd(*self == Self::Zstd)
+            .quality(quality)
+            .compress_when(predicate)
+    }
+}
+
+// One level for every algorithm, or a level per algorithm name
+#[derive(FromPyObject, Debug, Clone)]
+pub enum CompressionLevels {
+    All(u8),
+    PerAlgorithm(HashMap<String, u8>),
+}
+
+// Which responses get compressed
+#[derive(Debug, Clone)]
+struct CompressWhen {
+    min_size: u64,
+    // None keeps everything but images, grpc and event streams
+    content_types: Option<Arc<Vec<String>>>,
+}
+
+impl CompressWhen {
+    fn allows(&self, content_type: &str) -> bool {
+        match &self.content_types {
+            Some(patterns) => patterns.iter().any(|pattern| match pattern.strip_suffix("/*") {
+                Some("*") => true,
+                Some(kind) => content_type.split('/').next() == Some(kind),
+                None => pattern == content_type,
+            }),
+            None => {
+                !(content_type.starts_with("image/") && content_type != "image/svg+xml")
+                    && !content_type.starts_with("application/grpc")
+            }
+        }
+    }
+}
+
+impl Predicate for CompressWhen {
+    fn should_compress<B>(&self, response: &Response<B>) -> bool
+    where
+        B: HttpBody,
+    {
+        // bodies of unknown size are streams, compressing them would hold chunks back
+        let size = match response.body().size_hint().exact() {
+            Some(size) => size,
+            None => return false,
+        };
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_lowercase())
+            .unwrap_or_default();
+        size >= self.min_size && content_type != "text/event-stream" && self.allows(&content_type)
+    }
+}
+
+// Encodings offered to clients in order of preference, with the level of each
+#[derive(Debug, Clone)]
+pub struct CompressionConfig {
+    // None is the default level of the algorithm
+    algorithms: Vec<(Algorithm, Option<u8>)>,
+    predicate: CompressWhen,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            algorithms: vec![
+                (Algorithm::Br, None),
+                (Algorithm::Gzip, None),
+                (Algorithm::Deflate, None),
+            ],
+            predicate: CompressWhen {
+                min_size: 32,
+                content_types: None,
+            },
+        }
+    }
+}
+
+impl CompressionConfig {
+    // `algorithms` are gzip, br (or brotli), deflate and zstd. Levels go up to 9 for gzip and
+    // deflate, 11 for brotli and 22 for zstd. `content_types` are mime types or `type/*`
+    pub fn new(
+        algorithms: &[String],
+        level: Option<CompressionLevels>,
+        min_size: u64,
+        content_types: &[String],
+    ) -> PyResult<Self> {
+        let mut parsed: Vec<Algorithm> = Vec::new();
+        for name in algorithms {
+            let algorithm = Algorithm::parse(name)?;
+            if !parsed.contains(&algorithm) {
+                parsed.push(algorithm);
+            }
+        }
+        if parsed.is_empty() {
+            return Err(PyValueError::new_err(
+                "At least one compression algorithm is required",
+            ));
+        }
+
+        let mut per_algorithm: Vec<(Algorithm, u8)> = Vec::new();
+        if let Some(CompressionLevels::PerAlgorithm(levels)) = &level {
+            for (name, level) in levels {
+                let algorithm = Algorithm::parse(name)?;
+                if !parsed.contains(&algorithm) {
+                    return Err(PyValueError::new_err(format!(
+                        "Compression level set for {}, which is not in algorithms",
+                        name
+                    )));
+                }
+                per_algorithm.push((algorithm, *level));
+            }
+        }
+
+        let mut configured = Vec::with_capacity(parsed.len());
+        for algorithm in parsed {
+            let algorithm_level = match &level {
+                Some(CompressionLevels::All(level)) => Some(*level),
+                Some(CompressionLevels::PerAlgorithm(_)) => per_algorithm
+                    .iter()
+                    .find(|(configured, _)| *configured == algorithm)
+                    .map(|(_, level)| *level),
+                None => None,
+            };
+            if let Some(level) = algorithm_level.filter(|level| *level > algorithm.max_level()) {
+                return Err(PyValueError::new_err(format!(
+                    "Invalid compression level: {}, expected 0 to {} for {}",
+                    level,
+                    algorithm.max_level(),
+                    algorithm.name()
+                )));
+            }
+            configured.push((algorithm, algorithm_level));
+        }
+
+        let mut patterns = Vec::with_capacity(content_types.len());
+        for content_type in content_types {
+            let content_type = content_type.trim().to_lowercase();
+            if !content_type.contains('/') {
+                return Err(PyValueError::new_err(format!(
+                    "Invalid content type: {}, expected a mime type such as text/html or text/*",
+                    content_type
+                )));
+            }
+            patterns.push(content_type);
+        }
+
+        Ok(Self {
+            algorithms: configured,
+            predicate: CompressWhen {
+                min_size,
+                content_types: Some(Arc::new(patterns)),
+            },
+        })
+    }
+
+    // One layer per algorithm, the first one the client accepts compresses the response and
+    // the outer ones leave an encoded response alone
+    pub fn apply(&self, mut app: Router) -> Router {
+        for (algorithm, level) in &self.algorithms {
+            app = app.layer(algorithm.layer(*level, self.predicate.clone()));
+        }
+        app
+    }
+}
@@ -0,0 +1,113 @@
+use axum::{
+    body::{to_bytes, Body, HttpBody},
+    extract::Request as HttpRequest,
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response as ServerResponse},
+};
+use chrono::DateTime;
+use tracing::error;
+use xxhash_rust::xxh3::xxh3_64;
+
+// bodies up to this size get an ETag computed from their content
+pub const DEFAULT_ETAG_MAX_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct ConditionalResponses {
+    pub etag_max_size: usize,
+}
+
+// Validators sent by the client, only GET and HEAD requests are answered with a 304
+#[derive(Debug)]
+pub struct Preconditions {
+    if_none_match: Option<String>,
+    if_modified_since: Option<String>,
+}
+
+impl Preconditions {
+    pub fn from_request(req: &HttpRequest) -> Option<Self> {
+        if !matches!(req.method().as_str(), "GET" | "HEAD") {
+            return None;
+        }
+        let value = |name: HeaderName| {
+            req.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        Some(Self {
+            if_none_match: value(header::IF_NONE_MATCH),
+            if_modified_since: value(header::IF_MODIFIED_SINCE),
+        })
+    }
+
+    // If-Modified-Since is ignored when If-None-Match is sent (RFC 9110 13.1.3)
+    fn not_modified(&self, headers: &HeaderMap) -> bool {
+        let value = |name: HeaderName| headers.get(name).and_then(|value| value.to_str().ok());
+        if let Some(if_none_match) = &self.if_none_match {
+            return value(header::ETAG).is_some_and(|etag| etag_matches(if_none_match, etag));
+        }
+        let (Some(if_modified_since), Some(last_modified)) =
+            (&self.if_modified_since, value(header::LAST_MODIFIED))
+        else {
+            return false;
+        };
+        match (
+            DateTime::parse_from_rfc2822(if_modified_since),
+            DateTime::parse_from_rfc2822(last_modified),
+        ) {
+            (Ok(since), Ok(modified)) => modified <= since,
+            _ => false,
+        }
+    }
+}
+
+// weak comparison, a `W/` prefix on either side is ignored
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = opaque(etag);
+    if_none_match
+        .split(',')
+        .any(|tag| tag.trim() == "*" || opaque(tag) == etag)
+}
+
+// Add an ETag to a successful response without one, then answer 304 when the client's copy is current
+pub async fn conditional_response(
+    response: ServerResponse,
+    preconditions: &Preconditions,
+    etag_max_size: usize,
+) -> ServerResponse {
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+
+    let mut response = response;
+    let small = response
+        .body()
+        .size_hint()
+        .exact()
+        .is_some_and(|size| size as usize <= etag_max_size);
+    if !response.headers().contains_key(header::ETAG) && small {
+        let (mut parts, body) = response.into_parts();
+        let body = match to_bytes(body, etag_max_size).await {
+            Ok(body) => body,
+            Err(e) => {
+                error!("Failed to read response body for ETag: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+        let etag = format!("\"{:016x}\"", xxh3_64(&body));
+        parts
+            .headers
+            .insert(header::ETAG, HeaderValue::from_str(&etag).unwrap());
+        response = ServerResponse::from_parts(parts, Body::from(body));
+    }
+
+    if !preconditions.not_modified(response.headers()) {
+        return response;
+    }
+    let (mut parts, _) = response.into_parts();
+    parts.status = StatusCode::NOT_MODIFIED;
+    parts.headers.remove(header::CONTENT_TYPE);
+    parts.headers.remove(header::CONTENT_LENGTH);
+    ServerResponse::from_parts(parts, Body::empty())
+}
@@ -0,0 +1,97 @@
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use super::signing::{constant_time_eq, sign_value, unsign_value};
+use crate::types::{
+    cookie::{parse_cookies, SetCookie},
+    request::Request,
+};
+
+const SAFE_METHODS: [&str; 3] = ["GET", "HEAD", "OPTIONS"];
+
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct CsrfConfig {
+    #[pyo3(get, set)]
+    pub secret: String,
+    #[pyo3(get, set)]
+    pub cookie_name: String,
+    #[pyo3(get, set)]
+    pub header_name: String,
+    // urlencoded form field checked when the header is missing
+    #[pyo3(get, set)]
+    pub form_field: String,
+}
+
+#[pymethods]
+impl CsrfConfig {
+    #[new]
+    #[pyo3(signature = (secret, cookie_name="csrftoken", header_name="X-CSRF-Token", form_field="csrf_token"))]
+    pub fn new(secret: String, cookie_name: &str, header_name: &str, form_field: &str) -> PyResult<Self> {
+        if secret.is_empty() {
+            return Err(PyValueError::new_err("CSRF secret must not be empty"));
+        }
+        Ok(Self {
+            secret,
+            cookie_name: cookie_name.to_string(),
+            header_name: header_name.to_string(),
+            form_field: form_field.to_string(),
+        })
+    }
+}
+
+// Double submit check: the header or form field must repeat the signed token of the cookie
+#[derive(Debug, Clone)]
+pub struct CsrfMiddleware {
+    config: CsrfConfig,
+}
+
+impl CsrfMiddleware {
+    pub fn new(config: CsrfConfig) -> Self {
+        Self { config }
+    }
+
+    // the signed token from the request cookie, if it carries a valid one
+    fn cookie_token(&self, request: &Request) -> Option<String> {
+        let cookies = parse_cookies(request.headers.get_all("cookie".to_string()).iter());
+        let token = cookies.get(&self.config.cookie_name)?;
+        unsign_value(std::slice::from_ref(&self.config.secret), token)?;
+        Some(token.clone())
+    }
+
+    // Returns the token of the request, and a new one when the cookie has to be (re)issued
+    pub fn before(&self, request: &Request) -> Result<(String, bool), &'static str> {
+        let cookie_token = self.cookie_token(request);
+
+        if !SAFE_METHODS.contains(&request.method.to_uppercase().as_str()) {
+            let cookie_token = cookie_token.as_ref().ok_or("CSRF cookie missing or invalid")?;
+            let submitted = request
+                .headers
+                .get(self.config.header_name.clone())
+                .or_else(|| request.body.form_value(&self.config.form_field))
+                .ok_or("CSRF token missing")?;
+            if !constant_time_eq(submitted.as_bytes(), cookie_token.as_bytes()) {
+                return Err("CSRF token mismatch");
+            }
+        }
+
+        match cookie_token {
+            Some(token) => Ok((token, false)),
+            None => {
+                let token = uuid::Uuid::new_v4().simple().to_string();
+                Ok((sign_value(&self.config.secret, &token), true))
+            }
+        }
+    }
+
+    // readable by scripts, which have to copy it into the header
+    pub fn cookie(&self, token: &str) -> PyResult<String> {
+        SetCookie {
+            key: self.config.cookie_name.clone(),
+            value: token.to_string(),
+            path: Some("/".to_string()),
+            samesite: Some("Lax".to_string()),
+            ..Default::default()
+        }
+        .build()
+    }
+}
@@ -0,0 +1,136 @@
+use std::{net::IpAddr, str::FromStr};
+
+use axum::{body::Body, http::StatusCode, response::Response as ServerResponse};
+use ipnetwork::IpNetwork;
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use super::proxy::canonical_ip;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterMode {
+    // only matching addresses are let through
+    Allowlist,
+    // matching addresses are rejected
+    Blocklist,
+}
+
+impl FromStr for FilterMode {
+    type Err = PyErr;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode.to_lowercase().as_str() {
+            "allowlist" => Ok(FilterMode::Allowlist),
+            "blocklist" => Ok(FilterMode::Blocklist),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid filter mode: {}, expected allowlist or blocklist",
+                mode
+            ))),
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct IpFilterConfig {
+    #[pyo3(get)]
+    pub cidrs: Vec<String>,
+    #[pyo3(get)]
+    pub mode: String,
+    // only these ranges are served when not empty, on top of an allowlist `cidrs`
+    #[pyo3(get)]
+    pub allow: Vec<String>,
+    // always rejected, even when also allowed
+    #[pyo3(get)]
+    pub deny: Vec<String>,
+    // status of rejected requests
+    #[pyo3(get)]
+    pub status: u16,
+}
+
+#[pymethods]
+impl IpFilterConfig {
+    #[new]
+    #[pyo3(signature = (cidrs=Vec::new(), mode="allowlist", allow=Vec::new(), deny=Vec::new(), status=403))]
+    pub fn new(
+        cidrs: Vec<String>,
+        mode: &str,
+        allow: Vec<String>,
+        deny: Vec<String>,
+        status: u16,
+    ) -> PyResult<Self> {
+        let config = Self {
+            cidrs,
+            mode: mode.to_string(),
+            allow,
+            deny,
+            status,
+        };
+        // validate now so a typo fails at startup instead of on every request
+        IpFilterMiddleware::from_config(&config)?;
+        Ok(config)
+    }
+}
+
+fn parse_networks(cidrs: &[String]) -> PyResult<Vec<IpNetwork>> {
+    cidrs
+        .iter()
+        .map(|cidr| {
+            // a plain address is a single host network
+            IpNetwork::from_str(cidr.trim())
+                .map_err(|e| PyValueError::new_err(format!("Invalid CIDR {:?}: {}", cidr, e)))
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct IpFilterMiddleware {
+    // empty lets every address through
+    allow: Vec<IpNetwork>,
+    deny: Vec<IpNetwork>,
+    pub status: StatusCode,
+}
+
+impl IpFilterMiddleware {
+    pub fn from_config(config: &IpFilterConfig) -> PyResult<Self> {
+        let mut allow = parse_networks(&config.allow)?;
+        let mut deny = parse_networks(&config.deny)?;
+        match config.mode.parse::<FilterMode>()? {
+            FilterMode::Allowlist => allow.extend(parse_networks(&config.cidrs)?),
+            FilterMode::Blocklist => deny.extend(parse_networks(&config.cidrs)?),
+        }
+        let status = StatusCode::from_u16(config.status)
+            .ok()
+            .filter(|status| status.is_client_error() || status.is_server_error())
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "Invalid ip filter status {}, expected a 4xx or 5xx code",
+                    config.status
+                ))
+            })?;
+        Ok(Self {
+            allow,
+            deny,
+            status,
+        })
+    }
+
+    // whether a request from `client_ip` may go on, the deny list wins over the allow list and
+    // unparsable addresses only pass a filter without an allow list
+    pub fn allows(&self, client_ip: &str) -> bool {
+        let ip = match IpAddr::from_str(client_ip) {
+            Ok(ip) => canonical_ip(ip),
+            Err(_) => return self.allow.is_empty(),
+        };
+        if self.deny.iter().any(|network| network.contains(ip)) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(|network| network.contains(ip))
+    }
+
+    pub fn rejection(&self) -> ServerResponse {
+        ServerResponse::builder()
+            .status(self.status)
+            .body(Body::from(self.status.canonical_reason().unwrap_or("Forbidden")))
+            .unwrap()
+    }
+}
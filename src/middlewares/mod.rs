@@ -1 +1,12 @@
-pub mod base;
\ No newline at end of file
+pub mod base;
+pub mod cache;
+pub mod compression;
+pub mod conditional;
+pub mod csrf;
+pub mod ip_filter;
+pub mod proxy;
+pub mod range;
+pub mod rate_limit;
+pub mod security_headers;
+pub mod session;
+pub mod signing;
@@ -0,0 +1,196 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+    sync::Arc,
+};
+
+use axum::{
+    extract::{ConnectInfo, Request as HttpRequest},
+    http::HeaderMap,
+};
+use ipnetwork::IpNetwork;
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+#[cfg(unix)]
+use crate::unix_socket::UnixPeer;
+
+// IPv4 clients on a dual stack socket show up as ::ffff:a.b.c.d
+pub fn canonical_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        IpAddr::V4(_) => ip,
+    }
+}
+
+// A forwarded hop may be quoted and carry a port: `"[2001:db8::1]:4711"` or `192.0.2.1:80`
+fn parse_hop(hop: &str) -> Option<IpAddr> {
+    let hop = hop.trim().trim_matches('"');
+    let address = match hop.strip_prefix('[') {
+        Some(rest) => rest.split(']').next()?,
+        None if hop.matches(':').count() == 1 => hop.split(':').next()?,
+        None => hop,
+    };
+    IpAddr::from_str(address).ok().map(canonical_ip)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    networks: Vec<IpNetwork>,
+}
+
+impl TrustedProxies {
+    pub fn new(cidrs: &[String]) -> PyResult<Self> {
+        let networks = cidrs
+            .iter()
+            .map(|cidr| {
+                IpNetwork::from_str(cidr.trim())
+                    .map_err(|e| PyValueError::new_err(format!("Invalid CIDR {:?}: {}", cidr, e)))
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(Self { networks })
+    }
+
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.networks.iter().any(|network| network.contains(ip))
+    }
+
+    // Hops the proxies recorded, closest to the client first. RFC 7239 `Forwarded` wins over
+    // `X-Forwarded-For`, a hop that can't be parsed (`unknown`, obfuscated names) is kept as None
+    fn forwarded_hops(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+        let forwarded: Vec<Option<IpAddr>> = headers
+            .get_all("forwarded")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|element| {
+                element.split(';').find_map(|pair| {
+                    let (key, value) = pair.split_once('=')?;
+                    key.trim().eq_ignore_ascii_case("for").then(|| parse_hop(value))
+                })
+            })
+            .collect();
+        if !forwarded.is_empty() {
+            return forwarded;
+        }
+        headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(parse_hop)
+            .collect()
+    }
+
+    // Walk back from the peer through trusted proxies, the first untrusted hop is the client
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        let peer = canonical_ip(peer);
+        if !self.is_trusted(peer) {
+            return peer;
+        }
+        self.forwarded_client(headers).unwrap_or(peer)
+    }
+
+    // The client according to the hops of a peer already known to be a proxy
+    fn forwarded_client(&self, headers: &HeaderMap) -> Option<IpAddr> {
+        let mut client = None;
+        for hop in Self::forwarded_hops(headers).into_iter().rev() {
+            match hop {
+                Some(ip) => {
+                    client = Some(ip);
+                    if !self.is_trusted(ip) {
+                        break;
+                    }
+                }
+                // the chain can't be followed further
+                None => break,
+            }
+        }
+        client
+    }
+
+    // Scheme the client used as reported by a trusted proxy
+    pub fn forwarded_proto(&self, peer: IpAddr, headers: &HeaderMap) -> Option<String> {
+        if !self.is_trusted(canonical_ip(peer)) {
+            return None;
+        }
+        Self::proto(headers)
+    }
+
+    // `Forwarded` wins over `X-Forwarded-Proto`
+    fn proto(headers: &HeaderMap) -> Option<String> {
+        let forwarded = headers
+            .get_all("forwarded")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .find_map(|element| {
+                element.split(';').find_map(|pair| {
+                    let (key, value) = pair.split_once('=')?;
+                    key.trim()
+                        .eq_ignore_ascii_case("proto")
+                        .then(|| value.trim().trim_matches('"').to_lowercase())
+                })
+            });
+        forwarded.or_else(|| {
+            let proto = headers.get("x-forwarded-proto")?.to_str().ok()?;
+            proto.split(',').next().map(|proto| proto.trim().to_lowercase())
+        })
+    }
+}
+
+// Whether the request came over a unix socket, whose peer can only be a local proxy
+fn from_unix_socket(req: &HttpRequest) -> bool {
+    #[cfg(unix)]
+    if req.extensions().get::<ConnectInfo<UnixPeer>>().is_some() {
+        return true;
+    }
+    let _ = req;
+    false
+}
+
+// Scheme of the request URL, plain http unless a trusted proxy or the request says otherwise
+pub fn request_scheme(req: &HttpRequest) -> String {
+    if from_unix_socket(req) {
+        if let Some(proto) = TrustedProxies::proto(req.headers()) {
+            return proto;
+        }
+    }
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let proxies = req.extensions().get::<Arc<TrustedProxies>>();
+    if let (Some(peer), Some(proxies)) = (peer, proxies) {
+        if let Some(proto) = proxies.forwarded_proto(peer, req.headers()) {
+            return proto;
+        }
+    }
+    req.uri().scheme_str().unwrap_or("http").to_string()
+}
+
+// `(remote_addr, client_ip)` of a request, both empty when the peer is unknown. Over a unix
+// socket remote_addr holds the peer credentials and client_ip comes from the forwarding
+// headers, the peer being trusted like a proxy. It stays empty without them
+pub fn request_addresses(req: &HttpRequest) -> (String, String) {
+    let proxies = req.extensions().get::<Arc<TrustedProxies>>();
+    let peer = match req.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(addr)) => addr.ip(),
+        None => {
+            #[cfg(unix)]
+            if let Some(ConnectInfo(peer)) = req.extensions().get::<ConnectInfo<UnixPeer>>() {
+                let client_ip = proxies
+                    .map(|proxies| proxies.forwarded_client(req.headers()))
+                    .unwrap_or_else(|| TrustedProxies::default().forwarded_client(req.headers()))
+                    .map(|ip| ip.to_string())
+                    .unwrap_or_default();
+                return (peer.to_string(), client_ip);
+            }
+            return (String::new(), String::new());
+        }
+    };
+    let client_ip = match proxies {
+        Some(proxies) => proxies.client_ip(peer, req.headers()),
+        None => canonical_ip(peer),
+    };
+    (peer.to_string(), client_ip.to_string())
+}
@@ -0,0 +1,189 @@
+use std::{io::SeekFrom, path::PathBuf};
+
+use axum::{
+    body::{to_bytes, Body, Bytes, HttpBody},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response as ServerResponse},
+};
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncSeekExt},
+};
+use tracing::error;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// Set by `Response::to_axum_response` for responses with a `file_path`, the file is only
+// opened once the Range header of the request is known
+#[derive(Debug, Clone)]
+pub struct FileBody(pub PathBuf);
+
+#[derive(Debug, PartialEq)]
+enum ByteRange {
+    // first and last byte, inclusive
+    Satisfiable(u64, u64),
+    Unsatisfiable,
+    // not a bytes range, the whole body is sent
+    Ignored,
+}
+
+fn parse_range(range: &str, total: u64) -> ByteRange {
+    let Some(spec) = range.trim().strip_prefix("bytes=") else {
+        return ByteRange::Ignored;
+    };
+    // only single ranges are served
+    if spec.contains(',') {
+        return ByteRange::Unsatisfiable;
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return ByteRange::Ignored;
+    };
+    let (start, end) = (start.trim(), end.trim());
+    if total == 0 {
+        return ByteRange::Unsatisfiable;
+    }
+
+    // `-n` is the last n bytes
+    if start.is_empty() {
+        return match end.parse::<u64>() {
+            Ok(0) => ByteRange::Unsatisfiable,
+            Ok(suffix) => ByteRange::Satisfiable(total.saturating_sub(suffix), total - 1),
+            Err(_) => ByteRange::Ignored,
+        };
+    }
+    let Ok(start) = start.parse::<u64>() else {
+        return ByteRange::Ignored;
+    };
+    let end = match end {
+        "" => total - 1,
+        end => match end.parse::<u64>() {
+            Ok(end) if end >= start => end.min(total - 1),
+            _ => return ByteRange::Ignored,
+        },
+    };
+    if start >= total {
+        return ByteRange::Unsatisfiable;
+    }
+    ByteRange::Satisfiable(start, end)
+}
+
+fn file_stream(file: File, len: u64) -> Body {
+    let chunks = futures::stream::unfold((file, len), |(mut file, remaining)| async move {
+        if remaining == 0 {
+            return None;
+        }
+        let mut chunk = vec![0; CHUNK_SIZE.min(remaining as usize)];
+        match file.read(&mut chunk).await {
+            Ok(0) => None,
+            Ok(read) => {
+                chunk.truncate(read);
+                Some((Ok(Bytes::from(chunk)), (file, remaining - read as u64)))
+            }
+            Err(e) => Some((Err(e), (file, 0))),
+        }
+    });
+    Body::from_stream(chunks)
+}
+
+fn not_satisfiable(total: u64) -> ServerResponse {
+    ServerResponse::builder()
+        .status(StatusCode::RANGE_NOT_SATISFIABLE)
+        .header(header::CONTENT_RANGE, format!("bytes */{}", total))
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn set_header(response: &mut ServerResponse, name: header::HeaderName, value: String) {
+    if let Ok(value) = HeaderValue::from_str(&value) {
+        response.headers_mut().insert(name, value);
+    }
+}
+
+// Attach the file of a `file_path` response and answer a single `Range` with 206.
+// Multiple ranges and ranges past the end get 416
+pub async fn partial_content(response: ServerResponse, range: Option<&str>) -> ServerResponse {
+    let file_body = response.extensions().get::<FileBody>().cloned();
+    let range = range.filter(|_| response.status() == StatusCode::OK);
+
+    let Some(FileBody(path)) = file_body else {
+        return match range {
+            Some(range) => memory_range(response, range).await,
+            None => response,
+        };
+    };
+
+    let mut file = match File::open(&path).await {
+        Ok(file) => file,
+        Err(e) => {
+            error!("Failed to open response file {}: {}", path.display(), e);
+            return StatusCode::NOT_FOUND.into_response();
+        }
+    };
+    let total = match file.metadata().await {
+        Ok(metadata) => metadata.len(),
+        Err(e) => {
+            error!("Failed to read response file {}: {}", path.display(), e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let (mut parts, _) = response.into_parts();
+    parts.headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    let (start, end) = match range.map(|range| parse_range(range, total)) {
+        Some(ByteRange::Satisfiable(start, end)) => (start, end),
+        Some(ByteRange::Unsatisfiable) => return not_satisfiable(total),
+        Some(ByteRange::Ignored) | None => {
+            let mut response = ServerResponse::from_parts(parts, file_stream(file, total));
+            set_header(&mut response, header::CONTENT_LENGTH, total.to_string());
+            return response;
+        }
+    };
+
+    if let Err(e) = file.seek(SeekFrom::Start(start)).await {
+        error!("Failed to read response file {}: {}", path.display(), e);
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    let len = end - start + 1;
+    parts.status = StatusCode::PARTIAL_CONTENT;
+    let mut response = ServerResponse::from_parts(parts, file_stream(file, len));
+    set_header(&mut response, header::CONTENT_LENGTH, len.to_string());
+    set_header(
+        &mut response,
+        header::CONTENT_RANGE,
+        format!("bytes {}-{}/{}", start, end, total),
+    );
+    response
+}
+
+// Streamed bodies have no known size and are sent whole
+async fn memory_range(response: ServerResponse, range: &str) -> ServerResponse {
+    let Some(total) = response.body().size_hint().exact() else {
+        return response;
+    };
+    let (start, end) = match parse_range(range, total) {
+        ByteRange::Satisfiable(start, end) => (start, end),
+        ByteRange::Unsatisfiable => return not_satisfiable(total),
+        ByteRange::Ignored => return response,
+    };
+
+    let (mut parts, body) = response.into_parts();
+    let body = match to_bytes(body, total as usize).await {
+        Ok(body) => body,
+        Err(e) => {
+            error!("Failed to read response body for a range: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    parts.status = StatusCode::PARTIAL_CONTENT;
+    parts.headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    let mut response = ServerResponse::from_parts(
+        parts,
+        Body::from(body.slice(start as usize..=end as usize)),
+    );
+    set_header(
+        &mut response,
+        header::CONTENT_RANGE,
+        format!("bytes {}-{}/{}", start, end, total),
+    );
+    response
+}
@@ -0,0 +1,265 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    body::Body,
+    extract::Request as HttpRequest,
+    http::{HeaderValue, StatusCode},
+    response::Response as ServerResponse,
+};
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use pyo3::{exceptions::PyValueError, prelude::*};
+use tracing::error;
+
+use super::proxy::request_addresses;
+use crate::{caching::redis_pool::RedisPool, router::router::Router};
+
+const REDIS_KEY_PREFIX: &str = "ratelimit:";
+// how often stale in-memory windows are swept, one request pays for it
+const MEMORY_SWEEP_MILLIS: u64 = 60_000;
+
+lazy_static! {
+    // Same decision as the in-memory counters, made atomically so only allowed requests are
+    // counted: KEYS are the current and previous windows, ARGV the limit, the share of the
+    // previous window still overlapping and the expiry of the current one
+    static ref REDIS_CHECK: redis::Script = redis::Script::new(
+        r"
+        local current = tonumber(redis.call('GET', KEYS[1]) or '0')
+        local previous = tonumber(redis.call('GET', KEYS[2]) or '0')
+        local allowed = previous * tonumber(ARGV[2]) + current + 1 <= tonumber(ARGV[1])
+        if allowed then
+            current = redis.call('INCR', KEYS[1])
+            redis.call('EXPIRE', KEYS[1], ARGV[3])
+        end
+        return {allowed and 1 or 0, current, previous}
+        "
+    );
+}
+
+// How many requests are allowed per window, server wide or for one route
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitRule {
+    pub limit: u64,
+    pub window_secs: u64,
+}
+
+impl RateLimitRule {
+    pub fn new(limit: u64, window_secs: u64) -> PyResult<Self> {
+        if limit == 0 {
+            return Err(PyValueError::new_err("Rate limit must be greater than 0"));
+        }
+        if window_secs == 0 {
+            return Err(PyValueError::new_err(
+                "Rate limit window must be greater than 0 seconds",
+            ));
+        }
+        Ok(Self { limit, window_secs })
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ClientKey {
+    Ip,
+    // requests without the header are keyed on the client address
+    Header(String),
+}
+
+impl ClientKey {
+    fn parse(key: &str) -> PyResult<Self> {
+        match key.split_once(':') {
+            None if key.eq_ignore_ascii_case("ip") => Ok(ClientKey::Ip),
+            Some((kind, name)) if kind.eq_ignore_ascii_case("header") && !name.trim().is_empty() => {
+                Ok(ClientKey::Header(name.trim().to_lowercase()))
+            }
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid rate limit key '{}', expected 'ip' or 'header:<name>'",
+                key
+            ))),
+        }
+    }
+
+    fn of(&self, req: &HttpRequest) -> String {
+        if let ClientKey::Header(name) = self {
+            if let Some(value) = req.headers().get(name.as_str()) {
+                return format!("{}={}", name, String::from_utf8_lossy(value.as_bytes()));
+            }
+        }
+        let (_, client_ip) = request_addresses(req);
+        format!("ip={}", client_ip)
+    }
+}
+
+#[derive(Debug)]
+enum Counters {
+    // window length, window index, requests in that window and in the one before it, and
+    // when the counters were last swept
+    Memory(DashMap<String, (u64, u64, u64, u64)>, AtomicU64),
+    Redis(RedisPool),
+}
+
+// Outcome of counting one request
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u64,
+    pub remaining: u64,
+    pub retry_after: u64,
+}
+
+impl RateLimitDecision {
+    fn headers(&self) -> [(&'static str, String); 2] {
+        [
+            ("x-ratelimit-limit", self.limit.to_string()),
+            ("x-ratelimit-remaining", self.remaining.to_string()),
+        ]
+    }
+
+    // 429 for a rejected request
+    pub fn rejection(&self) -> ServerResponse {
+        let mut response = ServerResponse::builder()
+            .status(StatusCode::TOO_MANY_REQUESTS)
+            .header("retry-after", self.retry_after.to_string())
+            .body(Body::from("Too Many Requests"))
+            .unwrap();
+        self.apply(&mut response);
+        response
+    }
+
+    pub fn apply(&self, response: &mut ServerResponse) {
+        for (name, value) in self.headers() {
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                response.headers_mut().insert(name, value);
+            }
+        }
+    }
+}
+
+// Sliding window counter: the count of the previous fixed window is weighted by how much of it
+// still overlaps the last `window_secs`, so bursts at a window boundary are not let through twice
+#[derive(Debug)]
+pub struct RateLimiter {
+    rule: RateLimitRule,
+    key: ClientKey,
+    exempt_paths: Vec<String>,
+    counters: Counters,
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+impl RateLimiter {
+    pub fn new(
+        limit: u64,
+        window_secs: u64,
+        key: &str,
+        backend: Option<&str>,
+        exempt_paths: Vec<String>,
+    ) -> PyResult<Self> {
+        let counters = match backend {
+            None => Counters::Memory(DashMap::new(), AtomicU64::new(now_millis())),
+            Some(redis_url) => Counters::Redis(RedisPool::open(redis_url)?),
+        };
+        Ok(Self {
+            rule: RateLimitRule::new(limit, window_secs)?,
+            key: ClientKey::parse(key)?,
+            exempt_paths,
+            counters,
+        })
+    }
+
+    pub fn is_exempt(&self, path: &str) -> bool {
+        self.exempt_paths
+            .iter()
+            .any(|pattern| pattern == path || Router::path_matches_pattern(path, pattern))
+    }
+
+    // Count a request for `route` (method and path) under `rule`, the route one or the global one
+    pub async fn check(
+        &self,
+        req: &HttpRequest,
+        route: &str,
+        rule: Option<RateLimitRule>,
+    ) -> RateLimitDecision {
+        let (rule, scope) = match rule {
+            Some(rule) => (rule, route),
+            None => (self.rule, "*"),
+        };
+        let client = format!("{}|{}", scope, self.key.of(req));
+
+        let window_millis = rule.window_secs * 1000;
+        let now = now_millis();
+        let window = now / window_millis;
+        // share of the previous window still inside the sliding window
+        let overlap = 1.0 - (now % window_millis) as f64 / window_millis as f64;
+        let estimate = |previous: u64, current: u64| previous as f64 * overlap + current as f64;
+
+        let (allowed, used) = match &self.counters {
+            Counters::Memory(counters, last_sweep) => {
+                let swept = last_sweep.load(Ordering::Relaxed);
+                if now >= swept + MEMORY_SWEEP_MILLIS
+                    && last_sweep
+                        .compare_exchange(swept, now, Ordering::Relaxed, Ordering::Relaxed)
+                        .is_ok()
+                {
+                    // a counter is stale once its window no longer overlaps the sliding one
+                    counters.retain(|_, (length, index, _, _)| (*index + 2) * *length > now);
+                }
+                let mut entry = counters
+                    .entry(client)
+                    .or_insert((window_millis, window, 0, 0));
+                let (_, index, current, previous) = entry.value_mut();
+                if *index != window {
+                    *previous = if *index + 1 == window { *current } else { 0 };
+                    *current = 0;
+                    *index = window;
+                }
+                // rejected requests are not counted, so a client backing off recovers
+                let allowed = estimate(*previous, *current + 1) <= rule.limit as f64;
+                if allowed {
+                    *current += 1;
+                }
+                (allowed, estimate(*previous, *current))
+            }
+            Counters::Redis(pool) => {
+                let key = format!("{}{}:", REDIS_KEY_PREFIX, client);
+                let counted = match pool.connection().await {
+                    Ok(mut connection) => {
+                        REDIS_CHECK
+                            .key(format!("{}{}", key, window))
+                            .key(format!("{}{}", key, window - 1))
+                            .arg(rule.limit)
+                            .arg(overlap)
+                            .arg(rule.window_secs * 2)
+                            .invoke_async::<_, (u64, u64, u64)>(&mut connection)
+                            .await
+                    }
+                    Err(e) => Err(e),
+                };
+                match counted {
+                    Ok((allowed, current, previous)) => {
+                        (allowed == 1, estimate(previous, current))
+                    }
+                    // an unreachable redis does not take the service down with it
+                    Err(e) => {
+                        error!("Failed to count request for rate limiting: {}", e);
+                        (true, 0.0)
+                    }
+                }
+            }
+        };
+
+        RateLimitDecision {
+            allowed,
+            limit: rule.limit,
+            remaining: (rule.limit as f64 - used.ceil()).max(0.0) as u64,
+            // the current window has to end before the estimate drops
+            retry_after: (window_millis - now % window_millis).div_ceil(1000).max(1),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request as HttpRequest, State},
+    http::{header, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response as ServerResponse,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use super::proxy::request_scheme;
+
+fn header_value(name: &HeaderName, value: &str) -> PyResult<HeaderValue> {
+    HeaderValue::from_str(value)
+        .map_err(|_| PyValueError::new_err(format!("Invalid value for {}: {:?}", name, value)))
+}
+
+// Headers added to every response, static files and rejections included
+#[derive(Debug)]
+pub struct SecurityHeaders {
+    headers: Vec<(HeaderName, HeaderValue)>,
+    hsts: Option<HeaderValue>,
+    // send HSTS on plain http too, for TLS terminated by a proxy that isn't trusted
+    force_hsts: bool,
+}
+
+impl SecurityHeaders {
+    pub fn new(
+        hsts_max_age: Option<u64>,
+        csp: Option<&str>,
+        frame_options: Option<&str>,
+        nosniff: bool,
+        referrer_policy: Option<&str>,
+        force_hsts: bool,
+    ) -> PyResult<Self> {
+        let mut headers = Vec::new();
+        if let Some(csp) = csp {
+            let value = header_value(&header::CONTENT_SECURITY_POLICY, csp)?;
+            headers.push((header::CONTENT_SECURITY_POLICY, value));
+        }
+        if let Some(frame_options) = frame_options {
+            let frame_options = frame_options.to_uppercase();
+            if frame_options != "DENY" && frame_options != "SAMEORIGIN" {
+                return Err(PyValueError::new_err(format!(
+                    "Invalid frame_options: {}, expected DENY or SAMEORIGIN",
+                    frame_options
+                )));
+            }
+            let value = header_value(&header::X_FRAME_OPTIONS, &frame_options)?;
+            headers.push((header::X_FRAME_OPTIONS, value));
+        }
+        if nosniff {
+            headers.push((
+                header::X_CONTENT_TYPE_OPTIONS,
+                HeaderValue::from_static("nosniff"),
+            ));
+        }
+        if let Some(referrer_policy) = referrer_policy {
+            let value = header_value(&header::REFERRER_POLICY, referrer_policy)?;
+            headers.push((header::REFERRER_POLICY, value));
+        }
+        let hsts = hsts_max_age
+            .map(|max_age| format!("max-age={}", max_age))
+            .map(|value| header_value(&header::STRICT_TRANSPORT_SECURITY, &value))
+            .transpose()?;
+
+        Ok(Self {
+            headers,
+            hsts,
+            force_hsts,
+        })
+    }
+}
+
+// Headers already set by the application are kept
+pub async fn security_headers(
+    State(config): State<Arc<SecurityHeaders>>,
+    req: HttpRequest,
+    next: Next,
+) -> ServerResponse {
+    // browsers ignore HSTS received over plain http
+    let https = config.force_hsts || request_scheme(&req) == "https";
+    let mut response = next.run(req).await;
+
+    let headers = response.headers_mut();
+    for (name, value) in &config.headers {
+        if !headers.contains_key(name) {
+            headers.insert(name.clone(), value.clone());
+        }
+    }
+    if let (Some(hsts), true) = (&config.hsts, https) {
+        if !headers.contains_key(header::STRICT_TRANSPORT_SECURITY) {
+            headers.insert(header::STRICT_TRANSPORT_SECURITY, hsts.clone());
+        }
+    }
+    response
+}
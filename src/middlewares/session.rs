@@ -0,0 +1,324 @@
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use pyo3::{
+    exceptions::PyValueError,
+    prelude::*,
+    types::{PyDict, PyString, PyTuple},
+};
+use tracing::{error, warn};
+
+use super::signing::{sign_value, unsign_value};
+use crate::{
+    caching::redis_pool::RedisPool,
+    security::token::generate_hex_token,
+    types::{
+        cookie::{parse_cookies, SetCookie},
+        header::Header,
+        json::{to_json_vec, JsonOptions},
+    },
+};
+
+const SESSION_BACKENDS: [&str; 2] = ["cookie", "redis"];
+const REDIS_KEY_PREFIX: &str = "session:";
+// random bytes in the id of a server side session
+const SESSION_ID_BYTES: usize = 32;
+// a python cache backend needs these coroutine methods
+const CACHE_METHODS: [&str; 3] = ["get", "set", "delete"];
+// browsers drop cookies larger than this
+const MAX_COOKIE_SIZE: usize = 4096;
+
+#[derive(Debug, Clone)]
+enum SessionBackend {
+    // the whole session is stored in the signed cookie
+    Cookie,
+    // the cookie only holds a signed session id
+    Redis(Arc<RedisPool>),
+    // same, with the data kept in a `hypern.caching` backend such as MemoryBackend
+    Cache(Arc<PyObject>),
+}
+
+#[derive(Debug, Clone)]
+pub struct SessionStore {
+    // the first key signs new cookies, the others are still accepted so keys can be rotated
+    keys: Vec<String>,
+    cookie_name: String,
+    max_age: u64,
+    same_site: String,
+    secure: bool,
+    backend: SessionBackend,
+}
+
+// Session state of one request, `data` is the JSON it was loaded with
+pub struct Session {
+    id: Option<String>,
+    data: String,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+impl SessionStore {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        secret_key: String,
+        old_keys: Vec<String>,
+        cookie_name: String,
+        max_age: u64,
+        same_site: String,
+        secure: bool,
+        backend: Option<&PyAny>,
+        redis_url: &str,
+    ) -> PyResult<Self> {
+        if secret_key.is_empty() {
+            return Err(PyValueError::new_err("Session secret key must not be empty"));
+        }
+        let backend = match backend {
+            None => SessionBackend::Cookie,
+            Some(backend) if backend.is_instance_of::<PyString>() => match backend.extract::<&str>()? {
+                "cookie" => SessionBackend::Cookie,
+                "redis" => SessionBackend::Redis(Arc::new(RedisPool::open(redis_url)?)),
+                name => {
+                    return Err(PyValueError::new_err(format!(
+                        "Invalid session backend: {}, expected one of {:?} or a cache backend",
+                        name, SESSION_BACKENDS
+                    )))
+                }
+            },
+            Some(backend) => {
+                for method in CACHE_METHODS {
+                    if !backend.hasattr(method)? {
+                        return Err(PyValueError::new_err(format!(
+                            "Session cache backend has no {} method",
+                            method
+                        )));
+                    }
+                }
+                SessionBackend::Cache(Arc::new(backend.into()))
+            }
+        };
+        let store = Self {
+            keys: std::iter::once(secret_key).chain(old_keys).collect(),
+            cookie_name,
+            max_age,
+            same_site,
+            secure,
+            backend,
+        };
+        // fail on startup rather than on the first response
+        store.session_cookie("").build()?;
+        Ok(store)
+    }
+
+    fn sign(&self, value: &str) -> String {
+        sign_value(&self.keys[0], value)
+    }
+
+    fn unsign<'a>(&self, signed: &'a str) -> Option<&'a str> {
+        unsign_value(&self.keys, signed)
+    }
+
+    // id of a server side session, new sessions get a random one
+    fn session_id(&self, session: &Session) -> PyResult<String> {
+        match &session.id {
+            Some(id) => Ok(id.clone()),
+            None => generate_hex_token(SESSION_ID_BYTES),
+        }
+    }
+
+    fn session_cookie(&self, value: &str) -> SetCookie {
+        SetCookie {
+            key: self.cookie_name.clone(),
+            value: value.to_string(),
+            max_age: Some(self.max_age as i64),
+            path: Some("/".to_string()),
+            secure: self.secure,
+            httponly: true,
+            samesite: Some(self.same_site.clone()),
+            ..Default::default()
+        }
+    }
+
+    // Missing, expired and tampered cookies all give an empty session
+    pub async fn load(&self, headers: &Header) -> Session {
+        let empty = Session {
+            id: None,
+            data: "{}".to_string(),
+        };
+        let cookies = parse_cookies(headers.get_all("cookie".to_string()).iter());
+        let value = match cookies
+            .get(&self.cookie_name)
+            .and_then(|cookie| self.unsign(cookie))
+        {
+            Some(value) => value.to_string(),
+            None => return empty,
+        };
+
+        match &self.backend {
+            SessionBackend::Cookie => {
+                let (payload, issued_at) = match value.rsplit_once('.') {
+                    Some(parts) => parts,
+                    None => return empty,
+                };
+                match issued_at.parse::<u64>() {
+                    Ok(issued_at) if issued_at + self.max_age >= now() => {}
+                    _ => return empty,
+                }
+                match URL_SAFE_NO_PAD
+                    .decode(payload)
+                    .ok()
+                    .and_then(|data| String::from_utf8(data).ok())
+                {
+                    Some(data) => Session { id: None, data },
+                    None => empty,
+                }
+            }
+            SessionBackend::Redis(pool) => {
+                let key = format!("{}{}", REDIS_KEY_PREFIX, value);
+                let data = match pool.connection().await {
+                    Ok(mut connection) => {
+                        redis::cmd("GET")
+                            .arg(&key)
+                            .query_async::<_, Option<String>>(&mut connection)
+                            .await
+                    }
+                    Err(e) => Err(e),
+                };
+                match data {
+                    Ok(Some(data)) => Session {
+                        id: Some(value),
+                        data,
+                    },
+                    Ok(None) => empty,
+                    Err(e) => {
+                        error!("Failed to load session: {}", e);
+                        empty
+                    }
+                }
+            }
+            SessionBackend::Cache(cache) => {
+                let key = format!("{}{}", REDIS_KEY_PREFIX, value);
+                let data = call_cache(cache, "get", (key,)).await.and_then(|data| {
+                    Python::with_gil(|py| data.extract::<Option<String>>(py))
+                });
+                match data {
+                    Ok(Some(data)) => Session {
+                        id: Some(value),
+                        data,
+                    },
+                    Ok(None) => empty,
+                    Err(e) => {
+                        error!("Failed to load session: {}", e);
+                        empty
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn to_dict(&self, py: Python, session: &Session) -> PyResult<Py<PyDict>> {
+        let data = py
+            .import("json")?
+            .call_method1("loads", (session.data.as_str(),))?;
+        match data.downcast::<PyDict>() {
+            Ok(dict) => Ok(dict.into()),
+            Err(_) => Ok(PyDict::new(py).into()),
+        }
+    }
+
+    // Persist a changed session, returns the Set-Cookie value to send if any
+    pub async fn save(&self, session: &Session, dict: &Py<PyDict>) -> PyResult<Option<String>> {
+        let data = Python::with_gil(|py| -> PyResult<String> {
+            let data = to_json_vec(dict.as_ref(py), &JsonOptions::default())?;
+            Ok(String::from_utf8_lossy(&data).into_owned())
+        })?;
+        if data == session.data {
+            return Ok(None);
+        }
+
+        // an emptied session drops the cookie
+        if data == "{}" {
+            if let Some(id) = &session.id {
+                let key = format!("{}{}", REDIS_KEY_PREFIX, id);
+                match &self.backend {
+                    SessionBackend::Redis(pool) => run_redis(pool, redis::cmd("DEL").arg(&key)).await,
+                    SessionBackend::Cache(cache) => run_cache(cache, "delete", (key,)).await,
+                    SessionBackend::Cookie => {}
+                }
+            }
+            let mut cookie = self.session_cookie("");
+            cookie.max_age = Some(0);
+            cookie.expires = Some("Thu, 01 Jan 1970 00:00:00 GMT".to_string());
+            return cookie.build().map(Some);
+        }
+
+        let value = match &self.backend {
+            SessionBackend::Cookie => {
+                let payload = format!("{}.{}", URL_SAFE_NO_PAD.encode(data.as_bytes()), now());
+                self.sign(&payload)
+            }
+            SessionBackend::Redis(pool) => {
+                let id = self.session_id(session)?;
+                let key = format!("{}{}", REDIS_KEY_PREFIX, id);
+                run_redis(
+                    pool,
+                    redis::cmd("SET").arg(&key).arg(data).arg("EX").arg(self.max_age),
+                )
+                .await;
+                self.sign(&id)
+            }
+            SessionBackend::Cache(cache) => {
+                let id = self.session_id(session)?;
+                let key = format!("{}{}", REDIS_KEY_PREFIX, id);
+                run_cache(cache, "set", (key, data, self.max_age)).await;
+                self.sign(&id)
+            }
+        };
+
+        let cookie = self.session_cookie(&value).build()?;
+        if cookie.len() > MAX_COOKIE_SIZE {
+            warn!(
+                "Session cookie is {} bytes, browsers may drop cookies over {} bytes",
+                cookie.len(),
+                MAX_COOKIE_SIZE
+            );
+        }
+        Ok(Some(cookie))
+    }
+}
+
+async fn run_redis(pool: &RedisPool, command: &redis::Cmd) {
+    let result = match pool.connection().await {
+        Ok(mut connection) => command.query_async::<_, ()>(&mut connection).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        error!("Failed to save session: {}", e);
+    }
+}
+
+// Await a coroutine method of a python cache backend
+async fn call_cache(
+    cache: &PyObject,
+    method: &str,
+    args: impl IntoPy<Py<PyTuple>>,
+) -> PyResult<PyObject> {
+    let future = Python::with_gil(|py| {
+        let coroutine = cache.call_method1(py, method, args)?;
+        pyo3_asyncio::tokio::into_future(coroutine.as_ref(py))
+    })?;
+    future.await
+}
+
+async fn run_cache(cache: &PyObject, method: &str, args: impl IntoPy<Py<PyTuple>>) {
+    if let Err(e) = call_cache(cache, method, args).await {
+        error!("Failed to save session: {}", e);
+    }
+}
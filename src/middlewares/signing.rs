@@ -0,0 +1,32 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// `value.signature` with an HMAC-SHA256 signature
+pub fn sign_value(key: &str, value: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(key.as_bytes()).unwrap();
+    mac.update(value.as_bytes());
+    format!("{}.{}", value, URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes()))
+}
+
+// The signed value, or None when none of the keys produced the signature
+pub fn unsign_value<'a>(keys: &[String], signed: &'a str) -> Option<&'a str> {
+    let (value, signature) = signed.rsplit_once('.')?;
+    let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+    keys.iter().find_map(|key| {
+        let mut mac = HmacSha256::new_from_slice(key.as_bytes()).ok()?;
+        mac.update(value.as_bytes());
+        // verify_slice compares in constant time
+        mac.verify_slice(&signature).ok().map(|_| value)
+    })
+}
+
+// Compare without returning early, so the time taken does not reveal the matching prefix
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
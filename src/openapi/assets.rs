@@ -0,0 +1,119 @@
+use axum::{
+    extract::Path,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+};
+
+// Where the bundles compiled into the extension are served from
+pub const EMBEDDED_ASSETS_URL: &str = "/_hypern/docs-assets";
+
+// (file, content type, content), put in OUT_DIR by build.rs. Empty when scripts/fetch_docs_assets.py
+// was not run before the build
+const EMBEDDED_ASSETS: [(&str, &str, &[u8]); 3] = [
+    (
+        "swagger-ui.css",
+        "text/css; charset=utf-8",
+        include_bytes!(concat!(env!("OUT_DIR"), "/swagger-ui.css")),
+    ),
+    (
+        "swagger-ui-bundle.js",
+        "text/javascript; charset=utf-8",
+        include_bytes!(concat!(env!("OUT_DIR"), "/swagger-ui-bundle.js")),
+    ),
+    (
+        "redoc.standalone.js",
+        "text/javascript; charset=utf-8",
+        include_bytes!(concat!(env!("OUT_DIR"), "/redoc.standalone.js")),
+    ),
+];
+
+fn has_embedded_assets() -> bool {
+    EMBEDDED_ASSETS
+        .iter()
+        .all(|(_, _, content)| !content.is_empty())
+}
+
+// Serves a bundle of `assets_mode="embedded"` under EMBEDDED_ASSETS_URL
+pub async fn embedded_asset(Path(file): Path<String>) -> Response {
+    match EMBEDDED_ASSETS.iter().find(|(name, _, _)| *name == file) {
+        Some((_, content_type, content)) if !content.is_empty() => (
+            [
+                (header::CONTENT_TYPE, *content_type),
+                (header::CACHE_CONTROL, "public, max-age=86400"),
+            ],
+            *content,
+        )
+            .into_response(),
+        _ => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+const SWAGGER_CDN_URL: &str = "https://cdn.jsdelivr.net/npm/swagger-ui-dist@5.9.0";
+const REDOC_CDN_URL: &str = "https://cdn.jsdelivr.net/npm/redoc@2.1.3/bundles";
+
+// Where the docs pages load their JS/CSS bundles from
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssetsMode {
+    Cdn,
+    // base URL of a user hosted copy of the bundles
+    StaticUrl(String),
+    Embedded,
+}
+
+impl AssetsMode {
+    pub fn parse(mode: &str, assets_url: Option<String>) -> PyResult<Self> {
+        match (mode.to_lowercase().as_str(), assets_url) {
+            ("cdn", _) => Ok(AssetsMode::Cdn),
+            ("static_url", Some(url)) => Ok(AssetsMode::StaticUrl(
+                url.trim_end_matches('/').to_string(),
+            )),
+            ("static_url", None) => Err(PyValueError::new_err(
+                "assets_url is required when assets_mode is static_url",
+            )),
+            ("embedded", _) if has_embedded_assets() => Ok(AssetsMode::Embedded),
+            ("embedded", _) => Err(PyRuntimeError::new_err(
+                "This build of hypern has no embedded docs bundles. Run scripts/fetch_docs_assets.py \
+                 and build again, or use another assets_mode",
+            )),
+            (mode, _) => Err(PyValueError::new_err(format!(
+                "Invalid assets_mode: {}, expected cdn, static_url or embedded",
+                mode
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AssetsMode::Cdn => "cdn",
+            AssetsMode::StaticUrl(_) => "static_url",
+            AssetsMode::Embedded => "embedded",
+        }
+    }
+
+    pub fn assets_url(&self) -> Option<String> {
+        match self {
+            AssetsMode::StaticUrl(url) => Some(url.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn swagger_url(&self, file: &str) -> String {
+        self.url(SWAGGER_CDN_URL, file)
+    }
+
+    pub fn redoc_url(&self, file: &str) -> String {
+        self.url(REDOC_CDN_URL, file)
+    }
+
+    fn url(&self, cdn_url: &str, file: &str) -> String {
+        match self {
+            AssetsMode::Cdn => format!("{}/{}", cdn_url, file),
+            AssetsMode::StaticUrl(url) => format!("{}/{}", url, file),
+            AssetsMode::Embedded => format!("{}/{}", EMBEDDED_ASSETS_URL, file),
+        }
+    }
+}
@@ -1,3 +1,5 @@
-
+pub mod assets;
+pub mod redoc;
 pub mod swagger;
-pub mod schemas;
\ No newline at end of file
+pub mod schemas;
+pub mod spec;
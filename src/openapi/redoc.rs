@@ -0,0 +1,73 @@
+use pyo3::prelude::*;
+
+use super::assets::{AssetsMode, EMBEDDED_ASSETS_URL};
+
+#[pyclass]
+pub struct ReDocUI {
+    #[pyo3(get, set)]
+    title: String,
+
+    #[pyo3(get, set)]
+    openapi_url: String,
+
+    assets: AssetsMode,
+}
+
+#[pymethods]
+impl ReDocUI {
+    #[classattr]
+    const EMBEDDED_ASSETS_URL: &'static str = EMBEDDED_ASSETS_URL;
+
+    #[new]
+    #[pyo3(signature = (title, openapi_url, assets_mode="cdn", assets_url=None))]
+    fn new(
+        title: String,
+        openapi_url: String,
+        assets_mode: &str,
+        assets_url: Option<String>,
+    ) -> PyResult<Self> {
+        Ok(ReDocUI {
+            title,
+            openapi_url,
+            assets: AssetsMode::parse(assets_mode, assets_url)?,
+        })
+    }
+
+    #[getter]
+    fn assets_mode(&self) -> &str {
+        self.assets.name()
+    }
+
+    #[getter]
+    fn assets_url(&self) -> Option<String> {
+        self.assets.assets_url()
+    }
+
+    pub fn get_html_content(&self) -> String {
+        format!(
+            r#"
+                <!DOCTYPE html>
+                <html>
+                <head>
+                <title>{}</title>
+                <meta charset="utf-8"/>
+                <meta name="viewport" content="width=device-width, initial-scale=1">
+                <style>
+                body {{
+                    margin: 0;
+                    padding: 0;
+                }}
+                </style>
+                </head>
+                <body>
+                <redoc spec-url="{}"></redoc>
+                <script src="{}"></script>
+                </body>
+                </html>
+            "#,
+            self.title,
+            self.openapi_url,
+            self.assets.redoc_url("redoc.standalone.js")
+        )
+    }
+}
@@ -1,9 +1,18 @@
 use yaml_rust::Yaml;
 use serde_json::Value;
-use pyo3::{prelude::*, types::PyDict};
+use pyo3::{
+    exceptions::{PyTypeError, PyValueError},
+    prelude::*,
+    types::PyDict,
+};
 use regex::Regex;
 use yaml_rust::YamlLoader;
 
+use crate::{
+    router::route::Route,
+    types::json::{to_json_value, JsonOptions},
+};
+
 
 fn yaml_to_json(yaml: &Yaml) -> Value {
     match yaml {
@@ -14,9 +23,14 @@ fn yaml_to_json(yaml: &Yaml) -> Value {
         Yaml::Hash(h) => {
             let mut map = serde_json::Map::new();
             for (k, v) in h {
-                if let Yaml::String(key) = k {
-                    map.insert(key.clone(), yaml_to_json(v));
-                }
+                // `200:` in responses is an integer key
+                let key = match k {
+                    Yaml::String(key) | Yaml::Real(key) => key.clone(),
+                    Yaml::Integer(key) => key.to_string(),
+                    Yaml::Boolean(key) => key.to_string(),
+                    _ => continue,
+                };
+                map.insert(key, yaml_to_json(v));
             }
             Value::Object(map)
         }
@@ -31,46 +45,241 @@ pub struct BaseSchemaGenerator{
 
     #[pyo3(get, set)]
     base_schema: Py<PyDict>,
+
+    // invalid docstrings raise instead of being left out of the document
+    #[pyo3(get, set)]
+    strict: bool,
 }
 
 #[pymethods]
 impl BaseSchemaGenerator {
     #[new]
-    fn new(base_schema: Py<PyDict>) -> Self {
+    #[pyo3(signature = (base_schema, strict=false))]
+    fn new(base_schema: Py<PyDict>, strict: bool) -> Self {
         BaseSchemaGenerator{
-            base_schema
+            base_schema,
+            strict,
         }
     }
 
     fn remove_converter(&self, path: String) -> String {
-        let re = Regex::new(r":\w+}").unwrap();
-        re.replace_all(&path, "}").into_owned()
-    }
-
-    fn parse_docstring(&self, func_or_method: Py<PyAny>) -> String {
-        let docstring: String =
-            Python::with_gil(|py| match func_or_method.getattr(py, "__doc__") {
-                Ok(doc) => match doc.extract::<String>(py) {
-                    Ok(doc) => doc,
-                    Err(_) => "".to_string(),
-                },
-                Err(_) => "".to_string(),
-            });
-        if docstring.is_empty() {
-            return "".to_string();
+        remove_converter(&path)
+    }
+
+    fn parse_docstring(&self, py: Python, func_or_method: Py<PyAny>) -> PyResult<String> {
+        let func_or_method = func_or_method.as_ref(py);
+        if !self.strict {
+            return Ok(docstring_operation(func_or_method)
+                .map(|doc_json| doc_json.to_string())
+                .unwrap_or_default());
         }
-        let part_of_docs: Vec<&str> = docstring.split("---").collect();
-        let part = part_of_docs.last().unwrap();
-
-        match YamlLoader::load_from_str(&part) {
-            Ok(docs) => {
-                let doc = &docs[0];
-                let doc_json = yaml_to_json(doc);
-                return doc_json.to_string();
+        let problems = docstring_problems(func_or_method);
+        if !problems.is_empty() {
+            return Err(PyValueError::new_err(problems.join("\n")));
+        }
+        Ok(docstring_operation(func_or_method)
+            .map(|doc_json| doc_json.to_string())
+            .unwrap_or_default())
+    }
+
+    /// Every problem of the docstrings of `routes`, one line per problem, empty when all are valid
+    fn validate_all(&self, py: Python, routes: Vec<Route>) -> Vec<String> {
+        routes
+            .iter()
+            .flat_map(|route| docstring_problems(route.function.handler.as_ref(py)))
+            .collect()
+    }
+
+    /// Register a shared model under `components.schemas`
+    fn add_component(&self, py: Python, name: &str, schema: &PyDict) -> PyResult<()> {
+        self.components_section(py, "schemas")?.set_item(name, schema)
+    }
+
+    /// Register an authentication scheme under `components.securitySchemes`
+    fn add_security_scheme(&self, py: Python, name: &str, scheme: &PyDict) -> PyResult<()> {
+        self.components_section(py, "securitySchemes")?
+            .set_item(name, scheme)
+    }
+
+    /// The local `$ref`s of `schema` that point to nothing in it
+    fn missing_refs(&self, schema: &PyAny) -> PyResult<Vec<String>> {
+        let document = to_json_value(schema, &JsonOptions::default())?;
+        Ok(missing_refs(&document, &document))
+    }
+}
+
+impl BaseSchemaGenerator {
+    fn components_section<'py>(&self, py: Python<'py>, key: &str) -> PyResult<&'py PyDict> {
+        let base_schema = self.base_schema.as_ref(py);
+        let components = base_schema.call_method1("setdefault", ("components", PyDict::new(py)))?;
+        components
+            .call_method1("setdefault", (key, PyDict::new(py)))?
+            .downcast::<PyDict>()
+            .map_err(|_| PyTypeError::new_err(format!("components.{} must be a dict", key)))
+    }
+}
+
+// `$ref: User` is short for `$ref: '#/components/schemas/User'`
+pub fn resolve_refs(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                match value {
+                    Value::String(reference)
+                        if key == "$ref" && !reference.contains('#') && !reference.contains('/') =>
+                    {
+                        *reference = format!("#/components/schemas/{}", reference);
+                    }
+                    value => resolve_refs(value),
+                }
             }
-            Err(_e) => {
-                return "".to_string();
+        }
+        Value::Array(items) => items.iter_mut().for_each(resolve_refs),
+        _ => {}
+    }
+}
+
+// `#/...` references of `value` that `document` has nothing at, sorted without duplicates.
+// References to other documents are not checked
+pub fn missing_refs(value: &Value, document: &Value) -> Vec<String> {
+    let mut missing = Vec::new();
+    collect_missing_refs(value, document, &mut missing);
+    missing.sort();
+    missing.dedup();
+    missing
+}
+
+fn collect_missing_refs(value: &Value, document: &Value, missing: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                match (key.as_str(), value) {
+                    ("$ref", Value::String(reference)) => {
+                        let found = reference
+                            .strip_prefix('#')
+                            .map(|pointer| document.pointer(pointer).is_some());
+                        if found == Some(false) {
+                            missing.push(reference.clone());
+                        }
+                    }
+                    _ => collect_missing_refs(value, document, missing),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_missing_refs(item, document, missing);
+            }
+        }
+        _ => {}
+    }
+}
+
+// `{id:int}` becomes `{id}`
+pub fn remove_converter(path: &str) -> String {
+    let re = Regex::new(r":\w+}").unwrap();
+    re.replace_all(path, "}").into_owned()
+}
+
+fn handler_name(func_or_method: &PyAny) -> String {
+    func_or_method
+        .getattr("__qualname__")
+        .and_then(|name| name.extract::<String>())
+        .unwrap_or_else(|_| func_or_method.to_string())
+}
+
+// Handler docstring and the YAML after its last `---`, None when there is no docstring
+fn docstring_yaml(func_or_method: &PyAny) -> Option<(String, usize)> {
+    let docstring = func_or_method
+        .getattr("__doc__")
+        .and_then(|doc| doc.extract::<String>())
+        .unwrap_or_default();
+    if docstring.is_empty() {
+        return None;
+    }
+    let start = docstring.rfind("---").map(|index| index + 3).unwrap_or(0);
+    Some((docstring, start))
+}
+
+// The YAML after the last `---` of a handler docstring, as JSON. None when it is not valid YAML
+// or not shaped like an operation, so a broken docstring is left out of the document
+pub fn docstring_operation(func_or_method: &PyAny) -> Option<Value> {
+    let (docstring, start) = docstring_yaml(func_or_method)?;
+
+    let docs = YamlLoader::load_from_str(&docstring[start..]).ok()?;
+    let mut operation = docs.first().map(yaml_to_json)?;
+    if !operation_problems(&operation).is_empty() {
+        return None;
+    }
+    resolve_refs(&mut operation);
+    Some(operation)
+}
+
+// What is wrong with the docstring of a handler: YAML errors with the docstring line and
+// column, then the shape of the operation. Docstrings without `---` are not documentation
+pub fn docstring_problems(func_or_method: &PyAny) -> Vec<String> {
+    let Some((docstring, start)) = docstring_yaml(func_or_method) else {
+        return Vec::new();
+    };
+    if start == 0 {
+        return Vec::new();
+    }
+    let name = handler_name(func_or_method);
+
+    let docs = match YamlLoader::load_from_str(&docstring[start..]) {
+        Ok(docs) => docs,
+        Err(e) => {
+            // marker lines start at 1 and count from the `---` line
+            let line = docstring[..start].matches('\n').count() + e.marker().line();
+            let snippet = docstring.lines().nth(line - 1).unwrap_or_default().trim();
+            let message = e.to_string();
+            let message = message.split(" at line ").next().unwrap_or_default();
+            return vec![format!(
+                "{}: line {} column {}: {}: {}",
+                name,
+                line,
+                e.marker().col() + 1,
+                message,
+                snippet
+            )];
+        }
+    };
+    let operation = docs.first().map(yaml_to_json).unwrap_or(Value::Null);
+    operation_problems(&operation)
+        .into_iter()
+        .map(|problem| format!("{}: {}", name, problem))
+        .collect()
+}
+
+fn is_status_code(code: &str) -> bool {
+    if code == "default" {
+        return true;
+    }
+    let bytes = code.as_bytes();
+    bytes.len() == 3
+        && (b'1'..=b'5').contains(&bytes[0])
+        && (bytes[1..].iter().all(u8::is_ascii_digit) || &bytes[1..] == b"XX")
+}
+
+// The minimal shape of an operation: a map, `responses` a map keyed by status codes,
+// `parameters` a list
+fn operation_problems(operation: &Value) -> Vec<String> {
+    let Value::Object(operation) = operation else {
+        return vec!["the operation must be a map".to_string()];
+    };
+    let mut problems = Vec::new();
+    match operation.get("responses") {
+        Some(Value::Object(responses)) => {
+            for code in responses.keys().filter(|code| !is_status_code(code)) {
+                problems.push(format!("responses: {} is not a status code", code));
             }
         }
+        Some(_) => problems.push("responses must be a map keyed by status codes".to_string()),
+        None => {}
+    }
+    match operation.get("parameters") {
+        Some(Value::Array(_)) | None => {}
+        Some(_) => problems.push("parameters must be a list".to_string()),
     }
+    problems
 }
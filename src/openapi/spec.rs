@@ -0,0 +1,111 @@
+use axum::{
+    body::Bytes,
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response as ServerResponse},
+};
+use pyo3::prelude::*;
+use serde_json::{json, Map, Value};
+use tracing::warn;
+
+use super::schemas::{docstring_operation, missing_refs, remove_converter};
+use crate::router::{route::Route, router::Router};
+
+// What `enable_openapi` serves
+#[derive(Debug, Clone)]
+pub struct OpenApiConfig {
+    pub path: String,
+    pub title: String,
+    pub version: String,
+    // `info`, `servers`, `components`... the document starts from
+    pub base_schema: Map<String, Value>,
+}
+
+// Docstring YAML of the handler merged with what the route itself tells
+fn operation(py: Python, route: &Route, path_params: &[String]) -> Value {
+    let mut operation = match docstring_operation(route.function.handler.as_ref(py)) {
+        Some(Value::Object(operation)) => operation,
+        _ => Map::new(),
+    };
+
+    // documented parameters are kept, the others are added as plain strings
+    let parameters = operation
+        .entry("parameters")
+        .or_insert_with(|| json!([]));
+    if let Value::Array(parameters) = parameters {
+        for name in path_params {
+            let documented = parameters.iter().any(|parameter| {
+                parameter["in"] == "path" && parameter["name"] == name.as_str()
+            });
+            if !documented {
+                parameters.push(json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": {"type": "string"},
+                }));
+            }
+        }
+        if parameters.is_empty() {
+            operation.remove("parameters");
+        }
+    }
+    // required by the specification
+    operation
+        .entry("responses")
+        .or_insert_with(|| json!({"200": {"description": "Successful response"}}));
+    if !route.tags.is_empty() {
+        operation.insert("tags".to_string(), json!(route.tags));
+    }
+    let metadata = [
+        ("summary", &route.summary),
+        ("description", &route.description),
+        ("operationId", &route.operation_id),
+    ];
+    for (key, value) in metadata {
+        if let Some(value) = value {
+            operation.insert(key.to_string(), json!(value));
+        }
+    }
+    if route.deprecated {
+        operation.insert("deprecated".to_string(), Value::Bool(true));
+    }
+    Value::Object(operation)
+}
+
+// OpenAPI 3 document of every route of `router` included in the schema
+pub fn build_spec(py: Python, router: &Router, config: &OpenApiConfig) -> Value {
+    let mut paths = Map::new();
+    for route in router.iter().filter(|route| route.include_in_schema) {
+        let path = remove_converter(&route.openapi_path());
+        let operation = operation(py, route, &route.get_path_params());
+        let item = paths.entry(path).or_insert_with(|| json!({}));
+        item[route.method.to_lowercase()] = operation;
+    }
+    let mut spec = config.base_schema.clone();
+    spec.entry("openapi").or_insert_with(|| json!("3.0.0"));
+    let info = spec.entry("info").or_insert_with(|| json!({}));
+    if let Value::Object(info) = info {
+        info.entry("title").or_insert_with(|| json!(config.title));
+        info.entry("version").or_insert_with(|| json!(config.version));
+    }
+    spec.insert("paths".to_string(), Value::Object(paths));
+
+    let spec = Value::Object(spec);
+    let missing = missing_refs(&spec, &spec);
+    if !missing.is_empty() {
+        warn!(
+            "OpenAPI document references missing components: {}",
+            missing.join(", ")
+        );
+    }
+    spec
+}
+
+// The document is built once at startup, every request gets the same bytes
+pub async fn openapi_handler(spec: Bytes) -> ServerResponse {
+    (
+        [(header::CONTENT_TYPE, HeaderValue::from_static("application/json"))],
+        spec,
+    )
+        .into_response()
+}
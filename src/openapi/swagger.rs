@@ -1,5 +1,93 @@
 use pyo3::prelude::*;
 
+use super::assets::{AssetsMode, EMBEDDED_ASSETS_URL};
+
+const FAVICON_URL: &str =
+    "https://res.cloudinary.com/dslpmba3s/image/upload/v1731161593/logo/hypern-180x180.png";
+
+// Page the authorization server redirects back to, it hands the result to the docs window.
+// Same as oauth2-redirect.html of swagger-ui-dist, it has to be served from the docs origin
+const OAUTH2_REDIRECT_HTML: &str = r#"<!doctype html>
+<html lang="en-US">
+<head>
+    <title>Swagger UI: OAuth2 Redirect</title>
+</head>
+<body>
+<script>
+    'use strict';
+    function run () {
+        var oauth2 = window.opener.swaggerUIRedirectOauth2;
+        var sentState = oauth2.state;
+        var redirectUrl = oauth2.redirectUrl;
+        var isValid, qp, arr;
+
+        if (/code|token|error/.test(window.location.hash)) {
+            qp = window.location.hash.substring(1).replace('?', '&');
+        } else {
+            qp = location.search.substring(1);
+        }
+
+        arr = qp.split("&");
+        arr.forEach(function (v,i,_arr) { _arr[i] = '"' + v.replace('=', '":"') + '"';});
+        qp = qp ? JSON.parse('{' + arr.join() + '}',
+                function (key, value) {
+                    return key === "" ? value : decodeURIComponent(value);
+                }
+        ) : {};
+
+        isValid = qp.state === sentState;
+
+        if ((
+          oauth2.auth.schema.get("flow") === "accessCode" ||
+          oauth2.auth.schema.get("flow") === "authorizationCode" ||
+          oauth2.auth.schema.get("flow") === "authorization_code"
+        ) && !oauth2.auth.code) {
+            if (!isValid) {
+                oauth2.errCb({
+                    authId: oauth2.auth.name,
+                    source: "auth",
+                    level: "warning",
+                    message: "Authorization may be unsafe, passed state was changed in server. The passed state wasn't returned from auth server."
+                });
+            }
+
+            if (qp.code) {
+                delete oauth2.state;
+                oauth2.auth.code = qp.code;
+                oauth2.callback({auth: oauth2.auth, redirectUrl: redirectUrl});
+            } else {
+                let oauthErrorMsg;
+                if (qp.error) {
+                    oauthErrorMsg = "["+qp.error+"]: " +
+                        (qp.error_description ? qp.error_description+ ". " : "no accessCode received from the server. ") +
+                        (qp.error_uri ? "More info: "+qp.error_uri : "");
+                }
+
+                oauth2.errCb({
+                    authId: oauth2.auth.name,
+                    source: "auth",
+                    level: "error",
+                    message: oauthErrorMsg || "[Authorization failed]: no accessCode received from the server."
+                });
+            }
+        } else {
+            oauth2.callback({auth: oauth2.auth, token: qp, isValid: isValid, redirectUrl: redirectUrl});
+        }
+        window.close();
+    }
+
+    if (document.readyState !== 'loading') {
+        run();
+    } else {
+        document.addEventListener('DOMContentLoaded', function () {
+            run();
+        });
+    }
+</script>
+</body>
+</html>
+"#;
+
 #[pyclass]
 pub struct SwaggerUI {
     #[pyo3(get, set)]
@@ -7,42 +95,78 @@ pub struct SwaggerUI {
 
     #[pyo3(get, set)]
     openapi_url: String,
+
+    // path of the page serving `get_oauth2_redirect_html`, None disables the OAuth2 redirect
+    #[pyo3(get, set)]
+    oauth2_redirect_url: Option<String>,
+
+    assets: AssetsMode,
 }
 
 #[pymethods]
 impl SwaggerUI {
+    #[classattr]
+    const EMBEDDED_ASSETS_URL: &'static str = EMBEDDED_ASSETS_URL;
+
     #[new]
-    fn new(title: String, openapi_url: String) -> Self {
-        SwaggerUI {
+    #[pyo3(signature = (title, openapi_url, oauth2_redirect_url=None, assets_mode="cdn", assets_url=None))]
+    fn new(
+        title: String,
+        openapi_url: String,
+        oauth2_redirect_url: Option<String>,
+        assets_mode: &str,
+        assets_url: Option<String>,
+    ) -> PyResult<Self> {
+        Ok(SwaggerUI {
             title,
             openapi_url,
-        }
+            oauth2_redirect_url,
+            assets: AssetsMode::parse(assets_mode, assets_url)?,
+        })
     }
 
-    pub fn get_html_content(&self) -> String{
-        let oauth2_redirect_url = false;// TODO
+    #[getter]
+    fn assets_mode(&self) -> &str {
+        self.assets.name()
+    }
+
+    #[getter]
+    fn assets_url(&self) -> Option<String> {
+        self.assets.assets_url()
+    }
+
+    pub fn get_html_content(&self) -> String {
+        // the icon is only linked when the page may reach the internet anyway
+        let favicon = match self.assets {
+            AssetsMode::Cdn => format!(r#"<link rel="shortcut icon" href="{}">"#, FAVICON_URL),
+            _ => String::new(),
+        };
 
         let mut html = format!(
             r#"
                 <!DOCTYPE html>
                  <html>
                  <head>
-                 <link type="text/css" rel="stylesheet" href="https://cdn.jsdelivr.net/npm/swagger-ui-dist@5.9.0/swagger-ui.css">
-                 <link rel="shortcut icon" href="https://res.cloudinary.com/dslpmba3s/image/upload/v1731161593/logo/hypern-180x180.png">
+                 <link type="text/css" rel="stylesheet" href="{}">
+                 {}
                  <title>{}</title>
                  </head>
                  <body>
                  <div id="swagger-ui">
                  </div>
-                 <script src="https://cdn.jsdelivr.net/npm/swagger-ui-dist@5.9.0/swagger-ui-bundle.js"></script>
+                 <script src="{}"></script>
                  <!-- `SwaggerUIBundle` is now available on the page -->
                  <script>
                  const ui = SwaggerUIBundle({{
                      url: '{}',
             "#,
-            self.title, self.openapi_url
+            self.assets.swagger_url("swagger-ui.css"),
+            favicon,
+            self.title,
+            self.assets.swagger_url("swagger-ui-bundle.js"),
+            self.openapi_url
         );
-        if oauth2_redirect_url {
+        if let Some(oauth2_redirect_url) = &self.oauth2_redirect_url {
             html.push_str(
                 format!(r#"
                     oauth2RedirectUrl: window.location.origin + '{}',
@@ -70,4 +194,8 @@ impl SwaggerUI {
         );
         html
     }
+
+    pub fn get_oauth2_redirect_html(&self) -> &'static str {
+        OAUTH2_REDIRECT_HTML
+    }
 }
@@ -0,0 +1,137 @@
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+const DEFAULT_CAPACITY: usize = 1024;
+const DEFAULT_TTL_SECS: u64 = 300;
+
+#[derive(Debug)]
+struct CacheEntry {
+    // index into the router's route table, None caches a miss in the table
+    route_index: Option<usize>,
+    inserted_at: Instant,
+    tick: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheInner {
+    entries: HashMap<String, CacheEntry>,
+    // access tick -> key, the first entry is the least recently used
+    order: BTreeMap<u64, String>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl CacheInner {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.order.remove(&entry.tick);
+        }
+    }
+}
+
+/// Least recently used cache of route lookups keyed by "{method} {path}"
+#[derive(Debug)]
+pub struct RouteCache {
+    capacity: usize,
+    ttl: Duration,
+    inner: Mutex<CacheInner>,
+}
+
+impl Default for RouteCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY, Duration::from_secs(DEFAULT_TTL_SECS))
+    }
+}
+
+impl RouteCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            inner: Mutex::new(CacheInner::default()),
+        }
+    }
+
+    pub fn key(method: &str, path: &str) -> String {
+        format!("{} {}", method.to_uppercase(), path)
+    }
+
+    /// Returns Some(lookup result) on a hit, None on a miss or expired entry
+    pub fn get(&self, key: &str) -> Option<Option<usize>> {
+        let mut inner = self.inner.lock();
+        let expired = match inner.entries.get(key) {
+            Some(entry) => entry.inserted_at.elapsed() > self.ttl,
+            None => {
+                inner.misses += 1;
+                return None;
+            }
+        };
+        if expired {
+            inner.remove(key);
+            inner.misses += 1;
+            return None;
+        }
+
+        // move the entry to the back of the access order
+        let tick = inner.next_tick();
+        let entry = inner.entries.get_mut(key).unwrap();
+        let old_tick = std::mem::replace(&mut entry.tick, tick);
+        let route_index = entry.route_index;
+        inner.order.remove(&old_tick);
+        inner.order.insert(tick, key.to_string());
+        inner.hits += 1;
+        Some(route_index)
+    }
+
+    pub fn insert(&self, key: String, route_index: Option<usize>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut inner = self.inner.lock();
+        inner.remove(&key);
+        while inner.entries.len() >= self.capacity {
+            let oldest = match inner.order.pop_first() {
+                Some((_, oldest)) => oldest,
+                None => break,
+            };
+            inner.entries.remove(&oldest);
+            inner.evictions += 1;
+        }
+
+        let tick = inner.next_tick();
+        inner.order.insert(tick, key.clone());
+        inner.entries.insert(
+            key,
+            CacheEntry {
+                route_index,
+                inserted_at: Instant::now(),
+                tick,
+            },
+        );
+    }
+
+    /// Drop every cached lookup, called whenever the route table changes
+    pub fn invalidate(&self) {
+        let mut inner = self.inner.lock();
+        inner.entries.clear();
+        inner.order.clear();
+    }
+
+    pub fn stats(&self) -> HashMap<String, u64> {
+        let inner = self.inner.lock();
+        HashMap::from([
+            ("size".to_string(), inner.entries.len() as u64),
+            ("hits".to_string(), inner.hits),
+            ("misses".to_string(), inner.misses),
+            ("evictions".to_string(), inner.evictions),
+        ])
+    }
+}
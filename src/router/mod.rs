@@ -1,2 +1,3 @@
+pub mod cache;
 pub mod route;
-pub mod router;
\ No newline at end of file
+pub mod router;
@@ -1,5 +1,13 @@
 use pyo3::prelude::*;
-use crate::types::function_info::FunctionInfo;
+use std::{collections::HashMap, sync::Arc};
+use crate::{
+    middlewares::{
+        cache::CacheRule,
+        ip_filter::{IpFilterConfig, IpFilterMiddleware},
+        rate_limit::RateLimitRule,
+    },
+    types::function_info::FunctionInfo,
+};
 
 #[pyclass]
 #[derive(Debug, Clone)]
@@ -12,19 +20,151 @@ pub struct Route {
 
     #[pyo3(get, set)]
     pub method: String,
+
+    // overrides the router trailing slash policy for this route
+    #[pyo3(get, set)]
+    pub trailing_slash: Option<String>,
+
+    // deprecated routes still serve requests but announce it with a `Deprecation` header
+    #[pyo3(get, set)]
+    pub deprecated: bool,
+
+    // HTTP date after which the route may be removed, sent as the `Sunset` header
+    #[pyo3(get, set)]
+    pub sunset_date: Option<String>,
+
+    // computes ETags and answers 304s for this route, None follows the server setting
+    #[pyo3(get, set)]
+    pub etag: Option<bool>,
+
+    // listed in the OpenAPI document served by `enable_openapi`
+    #[pyo3(get, set)]
+    pub include_in_schema: bool,
+
+    // OpenAPI metadata, set values replace the ones of the handler docstring
+    #[pyo3(get, set)]
+    pub tags: Vec<String>,
+    #[pyo3(get, set)]
+    pub summary: Option<String>,
+    #[pyo3(get, set)]
+    pub description: Option<String>,
+    #[pyo3(get, set)]
+    pub operation_id: Option<String>,
+
+    // set with `cache`, responses are stored once the server has a response cache
+    pub cache: Option<CacheRule>,
+
+    // set with `rate_limit`, replaces the server limit for this route
+    pub rate_limit: Option<RateLimitRule>,
+
+    // set with `ip_filter`, replaces the server filter for this route
+    pub ip_filter: Option<Arc<IpFilterMiddleware>>,
+
+    // the body is left unread for `request.streaming_body()`
+    #[pyo3(get, set)]
+    pub stream_body: bool,
 }
 
+// How a request differing from a route only by a trailing slash is handled
+pub const TRAILING_SLASH_POLICIES: [&str; 3] = ["strict", "redirect", "merge"];
+
 #[pymethods]
 impl Route {
     #[new]
-    pub fn new(path: &str, function: FunctionInfo, method: String) -> Self {
+    #[pyo3(signature = (path, function, method, trailing_slash=None, deprecated=false, sunset_date=None, etag=None, include_in_schema=true, tags=None, summary=None, description=None, operation_id=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        path: &str,
+        function: FunctionInfo,
+        method: String,
+        trailing_slash: Option<String>,
+        deprecated: bool,
+        sunset_date: Option<String>,
+        etag: Option<bool>,
+        include_in_schema: bool,
+        tags: Option<Vec<String>>,
+        summary: Option<String>,
+        description: Option<String>,
+        operation_id: Option<String>,
+    ) -> Self {
         Self {
             path: path.to_string(),
             function,
-            method
+            method,
+            trailing_slash,
+            deprecated,
+            sunset_date,
+            etag,
+            include_in_schema,
+            tags: tags.unwrap_or_default(),
+            summary,
+            description,
+            operation_id,
+            cache: None,
+            rate_limit: None,
+            ip_filter: None,
+            stream_body: false,
         }
     }
 
+    // Create a route that is already marked as deprecated
+    #[staticmethod]
+    #[pyo3(signature = (path, function, method, sunset=None))]
+    pub fn new_deprecated(
+        path: &str,
+        function: FunctionInfo,
+        method: String,
+        sunset: Option<String>,
+    ) -> Self {
+        Self::new(
+            path, function, method, None, true, sunset, None, true, None, None, None, None,
+        )
+    }
+
+    // Cache successful GET responses of this route for `ttl` seconds, keyed on `key` and `vary_headers`
+    #[pyo3(signature = (ttl=60, key="method+path+query", vary_headers=None))]
+    pub fn cache(&mut self, ttl: u64, key: &str, vary_headers: Option<Vec<String>>) -> PyResult<()> {
+        self.cache = Some(CacheRule::new(ttl, key, vary_headers.unwrap_or_default())?);
+        Ok(())
+    }
+
+    // Allow `limit` requests per client every `window_secs` on this route, once the server rate limits
+    pub fn rate_limit(&mut self, limit: u64, window_secs: u64) -> PyResult<()> {
+        self.rate_limit = Some(RateLimitRule::new(limit, window_secs)?);
+        Ok(())
+    }
+
+    // Only serve this route to `allow` (everyone when empty) minus `deny`, whatever the server filter
+    #[pyo3(signature = (allow=None, deny=None, status=403))]
+    pub fn ip_filter(
+        &mut self,
+        allow: Option<Vec<String>>,
+        deny: Option<Vec<String>>,
+        status: u16,
+    ) -> PyResult<()> {
+        let config = IpFilterConfig::new(
+            Vec::new(),
+            "allowlist",
+            allow.unwrap_or_default(),
+            deny.unwrap_or_default(),
+            status,
+        )?;
+        self.ip_filter = Some(Arc::new(IpFilterMiddleware::from_config(&config)?));
+        Ok(())
+    }
+
+    // Headers announcing the deprecation, empty for routes that are not deprecated
+    pub fn deprecation_headers(&self) -> Vec<(String, String)> {
+        if !self.deprecated {
+            return Vec::new();
+        }
+        let mut headers = vec![("deprecation".to_string(), "true".to_string())];
+        if let Some(sunset) = &self.sunset_date {
+            headers.push(("sunset".to_string(), sunset.clone()));
+        }
+        headers
+    }
+
     // Get a formatted string representation of the route
     pub fn __str__(&self) -> PyResult<String> {
         Ok(format!("{} {}", self.method, self.path))
@@ -32,8 +172,26 @@ impl Route {
 
     // Get a formatted representation for debugging
     pub fn __repr__(&self) -> PyResult<String> {
-        Ok(format!("Route(path='{}', method='{}')", 
-            self.path, self.method))
+        let quoted = |value: &Option<String>| match value {
+            Some(value) => format!("'{}'", value),
+            None => "None".to_string(),
+        };
+        let tags: Vec<String> = self.tags.iter().map(|tag| format!("'{}'", tag)).collect();
+        Ok(format!(
+            "Route(path='{}', method='{}', tags=[{}], summary={}, description={}, operation_id={}, deprecated={})",
+            self.path,
+            self.method,
+            tags.join(", "),
+            quoted(&self.summary),
+            quoted(&self.description),
+            quoted(&self.operation_id),
+            if self.deprecated { "True" } else { "False" },
+        ))
+    }
+
+    // Whether the route carries `tag`
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|route_tag| route_tag == tag)
     }
 
     // Check if route matches given path and method
@@ -63,6 +221,20 @@ impl Route {
             return false;
         }
 
+        // A `*name` wildcard must be named and can only be the final segment
+        let segments: Vec<&str> = self.path.split('/').filter(|s| !s.is_empty()).collect();
+        for (i, segment) in segments.iter().enumerate() {
+            if segment.starts_with('*') && (segment.len() == 1 || i != segments.len() - 1) {
+                return false;
+            }
+        }
+
+        if let Some(policy) = &self.trailing_slash {
+            if !TRAILING_SLASH_POLICIES.contains(&policy.as_str()) {
+                return false;
+            }
+        }
+
         // Method should be a valid HTTP method
         let valid_methods = ["GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS"];
         if !valid_methods.contains(&self.method.to_uppercase().as_str()) {
@@ -72,18 +244,59 @@ impl Route {
         true
     }
 
-    // Get route parameters from path
+    // Get route parameters from path, including a trailing `*name` wildcard
     pub fn get_path_params(&self) -> Vec<String> {
         self.path
             .split('/')
-            .filter(|segment| segment.starts_with(':'))
+            .filter(|segment| segment.starts_with(':') || segment.starts_with('*'))
             .map(|param| param[1..].to_string())
             .collect()
     }
 
+    // Capture `:name` segments and a trailing `*name` wildcard from a matching request path,
+    // a wildcard holds the remaining path including slashes
+    pub fn extract_path_params(&self, path: &str) -> HashMap<String, String> {
+        let path_segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let mut params = HashMap::new();
+        for (i, segment) in self.path.split('/').filter(|s| !s.is_empty()).enumerate() {
+            if let Some(name) = segment.strip_prefix(':') {
+                if let Some(value) = path_segments.get(i) {
+                    params.insert(name.to_string(), percent_decode(value));
+                }
+            } else if let Some(name) = segment.strip_prefix('*') {
+                let rest = path_segments.get(i..).unwrap_or_default().join("/");
+                params.insert(name.to_string(), percent_decode(&rest));
+            }
+        }
+        params
+    }
+
     // Check if route has path parameters
     pub fn has_parameters(&self) -> bool {
-        self.path.contains(':')
+        self.path.contains(':') || self.has_wildcard()
+    }
+
+    // Check if route ends with a catch-all `*name` segment
+    pub fn has_wildcard(&self) -> bool {
+        self.path
+            .split('/')
+            .last()
+            .map_or(false, |segment| segment.starts_with('*'))
+    }
+
+    // Path in OpenAPI form, `:id` and `*path` become `{id}` and `{path}`
+    pub fn openapi_path(&self) -> String {
+        self.path
+            .split('/')
+            .map(|segment| {
+                if segment.starts_with(':') || segment.starts_with('*') {
+                    format!("{{{}}}", &segment[1..])
+                } else {
+                    segment.to_string()
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("/")
     }
 
     // Generate a normalized version of the path
@@ -158,4 +371,24 @@ impl Route {
             Ok(false)
         }
     }
-}
\ No newline at end of file
+}
+
+// Decode `%XX` escapes in a path segment, invalid escapes are kept as is
+pub(crate) fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
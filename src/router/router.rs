@@ -1,16 +1,49 @@
-use super::route::Route;
+use super::cache::RouteCache;
+use super::route::{Route, TRAILING_SLASH_POLICIES};
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use std::collections::HashMap;
 
 /// Contains the thread safe hashmaps of different routes
 #[pyclass]
-#[derive(Debug, Default, FromPyObject)]
+#[derive(Debug, Default)]
 pub struct Router {
     #[pyo3(get, set)]
     path: String,
 
-    #[pyo3(get, set)]
+    #[pyo3(get)]
     routes: Vec<Route>,
+
+    // "strict", "redirect" or "merge", see TRAILING_SLASH_POLICIES
+    #[pyo3(get)]
+    trailing_slash: String,
+
+    cache: RouteCache,
+}
+
+/// Result of resolving a request against the route table
+pub enum RouteMatch<'a> {
+    // matched route and the path its parameters are extracted from
+    Found(&'a Route, String),
+    // canonical path the request should be redirected to
+    Redirect(String),
+    MethodNotAllowed,
+    NotFound,
+}
+
+impl FromPyObject<'_> for Router {
+    fn extract(ob: &PyAny) -> PyResult<Self> {
+        let path = ob.getattr("path")?.extract::<String>()?;
+        let routes = ob.getattr("routes")?.extract::<Vec<Route>>()?;
+        let trailing_slash = ob.getattr("trailing_slash")?.extract::<String>()?;
+
+        Ok(Self {
+            path,
+            routes,
+            trailing_slash,
+            cache: RouteCache::default(),
+        })
+    }
 }
 
 #[pymethods]
@@ -20,9 +53,30 @@ impl Router {
         Self {
             path: path.to_string(),
             routes: Vec::new(),
+            trailing_slash: "strict".to_string(),
+            cache: RouteCache::default(),
         }
     }
 
+    #[setter]
+    fn set_trailing_slash(&mut self, policy: &str) -> PyResult<()> {
+        if !TRAILING_SLASH_POLICIES.contains(&policy) {
+            return Err(PyValueError::new_err(format!(
+                "Invalid trailing slash policy '{}', expected one of: {}",
+                policy,
+                TRAILING_SLASH_POLICIES.join(", ")
+            )));
+        }
+        self.trailing_slash = policy.to_string();
+        Ok(())
+    }
+
+    #[setter]
+    fn set_routes(&mut self, routes: Vec<Route>) {
+        self.routes = routes;
+        self.cache.invalidate();
+    }
+
     /// Add a new route to the router
     pub fn add_route(&mut self, mut route: Route) -> PyResult<()> {
         // Validate route before adding
@@ -30,6 +84,10 @@ impl Router {
             return Err(PyValueError::new_err("Invalid route configuration"));
         }
 
+        // get full path and update to route
+        let full_path = self.get_full_path(&route.path);
+        route.update_path(&full_path);
+
         // Check for duplicate routes
         if self.has_duplicate_route(&route) {
             return Err(PyValueError::new_err(format!(
@@ -38,13 +96,10 @@ impl Router {
             )));
         }
 
-        // get full path and update to route
-        let full_path = self.get_full_path(&route.path);
-        route.update_path(&full_path);
-
         self.routes.push(route);
         // Sort routes after adding new one
         self.sort_routes();
+        self.cache.invalidate();
         Ok(())
     }
 
@@ -56,6 +111,40 @@ impl Router {
         Ok(())
     }
 
+    /// Mount all routes of another router under a prefix
+    pub fn include_router(&mut self, other: PyRef<Router>, prefix: &str) -> PyResult<()> {
+        let mut routes = Vec::with_capacity(other.routes.len());
+        for route in other.routes.iter() {
+            let mut route = route.clone();
+            let relative = route.path.trim_start_matches('/');
+            let mounted = if relative.is_empty() {
+                prefix.to_string()
+            } else {
+                format!("{}/{}", prefix.trim_end_matches('/'), relative)
+            };
+            let full_path = self.get_full_path(&mounted);
+            route.update_path(&full_path);
+
+            // reject the whole mount if any route collides, so nothing is half merged
+            if self.has_duplicate_route(&route)
+                || routes.iter().any(|r: &Route| {
+                    r.path == route.path && r.method.to_uppercase() == route.method.to_uppercase()
+                })
+            {
+                return Err(PyValueError::new_err(format!(
+                    "Route {} {} already exists",
+                    route.method, route.path
+                )));
+            }
+            routes.push(route);
+        }
+
+        self.routes.extend(routes);
+        self.sort_routes();
+        self.cache.invalidate();
+        Ok(())
+    }
+
     /// Remove a route by path and method
     pub fn remove_route(&mut self, path: &str, method: &str) -> PyResult<bool> {
         if let Some(index) = self
@@ -64,6 +153,7 @@ impl Router {
             .position(|r| r.path == path && r.method.to_uppercase() == method.to_uppercase())
         {
             self.routes.remove(index);
+            self.cache.invalidate();
             Ok(true)
         } else {
             Ok(false)
@@ -100,9 +190,20 @@ impl Router {
             .collect()
     }
 
+    /// Get all routes carrying a tag
+    #[pyo3(name = "get_routes_by_tag")]
+    pub fn get_routes_by_tag_py(&self, tag: &str) -> Vec<Route> {
+        self.routes
+            .iter()
+            .filter(|r| r.has_tag(tag))
+            .cloned()
+            .collect()
+    }
+
     /// Clear all routes
     pub fn clear_routes(&mut self) {
         self.routes.clear();
+        self.cache.invalidate();
     }
 
     /// Get number of routes
@@ -130,7 +231,24 @@ impl Router {
         if !new_path.starts_with('/') {
             return Err(PyValueError::new_err("Base path must start with '/'"));
         }
+
+        // routes are stored with their full path, move them under the new base
+        let old_base = normalize_path(&self.path);
         self.path = new_path.to_string();
+        for i in 0..self.routes.len() {
+            let relative = match old_base.as_str() {
+                "/" => self.routes[i].path.clone(),
+                base => self.routes[i]
+                    .path
+                    .strip_prefix(base)
+                    .unwrap_or(&self.routes[i].path)
+                    .to_string(),
+            };
+            let full_path = self.get_full_path(&relative);
+            self.routes[i].update_path(&full_path);
+        }
+        self.sort_routes();
+        self.cache.invalidate();
         Ok(())
     }
 
@@ -138,13 +256,14 @@ impl Router {
     pub fn get_full_path(&self, route_path: &str) -> String {
         let base = self.path.trim_end_matches('/');
         let route = route_path.trim_start_matches('/');
-        if base.is_empty() {
+        let full_path = if base.is_empty() {
             format!("/{}", route)
         } else if route.is_empty() {
             base.to_string()
         } else {
             format!("{}/{}", base, route)
-        }
+        };
+        normalize_path(&full_path)
     }
 
     /// Check if router contains a specific route
@@ -152,13 +271,24 @@ impl Router {
         self.routes.iter().any(|r| r.matches(path, method))
     }
 
-    /// Get string representation of router
-    fn __str__(&self) -> PyResult<String> {
-        Ok(format!(
-            "Router(base_path='{}', routes={})",
-            self.path,
-            self.routes.len()
-        ))
+    /// Get the routes as a table, so `print(router)` lists them
+    fn __str__(&self, py: Python) -> PyResult<String> {
+        self.routes_table(py)
+    }
+
+    /// Print a table of the registered routes and return it
+    pub fn print_routes(&self, py: Python) -> PyResult<String> {
+        let table = self.routes_table(py)?;
+        py.import("builtins")?.call_method1("print", (&table,))?;
+        Ok(table)
+    }
+
+    /// Get `(method, path, has_params)` for every registered route
+    pub fn route_summary(&self) -> Vec<(String, String, bool)> {
+        self.routes
+            .iter()
+            .map(|r| (r.method.clone(), r.path.clone(), r.has_parameters()))
+            .collect()
     }
 
     /// Get detailed representation of router
@@ -180,9 +310,59 @@ impl Router {
     pub fn find_matching_route_py(&self, path: &str, method: &str) -> PyResult<Option<Route>> {
         Ok(self.find_matching_route(path, method).cloned())
     }
+
+    /// Get route lookup cache statistics: size, hits, misses and evictions
+    pub fn cache_stats(&self) -> HashMap<String, u64> {
+        self.cache.stats()
+    }
 }
 
 impl Router {
+    // ASCII table of method, path, params and handler name, columns padded to the widest cell
+    fn routes_table(&self, py: Python) -> PyResult<String> {
+        let header = ["Method", "Path", "Params", "Handler"];
+        let mut rows = Vec::with_capacity(self.routes.len());
+        for route in &self.routes {
+            let handler = route.function.handler.as_ref(py);
+            let name = match handler.getattr("__name__") {
+                Ok(name) => name.extract::<String>()?,
+                Err(_) => handler.get_type().name()?.to_string(),
+            };
+            let params = if route.has_parameters() { "yes" } else { "no" };
+            rows.push([route.method.clone(), route.path.clone(), params.to_string(), name]);
+        }
+
+        let mut widths = header.map(|cell| cell.chars().count());
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let border = widths
+            .iter()
+            .map(|width| "-".repeat(width + 2))
+            .collect::<Vec<_>>()
+            .join("+");
+        let border = format!("+{}+", border);
+        let line = |cells: &[&str]| {
+            let cells = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!(" {:<width$} ", cell, width = width))
+                .collect::<Vec<_>>()
+                .join("|");
+            format!("|{}|", cells)
+        };
+
+        let mut table = vec![border.clone(), line(&header), border.clone()];
+        for row in &rows {
+            table.push(line(&row.iter().map(String::as_str).collect::<Vec<_>>()));
+        }
+        table.push(border);
+        Ok(table.join("\n"))
+    }
+
     pub fn iter(&self) -> std::slice::Iter<Route> {
         self.routes.iter()
     }
@@ -208,26 +388,108 @@ impl Router {
         });
     }
 
-    // Find most specific matching route for a path (internal method)
-    fn find_matching_route(&self, path: &str, method: &str) -> Option<&Route> {
+    // Find most specific matching route for a path, consulting the lookup cache first
+    pub fn find_matching_route(&self, path: &str, method: &str) -> Option<&Route> {
+        let key = RouteCache::key(method, path);
+        if let Some(index) = self.cache.get(&key) {
+            return index.and_then(|i| self.routes.get(i));
+        }
+
+        let index = self
+            .match_route(path, method)
+            .and_then(|route| self.routes.iter().position(|r| std::ptr::eq(r, route)));
+        self.cache.insert(key, index);
+        index.and_then(|i| self.routes.get(i))
+    }
+
+    fn match_route(&self, path: &str, method: &str) -> Option<&Route> {
         // First try exact match
         if let Some(route) = self.routes.iter().find(|r| r.matches(path, method)) {
             return Some(route);
         }
 
-        // Then try parameterized routes
-        self.routes
+        // Then try parameterized routes, catch-all wildcards have the lowest priority
+        let candidates = self
+            .routes
             .iter()
             .filter(|r| r.method.to_uppercase() == method.to_uppercase())
-            .find(|r| self.path_matches_pattern(path, &r.path))
+            .filter(|r| Self::path_matches_pattern(path, &r.path));
+        let (wildcards, params): (Vec<&Route>, Vec<&Route>) =
+            candidates.partition(|r| r.has_wildcard());
+        params.into_iter().chain(wildcards).next()
+    }
+
+    // Resolve a request, applying the trailing slash policy when only the other form exists
+    pub fn resolve(&self, path: &str, method: &str) -> RouteMatch<'_> {
+        if let Some(route) = self.find_route_for_request(path, method) {
+            return RouteMatch::Found(route, path.to_string());
+        }
+
+        // `//host/` would come back as a Location naming another host, so the other form
+        // starts with a single slash
+        let path_from_root = format!("/{}", path.trim_start_matches(['/', '\\']));
+        let trimmed = path_from_root.trim_end_matches('/');
+        if !trimmed.is_empty() {
+            let alternate = if path_from_root.ends_with('/') {
+                trimmed.to_string()
+            } else {
+                format!("{}/", path_from_root)
+            };
+            if let Some(route) = self.find_route_for_request(&alternate, method) {
+                let policy = route
+                    .trailing_slash
+                    .as_deref()
+                    .unwrap_or(self.trailing_slash.as_str());
+                match policy {
+                    "merge" => return RouteMatch::Found(route, alternate),
+                    "redirect" => return RouteMatch::Redirect(alternate),
+                    _ => {}
+                }
+            }
+        }
+
+        if self.has_path(path) {
+            RouteMatch::MethodNotAllowed
+        } else {
+            RouteMatch::NotFound
+        }
+    }
+
+    // like axum, GET routes also answer HEAD requests
+    fn find_route_for_request(&self, path: &str, method: &str) -> Option<&Route> {
+        match self.find_matching_route(path, method) {
+            None if method.eq_ignore_ascii_case("HEAD") => self.find_matching_route(path, "GET"),
+            route => route,
+        }
+    }
+
+    // Check if any route, whatever its method, matches a path
+    pub fn has_path(&self, path: &str) -> bool {
+        self.routes
+            .iter()
+            .any(|r| r.path == path || Self::path_matches_pattern(path, &r.path))
     }
 
     // Check if a path matches a pattern (including parameters)
-    fn path_matches_pattern(&self, path: &str, pattern: &str) -> bool {
+    pub fn path_matches_pattern(path: &str, pattern: &str) -> bool {
         let path_segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
         let pattern_segments: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
 
-        if path_segments.len() != pattern_segments.len() {
+        // a trailing `*name` swallows one or more remaining segments
+        if let Some(last) = pattern_segments.last() {
+            if last.starts_with('*') {
+                let prefix = &pattern_segments[..pattern_segments.len() - 1];
+                return path_segments.len() > prefix.len()
+                    && path_segments.iter().zip(prefix.iter()).all(|(path_seg, pattern_seg)| {
+                        pattern_seg.starts_with(':') || path_seg == pattern_seg
+                    });
+            }
+        }
+
+        // `/items` and `/items/` are distinct routes
+        if path_segments.len() != pattern_segments.len()
+            || path.ends_with('/') != pattern.ends_with('/')
+        {
             return false;
         }
 
@@ -236,4 +498,17 @@ impl Router {
             .zip(pattern_segments.iter())
             .all(|(path_seg, pattern_seg)| pattern_seg.starts_with(':') || path_seg == pattern_seg)
     }
-}
\ No newline at end of file
+}
+
+// Collapse repeated slashes, keeping a single trailing slash if the path had one
+pub fn normalize_path(path: &str) -> String {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    if segments.is_empty() {
+        return "/".to_string();
+    }
+    let mut normalized = format!("/{}", segments.join("/"));
+    if path.ends_with('/') {
+        normalized.push('/');
+    }
+    normalized
+}
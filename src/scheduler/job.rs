@@ -8,13 +8,43 @@ use std::time::Duration;
 use uuid;
 
 use super::retry::RetryPolicy;
+use super::store::JobRecord;
 
 #[derive(Clone)]
 pub enum JobType {
     INTERVAL(Duration),
     CRON(String),
+    ONCE(DateTime<Utc>),
 }
 
+// What to do when a job becomes due while `max_concurrency` runs are already in flight
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MisfirePolicy {
+    SKIP,
+    QUEUE,
+}
+
+impl FromStr for MisfirePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(MisfirePolicy::SKIP),
+            "queue" => Ok(MisfirePolicy::QUEUE),
+            _ => Err(format!("Invalid misfire policy '{}'. Must be 'skip' or 'queue'", s)),
+        }
+    }
+}
+
+// Everything a single run of a job needs, copied out while the jobs lock is held
+#[derive(Clone)]
+pub struct JobRun {
+    pub job_id: String,
+    pub task: PyObject,
+    pub timeout: Option<Duration>,
+    pub on_success: Option<PyObject>,
+    pub on_failure: Option<PyObject>,
+}
 
 pub struct Job {
     id: String,
@@ -27,15 +57,36 @@ pub struct Job {
     retry_policy: Option<RetryPolicy>,
     next_retry: Option<DateTime<Utc>>,
     failed_dependencies: HashSet<String>,
+    max_concurrency: usize,
+    misfire_policy: MisfirePolicy,
+    in_flight: usize,
+    pending_runs: usize,
+    skipped_runs: u64,
+    timeout: Option<Duration>,
+    on_success: Option<PyObject>,
+    on_failure: Option<PyObject>,
+    last_error: Option<String>,
+    task_name: Option<String>,
+    // names the callbacks were registered under, to re-attach them on restore
+    on_success_name: Option<String>,
+    on_failure_name: Option<String>,
+    // skip a due run while any run is in flight, whatever max_concurrency and misfire_policy say
+    skip_if_running: bool,
 }
 
 impl Job {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         job_type: JobType,
         task: PyObject,
         timezone: Tz,
         dependencies: HashSet<String>,
         retry_policy: Option<RetryPolicy>,
+        max_concurrency: usize,
+        misfire_policy: MisfirePolicy,
+        timeout: Option<Duration>,
+        on_success: Option<PyObject>,
+        on_failure: Option<PyObject>,
     ) -> Self {
         Job {
             id: uuid::Uuid::new_v4().to_string(),
@@ -48,9 +99,151 @@ impl Job {
             retry_policy,
             next_retry: None,
             failed_dependencies: HashSet::new(),
+            max_concurrency: max_concurrency.max(1),
+            misfire_policy,
+            in_flight: 0,
+            pending_runs: 0,
+            skipped_runs: 0,
+            timeout,
+            on_success,
+            on_failure,
+            last_error: None,
+            task_name: None,
+            on_success_name: None,
+            on_failure_name: None,
+            skip_if_running: false,
         }
     }
 
+    // Rebuild a persisted job, re-attaching the task and callbacks registered under its names
+    pub fn from_record(
+        record: &JobRecord,
+        task: PyObject,
+        on_success: Option<PyObject>,
+        on_failure: Option<PyObject>,
+    ) -> Result<Self, String> {
+        let timezone: Tz = record
+            .timezone
+            .parse()
+            .map_err(|e| format!("Invalid timezone: {}", e))?;
+        let job_type = match record.job_type.as_str() {
+            "interval" => JobType::INTERVAL(Duration::from_secs(
+                record
+                    .schedule_param
+                    .parse::<u64>()
+                    .map_err(|e| format!("Invalid interval: {}", e))?,
+            )),
+            "cron" => JobType::CRON(record.schedule_param.clone()),
+            "date" => JobType::ONCE(
+                DateTime::parse_from_rfc3339(&record.schedule_param)
+                    .map_err(|e| format!("Invalid date: {}", e))?
+                    .with_timezone(&Utc),
+            ),
+            other => return Err(format!("Unknown job type '{}'", other)),
+        };
+        let retry_policy = record
+            .retry_policy
+            .map(|(max_retries, retry_delay_secs, exponential_backoff, jitter_factor, max_delay_secs)| {
+                let mut policy = RetryPolicy::new(
+                    max_retries,
+                    retry_delay_secs,
+                    exponential_backoff,
+                    jitter_factor,
+                    max_delay_secs,
+                );
+                policy.set_current_retry(record.current_retry);
+                policy
+            });
+
+        let mut job = Job::new(
+            job_type,
+            task,
+            timezone,
+            record.dependencies.iter().cloned().collect(),
+            retry_policy,
+            record.max_concurrency,
+            MisfirePolicy::from_str(&record.misfire_policy)?,
+            record.timeout_secs.map(Duration::from_secs),
+            on_success,
+            on_failure,
+        );
+        job.id = record.id.clone();
+        job.last_run = record.last_run.and_then(|ts| DateTime::from_timestamp(ts, 0));
+        job.last_success = record.last_success.and_then(|ts| DateTime::from_timestamp(ts, 0));
+        job.next_retry = record.next_retry.and_then(|ts| DateTime::from_timestamp(ts, 0));
+        job.task_name = Some(record.task_name.clone());
+        job.on_success_name = record.on_success.clone();
+        job.on_failure_name = record.on_failure.clone();
+        job.skip_if_running = record.skip_if_running;
+        Ok(job)
+    }
+
+    // Only jobs whose task and callbacks were registered by name can be persisted
+    pub fn to_record(&self) -> Option<JobRecord> {
+        let task_name = self.task_name.clone()?;
+        if (self.on_success.is_some() && self.on_success_name.is_none())
+            || (self.on_failure.is_some() && self.on_failure_name.is_none())
+        {
+            return None;
+        }
+        let (job_type, schedule_param) = match &self.job_type {
+            JobType::INTERVAL(duration) => ("interval", duration.as_secs().to_string()),
+            JobType::CRON(expression) => ("cron", expression.clone()),
+            JobType::ONCE(run_at) => ("date", run_at.to_rfc3339()),
+        };
+        Some(JobRecord {
+            id: self.id.clone(),
+            task_name,
+            job_type: job_type.to_string(),
+            schedule_param,
+            timezone: self.timezone.name().to_string(),
+            dependencies: self.dependencies.iter().cloned().collect(),
+            retry_policy: self.retry_policy.as_ref().map(|policy| {
+                (
+                    policy.get_max_retries(),
+                    policy.get_retry_delay_secs(),
+                    policy.is_exponential_backoff(),
+                    policy.get_jitter_factor(),
+                    policy.get_max_delay_secs(),
+                )
+            }),
+            max_concurrency: self.max_concurrency,
+            misfire_policy: match self.misfire_policy {
+                MisfirePolicy::SKIP => "skip".to_string(),
+                MisfirePolicy::QUEUE => "queue".to_string(),
+            },
+            timeout_secs: self.timeout.map(|timeout| timeout.as_secs()),
+            skip_if_running: self.skip_if_running,
+            last_run: self.last_run.map(|dt| dt.timestamp()),
+            last_success: self.last_success.map(|dt| dt.timestamp()),
+            next_retry: self.next_retry.map(|dt| dt.timestamp()),
+            current_retry: self
+                .retry_policy
+                .as_ref()
+                .map_or(0, |policy| policy.get_current_retry()),
+            on_success: self.on_success_name.clone(),
+            on_failure: self.on_failure_name.clone(),
+        })
+    }
+
+    pub fn set_task_name(&mut self, task_name: Option<String>) {
+        self.task_name = task_name;
+    }
+
+    pub fn set_callback_names(&mut self, on_success: Option<String>, on_failure: Option<String>) {
+        self.on_success_name = on_success;
+        self.on_failure_name = on_failure;
+    }
+
+    pub fn set_skip_if_running(&mut self, skip_if_running: bool) {
+        self.skip_if_running = skip_if_running;
+    }
+
+    // Whether a due run must be skipped because a previous one is still executing
+    pub fn skips_while_running(&self) -> bool {
+        self.skip_if_running && self.in_flight > 0
+    }
+
     pub fn get_id(&self) -> String {
         self.id.clone()
     }
@@ -63,6 +256,24 @@ impl Job {
         self.task.clone()
     }
 
+    pub fn to_run(&self) -> JobRun {
+        JobRun {
+            job_id: self.id.clone(),
+            task: self.task.clone(),
+            timeout: self.timeout,
+            on_success: self.on_success.clone(),
+            on_failure: self.on_failure.clone(),
+        }
+    }
+
+    pub fn get_last_error(&self) -> Option<String> {
+        self.last_error.clone()
+    }
+
+    pub fn set_last_error(&mut self, last_error: Option<String>) {
+        self.last_error = last_error;
+    }
+
     pub fn get_last_run(&self) -> Option<DateTime<Utc>> {
         self.last_run
     }
@@ -74,6 +285,10 @@ impl Job {
     pub fn get_last_success(&self) -> Option<DateTime<Utc>> {
         self.last_success
     }
+
+    pub fn is_one_shot(&self) -> bool {
+        matches!(self.job_type, JobType::ONCE(_))
+    }
     pub fn set_last_success(&mut self, last_success: DateTime<Utc>) {
         self.last_success = Some(last_success);
     }
@@ -89,6 +304,10 @@ impl Job {
         self.retry_policy.clone()
     }
 
+    pub fn get_retry_policy_mut(&mut self) -> Option<&mut RetryPolicy> {
+        self.retry_policy.as_mut()
+    }
+
     pub fn get_failed_dependencies(&self) -> HashSet<String> {
         self.failed_dependencies.clone()
     }
@@ -97,6 +316,53 @@ impl Job {
         self.timezone
     }
 
+    pub fn get_misfire_policy(&self) -> MisfirePolicy {
+        self.misfire_policy
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.in_flight > 0
+    }
+
+    pub fn get_skipped_runs(&self) -> u64 {
+        self.skipped_runs
+    }
+
+    // Whether another run may start without exceeding max_concurrency
+    pub fn has_capacity(&self) -> bool {
+        self.in_flight < self.max_concurrency
+    }
+
+    pub fn start_run(&mut self) {
+        self.in_flight += 1;
+    }
+
+    pub fn finish_run(&mut self) {
+        self.in_flight = self.in_flight.saturating_sub(1);
+    }
+
+    pub fn skip_run(&mut self) {
+        self.skipped_runs += 1;
+    }
+
+    // Record a run that became due while the job was saturated
+    pub fn record_misfire(&mut self) {
+        match self.misfire_policy {
+            MisfirePolicy::SKIP => self.skipped_runs += 1,
+            MisfirePolicy::QUEUE => self.pending_runs += 1,
+        }
+    }
+
+    // Claim a queued run if one is waiting and there is capacity for it
+    pub fn take_pending_run(&mut self) -> bool {
+        if self.pending_runs > 0 && self.has_capacity() {
+            self.pending_runs -= 1;
+            self.start_run();
+            return true;
+        }
+        false
+    }
+
     pub fn should_run(&self, now: DateTime<Utc>, completed_jobs: &HashSet<String>) -> bool {
         // Check dependencies
         if !self.dependencies.is_subset(completed_jobs) {
@@ -113,9 +379,11 @@ impl Job {
         match &self.job_type {
             &JobType::INTERVAL(duration) => match self.last_run {
                 None => true,
-                Some(last_run) => {
-                    now.signed_duration_since(last_run).to_std().unwrap() >= duration 
-                }
+                // a last run in the future, after the clock went back, waits for the clock to catch up
+                Some(last_run) => now
+                    .signed_duration_since(last_run)
+                    .to_std()
+                    .is_ok_and(|elapsed| elapsed >= duration),
             },
             &JobType::CRON(ref expression) =>  {
                 let schedule = Schedule::from_str(expression).unwrap();
@@ -134,6 +402,12 @@ impl Job {
                     }
                 }
             }
+            &JobType::ONCE(run_at) => {
+                // a one-shot job only runs again to retry a failed attempt
+                now >= run_at
+                    && self.last_success.is_none()
+                    && (self.last_run.is_none() || self.next_retry.is_some())
+            }
         }
     }
 }
@@ -1,3 +1,4 @@
 pub mod retry;
 pub mod job;
-pub mod scheduler;
\ No newline at end of file
+pub mod scheduler;
+pub mod store;
\ No newline at end of file
@@ -1,29 +1,91 @@
 use std::time::Duration;
 
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyTuple};
 
 #[derive(Clone)]
 pub struct RetryPolicy {
     max_retries: u32,
     retry_delay: Duration,
     exponential_backoff: bool,
+    // up to this fraction of the delay is added at random, so failed jobs don't all retry at once
+    jitter_factor: f64,
+    // upper bound of a single delay, exponential backoff grows quickly
+    max_delay: Option<Duration>,
     current_retry: u32,
 }
 
 impl RetryPolicy {
-    pub fn new(max_retries: u32, retry_delay_secs: u64, exponential_backoff: bool) -> Self {
+    pub fn new(
+        max_retries: u32,
+        retry_delay_secs: u64,
+        exponential_backoff: bool,
+        jitter_factor: f64,
+        max_delay_secs: Option<u64>,
+    ) -> Self {
         RetryPolicy {
             max_retries,
             retry_delay: Duration::from_secs(retry_delay_secs),
             exponential_backoff,
+            jitter_factor,
+            max_delay: max_delay_secs.map(Duration::from_secs),
             current_retry: 0,
         }
     }
 
+    // (max_retries, retry_delay_secs, exponential_backoff[, jitter_factor[, max_delay_secs]]) as given to `add_job`
+    pub fn from_py_tuple(policy: &PyTuple) -> PyResult<Self> {
+        if !(3..=5).contains(&policy.len()) {
+            return Err(PyValueError::new_err(
+                "retry_policy must be (max_retries, retry_delay_secs, exponential_backoff[, jitter_factor[, max_delay_secs]])",
+            ));
+        }
+        let jitter_factor = match policy.len() {
+            3 => 0.0,
+            _ => policy.get_item(3)?.extract::<Option<f64>>()?.unwrap_or(0.0),
+        };
+        if !(0.0..=1.0).contains(&jitter_factor) {
+            return Err(PyValueError::new_err(format!(
+                "jitter_factor must be between 0.0 and 1.0, got {}",
+                jitter_factor
+            )));
+        }
+        let max_delay_secs = match policy.len() {
+            5 => policy.get_item(4)?.extract::<Option<u64>>()?,
+            _ => None,
+        };
+        if max_delay_secs == Some(0) {
+            return Err(PyValueError::new_err("max_delay_secs must be greater than 0"));
+        }
+        Ok(RetryPolicy::new(
+            policy.get_item(0)?.extract()?,
+            policy.get_item(1)?.extract()?,
+            policy.get_item(2)?.extract()?,
+            jitter_factor,
+            max_delay_secs,
+        ))
+    }
+
     pub fn get_next_retry_delay(&self) -> Duration {
-        if self.exponential_backoff {
-            self.retry_delay * 2u32.pow(self.current_retry)
+        let base_delay = if self.exponential_backoff {
+            // saturate instead of overflowing, the cap below brings it back down
+            2u32.checked_pow(self.current_retry)
+                .and_then(|factor| self.retry_delay.checked_mul(factor))
+                .unwrap_or(Duration::MAX)
         } else {
             self.retry_delay
+        };
+        let delay = if self.jitter_factor > 0.0 {
+            let jitter = Duration::try_from_secs_f64(
+                base_delay.as_secs_f64() * rand::random::<f64>() * self.jitter_factor,
+            )
+            .unwrap_or(Duration::MAX);
+            base_delay.saturating_add(jitter)
+        } else {
+            base_delay
+        };
+        match self.max_delay {
+            Some(max_delay) => delay.min(max_delay),
+            None => delay,
         }
     }
 
@@ -38,6 +100,22 @@ impl RetryPolicy {
     pub fn get_max_retries(&self) -> u32 {
         self.max_retries
     }
+
+    pub fn get_retry_delay_secs(&self) -> u64 {
+        self.retry_delay.as_secs()
+    }
+
+    pub fn is_exponential_backoff(&self) -> bool {
+        self.exponential_backoff
+    }
+
+    pub fn get_jitter_factor(&self) -> f64 {
+        self.jitter_factor
+    }
+
+    pub fn get_max_delay_secs(&self) -> Option<u64> {
+        self.max_delay.map(|max_delay| max_delay.as_secs())
+    }
     
     pub fn increase_current_retry(&mut self) {
         self.current_retry += 1;
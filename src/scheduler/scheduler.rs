@@ -1,49 +1,96 @@
-use pyo3::prelude::*;
+use pyo3::{exceptions::PyTimeoutError, ffi, prelude::*, types::PyTuple, AsPyPointer};
+use std::os::raw::c_ulong;
 use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use chrono::Utc;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
 use chrono_tz::Tz;
 use std::thread;
 use cron::Schedule;
 use std::str::FromStr;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, warn};
 
 use crate::instants::get_runtime;
 use super::retry::RetryPolicy;
-use super::job::{Job, JobType};
+use super::job::{Job, JobRun, JobType, MisfirePolicy};
+use super::store::{JobRecord, JobStore};
 
 #[pyclass(subclass)]
 pub struct Scheduler {
     jobs: Arc<Mutex<HashMap<String, Job>>>,
     is_running: Arc<Mutex<bool>>,
     completed_jobs: Arc<Mutex<HashSet<String>>>,
+    registered_tasks: Arc<Mutex<HashMap<String, PyObject>>>,
+    store: Option<Arc<JobStore>>,
+    unrestored_jobs: Arc<Mutex<Vec<(String, String)>>>,
 }
 
 #[pymethods]
 impl Scheduler {
     #[new]
-    pub fn new() -> PyResult<Self> {
+    #[pyo3(signature = (persist_path=None))]
+    pub fn new(persist_path: Option<String>) -> PyResult<Self> {
+        let store = match persist_path {
+            Some(path) => {
+                let store = futures::executor::block_on(JobStore::open(&path)).map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to open job store: {}", e))
+                })?;
+                Some(Arc::new(store))
+            }
+            None => None,
+        };
+
         Ok(Scheduler {
             jobs: Arc::new(Mutex::new(HashMap::new())),
             is_running: Arc::new(Mutex::new(false)),
             completed_jobs: Arc::new(Mutex::new(HashSet::new())),
+            registered_tasks: Arc::new(Mutex::new(HashMap::new())),
+            store,
+            unrestored_jobs: Arc::new(Mutex::new(Vec::new())),
         })
     }
 
-    #[pyo3(signature = (job_type, schedule_param, task, timezone, dependencies, retry_policy=None))]
+    // Persisted jobs re-attach their task and callbacks through the names they were registered under
+    pub fn register_task(&self, py: Python<'_>, name: &str, task: PyObject) -> PyResult<()> {
+        if !task.as_ref(py).is_callable() {
+            return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>("Task must be callable"));
+        }
+        self.registered_tasks.lock().unwrap().insert(name.to_string(), task);
+        Ok(())
+    }
+
+    // (job_id, name) of persisted jobs that could not be restored because the task or callback of that name isn't registered
+    pub fn get_unrestored_jobs(&self) -> Vec<(String, String)> {
+        self.unrestored_jobs.lock().unwrap().clone()
+    }
+
+    #[pyo3(signature = (job_type, schedule_param, task, timezone, dependencies, retry_policy=None, max_concurrency=1, misfire_policy="skip", timeout_secs=None, on_success=None, on_failure=None, skip_if_running=false))]
+    #[allow(clippy::too_many_arguments)]
     pub fn add_job(
         &self,
         py: Python<'_>,
         job_type: &str,
-        schedule_param: &str, // interval in seconds for interval jobs, cron expression for cron jobs
+        schedule_param: &str, // interval in seconds for interval jobs, cron expression for cron jobs, local datetime for date jobs
         task: PyObject,
         timezone: &str,
         dependencies: Vec<String>,
-        retry_policy: Option<(u32, u64, bool)>, // (max_retries, retry_delay_secs, exponential_backoff)
+        retry_policy: Option<&PyTuple>, // (max_retries, retry_delay_secs, exponential_backoff[, jitter_factor[, max_delay_secs]])
+        max_concurrency: usize,
+        misfire_policy: &str, // "skip" drops overlapping runs, "queue" runs them once the previous one finishes
+        timeout_secs: Option<u64>,
+        on_success: Option<PyObject>, // called with (job_id, return value)
+        on_failure: Option<PyObject>, // called with (job_id, error message)
+        skip_if_running: bool, // never overlap, even with max_concurrency > 1 or a "queue" misfire policy
     ) -> PyResult<String> {
         if !task.as_ref(py).is_callable() {
             return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>("Task must be callable"));
         }
+        for callback in [&on_success, &on_failure].into_iter().flatten() {
+            if !callback.as_ref(py).is_callable() {
+                return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>("Callbacks must be callable"));
+            }
+        }
 
         // Parse timezone
         let tz: Tz = timezone.parse().map_err(|e| {
@@ -64,30 +111,66 @@ impl Scheduler {
                 })?;
                 JobType::CRON(schedule_param.to_string())
             },
-            _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid job type. Must be 'interval' or 'cron'")),
+            "date" => {
+                let naive = NaiveDateTime::parse_from_str(schedule_param, "%Y-%m-%dT%H:%M:%S").map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid date: {} - {}", e, schedule_param))
+                })?;
+                // the datetime is given in the job's timezone
+                let run_at = tz.from_local_datetime(&naive).single().ok_or_else(|| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Ambiguous or non-existent local time: {}", schedule_param))
+                })?;
+                JobType::ONCE(run_at.with_timezone(&Utc))
+            },
+            _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid job type. Must be 'interval', 'cron' or 'date'")),
         };
 
+        let misfire_policy = MisfirePolicy::from_str(misfire_policy)
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+
         // Create retry policy if specified
-        let retry_policy = retry_policy.map(|(max_retries, retry_delay_secs, exponential_backoff)| {
-            RetryPolicy::new(max_retries, retry_delay_secs, exponential_backoff)
-        });
+        let retry_policy = retry_policy.map(RetryPolicy::from_py_tuple).transpose()?;
 
-        let job = Job::new(
+        let task_name = self.registered_task_name(py, &task);
+        let callback_name = |callback: &Option<PyObject>| {
+            callback.as_ref().and_then(|callback| self.registered_task_name(py, callback))
+        };
+        let (on_success_name, on_failure_name) = (callback_name(&on_success), callback_name(&on_failure));
+        let mut job = Job::new(
             job_type,
             task,
             tz,
             dependencies.into_iter().collect(),
             retry_policy,
+            max_concurrency,
+            misfire_policy,
+            timeout_secs.map(Duration::from_secs),
+            on_success,
+            on_failure,
         );
 
+        job.set_task_name(task_name);
+        job.set_callback_names(on_success_name, on_failure_name);
+        job.set_skip_if_running(skip_if_running);
+        if let Some(store) = &self.store {
+            match job.to_record() {
+                Some(record) => save_record(store, &record),
+                None => warn!("Job {} is not persisted: its task or a callback is not registered with register_task", job.get_id()),
+            }
+        }
+
         let job_id = job.get_id();
         self.jobs.lock().unwrap().insert(job_id.clone(), job);
-        
+
         Ok(job_id)
     }
 
     pub fn remove_job(&self, id: &str) -> PyResult<()> {
         self.jobs.lock().unwrap().remove(id);
+        if let Some(store) = &self.store {
+            if let Err(e) = futures::executor::block_on(store.delete(id)) {
+                error!("Failed to delete job {} from store: {}", id, e);
+            }
+        }
         Ok(())
     }
 
@@ -99,53 +182,54 @@ impl Scheduler {
         *is_running = true;
         drop(is_running);
 
+        if let Some(store) = &self.store {
+            self.restore_jobs(store)?;
+        }
+
         let jobs = Arc::clone(&self.jobs);
         let is_running = Arc::clone(&self.is_running);
         let runtime = get_runtime();
         let completed_jobs = Arc::clone(&self.completed_jobs);
+        let store = self.store.clone();
 
         thread::spawn(move || {
             runtime.block_on(async {
                 while *is_running.lock().unwrap() {
-                    Python::with_gil(|py| {
+                    let now = Utc::now();
+
+                    // Collect due jobs, reserving a concurrency slot for each one we dispatch
+                    let due_jobs: Vec<JobRun> = {
                         let mut jobs_guard = jobs.lock().unwrap();
                         let completed_jobs_guard = completed_jobs.lock().unwrap();
-                        let now = Utc::now();
+                        let mut due_jobs = Vec::new();
 
                         for job in jobs_guard.values_mut() {
                             if job.should_run(now, &completed_jobs_guard) {
-                                let result = job.get_task().call0(py);
                                 job.set_last_run(now);
-
-                                match result {
-                                    Ok(_) => {
-                                        job.set_last_success(now);
-                                        job.set_next_retry(None);
-                                        if let Some(policy) = &mut job.get_retry_policy() {
-                                            policy.set_current_retry(0);
-                                        }
-                                    },
-                                    Err(_e) => {
-                                        if let Some(policy) = &mut job.get_retry_policy() {
-                                            if policy.get_current_retry() < policy.get_max_retries() {
-                                                let delay = policy.get_next_retry_delay();
-                                                job.set_next_retry(Some(now + chrono::Duration::from_std(delay).unwrap()));
-                                                policy.increase_current_retry();
-                                            } else {
-                                                job.set_next_retry(None);
-                                                job.get_failed_dependencies().iter().for_each(|dep| {
-                                                    completed_jobs.lock().unwrap().remove(dep);
-                                                });
-                                            }
-                                        } else {
-                                            job.set_next_retry(None);
-                                        }
-                                    }
+                                if job.skips_while_running() {
+                                    debug!("Job {} skipped: its previous run is still executing", job.get_id());
+                                    job.skip_run();
+                                } else if job.has_capacity() {
+                                    job.start_run();
+                                    due_jobs.push(job.to_run());
+                                } else {
+                                    job.record_misfire();
                                 }
                             }
                         }
-                    });
-                    
+                        due_jobs
+                    };
+
+                    // Run each job off the scheduler loop so a slow task can't delay the others
+                    for run in due_jobs {
+                        tokio::spawn(run_job(
+                            Arc::clone(&jobs),
+                            Arc::clone(&completed_jobs),
+                            store.clone(),
+                            run,
+                        ));
+                    }
+
                     tokio::time::sleep(Duration::from_secs(1)).await;
                 }
             });
@@ -160,7 +244,7 @@ impl Scheduler {
         Ok(())
     }
 
-    pub fn get_job_status(&self, id: &str) -> PyResult<Option<(f64, f64, Vec<String>, u32)>> {
+    pub fn get_job_status(&self, id: &str) -> PyResult<Option<(f64, f64, Vec<String>, u32, bool, u64)>> {
         let jobs = self.jobs.lock().unwrap();
         if let Some(job) = jobs.get(id) {
             Ok(Some((
@@ -168,12 +252,19 @@ impl Scheduler {
                 job.get_last_success().map_or(0.0, |dt| dt.timestamp() as f64),
                 job.get_failed_dependencies().iter().cloned().collect(),
                 job.get_retry_policy().as_ref().map_or(0, |p| p.get_current_retry()),
+                job.is_running(),
+                job.get_skipped_runs(),
             )))
         } else {
             Ok(None)
         }
     }
 
+    pub fn get_last_error(&self, id: &str) -> PyResult<Option<String>> {
+        let jobs = self.jobs.lock().unwrap();
+        Ok(jobs.get(id).and_then(|job| job.get_last_error()))
+    }
+
     pub fn get_next_run(&self, id: &str) -> PyResult<Option<f64>> {
         let jobs = self.jobs.lock().unwrap();
         if let Some(job) = jobs.get(id) {
@@ -187,7 +278,7 @@ impl Scheduler {
             match &job.get_job_type() {
                 JobType::INTERVAL(duration) => {
                     let next_run = match job.get_last_run() {
-                        Some(last_run) => last_run + chrono::Duration::from_std(*duration).unwrap(),
+                        Some(last_run) => after_delay(last_run, *duration),
                         None => now,
                     };
                     Ok(Some(next_run.timestamp() as f64))
@@ -201,9 +292,258 @@ impl Scheduler {
                         None => Ok(None),
                     }
                 }
+                // a pending retry was returned above, a run without one is the last
+                JobType::ONCE(run_at) => match job.get_last_run() {
+                    Some(_) => Ok(None),
+                    None => Ok(Some(run_at.timestamp() as f64)),
+                },
             }
         } else {
             Ok(None)
         }
     }
 }
+
+impl Scheduler {
+    fn registered_task_name(&self, py: Python<'_>, task: &PyObject) -> Option<String> {
+        self.registered_tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(_, registered)| registered.as_ref(py).is(task.as_ref(py)))
+            .map(|(name, _)| name.clone())
+    }
+
+    fn restore_jobs(&self, store: &JobStore) -> PyResult<()> {
+        let records = futures::executor::block_on(store.load_all()).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to load persisted jobs: {}", e))
+        })?;
+
+        let registered_tasks = self.registered_tasks.lock().unwrap();
+        let mut jobs = self.jobs.lock().unwrap();
+        let mut unrestored_jobs = self.unrestored_jobs.lock().unwrap();
+        unrestored_jobs.clear();
+
+        for record in records {
+            if jobs.contains_key(&record.id) {
+                continue;
+            }
+            let missing = [Some(&record.task_name), record.on_success.as_ref(), record.on_failure.as_ref()]
+                .into_iter()
+                .flatten()
+                .find(|name| !registered_tasks.contains_key(*name));
+            if let Some(name) = missing {
+                warn!("Job {} was not restored: '{}' is not registered with register_task", record.id, name);
+                unrestored_jobs.push((record.id.clone(), name.clone()));
+                continue;
+            }
+            let registered = |name: &Option<String>| name.as_ref().map(|name| registered_tasks[name].clone());
+            match Job::from_record(
+                &record,
+                registered_tasks[&record.task_name].clone(),
+                registered(&record.on_success),
+                registered(&record.on_failure),
+            ) {
+                Ok(job) => {
+                    jobs.insert(record.id.clone(), job);
+                }
+                Err(e) => {
+                    warn!("Job {} was not restored: {}", record.id, e);
+                    unrestored_jobs.push((record.id.clone(), record.task_name.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// `delay` after `at`, a time past what chrono can represent is clamped to its latest date
+fn after_delay(at: DateTime<Utc>, delay: Duration) -> DateTime<Utc> {
+    chrono::Duration::from_std(delay)
+        .ok()
+        .and_then(|delay| at.checked_add_signed(delay))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+fn save_record(store: &JobStore, record: &JobRecord) {
+    if let Err(e) = futures::executor::block_on(store.save(record)) {
+        error!("Failed to persist job {}: {}", record.id, e);
+    }
+}
+
+// The python thread running a sync task, only used with the GIL held
+#[derive(Default)]
+struct TaskThread {
+    ident: Option<c_ulong>,
+    interrupted: bool,
+}
+
+impl TaskThread {
+    // Raise TimeoutError in the thread at its next python instruction
+    fn interrupt(&mut self, py: Python<'_>) {
+        if let Some(ident) = self.ident {
+            self.interrupted = true;
+            let exception = py.get_type::<PyTimeoutError>();
+            unsafe { ffi::PyThreadState_SetAsyncExc(ident, exception.as_ptr()) };
+        }
+    }
+
+    // Called by the thread once the task returned
+    fn finish(&mut self) {
+        if let (Some(ident), true) = (self.ident.take(), self.interrupted) {
+            // an exception that wasn't raised yet must not hit what the thread runs next
+            unsafe { ffi::PyThreadState_SetAsyncExc(ident, std::ptr::null_mut()) };
+        }
+    }
+}
+
+// Call the task on a blocking thread. Coroutines returned by async tasks are driven
+// to completion there as well, wrapped in `asyncio.wait_for` so a timeout cancels them.
+// A sync task that overruns its timeout gets a TimeoutError raised in its thread, which
+// only shows once it runs python code again. Its result is abandoned and the handle of
+// the thread still running it is returned with the error.
+async fn execute_task(
+    task: PyObject,
+    timeout: Option<Duration>,
+) -> (Result<PyObject, String>, Option<JoinHandle<PyResult<PyObject>>>) {
+    let thread = Arc::new(Mutex::new(TaskThread::default()));
+    let task_thread = Arc::clone(&thread);
+    let mut handle = tokio::task::spawn_blocking(move || {
+        Python::with_gil(|py| -> PyResult<PyObject> {
+            let ident: c_ulong = py.import("threading")?.call_method0("get_ident")?.extract()?;
+            task_thread.lock().unwrap().ident = Some(ident);
+            let output = task.call0(py);
+            task_thread.lock().unwrap().finish();
+            let output = output?;
+
+            let asyncio = py.import("asyncio")?;
+            if !asyncio.call_method1("iscoroutine", (output.as_ref(py),))?.is_true()? {
+                return Ok(output);
+            }
+            let coro: PyObject = match timeout {
+                Some(timeout) => asyncio
+                    .call_method1("wait_for", (output, timeout.as_secs_f64()))?
+                    .into(),
+                None => output,
+            };
+            asyncio.call_method1("run", (coro,)).map(|result| result.into())
+        })
+    });
+
+    let joined = match timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, &mut handle).await {
+            Ok(joined) => joined,
+            Err(_) => {
+                Python::with_gil(|py| thread.lock().unwrap().interrupt(py));
+                let error = format!("Job timed out after {} seconds", timeout.as_secs());
+                return (Err(error), Some(handle));
+            }
+        },
+        None => handle.await,
+    };
+
+    let result = match joined {
+        Ok(Ok(output)) => Ok(output),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    (result, None)
+}
+
+fn invoke_callback(callback: &Option<PyObject>, job_id: &str, value: PyObject) {
+    if let Some(callback) = callback {
+        Python::with_gil(|py| {
+            if let Err(e) = callback.call1(py, (job_id, value)) {
+                error!("Callback for job {} failed: {}", job_id, e);
+            }
+        });
+    }
+}
+
+// Execute a job's task and record the outcome. Queued runs that piled up while
+// this one was in flight are executed back to back before returning.
+async fn run_job(
+    jobs: Arc<Mutex<HashMap<String, Job>>>,
+    completed_jobs: Arc<Mutex<HashSet<String>>>,
+    store: Option<Arc<JobStore>>,
+    run: JobRun,
+) {
+    loop {
+        let (result, still_running) = execute_task(run.task.clone(), run.timeout).await;
+        let now = Utc::now();
+
+        let record = {
+            let mut jobs_guard = jobs.lock().unwrap();
+            let job = match jobs_guard.get_mut(&run.job_id) {
+                Some(job) => job,
+                // The job was removed while it was running
+                None => return,
+            };
+
+            match &result {
+                Ok(_) => {
+                    job.set_last_success(now);
+                    job.set_last_error(None);
+                    // let jobs that depend on this one fire
+                    completed_jobs.lock().unwrap().insert(run.job_id.clone());
+                    job.set_next_retry(None);
+                    if let Some(policy) = job.get_retry_policy_mut() {
+                        policy.set_current_retry(0);
+                    }
+                }
+                Err(e) => {
+                    // timeouts end up here too, so a hung task is retried like a failed one
+                    error!("Job {} failed: {}", run.job_id, e);
+                    job.set_last_error(Some(e.clone()));
+                    let next_retry = match job.get_retry_policy_mut() {
+                        Some(policy) if policy.get_current_retry() < policy.get_max_retries() => {
+                            let delay = policy.get_next_retry_delay();
+                            policy.increase_current_retry();
+                            Some(after_delay(now, delay))
+                        }
+                        Some(_) => {
+                            let mut completed_jobs_guard = completed_jobs.lock().unwrap();
+                            job.get_failed_dependencies().iter().for_each(|dep| {
+                                completed_jobs_guard.remove(dep);
+                            });
+                            None
+                        }
+                        None => None,
+                    };
+                    job.set_next_retry(next_retry);
+                }
+            }
+            job.to_record()
+        };
+
+        if let (Some(store), Some(record)) = (&store, record) {
+            if let Err(e) = store.save(&record).await {
+                error!("Failed to persist job {}: {}", record.id, e);
+            }
+        }
+
+        // Callbacks run without the jobs lock so they are free to call back into the scheduler
+        match result {
+            Ok(output) => invoke_callback(&run.on_success, &run.job_id, output),
+            Err(e) => {
+                let error = Python::with_gil(|py| e.into_py(py));
+                invoke_callback(&run.on_failure, &run.job_id, error);
+            }
+        }
+
+        // a timed out task keeps its concurrency slot until its thread is done with it
+        if let Some(handle) = still_running {
+            let _ = handle.await;
+        }
+        let has_pending_run = match jobs.lock().unwrap().get_mut(&run.job_id) {
+            Some(job) => {
+                job.finish_run();
+                job.take_pending_run()
+            }
+            None => false,
+        };
+        if !has_pending_run {
+            return;
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    Pool, Row, Sqlite,
+};
+
+// Persisted metadata of a job. The task and callbacks themselves can't be stored, they are
+// re-attached on restore through the names they were registered under.
+#[derive(Debug, Clone)]
+pub struct JobRecord {
+    pub id: String,
+    pub task_name: String,
+    pub job_type: String,
+    pub schedule_param: String,
+    pub timezone: String,
+    pub dependencies: Vec<String>,
+    // (max_retries, retry_delay_secs, exponential_backoff, jitter_factor, max_delay_secs)
+    pub retry_policy: Option<(u32, u64, bool, f64, Option<u64>)>,
+    pub max_concurrency: usize,
+    pub misfire_policy: String,
+    pub timeout_secs: Option<u64>,
+    pub skip_if_running: bool,
+    pub last_run: Option<i64>,
+    pub last_success: Option<i64>,
+    // retry progress, so a job failing when the process stopped keeps its backoff
+    pub next_retry: Option<i64>,
+    pub current_retry: u32,
+    pub on_success: Option<String>,
+    pub on_failure: Option<String>,
+}
+
+// Rows written by older versions have fewer fields, the missing ones take their defaults
+fn parse_retry_policy(policy: &str) -> Option<(u32, u64, bool, f64, Option<u64>)> {
+    let fields: Vec<serde_json::Value> = serde_json::from_str(policy).ok()?;
+    Some((
+        fields.first()?.as_u64()? as u32,
+        fields.get(1)?.as_u64()?,
+        fields.get(2)?.as_bool()?,
+        fields.get(3).and_then(|field| field.as_f64()).unwrap_or(0.0),
+        fields.get(4).and_then(|field| field.as_u64()),
+    ))
+}
+
+pub struct JobStore {
+    pool: Pool<Sqlite>,
+}
+
+impl JobStore {
+    pub async fn open(path: &str) -> Result<Self, sqlx::Error> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS scheduler_jobs (
+                id TEXT PRIMARY KEY,
+                task_name TEXT NOT NULL,
+                job_type TEXT NOT NULL,
+                schedule_param TEXT NOT NULL,
+                timezone TEXT NOT NULL,
+                dependencies TEXT NOT NULL,
+                retry_policy TEXT,
+                max_concurrency INTEGER NOT NULL,
+                misfire_policy TEXT NOT NULL,
+                timeout_secs INTEGER,
+                last_run INTEGER,
+                last_success INTEGER,
+                skip_if_running INTEGER NOT NULL DEFAULT 0,
+                next_retry INTEGER,
+                current_retry INTEGER NOT NULL DEFAULT 0,
+                on_success TEXT,
+                on_failure TEXT
+            )",
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(Self { pool })
+    }
+
+    pub async fn save(&self, record: &JobRecord) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO scheduler_jobs (
+                id, task_name, job_type, schedule_param, timezone, dependencies, retry_policy,
+                max_concurrency, misfire_policy, timeout_secs, last_run, last_success, skip_if_running,
+                next_retry, current_retry, on_success, on_failure
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&record.id)
+        .bind(&record.task_name)
+        .bind(&record.job_type)
+        .bind(&record.schedule_param)
+        .bind(&record.timezone)
+        .bind(serde_json::to_string(&record.dependencies).unwrap())
+        .bind(record.retry_policy.map(|policy| serde_json::to_string(&policy).unwrap()))
+        .bind(record.max_concurrency as i64)
+        .bind(&record.misfire_policy)
+        .bind(record.timeout_secs.map(|secs| secs as i64))
+        .bind(record.last_run)
+        .bind(record.last_success)
+        .bind(record.skip_if_running)
+        .bind(record.next_retry)
+        .bind(record.current_retry as i64)
+        .bind(&record.on_success)
+        .bind(&record.on_failure)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM scheduler_jobs WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn load_all(&self) -> Result<Vec<JobRecord>, sqlx::Error> {
+        let rows = sqlx::query("SELECT * FROM scheduler_jobs")
+            .fetch_all(&self.pool)
+            .await?;
+
+        let records = rows
+            .iter()
+            .map(|row| {
+                let dependencies: String = row.get("dependencies");
+                let retry_policy: Option<String> = row.get("retry_policy");
+                let max_concurrency: i64 = row.get("max_concurrency");
+                let timeout_secs: Option<i64> = row.get("timeout_secs");
+                let skip_if_running: bool = row.get("skip_if_running");
+                let current_retry: i64 = row.get("current_retry");
+                JobRecord {
+                    id: row.get("id"),
+                    task_name: row.get("task_name"),
+                    job_type: row.get("job_type"),
+                    schedule_param: row.get("schedule_param"),
+                    timezone: row.get("timezone"),
+                    dependencies: serde_json::from_str(&dependencies).unwrap_or_default(),
+                    retry_policy: retry_policy.and_then(|policy| parse_retry_policy(&policy)),
+                    max_concurrency: max_concurrency as usize,
+                    misfire_policy: row.get("misfire_policy"),
+                    timeout_secs: timeout_secs.map(|secs| secs as u64),
+                    skip_if_running,
+                    last_run: row.get("last_run"),
+                    last_success: row.get("last_success"),
+                    next_retry: row.get("next_retry"),
+                    current_retry: current_retry as u32,
+                    on_success: row.get("on_success"),
+                    on_failure: row.get("on_failure"),
+                }
+            })
+            .collect();
+        Ok(records)
+    }
+}
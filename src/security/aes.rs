@@ -0,0 +1,61 @@
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes};
+
+const KEY_SIZE: usize = 32;
+const NONCE_SIZE: usize = 12;
+const TAG_SIZE: usize = 16;
+
+fn cipher(key: &[u8]) -> PyResult<Aes256Gcm> {
+    if key.len() != KEY_SIZE {
+        return Err(PyValueError::new_err(format!(
+            "AES-256-GCM key must be {} bytes, got {}",
+            KEY_SIZE,
+            key.len()
+        )));
+    }
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)))
+}
+
+// Output is `nonce || ciphertext || tag`, with a fresh random nonce per call
+pub fn encrypt_aes_gcm(plaintext: &[u8], key: &[u8]) -> PyResult<Vec<u8>> {
+    let cipher = cipher(key)?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| PyValueError::new_err("Encryption failed"))?;
+    let mut output = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+    output.extend_from_slice(&nonce);
+    output.extend_from_slice(&ciphertext);
+    Ok(output)
+}
+
+pub fn decrypt_aes_gcm(ciphertext: &[u8], key: &[u8]) -> PyResult<Vec<u8>> {
+    let cipher = cipher(key)?;
+    if ciphertext.len() < NONCE_SIZE + TAG_SIZE {
+        return Err(PyValueError::new_err("Ciphertext is too short"));
+    }
+    let (nonce, ciphertext) = ciphertext.split_at(NONCE_SIZE);
+    // a wrong key and tampered data both fail the tag check
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| PyValueError::new_err("Decryption failed, wrong key or corrupted data"))
+}
+
+/// Encrypt bytes with AES-256-GCM, the nonce and tag are part of the output
+#[pyfunction]
+#[pyo3(name = "encrypt_aes_gcm")]
+pub fn py_encrypt_aes_gcm<'p>(py: Python<'p>, plaintext: &[u8], key: &[u8]) -> PyResult<&'p PyBytes> {
+    let output = encrypt_aes_gcm(plaintext, key)?;
+    Ok(PyBytes::new(py, &output))
+}
+
+/// Decrypt the output of `encrypt_aes_gcm`
+#[pyfunction]
+#[pyo3(name = "decrypt_aes_gcm")]
+pub fn py_decrypt_aes_gcm<'p>(py: Python<'p>, ciphertext: &[u8], key: &[u8]) -> PyResult<&'p PyBytes> {
+    let output = decrypt_aes_gcm(ciphertext, key)?;
+    Ok(PyBytes::new(py, &output))
+}
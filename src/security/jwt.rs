@@ -0,0 +1,117 @@
+use std::{
+    collections::HashSet,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use jsonwebtoken::{
+    decode, encode, errors::ErrorKind, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use pyo3::{
+    create_exception,
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+    types::PyDict,
+};
+use serde_json::{Map, Value};
+
+use crate::types::json::{to_json_value, to_py_object, JsonOptions};
+
+// subclasses of the errors raised before, so existing `except` clauses keep working
+create_exception!(hypern, ExpiredSignatureError, PyValueError);
+create_exception!(hypern, InvalidTokenError, PyRuntimeError);
+create_exception!(hypern, InvalidSignatureError, InvalidTokenError);
+
+const ALGORITHMS: [&str; 6] = ["HS256", "HS384", "HS512", "RS256", "RS384", "RS512"];
+
+fn parse_algorithm(algorithm: &str) -> PyResult<Algorithm> {
+    match algorithm.to_uppercase().as_str() {
+        "HS256" => Ok(Algorithm::HS256),
+        "HS384" => Ok(Algorithm::HS384),
+        "HS512" => Ok(Algorithm::HS512),
+        "RS256" => Ok(Algorithm::RS256),
+        "RS384" => Ok(Algorithm::RS384),
+        "RS512" => Ok(Algorithm::RS512),
+        _ => Err(PyValueError::new_err(format!(
+            "Unsupported JWT algorithm: {}, expected one of {:?}",
+            algorithm, ALGORITHMS
+        ))),
+    }
+}
+
+fn is_rsa(algorithm: Algorithm) -> bool {
+    matches!(
+        algorithm,
+        Algorithm::RS256 | Algorithm::RS384 | Algorithm::RS512
+    )
+}
+
+/// Sign `claims` into a JWT, RS* algorithms take a PEM private key as `secret`
+#[pyfunction]
+#[pyo3(signature = (claims, secret, algorithm="HS256", expires_in_secs=None))]
+pub fn create_token(
+    claims: &PyDict,
+    secret: &str,
+    algorithm: &str,
+    expires_in_secs: Option<u64>,
+) -> PyResult<String> {
+    let algorithm = parse_algorithm(algorithm)?;
+    let mut claims: Map<String, Value> = match to_json_value(claims, &JsonOptions::default())? {
+        Value::Object(map) => map,
+        _ => unreachable!("a dict always converts to an object"),
+    };
+
+    if let Some(expires_in) = expires_in_secs {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        claims.insert("iat".to_string(), Value::from(now));
+        claims.insert("exp".to_string(), Value::from(now + expires_in));
+    }
+
+    let key = if is_rsa(algorithm) {
+        EncodingKey::from_rsa_pem(secret.as_bytes())
+            .map_err(|e| PyValueError::new_err(format!("Invalid RSA private key: {}", e)))?
+    } else {
+        EncodingKey::from_secret(secret.as_bytes())
+    };
+    encode(&Header::new(algorithm), &claims, &key)
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to create token: {}", e)))
+}
+
+/// Check the signature and expiry of a JWT and return its claims,
+/// RS* algorithms take a PEM public key as `secret`
+#[pyfunction]
+#[pyo3(signature = (token, secret, algorithm="HS256", verify_exp=true, leeway=0))]
+pub fn verify_token(
+    py: Python,
+    token: &str,
+    secret: &str,
+    algorithm: &str,
+    verify_exp: bool,
+    leeway: u64,
+) -> PyResult<Py<PyDict>> {
+    let algorithm = parse_algorithm(algorithm)?;
+    let key = if is_rsa(algorithm) {
+        DecodingKey::from_rsa_pem(secret.as_bytes())
+            .map_err(|e| PyValueError::new_err(format!("Invalid RSA public key: {}", e)))?
+    } else {
+        DecodingKey::from_secret(secret.as_bytes())
+    };
+
+    let mut validation = Validation::new(algorithm);
+    // `exp` is checked when present, tokens created without expiry stay valid
+    validation.required_spec_claims = HashSet::new();
+    validation.validate_exp = verify_exp;
+    // seconds of clock skew tolerated on `exp` and `nbf`
+    validation.leeway = leeway;
+
+    let data = decode::<Map<String, Value>>(token, &key, &validation).map_err(|e| match e.kind() {
+        ErrorKind::ExpiredSignature => ExpiredSignatureError::new_err("Token has expired"),
+        ErrorKind::InvalidSignature => InvalidSignatureError::new_err("Invalid token signature"),
+        _ => InvalidTokenError::new_err(format!("Invalid token: {}", e)),
+    })?;
+
+    let claims = to_py_object(py, &Value::Object(data.claims))?;
+    Ok(claims.downcast::<PyDict>(py)?.into())
+}
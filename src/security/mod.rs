@@ -0,0 +1,4 @@
+pub mod aes;
+pub mod jwt;
+pub mod password;
+pub mod token;
@@ -0,0 +1,58 @@
+use argon2::{
+    password_hash::{
+        rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+    },
+    Argon2,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+const ALGORITHMS: [&str; 2] = ["bcrypt", "argon2"];
+
+/// Hash a password with bcrypt or argon2, the GIL is released while hashing
+#[pyfunction]
+#[pyo3(signature = (plain, algorithm="argon2", cost=None))]
+pub fn hash_password(py: Python, plain: &str, algorithm: &str, cost: Option<u32>) -> PyResult<String> {
+    match algorithm {
+        "bcrypt" => {
+            let cost = cost.unwrap_or(bcrypt::DEFAULT_COST);
+            py.allow_threads(|| bcrypt::hash(plain, cost))
+                .map_err(|e| PyValueError::new_err(format!("Failed to hash password: {}", e)))
+        }
+        "argon2" => {
+            if cost.is_some() {
+                return Err(PyValueError::new_err("cost only applies to bcrypt"));
+            }
+            let salt = SaltString::generate(&mut OsRng);
+            py.allow_threads(|| {
+                Argon2::default()
+                    .hash_password(plain.as_bytes(), &salt)
+                    .map(|hash| hash.to_string())
+            })
+            .map_err(|e| PyValueError::new_err(format!("Failed to hash password: {}", e)))
+        }
+        _ => Err(PyValueError::new_err(format!(
+            "Unknown password hashing algorithm: {}, expected one of {:?}",
+            algorithm, ALGORITHMS
+        ))),
+    }
+}
+
+/// Check a password against a bcrypt or argon2 hash, the algorithm is read from the hash
+#[pyfunction]
+pub fn verify_password(py: Python, plain: &str, hashed: &str) -> PyResult<bool> {
+    // both crates compare the computed and stored hashes in constant time
+    if hashed.starts_with("$2a$") || hashed.starts_with("$2b$") || hashed.starts_with("$2y$") {
+        py.allow_threads(|| bcrypt::verify(plain, hashed))
+            .map_err(|e| PyValueError::new_err(format!("Invalid bcrypt hash: {}", e)))
+    } else if hashed.starts_with("$argon2") {
+        let hash = PasswordHash::new(hashed)
+            .map_err(|e| PyValueError::new_err(format!("Invalid argon2 hash: {}", e)))?;
+        Ok(py.allow_threads(|| {
+            Argon2::default()
+                .verify_password(plain.as_bytes(), &hash)
+                .is_ok()
+        }))
+    } else {
+        Err(PyValueError::new_err("Unknown password hash format"))
+    }
+}
@@ -0,0 +1,33 @@
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rand::{rngs::OsRng, Rng, RngCore};
+
+const URL_SAFE_ALPHABET: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Random string of `length` characters drawn from `alphabet`, URL safe by default
+#[pyfunction]
+#[pyo3(signature = (length=32, alphabet=None))]
+pub fn generate_token(length: usize, alphabet: Option<&str>) -> PyResult<String> {
+    if length == 0 {
+        return Err(PyValueError::new_err("Token length must be greater than 0"));
+    }
+    let alphabet: Vec<char> = alphabet.unwrap_or(URL_SAFE_ALPHABET).chars().collect();
+    if alphabet.is_empty() {
+        return Err(PyValueError::new_err("Token alphabet must not be empty"));
+    }
+    // gen_range is uniform, so every character is equally likely
+    Ok((0..length)
+        .map(|_| alphabet[OsRng.gen_range(0..alphabet.len())])
+        .collect())
+}
+
+/// Lowercase hex string of `bytes` random bytes
+#[pyfunction]
+#[pyo3(signature = (bytes=32))]
+pub fn generate_hex_token(bytes: usize) -> PyResult<String> {
+    if bytes == 0 {
+        return Err(PyValueError::new_err("Token length must be greater than 0"));
+    }
+    let mut buffer = vec![0u8; bytes];
+    OsRng.fill_bytes(&mut buffer);
+    Ok(buffer.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
@@ -6,37 +6,84 @@ use crate::{
         },
         sql::{config::DatabaseConfig, connection::DatabaseConnection},
     },
-    executor::{execute_http_function, execute_middleware_function, execute_startup_handler},
+    executor::{
+        execute_error_handler, execute_http_function, execute_middleware_function,
+        execute_startup_handler,
+    },
     instants::create_mem_pool,
-    middlewares::base::{Middleware, MiddlewareConfig},
-    router::router::Router,
-    types::{function_info::FunctionInfo, middleware::MiddlewareReturn, request::Request},
+    openapi::{
+        assets::{embedded_asset, EMBEDDED_ASSETS_URL},
+        spec::{build_spec, openapi_handler, OpenApiConfig},
+    },
+    metrics::{metrics_handler, record_ip_rejection, track_requests, MetricsState},
+    static_files::{StaticFiles, DEFAULT_CACHE_CONTROL},
+    http_server::{serve_tcp, HttpOptions},
+    health::{liveness, readiness, set_ready, HealthEndpoints, Readiness, ReadinessCheck},
+    logging::LoggingConfig,
+    telemetry::{init_tracing, shutdown_tracing, RequestSpan},
+    middlewares::{
+        base::{Middleware, MiddlewareConfig},
+        cache::{bypasses_cache, ResponseCache},
+        compression::{
+            CompressionConfig, CompressionLevels, DEFAULT_ALGORITHMS, DEFAULT_CONTENT_TYPES,
+            DEFAULT_MIN_SIZE,
+        },
+        conditional::{
+            conditional_response, ConditionalResponses, Preconditions, DEFAULT_ETAG_MAX_SIZE,
+        },
+        csrf::{CsrfConfig, CsrfMiddleware},
+        ip_filter::{IpFilterConfig, IpFilterMiddleware},
+        proxy::{request_addresses, TrustedProxies},
+        range::{partial_content, FileBody},
+        rate_limit::RateLimiter,
+        security_headers::{security_headers, SecurityHeaders},
+        session::SessionStore,
+    },
+    router::{
+        route::Route,
+        router::{RouteMatch, Router},
+    },
+    types::{
+        body_stream::{register_body_stream, BodyStreamGuard, StreamBody},
+        function_info::FunctionInfo,
+        json::{to_json_value, JsonOptions},
+        middleware::MiddlewareReturn,
+        request::{set_max_form_fields, Request},
+        upload::{set_max_upload_size, set_upload_spool_size},
+    },
     ws::{router::WebsocketRouter, socket::SocketHeld, websocket::websocket_handler},
 };
+#[cfg(unix)]
+use crate::unix_socket::serve_unix;
 use dashmap::DashMap;
-use futures::future::join_all;
-use pyo3::{prelude::*, types::PyDict};
+use futures::{future::join_all, FutureExt};
+use pyo3::{
+    exceptions::{PyKeyboardInterrupt, PyRuntimeError, PyValueError},
+    prelude::*,
+    types::{IntoPyDict, PyDict},
+};
 use std::{
     collections::HashMap,
+    future::IntoFuture,
+    net::SocketAddr,
     sync::{
         atomic::Ordering::{Relaxed, SeqCst},
-        RwLock,
+        Condvar, Mutex as StdMutex, RwLock,
     },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
-use std::{
-    process::exit,
-    sync::{atomic::AtomicBool, Arc},
-};
-use tower::ServiceBuilder;
+use std::sync::{atomic::AtomicBool, Arc};
+use tokio::sync::watch;
+use tower::service_fn;
 
 use axum::{
     body::Body,
     extract::{Request as HttpRequest, WebSocketUpgrade},
-    http::StatusCode,
+    http::{header, StatusCode},
+    middleware,
     response::{IntoResponse, Response as ServerResponse},
-    routing::{any, delete, get, head, options, patch, post, put, trace},
+    routing::{any, get},
     Extension, Router as RouterServer,
 };
 
@@ -44,10 +91,9 @@ use crate::di::DependencyInjection;
 use tower_http::{
     trace::{DefaultOnResponse, TraceLayer},
     LatencyUnit,
-    {compression::CompressionLayer, decompression::RequestDecompressionLayer},
+    decompression::RequestDecompressionLayer,
 };
-use tracing::{debug, Level};
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt};
+use tracing::{debug, error, warn, Level};
 
 static STARTED: AtomicBool = AtomicBool::new(false);
 
@@ -61,11 +107,52 @@ pub struct Server {
     middlewares: Middleware,
     extra_headers: Arc<DashMap<String, String>>,
     auto_compression: bool,
+    compression: CompressionConfig,
     database_config: Option<DatabaseConfig>,
     mem_pool_min_capacity: usize,
     mem_pool_max_capacity: usize,
+    health: Option<HealthEndpoints>,
+    readiness_checks: Vec<ReadinessCheck>,
+    metrics_path: Option<String>,
+    tracing_endpoint: Option<String>,
+    logging: LoggingConfig,
+    http_options: Option<HttpOptions>,
+    sessions: Option<Arc<SessionStore>>,
+    static_dirs: Vec<Arc<StaticFiles>>,
+    csrf: Option<Arc<CsrfMiddleware>>,
+    ip_filter: Option<Arc<IpFilterMiddleware>>,
+    trusted_proxies: Option<Arc<TrustedProxies>>,
+    conditional: Option<Arc<ConditionalResponses>>,
+    response_cache: Option<Arc<ResponseCache>>,
+    debug_headers: bool,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    error_handlers: ErrorHandlers,
+    security_headers: Option<Arc<SecurityHeaders>>,
+    openapi: Option<OpenApiConfig>,
+    docs_assets: bool,
 }
 
+// python handlers rendering the 404 and 405 responses, the plain text defaults are used without them
+#[derive(Clone, Default)]
+struct ErrorHandlers {
+    not_found: Option<FunctionInfo>,
+    method_not_allowed: Option<FunctionInfo>,
+}
+
+impl ErrorHandlers {
+    fn for_status(&self, status: StatusCode) -> Option<&FunctionInfo> {
+        match status {
+            StatusCode::NOT_FOUND => self.not_found.as_ref(),
+            StatusCode::METHOD_NOT_ALLOWED => self.method_not_allowed.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+// present in the request extensions when middleware and handler timings are sent as headers
+#[derive(Clone, Copy)]
+struct DebugHeaders;
+
 #[pymethods]
 impl Server {
     #[new]
@@ -81,9 +168,29 @@ impl Server {
             middlewares,
             extra_headers: Arc::new(DashMap::new()),
             auto_compression: true,
+            compression: CompressionConfig::default(),
             database_config: None,
             mem_pool_min_capacity: 10,
             mem_pool_max_capacity: 100,
+            health: None,
+            readiness_checks: Vec::new(),
+            metrics_path: None,
+            tracing_endpoint: None,
+            logging: LoggingConfig::default(),
+            http_options: None,
+            sessions: None,
+            static_dirs: Vec::new(),
+            csrf: None,
+            ip_filter: None,
+            trusted_proxies: None,
+            conditional: None,
+            response_cache: None,
+            debug_headers: false,
+            rate_limiter: None,
+            error_handlers: ErrorHandlers::default(),
+            security_headers: None,
+            openapi: None,
+            docs_assets: false,
         }
     }
 
@@ -126,10 +233,40 @@ impl Server {
         self.shutdown_handler = Some(Arc::new(handler));
     }
 
+    /// Render the response of requests matching no route
+    pub fn set_404_handler(&mut self, handler: FunctionInfo) {
+        self.error_handlers.not_found = Some(handler);
+    }
+
+    /// Render the response of requests whose path only has routes for other methods
+    pub fn set_405_handler(&mut self, handler: FunctionInfo) {
+        self.error_handlers.method_not_allowed = Some(handler);
+    }
+
     pub fn set_auto_compression(&mut self, enabled: bool) {
         self.auto_compression = enabled;
     }
 
+    /// Compress responses with `algorithms` (gzip, br, deflate, zstd), the first one the client
+    /// accepts wins. `level` is one level for all or a dict by algorithm, None keeps the default of
+    /// each. Only bodies of at least `min_size` bytes whose type matches `content_types` are
+    /// compressed, streams never are. Only applies while auto compression is enabled
+    #[pyo3(signature = (algorithms=None, level=None, min_size=DEFAULT_MIN_SIZE, content_types=None))]
+    pub fn set_compression_config(
+        &mut self,
+        algorithms: Option<Vec<String>>,
+        level: Option<CompressionLevels>,
+        min_size: u64,
+        content_types: Option<Vec<String>>,
+    ) -> PyResult<()> {
+        let algorithms = algorithms
+            .unwrap_or_else(|| DEFAULT_ALGORITHMS.iter().map(|name| name.to_string()).collect());
+        let content_types = content_types
+            .unwrap_or_else(|| DEFAULT_CONTENT_TYPES.iter().map(|name| name.to_string()).collect());
+        self.compression = CompressionConfig::new(&algorithms, level, min_size, &content_types)?;
+        Ok(())
+    }
+
     pub fn set_database_config(&mut self, config: DatabaseConfig) {
         self.database_config = Some(config);
     }
@@ -139,49 +276,425 @@ impl Server {
         self.mem_pool_max_capacity = max_capacity;
     }
 
-    pub fn start(
+    /// Serve the readiness probe alone at `path`, same as `enable_health(None, path)`
+    pub fn set_health_check(&mut self, path: &str) -> PyResult<()> {
+        self.enable_health(None, Some(path))
+    }
+
+    /// Serve liveness and readiness probes natively, None leaves one out. Readiness is 503 until
+    /// startup finished, then reports the database, the redis response cache and the readiness checks
+    #[pyo3(signature = (liveness=Some("/healthz"), readiness=Some("/readyz")))]
+    pub fn enable_health(&mut self, liveness: Option<&str>, readiness: Option<&str>) -> PyResult<()> {
+        let replaced = ["Liveness", "Readiness"];
+        if let Some(liveness) = liveness {
+            self.check_endpoint_path("Liveness", liveness, &replaced)?;
+        }
+        if let Some(readiness) = readiness {
+            self.check_endpoint_path("Readiness", readiness, &replaced)?;
+        }
+        if liveness.is_some() && liveness == readiness {
+            return Err(PyValueError::new_err(format!(
+                "Liveness and readiness paths must differ: {:?}",
+                liveness.unwrap_or_default()
+            )));
+        }
+        self.health = Some(HealthEndpoints {
+            liveness: liveness.map(str::to_string),
+            readiness: readiness.map(str::to_string),
+        });
+        Ok(())
+    }
+
+    /// Poll `check` every `interval` seconds in the background, the readiness probe reads its last result
+    #[pyo3(signature = (name, check, interval=5.0))]
+    pub fn add_readiness_check(&mut self, name: &str, check: PyObject, interval: f64) -> PyResult<()> {
+        let interval = Duration::try_from_secs_f64(interval)
+            .ok()
+            .filter(|interval| !interval.is_zero())
+            .ok_or_else(|| PyValueError::new_err("interval must be a positive number of seconds"))?;
+        self.readiness_checks.retain(|existing| existing.name != name);
+        self.readiness_checks.push(ReadinessCheck {
+            name: name.to_string(),
+            check,
+            interval,
+        });
+        Ok(())
+    }
+
+    pub fn set_metrics_path(&mut self, path: &str) -> PyResult<()> {
+        self.check_endpoint_path("Metrics", path, &["Metrics"])?;
+        self.metrics_path = Some(path.to_string());
+        Ok(())
+    }
+
+    /// Serve the Prometheus metrics at `path`, scrapes are not counted in them
+    #[pyo3(signature = (path="/metrics"))]
+    pub fn enable_metrics(&mut self, path: &str) -> PyResult<()> {
+        self.set_metrics_path(path)
+    }
+
+    /// Serve the Swagger UI and ReDoc bundles compiled into the extension, for `assets_mode="embedded"`
+    pub fn enable_docs_assets(&mut self) {
+        self.docs_assets = true;
+    }
+
+    /// Serve the OpenAPI document of the routes at `path`, built from the router when the server starts.
+    /// `base_schema` holds the `info`, `servers` and `components` the document starts from
+    #[pyo3(signature = (path="/openapi.json", title="Hypern", version="0.1.0", base_schema=None))]
+    pub fn enable_openapi(
+        &mut self,
+        path: &str,
+        title: &str,
+        version: &str,
+        base_schema: Option<&PyDict>,
+    ) -> PyResult<()> {
+        self.check_endpoint_path("OpenAPI", path, &["OpenAPI"])?;
+        let base_schema = match base_schema {
+            Some(base_schema) => match to_json_value(base_schema, &JsonOptions::default())? {
+                serde_json::Value::Object(base_schema) => base_schema,
+                _ => serde_json::Map::new(),
+            },
+            None => serde_json::Map::new(),
+        };
+        self.openapi = Some(OpenApiConfig {
+            path: path.to_string(),
+            title: title.to_string(),
+            version: version.to_string(),
+            base_schema,
+        });
+        Ok(())
+    }
+
+    /// Export OpenTelemetry spans for every request to an OTLP collector
+    pub fn set_tracing_endpoint(&mut self, url: &str) {
+        self.tracing_endpoint = Some(url.to_string());
+    }
+
+    /// Log at `level` (an EnvFilter directive, RUST_LOG wins when set), as JSON lines with `json`.
+    /// `access_log` emits one line per response
+    #[pyo3(signature = (level="info", json=false, access_log=true))]
+    pub fn set_logging(&mut self, level: &str, json: bool, access_log: bool) -> PyResult<()> {
+        self.logging = LoggingConfig::new(level, json, access_log)?;
+        Ok(())
+    }
+
+    /// Tune connection handling. Defaults: 30s keep-alive (0 disables it), 64 KiB of headers
+    /// (larger requests get 431, at least 8192), 200 concurrent HTTP/2 streams, HTTP/2 enabled,
+    /// TCP_NODELAY off and a listen backlog of 1024
+    #[pyo3(signature = (keep_alive_secs=30, max_header_bytes=65536, h2_max_concurrent_streams=200, h2_enabled=true, tcp_nodelay=false, backlog=1024))]
+    pub fn set_http_options(
+        &mut self,
+        keep_alive_secs: u64,
+        max_header_bytes: usize,
+        h2_max_concurrent_streams: u32,
+        h2_enabled: bool,
+        tcp_nodelay: bool,
+        backlog: i32,
+    ) -> PyResult<()> {
+        self.http_options = Some(HttpOptions::new(
+            keep_alive_secs,
+            max_header_bytes,
+            h2_max_concurrent_streams,
+            h2_enabled,
+            tcp_nodelay,
+            backlog,
+        )?);
+        Ok(())
+    }
+
+    /// Reject unsafe requests whose CSRF token does not match the signed cookie
+    pub fn set_csrf_config(&mut self, config: CsrfConfig) {
+        self.csrf = Some(Arc::new(CsrfMiddleware::new(config)));
+    }
+
+    /// Reject requests by client address before any python code runs
+    /// Takes an `IpFilterConfig`, or builds one from `allow`, `deny` and `status`
+    #[pyo3(signature = (config=None, allow=None, deny=None, status=403))]
+    pub fn set_ip_filter(
+        &mut self,
+        config: Option<IpFilterConfig>,
+        allow: Option<Vec<String>>,
+        deny: Option<Vec<String>>,
+        status: u16,
+    ) -> PyResult<()> {
+        let config = match config {
+            Some(config) => config,
+            None => IpFilterConfig::new(
+                Vec::new(),
+                "allowlist",
+                allow.unwrap_or_default(),
+                deny.unwrap_or_default(),
+                status,
+            )?,
+        };
+        self.ip_filter = Some(Arc::new(IpFilterMiddleware::from_config(&config)?));
+        Ok(())
+    }
+
+    /// Take the client address from X-Forwarded-For or Forwarded when the peer is in one of `cidrs`
+    pub fn set_trusted_proxies(&mut self, cidrs: Vec<String>) -> PyResult<()> {
+        self.trusted_proxies = Some(Arc::new(TrustedProxies::new(&cidrs)?));
+        Ok(())
+    }
+
+    /// Add an ETag to responses up to `max_size` bytes and answer 304 to clients that already have them
+    #[pyo3(signature = (max_size=DEFAULT_ETAG_MAX_SIZE))]
+    pub fn set_auto_etag(&mut self, max_size: usize) {
+        self.conditional = Some(Arc::new(ConditionalResponses {
+            etag_max_size: max_size,
+        }));
+    }
+
+    /// Store the responses of routes configured with `Route.cache` in `cache`
+    pub fn set_response_cache(&mut self, cache: ResponseCache) {
+        self.response_cache = Some(Arc::new(cache));
+    }
+
+    /// Answer 429 to clients sending more than `limit` requests in `window_secs`, counted in
+    /// process or in redis when `backend` is a redis url
+    #[pyo3(signature = (limit, window_secs, key="ip", backend=None, exempt_paths=None))]
+    pub fn enable_rate_limit(
+        &mut self,
+        limit: u64,
+        window_secs: u64,
+        key: &str,
+        backend: Option<&str>,
+        exempt_paths: Option<Vec<String>>,
+    ) -> PyResult<()> {
+        let limiter = RateLimiter::new(
+            limit,
+            window_secs,
+            key,
+            backend,
+            exempt_paths.unwrap_or_default(),
+        )?;
+        self.rate_limiter = Some(Arc::new(limiter));
+        Ok(())
+    }
+
+    /// Add HSTS, Content-Security-Policy, X-Frame-Options, X-Content-Type-Options and Referrer-Policy
+    /// to every response, None leaves a header out. HSTS is only sent on https requests unless `force_hsts`
+    #[pyo3(signature = (hsts_max_age=Some(31_536_000), csp=None, frame_options=Some("DENY"), nosniff=true, referrer_policy=Some("strict-origin-when-cross-origin"), force_hsts=false))]
+    pub fn enable_security_headers(
         &mut self,
+        hsts_max_age: Option<u64>,
+        csp: Option<&str>,
+        frame_options: Option<&str>,
+        nosniff: bool,
+        referrer_policy: Option<&str>,
+        force_hsts: bool,
+    ) -> PyResult<()> {
+        let headers = SecurityHeaders::new(
+            hsts_max_age,
+            csp,
+            frame_options,
+            nosniff,
+            referrer_policy,
+            force_hsts,
+        )?;
+        self.security_headers = Some(Arc::new(headers));
+        Ok(())
+    }
+
+    /// Add the time taken by each middleware and by the handler to responses as headers
+    pub fn set_debug_headers(&mut self, enabled: bool) {
+        self.debug_headers = enabled;
+    }
+
+    // limit on urlencoded form fields parsed per request
+    pub fn set_max_form_fields(&self, max_fields: usize) {
+        set_max_form_fields(max_fields);
+    }
+
+    // uploaded files larger than this are written to a temp file instead of memory
+    pub fn set_upload_spool_size(&self, max_size: usize) {
+        set_upload_spool_size(max_size);
+    }
+
+    // multipart bodies larger than this stop being read, the files received so far are kept
+    pub fn set_max_upload_size(&self, max_size: usize) {
+        set_max_upload_size(max_size);
+    }
+
+    /// Serve the files of `directory` under `url_prefix`, ahead of python routes and middlewares
+    #[pyo3(signature = (url_prefix, directory, cache_control=DEFAULT_CACHE_CONTROL))]
+    pub fn set_static_dir(
+        &mut self,
+        url_prefix: &str,
+        directory: &str,
+        cache_control: &str,
+    ) -> PyResult<()> {
+        let url_prefix = url_prefix.trim_end_matches('/');
+        if !url_prefix.starts_with('/') {
+            return Err(PyValueError::new_err(format!(
+                "Static url prefix must start with '/' and not be the root: {:?}",
+                url_prefix
+            )));
+        }
+        let directory = std::path::PathBuf::from(directory);
+        if !directory.is_dir() {
+            return Err(PyValueError::new_err(format!(
+                "Static directory does not exist: {}",
+                directory.display()
+            )));
+        }
+        self.static_dirs.push(Arc::new(StaticFiles {
+            url_prefix: url_prefix.to_string(),
+            directory,
+            cache_control: cache_control.to_string(),
+        }));
+        Ok(())
+    }
+
+    /// Load `request.session` from a signed cookie before the handler and save it after.
+    /// `backend` is "cookie", "redis" or a `hypern.caching` backend object, None is "cookie"
+    #[pyo3(signature = (secret_key, cookie_name="session", max_age=86400, same_site="lax", backend=None, old_keys=None, secure=false, redis_url="redis://localhost:6379"))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn enable_sessions(
+        &mut self,
+        secret_key: String,
+        cookie_name: &str,
+        max_age: u64,
+        same_site: &str,
+        backend: Option<&PyAny>,
+        old_keys: Option<Vec<String>>,
+        secure: bool,
+        redis_url: &str,
+    ) -> PyResult<()> {
+        let store = SessionStore::new(
+            secret_key,
+            old_keys.unwrap_or_default(),
+            cookie_name.to_string(),
+            max_age,
+            same_site.to_string(),
+            secure,
+            backend,
+            redis_url,
+        )?;
+        self.sessions = Some(Arc::new(store));
+        Ok(())
+    }
+
+    /// Register a route on the running server, it is served from the next request
+    pub fn add_route(&self, route: Route) -> PyResult<()> {
+        self.router.write().unwrap().add_route(route)
+    }
+
+    /// Remove a route from the running server
+    pub fn remove_route(&self, path: &str, method: &str) -> PyResult<bool> {
+        self.router.write().unwrap().remove_route(path, method)
+    }
+
+    /// Serve until interrupted, then shut down gracefully
+    pub fn start(
+        &self,
         py: Python,
         socket: &PyCell<SocketHeld>,
         workers: usize,
         max_blocking_threads: usize,
     ) -> PyResult<()> {
-        tracing_subscriber::registry()
-            .with(
-                tracing_subscriber::EnvFilter::try_from_default_env()
-                    .unwrap_or_else(|_| "debug".into()),
-            )
-            .with(fmt::layer().with_target(false).with_level(true))
-            .init();
+        let handle = self.serve(py, socket, workers, max_blocking_threads)?;
+        match handle.wait(py, None) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                handle.stop(py, true)?;
+                match e.is_instance_of::<PyKeyboardInterrupt>(py) {
+                    true => Ok(()),
+                    false => Err(e),
+                }
+            }
+        }
+    }
+
+    /// Start serving in the background and return a handle to stop it.
+    /// Takes &self so add_route/remove_route can be called while the server runs
+    pub fn serve(
+        &self,
+        py: Python,
+        socket: &PyCell<SocketHeld>,
+        workers: usize,
+        max_blocking_threads: usize,
+    ) -> PyResult<ServerHandle> {
+        self.logging.init();
 
         if STARTED
             .compare_exchange(false, true, SeqCst, Relaxed)
             .is_err()
         {
-            return Ok(());
+            return Err(PyRuntimeError::new_err("A server is already running in this process"));
         }
+        let stopped = Arc::new((StdMutex::new(false), Condvar::new()));
+        // released if a step below fails, or with the server thread, even when it panics
+        let running = Running {
+            stopped: stopped.clone(),
+        };
 
         let raw_socket = socket.try_borrow_mut()?.get_socket();
+        let unix_path = socket.try_borrow()?.unix_path.clone();
+        // abstract sockets have no file to clean up
+        let socket_file = unix_path.clone().filter(|path| !path.starts_with('@'));
 
         let router = self.router.clone();
         let websocket_router = self.websocket_router.clone();
 
+        // async handlers run on this loop, in a thread of its own so the caller is not blocked
         let asyncio = py.import("asyncio")?;
-        let event_loop = asyncio.call_method0("get_event_loop")?;
+        let event_loop = asyncio.call_method0("new_event_loop")?;
+        let kwargs = [
+            ("target", event_loop.getattr("run_forever")?),
+            ("daemon", true.into_py(py).into_ref(py)),
+        ]
+        .into_py_dict(py);
+        py.import("threading")?
+            .getattr("Thread")?
+            .call((), Some(kwargs))?
+            .call_method0("start")?;
+        let event_loop: PyObject = event_loop.into();
 
         let startup_handler = self.startup_handler.clone();
         let shutdown_handler = self.shutdown_handler.clone();
 
-        let task_locals = pyo3_asyncio::TaskLocals::new(event_loop).copy_context(py)?;
+        let task_locals = pyo3_asyncio::TaskLocals::new(event_loop.as_ref(py)).copy_context(py)?;
         let task_locals_copy = task_locals.clone();
 
+        let (stop_tx, stop_rx) = watch::channel(None);
+        let handle = ServerHandle {
+            stop_tx,
+            stopped: stopped.clone(),
+        };
+
         let injected = self.injected.clone();
         let copy_middlewares = self.middlewares.clone();
         let extra_headers = self.extra_headers.clone();
         let auto_compression = self.auto_compression;
+        let compression = self.compression.clone();
         let database_config = self.database_config.clone();
         let mem_pool_min_capacity = self.mem_pool_min_capacity;
         let mem_pool_max_capacity = self.mem_pool_max_capacity;
+        let health = self.health.clone();
+        let readiness_checks = self.readiness_checks.clone();
+        let metrics_path = self.metrics_path.clone();
+        let tracing_endpoint = self.tracing_endpoint.clone();
+        let access_log = self.logging.access_log;
+        let http_options = self.http_options.clone();
+        let sessions = self.sessions.clone();
+        let static_dirs = self.static_dirs.clone();
+        let docs_assets = self.docs_assets;
+        let csrf = self.csrf.clone();
+        let ip_filter = self.ip_filter.clone();
+        let trusted_proxies = self.trusted_proxies.clone();
+        let conditional = self.conditional.clone();
+        let response_cache = self.response_cache.clone();
+        let debug_headers = self.debug_headers;
+        let rate_limiter = self.rate_limiter.clone();
+        let error_handlers = Arc::new(self.error_handlers.clone());
+        let security_headers_config = self.security_headers.clone();
+        // serialized once, routes added at runtime are not listed
+        let openapi = self.openapi.as_ref().map(|config| {
+            let spec = build_spec(py, &self.router.read().unwrap(), config);
+            let spec = serde_json::to_vec(&spec).unwrap_or_default();
+            (config.path.clone(), axum::body::Bytes::from(spec))
+        });
 
         thread::spawn(move || {
             let rt = tokio::runtime::Builder::new_multi_thread()
@@ -202,50 +715,83 @@ impl Server {
             rt.block_on(async move {
                 create_mem_pool(mem_pool_min_capacity, mem_pool_max_capacity);
 
-                let _ = execute_startup_handler(startup_handler, &task_locals_copy).await;
+                if let Some(endpoint) = tracing_endpoint {
+                    if let Err(e) = init_tracing(&endpoint) {
+                        error!("Failed to set up tracing exporter {}: {}", endpoint, e);
+                    }
+                }
+
+                // the app is still served, but the readiness probe keeps reporting 503
+                let started = match execute_startup_handler(startup_handler, &task_locals_copy).await {
+                    Ok(_) => true,
+                    Err(e) => {
+                        error!("Startup handler failed: {}", e);
+                        false
+                    }
+                };
 
                 let mut app = RouterServer::new();
 
-                // handle logic for each route with pyo3
-                for route in router.read().unwrap().iter() {
-                    let task_locals_copy = task_locals_copy.clone();
-                    let route_copy = route.clone();
-                    let function = route_copy.function.clone();
-
-                    let copy_middlewares_clone = copy_middlewares.clone();
-                    let extra_headers = extra_headers.as_ref().clone();
-                    let handler = move |req| {
-                        mapping_method(
-                            req,
-                            function,
-                            task_locals_copy.clone(),
-                            copy_middlewares_clone.clone(),
-                            extra_headers.clone(),
-                        )
-                    };
+                let metrics_state = metrics_path.clone().map(|scrape_path| MetricsState {
+                    router: router.clone(),
+                    scrape_path,
+                });
 
-                    app = match route.method.as_str() {
-                        "GET" => app.route(&route.path, get(handler)),
-                        "POST" => app.route(&route.path, post(handler)),
-                        "PUT" => app.route(&route.path, put(handler)),
-                        "DELETE" => app.route(&route.path, delete(handler)),
-                        "PATCH" => app.route(&route.path, patch(handler)),
-                        "HEAD" => app.route(&route.path, head(handler)),
-                        "OPTIONS" => app.route(&route.path, options(handler)),
-                        "TRACE" => app.route(&route.path, trace(handler)),
-                        // Handle any custom methods using the any() method
-                        _ => app.route(&route.path, any(handler)),
-                    };
-                }
+                // every http route goes through one dispatcher that reads the shared router,
+                // so routes added or removed after start take effect on the next request
+                let dispatcher = move |req| {
+                    dispatch(
+                        req,
+                        router.clone(),
+                        task_locals_copy.clone(),
+                        copy_middlewares.clone(),
+                        extra_headers.as_ref().clone(),
+                    )
+                };
 
                 // handle logic for each websocket route with pyo3
                 for ws_route in websocket_router.iter() {
                     let ws_route_copy = ws_route.clone();
                     let handler = move |ws: WebSocketUpgrade| {
-                        websocket_handler(ws_route_copy.handler.clone(), ws)
+                        websocket_handler(ws_route_copy.clone(), ws)
                     };
                     app = app.route(&ws_route.path, any(handler));
                 }
+                app = app.fallback(dispatcher);
+
+                // health probes are served natively, without going through python middlewares
+                if let Some(health) = health {
+                    if let Some(path) = health.liveness {
+                        app = app.route(&path, get(liveness));
+                    }
+                    if let Some(path) = health.readiness {
+                        let state = Readiness::new(readiness_checks, response_cache.clone());
+                        state.spawn_checks();
+                        app = app.route(&path, get(move || readiness(state.clone())));
+                    }
+                }
+
+                // static files skip python middlewares and handlers entirely
+                for static_files in static_dirs {
+                    let url_prefix = static_files.url_prefix.clone();
+                    app = app.nest_service(
+                        &url_prefix,
+                        service_fn(move |req| static_files.clone().serve(req)),
+                    );
+                }
+
+                if docs_assets {
+                    app = app.route(&format!("{}/:file", EMBEDDED_ASSETS_URL), get(embedded_asset));
+                }
+
+                // served natively as well
+                if let Some(path) = metrics_path {
+                    app = app.route(&path, get(metrics_handler));
+                }
+
+                if let Some((path, spec)) = openapi {
+                    app = app.route(&path, get(move || openapi_handler(spec.clone())));
+                }
 
                 match database_config {
                     Some(config) => {
@@ -255,65 +801,370 @@ impl Server {
                     None => {}
                 };
 
+                // inside the extension layers so the trusted proxies tell https requests apart,
+                // and around every route so static files and rejections get the headers too
+                if let Some(config) = security_headers_config {
+                    app = app.layer(middleware::from_fn_with_state(config, security_headers));
+                }
                 app = app.layer(Extension(injected));
-                app = app.layer(
-                    TraceLayer::new_for_http().on_response(
-                        DefaultOnResponse::new()
-                            .level(Level::INFO)
-                            .latency_unit(LatencyUnit::Millis),
-                    ),
-                );
-                if auto_compression {
-                    // Add compression and decompression layers
+                if let Some(sessions) = sessions {
+                    app = app.layer(Extension(sessions));
+                }
+                if let Some(csrf) = csrf {
+                    app = app.layer(Extension(csrf));
+                }
+                if let Some(ip_filter) = ip_filter {
+                    app = app.layer(Extension(ip_filter));
+                }
+                if let Some(trusted_proxies) = trusted_proxies {
+                    app = app.layer(Extension(trusted_proxies));
+                }
+                if let Some(conditional) = conditional {
+                    app = app.layer(Extension(conditional));
+                }
+                if let Some(response_cache) = response_cache {
+                    app = app.layer(Extension(response_cache));
+                }
+                if debug_headers {
+                    app = app.layer(Extension(DebugHeaders));
+                }
+                if let Some(rate_limiter) = rate_limiter {
+                    app = app.layer(Extension(rate_limiter));
+                }
+                app = app.layer(Extension(error_handlers));
+                if let Some(state) = metrics_state {
+                    app = app.layer(middleware::from_fn_with_state(state, track_requests));
+                }
+                if access_log {
                     app = app.layer(
-                        ServiceBuilder::new()
-                            .layer(RequestDecompressionLayer::new())
-                            .layer(CompressionLayer::new()),
-                    )
+                        TraceLayer::new_for_http().on_response(
+                            DefaultOnResponse::new()
+                                .level(Level::INFO)
+                                .latency_unit(LatencyUnit::Millis),
+                        ),
+                    );
+                }
+                if auto_compression {
+                    // Add compression and decompression layers, decompression outermost
+                    app = compression.apply(app);
+                    app = app.layer(RequestDecompressionLayer::new());
                 }
+                set_ready(started);
                 debug!("Application started");
-                // run our app with hyper, listening globally on port 3000
-                let listener = tokio::net::TcpListener::from_std(raw_socket.into()).unwrap();
-                axum::serve(listener, app).await.unwrap();
+                // Some(true) waits for in-flight requests, Some(false) drops them
+                let mut graceful_rx = stop_rx.clone();
+                let mut forced_rx = stop_rx;
+                let graceful_stop = async move {
+                    let _ = graceful_rx.wait_for(|stop| stop.is_some()).await;
+                };
+                // the socket is already listening, listening again only changes the backlog
+                if let Some(options) = &http_options {
+                    if let Err(e) = raw_socket.listen(options.backlog) {
+                        warn!("Failed to set the listen backlog: {}", e);
+                    }
+                }
+                // connect info gives request.remote_addr its value
+                let server = match (unix_path, http_options) {
+                    #[cfg(unix)]
+                    (Some(_), options) => {
+                        raw_socket.set_nonblocking(true).unwrap();
+                        let listener = tokio::net::UnixListener::from_std(raw_socket.into()).unwrap();
+                        serve_unix(listener, app, options.unwrap_or_default(), graceful_stop).boxed()
+                    }
+                    (_, Some(options)) => {
+                        raw_socket.set_nonblocking(true).unwrap();
+                        let listener = tokio::net::TcpListener::from_std(raw_socket.into()).unwrap();
+                        serve_tcp(listener, app, options, graceful_stop).boxed()
+                    }
+                    _ => {
+                        let listener = tokio::net::TcpListener::from_std(raw_socket.into()).unwrap();
+                        axum::serve(
+                            listener,
+                            app.into_make_service_with_connect_info::<SocketAddr>(),
+                        )
+                        .with_graceful_shutdown(graceful_stop)
+                        .into_future()
+                        .boxed()
+                    }
+                };
+                tokio::select! {
+                    result = server => {
+                        if let Err(e) = result {
+                            error!("Server error: {}", e);
+                        }
+                    }
+                    _ = forced_rx.wait_for(|stop| *stop == Some(false)) => {}
+                }
+
+                // the python loop is still running, so async shutdown handlers can be awaited
+                if let Err(e) = execute_startup_handler(shutdown_handler, &task_locals).await {
+                    error!("Shutdown handler failed: {}", e);
+                }
             });
-        });
+            rt.shutdown_timeout(Duration::from_secs(5));
 
-        let event_loop = (*event_loop).call_method0("run_forever");
-        if event_loop.is_err() {
-            if let Some(function) = shutdown_handler {
-                if function.is_async {
-                    pyo3_asyncio::tokio::run_until_complete(
-                        task_locals.event_loop(py),
-                        pyo3_asyncio::into_future_with_locals(
-                            &task_locals.clone(),
-                            function.handler.as_ref(py).call0()?,
-                        )
-                        .unwrap(),
-                    )
-                    .unwrap();
-                } else {
-                    Python::with_gil(|py| function.handler.call0(py))?;
+            if let Some(path) = socket_file {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    warn!("Failed to remove socket file {}: {}", path, e);
                 }
             }
 
-            exit(0);
+            shutdown_tracing();
+            set_ready(false);
+            Python::with_gil(|py| {
+                let event_loop = event_loop.as_ref(py);
+                if let Err(e) = event_loop
+                    .getattr("stop")
+                    .and_then(|stop| event_loop.call_method1("call_soon_threadsafe", (stop,)))
+                {
+                    error!("Failed to stop the event loop: {}", e);
+                }
+            });
+            drop(running);
+        });
+
+        Ok(handle)
+    }
+}
+
+impl Server {
+    // Native endpoints are routed by axum, which panics at `serve()` on a path without a leading
+    // slash or on two endpoints at one path. `replaced` are the endpoints the caller configures
+    fn check_endpoint_path(&self, endpoint: &str, path: &str, replaced: &[&str]) -> PyResult<()> {
+        if !path.starts_with('/') {
+            return Err(PyValueError::new_err(format!(
+                "{} path must start with '/': {:?}",
+                endpoint, path
+            )));
+        }
+        let health = self.health.as_ref();
+        let configured = [
+            ("Liveness", health.and_then(|health| health.liveness.as_deref())),
+            ("Readiness", health.and_then(|health| health.readiness.as_deref())),
+            ("Metrics", self.metrics_path.as_deref()),
+            ("OpenAPI", self.openapi.as_ref().map(|config| config.path.as_str())),
+        ];
+        match configured
+            .into_iter()
+            .find(|(name, existing)| !replaced.contains(name) && *existing == Some(path))
+        {
+            Some((name, _)) => Err(PyValueError::new_err(format!(
+                "{} path {:?} is already served by the {} endpoint",
+                endpoint, path, name
+            ))),
+            None => Ok(()),
         }
+    }
+}
+
+// Marks the process as serving while alive, dropping it lets another server start and wakes
+// whoever waits on the handle
+struct Running {
+    stopped: Arc<(StdMutex<bool>, Condvar)>,
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        STARTED.store(false, SeqCst);
+        let (lock, condvar) = &*self.stopped;
+        // a panic while the lock was held must not keep the waiters asleep
+        *lock.lock().unwrap_or_else(|e| e.into_inner()) = true;
+        condvar.notify_all();
+    }
+}
+
+/// Returned by `Server.serve`, controls a server running in the background
+#[pyclass]
+pub struct ServerHandle {
+    stop_tx: watch::Sender<Option<bool>>,
+    stopped: Arc<(StdMutex<bool>, Condvar)>,
+}
+
+#[pymethods]
+impl ServerHandle {
+    /// Stop accepting connections, run the shutdown handler and return once stopped.
+    /// Graceful waits for in-flight requests to finish
+    #[pyo3(signature = (graceful=true))]
+    pub fn stop(&self, py: Python, graceful: bool) -> PyResult<()> {
+        self.stop_tx.send_modify(|stop| {
+            // a forced stop overrides a graceful one still in progress
+            if *stop != Some(false) {
+                *stop = Some(graceful);
+            }
+        });
+        self.wait(py, None)?;
         Ok(())
     }
+
+    pub fn is_running(&self) -> bool {
+        !*self.stopped.0.lock().unwrap()
+    }
+
+    /// Block until the server stopped, at most `timeout` seconds. Returns whether it stopped
+    #[pyo3(signature = (timeout=None))]
+    pub fn wait(&self, py: Python, timeout: Option<f64>) -> PyResult<bool> {
+        let deadline = match timeout {
+            Some(timeout) => Some(
+                Instant::now()
+                    + Duration::try_from_secs_f64(timeout)
+                        .map_err(|_| PyValueError::new_err("timeout must be a positive number of seconds"))?,
+            ),
+            None => None,
+        };
+        let (lock, condvar) = &*self.stopped;
+        loop {
+            // woken up regularly so Ctrl+C reaches python
+            let stopped = py.allow_threads(|| {
+                let guard = lock.lock().unwrap();
+                let (guard, _) = condvar
+                    .wait_timeout_while(guard, Duration::from_millis(100), |stopped| !*stopped)
+                    .unwrap();
+                *guard
+            });
+            if stopped {
+                return Ok(true);
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return Ok(false);
+            }
+            py.check_signals()?;
+        }
+    }
 }
 
 async fn execute_request(
     req: HttpRequest<Body>,
     function: FunctionInfo,
+    route_path: String,
+    path_params: HashMap<String, String>,
     middlewares: Middleware,
     extra_headers: DashMap<String, String>,
+) -> ServerResponse {
+    let span = RequestSpan::start(req.headers(), req.method().as_str(), &route_path);
+    let response = handle_request(
+        req,
+        function,
+        path_params,
+        middlewares,
+        extra_headers,
+        &span,
+    )
+    .await;
+    span.finish(response.status().as_u16());
+    response
+}
+
+// Run a middleware within its `timeout_ms`, None when it took longer
+async fn run_middleware<T>(
+    input: &T,
+    function: &FunctionInfo,
+    config: &MiddlewareConfig,
+) -> Option<PyResult<MiddlewareReturn>>
+where
+    T: for<'a> FromPyObject<'a> + ToPyObject,
+{
+    let Some(timeout_ms) = config.timeout_ms else {
+        return Some(execute_middleware_function(input, function).await);
+    };
+    let started = Instant::now();
+    let result = tokio::time::timeout(
+        Duration::from_millis(timeout_ms),
+        execute_middleware_function(input, function),
+    )
+    .await;
+    match result {
+        Ok(result) => Some(result),
+        Err(_) => {
+            warn!(
+                "Middleware {} timed out after {} ms",
+                Python::with_gil(|py| function.name(py)),
+                started.elapsed().as_millis()
+            );
+            None
+        }
+    }
+}
+
+fn format_millis(elapsed: Duration) -> String {
+    format!("{:.3}", elapsed.as_secs_f64() * 1000.0)
+}
+
+fn record_middleware_time(extra_headers: &DashMap<String, String>, n: usize, elapsed: Duration) {
+    extra_headers.insert(format!("x-middleware-{}-time-ms", n), format_millis(elapsed));
+}
+
+fn middleware_timeout_response() -> ServerResponse {
+    ServerResponse::builder()
+        .status(StatusCode::SERVICE_UNAVAILABLE)
+        .body(Body::from("Service Unavailable"))
+        .unwrap()
+}
+
+async fn handle_request(
+    req: HttpRequest<Body>,
+    function: FunctionInfo,
+    path_params: HashMap<String, String>,
+    middlewares: Middleware,
+    extra_headers: DashMap<String, String>,
+    span: &RequestSpan,
 ) -> ServerResponse {
     let response_builder = ServerResponse::builder();
 
     let deps = req.extensions().get::<DependencyInjection>().cloned();
+    let sessions = req.extensions().get::<Arc<SessionStore>>().cloned();
+    let csrf = req.extensions().get::<Arc<CsrfMiddleware>>().cloned();
+    let debug_headers = req.extensions().get::<DebugHeaders>().is_some();
     let database = get_sql_connect();
+    // numbers the middlewares in the order they ran, for the debug headers
+    let mut middleware_count = 0;
+
+    let mut request = if req.extensions().get::<StreamBody>().is_some() {
+        // read by the handler itself, chunk by chunk
+        let (parts, body) = req.into_parts();
+        let request = Request::from_head(&HttpRequest::from_parts(parts, Body::empty()), path_params);
+        register_body_stream(&request.context_id, body);
+        request
+    } else {
+        Request::from_request(req, path_params).await
+    };
+    let _body_stream = BodyStreamGuard(request.context_id.clone());
+    // a fresh dict rather than a pooled one, handlers may keep a reference after the request
+    request.state = Some(Python::with_gil(|py| PyDict::new(py).into()));
 
-    let mut request = Request::from_request(req).await;
+    // checked before anything else runs for the request
+    let csrf_token = match &csrf {
+        Some(csrf) => match csrf.before(&request) {
+            Ok((token, issued)) => {
+                request.csrf_token = Some(token.clone());
+                issued.then_some(token)
+            }
+            Err(reason) => {
+                return ServerResponse::builder()
+                    .status(StatusCode::FORBIDDEN)
+                    .body(Body::from(reason))
+                    .unwrap();
+            }
+        },
+        None => None,
+    };
+
+    // load request.session before any middleware runs
+    let session = match &sessions {
+        Some(store) => {
+            let session = store.load(&request.headers).await;
+            match Python::with_gil(|py| store.to_dict(py, &session)) {
+                Ok(dict) => {
+                    request.session = Some(dict);
+                    Some(session)
+                }
+                Err(e) => {
+                    error!("Failed to load session: {}", e);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
 
     // inject session db to global
     match database.clone() {
@@ -328,51 +1179,97 @@ async fn execute_request(
         middlewares
             .get_before_hooks()
             .into_iter()
-            .filter(|(_, config)| !config.is_conditional)
-            .map(|(middleware, _)| {
+            .filter(|(_, config)| !config.is_conditional && config.applies_to(&request.path))
+            .map(|(middleware, config)| {
                 let request = request.clone();
                 let middleware = middleware.clone();
-                async move { execute_middleware_function(&request, &middleware).await }
+                let middleware_span = span.child("before_request");
+                async move {
+                    let started = Instant::now();
+                    let result = run_middleware(&request, &middleware, &config).await;
+                    drop(middleware_span);
+                    (result, started.elapsed())
+                }
             }),
     )
     .await;
 
+    if debug_headers {
+        for (_, elapsed) in &before_results {
+            middleware_count += 1;
+            record_middleware_time(&extra_headers, middleware_count, *elapsed);
+        }
+    }
+
     // Process results and handle any errors
-    for result in before_results {
+    for (result, _) in before_results {
         match result {
-            Ok(MiddlewareReturn::Request(r)) => request = r,
-            Ok(MiddlewareReturn::Response(r)) => return r.to_axum_response(extra_headers),
-            Err(e) => {
+            Some(Ok(MiddlewareReturn::Request(r))) => request = r,
+            Some(Ok(MiddlewareReturn::Response(r))) => return r.to_axum_response(extra_headers),
+            Some(Err(e)) => {
                 return response_builder
                     .body(Body::from(format!("Error: {}", e)))
                     .unwrap();
             }
+            // timed out, the request goes on unmodified
+            None => {}
         }
     }
 
     // Execute conditional middlewares sequentially
     for (middleware, config) in middlewares.get_before_hooks() {
-        if config.is_conditional {
-            match execute_middleware_function(&request, &middleware).await {
-                Ok(MiddlewareReturn::Request(r)) => request = r,
-                Ok(MiddlewareReturn::Response(r)) => return r.to_axum_response(extra_headers),
-                Err(e) => {
+        if config.is_conditional && config.applies_to(&request.path) {
+            let _middleware_span = span.child("before_request");
+            let started = Instant::now();
+            let result = run_middleware(&request, &middleware, &config).await;
+            if debug_headers {
+                middleware_count += 1;
+                record_middleware_time(&extra_headers, middleware_count, started.elapsed());
+            }
+            match result {
+                Some(Ok(MiddlewareReturn::Request(r))) => request = r,
+                Some(Ok(MiddlewareReturn::Response(r))) => return r.to_axum_response(extra_headers),
+                Some(Err(e)) => {
                     return ServerResponse::builder()
                         .status(StatusCode::INTERNAL_SERVER_ERROR)
                         .body(Body::from(format!("Error: {}", e)))
                         .unwrap();
                 }
+                None => return middleware_timeout_response(),
             }
         }
     }
 
     // Execute the main handler
-    let mut response = execute_http_function(&request, &function, deps)
-        .await
-        .unwrap();
+    let handler_span = span.child("handler");
+    let started = Instant::now();
+    let result = execute_http_function(&request, &function, deps).await;
+    drop(handler_span);
+    if debug_headers {
+        extra_headers.insert("x-handler-time-ms".to_string(), format_millis(started.elapsed()));
+    }
+    let mut response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            error!("Unhandled exception in handler for {}: {}", request.path, e);
+
+            // roll back whatever the handler wrote before it raised
+            if database.is_some() {
+                if let Some(mut tx) = get_session_database(&request.context_id) {
+                    tx.rollback_internal().await;
+                }
+                remove_sql_session(&request.context_id);
+            }
+            return ServerResponse::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::from("Internal Server Error"))
+                .unwrap();
+        }
+    };
 
     // mapping context id
     response.context_id = request.context_id;
+    response.state = request.state.clone();
 
     // mapping neaded header request to response
     response.headers.set(
@@ -384,27 +1281,58 @@ async fn execute_request(
     );
 
     // Execute after middlewares with similar optimization
-    for (after_middleware, _) in middlewares.get_after_hooks() {
-        response = match execute_middleware_function(&response, &after_middleware).await {
-            Ok(MiddlewareReturn::Request(_)) => {
+    for (after_middleware, config) in middlewares.get_after_hooks() {
+        if !config.applies_to(&request.path) {
+            continue;
+        }
+        let _middleware_span = span.child("after_request");
+        let started = Instant::now();
+        let result = run_middleware(&response, &after_middleware, &config).await;
+        if debug_headers {
+            middleware_count += 1;
+            record_middleware_time(&extra_headers, middleware_count, started.elapsed());
+        }
+        response = match result {
+            Some(Ok(MiddlewareReturn::Request(_))) => {
                 return response_builder
                     .status(StatusCode::INTERNAL_SERVER_ERROR)
                     .body(Body::from("Middleware returned a response"))
                     .unwrap();
             }
-            Ok(MiddlewareReturn::Response(r)) => {
-                let response = r;
-                response
+            Some(Ok(MiddlewareReturn::Response(mut r))) => {
+                // a response built by the middleware doesn't carry the state yet
+                if r.state.is_none() {
+                    r.state = request.state.clone();
+                }
+                r
             }
-            Err(e) => {
+            Some(Err(e)) => {
                 return response_builder
                     .status(StatusCode::INTERNAL_SERVER_ERROR)
                     .body(Body::from(e.to_string()))
                     .unwrap();
             }
+            None if config.is_conditional => return middleware_timeout_response(),
+            None => response,
         };
     }
 
+    // save the session if the handler or a middleware changed it
+    if let (Some(store), Some(session), Some(dict)) = (&sessions, &session, &request.session) {
+        match store.save(session, dict).await {
+            Ok(Some(cookie)) => response.headers.append("set-cookie".to_string(), cookie),
+            Ok(None) => {}
+            Err(e) => error!("Failed to save session: {}", e),
+        }
+    }
+
+    if let (Some(csrf), Some(token)) = (&csrf, &csrf_token) {
+        match csrf.cookie(token) {
+            Ok(cookie) => response.headers.append("set-cookie".to_string(), cookie),
+            Err(e) => error!("Failed to set CSRF cookie: {}", e),
+        }
+    }
+
     // clean up session db
     // auto commit after response
     if !database.is_none() {
@@ -416,16 +1344,243 @@ async fn execute_request(
     response.to_axum_response(extra_headers)
 }
 
+pub(crate) async fn dispatch(
+    mut req: HttpRequest<Body>,
+    router: Arc<RwLock<Router>>,
+    task_locals: pyo3_asyncio::TaskLocals,
+    middlewares: Middleware,
+    extra_headers: DashMap<String, String>,
+) -> ServerResponse {
+    let path = req.uri().path().to_string();
+    let method = req.method().as_str().to_uppercase();
+
+    // the read guard is released before the handler runs, handlers may register routes
+    let matched = match router.read().unwrap().resolve(&path, &method) {
+        RouteMatch::Found(route, matched_path) => Ok((
+            route.function.clone(),
+            route.path.clone(),
+            route.extract_path_params(&matched_path),
+            route.deprecation_headers(),
+            route.etag,
+            route.cache.clone(),
+            route.rate_limit,
+            route.stream_body,
+            route.ip_filter.clone(),
+        )),
+        RouteMatch::Redirect(location) => Err(redirect(&req, &method, location)),
+        RouteMatch::MethodNotAllowed => Err(StatusCode::METHOD_NOT_ALLOWED.into_response()),
+        RouteMatch::NotFound => Err(StatusCode::NOT_FOUND.into_response()),
+    };
+
+    // rejected before the body is read, a route's own filter replaces the server one
+    let ip_filter = match &matched {
+        Ok((.., Some(route_filter))) => Some(route_filter.clone()),
+        _ => req.extensions().get::<Arc<IpFilterMiddleware>>().cloned(),
+    };
+    if let Some(ip_filter) = ip_filter {
+        let (_, client_ip) = request_addresses(&req);
+        if !ip_filter.allows(&client_ip) {
+            let route_path = match &matched {
+                Ok((_, route_path, ..)) => route_path.as_str(),
+                Err(_) => "",
+            };
+            debug!("Rejected {} {} from {}", method, path, client_ip);
+            record_ip_rejection(route_path);
+            return ip_filter.rejection();
+        }
+    }
+
+    match matched {
+        Ok((function, route_path, path_params, deprecation_headers, etag, cache_rule, rate_limit, stream_body, _)) => {
+            if stream_body {
+                req.extensions_mut().insert(StreamBody);
+            }
+            // counted before the cache, cached responses are limited too
+            let rate_limiter = req.extensions().get::<Arc<RateLimiter>>().cloned();
+            let decision = match rate_limiter {
+                Some(limiter) if !limiter.is_exempt(&path) => {
+                    let route = format!("{} {}", method, route_path);
+                    Some(limiter.check(&req, &route, rate_limit).await)
+                }
+                _ => None,
+            };
+            if let Some(decision) = &decision {
+                if !decision.allowed {
+                    return decision.rejection();
+                }
+            }
+
+            // the route setting wins over the server one
+            let conditional = req.extensions().get::<Arc<ConditionalResponses>>();
+            let etag_max_size = match (etag, conditional) {
+                (Some(false), _) | (None, None) => None,
+                (_, Some(conditional)) => Some(conditional.etag_max_size),
+                (Some(true), None) => Some(DEFAULT_ETAG_MAX_SIZE),
+            };
+            let preconditions = etag_max_size.and(Preconditions::from_request(&req));
+
+            // only GET responses are sliced, the header is meaningless on other methods
+            let range = req
+                .headers()
+                .get(header::RANGE)
+                .filter(|_| method == "GET")
+                .and_then(|range| range.to_str().ok())
+                .map(str::to_string);
+
+            let response_cache = req.extensions().get::<Arc<ResponseCache>>().cloned();
+            let cache_entry = match (cache_rule, response_cache) {
+                (Some(rule), Some(cache)) => rule
+                    .key(&req)
+                    .map(|key| (cache, key, rule.ttl, bypasses_cache(&req))),
+                _ => None,
+            };
+            // a hit is served without running python at all
+            let cached = match &cache_entry {
+                Some((cache, key, _, false)) => cache.lookup(key).await,
+                _ => None,
+            };
+            let mut response = match cached {
+                Some(response) => response,
+                None => {
+                    let response = mapping_method(
+                        req,
+                        function,
+                        route_path,
+                        path_params,
+                        task_locals,
+                        middlewares,
+                        extra_headers,
+                    )
+                    .await;
+                    match cache_entry {
+                        // file bodies are only read once the response is written
+                        Some(_) if response.extensions().get::<FileBody>().is_some() => response,
+                        Some((cache, key, ttl, _)) => cache.store(key, ttl, response).await,
+                        None => response,
+                    }
+                }
+            };
+            if let Some(decision) = &decision {
+                decision.apply(&mut response);
+            }
+            for (name, value) in deprecation_headers {
+                if let (Ok(name), Ok(value)) = (
+                    header::HeaderName::from_bytes(name.as_bytes()),
+                    header::HeaderValue::from_str(&value),
+                ) {
+                    response.headers_mut().insert(name, value);
+                }
+            }
+            // runs inside the compression layer, so the ETag is computed on the plain body
+            let response = match (preconditions, etag_max_size) {
+                (Some(preconditions), Some(max_size))
+                    if response.extensions().get::<FileBody>().is_none() =>
+                {
+                    conditional_response(response, &preconditions, max_size).await
+                }
+                _ => response,
+            };
+            partial_content(response, range.as_deref()).await
+        }
+        Err(response) => {
+            let handler = req
+                .extensions()
+                .get::<Arc<ErrorHandlers>>()
+                .and_then(|handlers| handlers.for_status(response.status()).cloned());
+            match handler {
+                Some(function) => {
+                    let response = pyo3_asyncio::tokio::scope(
+                        task_locals,
+                        error_page(req, response, function, middlewares, extra_headers),
+                    )
+                    .await;
+                    partial_content(response, None).await
+                }
+                None => response,
+            }
+        }
+    }
+}
+
+// Render a 404 or 405 with its python handler, then run the after hooks so it gets the usual headers
+async fn error_page(
+    req: HttpRequest<Body>,
+    default: ServerResponse,
+    function: FunctionInfo,
+    middlewares: Middleware,
+    extra_headers: DashMap<String, String>,
+) -> ServerResponse {
+    let status = default.status();
+    let mut request = Request::from_request(req, HashMap::new()).await;
+    request.state = Some(Python::with_gil(|py| PyDict::new(py).into()));
+
+    let mut response = match execute_error_handler(&request, &function, status.as_u16()).await {
+        Ok(response) => response,
+        Err(e) => {
+            error!("Unhandled exception in the {} handler: {}", status.as_u16(), e);
+            return default;
+        }
+    };
+    response.context_id = request.context_id.clone();
+    response.state = request.state.clone();
+
+    for (after_middleware, config) in middlewares.get_after_hooks() {
+        if !config.applies_to(&request.path) {
+            continue;
+        }
+        match run_middleware(&response, &after_middleware, &config).await {
+            Some(Ok(MiddlewareReturn::Response(mut r))) => {
+                if r.state.is_none() {
+                    r.state = request.state.clone();
+                }
+                response = r;
+            }
+            Some(Ok(MiddlewareReturn::Request(_))) => {
+                error!("After hook returned a request for the {} response", status.as_u16());
+            }
+            Some(Err(e)) => error!("After hook failed for the {} response: {}", status.as_u16(), e),
+            None => {}
+        }
+    }
+
+    response.to_axum_response(extra_headers)
+}
+
+// redirect to the canonical path, 308 keeps the method and body of non-GET requests
+fn redirect(req: &HttpRequest<Body>, method: &str, mut location: String) -> ServerResponse {
+    if let Some(query) = req.uri().query() {
+        location = format!("{}?{}", location, query);
+    }
+    let status = match method {
+        "GET" | "HEAD" => StatusCode::MOVED_PERMANENTLY,
+        _ => StatusCode::PERMANENT_REDIRECT,
+    };
+    ServerResponse::builder()
+        .status(status)
+        .header(header::LOCATION, location)
+        .body(Body::empty())
+        .unwrap()
+}
+
 async fn mapping_method(
     req: HttpRequest<Body>,
     function: FunctionInfo,
+    route_path: String,
+    path_params: HashMap<String, String>,
     task_locals: pyo3_asyncio::TaskLocals,
     middlewares: Middleware,
     extra_headers: DashMap<String, String>,
-) -> impl IntoResponse {
+) -> ServerResponse {
     pyo3_asyncio::tokio::scope(
         task_locals,
-        execute_request(req, function, middlewares, extra_headers),
+        execute_request(
+            req,
+            function,
+            route_path,
+            path_params,
+            middlewares,
+            extra_headers,
+        ),
     )
     .await
 }
@@ -0,0 +1,80 @@
+use std::{convert::Infallible, path::PathBuf, sync::Arc, time::UNIX_EPOCH};
+
+use axum::{
+    body::Body,
+    extract::Request as HttpRequest,
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response as ServerResponse},
+};
+use tower::ServiceExt;
+use tower_http::services::ServeDir;
+
+use crate::router::route::percent_decode;
+
+pub const DEFAULT_CACHE_CONTROL: &str = "public, max-age=3600";
+
+// Files under `directory` served natively, without python middlewares or handlers
+#[derive(Debug, Clone)]
+pub struct StaticFiles {
+    pub url_prefix: String,
+    pub directory: PathBuf,
+    pub cache_control: String,
+}
+
+impl StaticFiles {
+    // ServeDir handles Range and If-Modified-Since, the ETag is added here
+    pub async fn serve(self: Arc<Self>, req: HttpRequest<Body>) -> Result<ServerResponse, Infallible> {
+        // the prefix is already stripped by nest_service
+        let relative = percent_decode(req.uri().path());
+        if relative.split(['/', '\\']).any(|segment| segment == "..") {
+            return Ok(StatusCode::FORBIDDEN.into_response());
+        }
+
+        let etag = self.etag(relative.trim_start_matches('/')).await;
+        if let (Some(etag), Some(if_none_match)) = (&etag, req.headers().get(header::IF_NONE_MATCH)) {
+            let matched = if_none_match
+                .to_str()
+                .map(|value| value.split(',').any(|tag| tag.trim() == etag || tag.trim() == "*"))
+                .unwrap_or(false);
+            if matched {
+                let mut response = StatusCode::NOT_MODIFIED.into_response();
+                self.set_cache_headers(&mut response, Some(etag));
+                return Ok(response);
+            }
+        }
+
+        let mut response = match ServeDir::new(&self.directory).oneshot(req).await {
+            Ok(response) => response.map(Body::new),
+            Err(e) => match e {},
+        };
+        if response.status().is_success() || response.status() == StatusCode::NOT_MODIFIED {
+            self.set_cache_headers(&mut response, etag.as_deref());
+        }
+        Ok(response)
+    }
+
+    // weak ETag from the modification time and size of the file
+    async fn etag(&self, relative: &str) -> Option<String> {
+        let metadata = tokio::fs::metadata(self.directory.join(relative)).await.ok()?;
+        if !metadata.is_file() {
+            return None;
+        }
+        let modified = metadata
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_nanos();
+        Some(format!("W/\"{:x}-{:x}\"", modified, metadata.len()))
+    }
+
+    fn set_cache_headers(&self, response: &mut ServerResponse, etag: Option<&str>) {
+        let headers = response.headers_mut();
+        if let Ok(value) = HeaderValue::from_str(&self.cache_control) {
+            headers.insert(header::CACHE_CONTROL, value);
+        }
+        if let Some(value) = etag.and_then(|etag| HeaderValue::from_str(etag).ok()) {
+            headers.insert(header::ETAG, value);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use axum::http::HeaderMap;
+use opentelemetry::{
+    global::{self, BoxedSpan},
+    propagation::{Extractor, TextMapPropagator},
+    trace::{Span, SpanKind, TraceContextExt, TraceError, Tracer},
+    Context, KeyValue,
+};
+use opentelemetry_sdk::{propagation::TraceContextPropagator, runtime};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+const TRACER_NAME: &str = "hypern";
+
+// Export spans over OTLP, must run inside the tokio runtime
+pub fn init_tracing(endpoint: &str) -> Result<(), TraceError> {
+    let provider = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .install_batch(runtime::Tokio)?;
+    global::set_tracer_provider(provider);
+    ENABLED.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+pub fn shutdown_tracing() {
+    if ENABLED.load(Ordering::Relaxed) {
+        global::shutdown_tracer_provider();
+    }
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl<'a> Extractor for HeaderExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
+
+// Span covering one request, a no-op when tracing is not configured
+pub struct RequestSpan {
+    context: Option<Context>,
+}
+
+impl RequestSpan {
+    // continues the trace from the `traceparent` header when the caller sent one
+    pub fn start(headers: &HeaderMap, method: &str, route: &str) -> Self {
+        if !ENABLED.load(Ordering::Relaxed) {
+            return Self { context: None };
+        }
+        let parent = TraceContextPropagator::new().extract(&HeaderExtractor(headers));
+        let tracer = global::tracer(TRACER_NAME);
+        let span = tracer
+            .span_builder(format!("{} {}", method, route))
+            .with_kind(SpanKind::Server)
+            .with_attributes(vec![
+                KeyValue::new("http.method", method.to_string()),
+                KeyValue::new("http.route", route.to_string()),
+            ])
+            .start_with_context(&tracer, &parent);
+        Self {
+            context: Some(parent.with_span(span)),
+        }
+    }
+
+    // child span for one middleware or handler step, ended when it goes out of scope
+    pub fn child(&self, name: &str) -> Option<ChildSpan> {
+        self.context.as_ref().map(|context| {
+            ChildSpan(global::tracer(TRACER_NAME).start_with_context(name.to_string(), context))
+        })
+    }
+
+    pub fn finish(self, status_code: u16) {
+        if let Some(context) = self.context {
+            let span = context.span();
+            span.set_attribute(KeyValue::new("http.status_code", status_code as i64));
+            span.end();
+        }
+    }
+}
+
+pub struct ChildSpan(BoxedSpan);
+
+impl Drop for ChildSpan {
+    fn drop(&mut self) {
+        self.0.end();
+    }
+}
@@ -0,0 +1,448 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+};
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Request as HttpRequest},
+    http::header,
+};
+use dashmap::DashMap;
+use pyo3::{
+    exceptions::{PyAssertionError, PyRuntimeError, PyTypeError, PyValueError},
+    prelude::*,
+    types::{IntoPyDict, PyBytes, PyDict},
+};
+use pyo3_asyncio::TaskLocals;
+use tokio::runtime::Runtime;
+use tracing::warn;
+
+use crate::{
+    database::{
+        context::{get_sql_connect, set_sql_connect},
+        sql::{config::DatabaseConfig, connection::DatabaseConnection},
+    },
+    di::DependencyInjection,
+    middlewares::base::{Middleware, MiddlewareConfig},
+    router::router::Router,
+    server::dispatch,
+    types::{
+        cookie::parse_cookies,
+        function_info::FunctionInfo,
+        header::Header,
+        json::{from_json_slice, to_json_vec, JsonOptions},
+    },
+};
+
+const MULTIPART_BOUNDARY: &str = "hypern-test-client-boundary";
+const REQUEST_OPTIONS: [&str; 7] = ["headers", "params", "body", "json", "data", "files", "cookies"];
+
+type Hooks = Vec<(FunctionInfo, MiddlewareConfig)>;
+
+// A file in a multipart body: the content, or a (filename, content[, content_type]) tuple
+#[derive(FromPyObject)]
+enum TestFile {
+    Typed(String, Vec<u8>, String),
+    Named(String, Vec<u8>),
+    Content(Vec<u8>),
+}
+
+// Percent-encode a query or form component
+fn encode_component(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+fn urlencode(pairs: &HashMap<String, String>) -> String {
+    pairs
+        .iter()
+        .map(|(key, value)| format!("{}={}", encode_component(key), encode_component(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn multipart_body(data: &HashMap<String, String>, files: HashMap<String, TestFile>) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (name, value) in data {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                MULTIPART_BOUNDARY, name, value
+            )
+            .as_bytes(),
+        );
+    }
+    for (name, file) in files {
+        let (filename, content, content_type) = match file {
+            TestFile::Typed(filename, content, content_type) => (filename, content, content_type),
+            TestFile::Named(filename, content) => {
+                (filename, content, "application/octet-stream".to_string())
+            }
+            TestFile::Content(content) => {
+                (name.clone(), content, "application/octet-stream".to_string())
+            }
+        };
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+                MULTIPART_BOUNDARY, name, filename, content_type
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(&content);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{}--\r\n", MULTIPART_BOUNDARY).as_bytes());
+    body
+}
+
+/// Sends requests through the router, middlewares and dependencies of an app without a socket.
+/// Cookies set by responses are kept and sent with the next requests
+#[pyclass]
+pub struct TestClient {
+    router: Arc<RwLock<Router>>,
+    middlewares: Middleware,
+    injected: DependencyInjection,
+    runtime: Runtime,
+    // async handlers run on this loop, in a thread of its own
+    event_loop: PyObject,
+    task_locals: TaskLocals,
+    #[pyo3(get)]
+    cookies: HashMap<String, String>,
+}
+
+impl TestClient {
+    fn update_cookies(&mut self, headers: &Header) {
+        for set_cookie in headers.get_all("set-cookie".to_string()) {
+            let mut attributes = set_cookie.split(';');
+            let mut pair = attributes.next().unwrap_or_default().splitn(2, '=');
+            let name = pair.next().unwrap_or_default().trim().to_string();
+            let value = pair.next().unwrap_or_default().trim().trim_matches('"').to_string();
+            if name.is_empty() {
+                continue;
+            }
+            let expired = attributes.any(|attribute| {
+                let attribute = attribute.trim().to_ascii_lowercase();
+                attribute == "max-age=0" || attribute.starts_with("max-age=-")
+            });
+            if expired || value.is_empty() {
+                self.cookies.remove(&name);
+            } else {
+                self.cookies.insert(name, value);
+            }
+        }
+    }
+}
+
+#[pymethods]
+impl TestClient {
+    /// `middlewares` is the (before_hooks, after_hooks) pair, `injected` the dependencies by name.
+    /// With `database_config` every request gets the automatic transaction, as on the server
+    #[new]
+    #[pyo3(signature = (router, middlewares=None, injected=None, database_config=None))]
+    pub fn new(
+        py: Python,
+        router: Router,
+        middlewares: Option<(Hooks, Hooks)>,
+        injected: Option<Py<PyDict>>,
+        database_config: Option<DatabaseConfig>,
+    ) -> PyResult<Self> {
+        let mut middleware = Middleware::new()?;
+        if let Some((before_hooks, after_hooks)) = middlewares {
+            middleware.set_before_hooks(before_hooks);
+            middleware.set_after_hooks(after_hooks);
+        }
+        let injected = match injected {
+            Some(injected) => DependencyInjection::from_object(injected),
+            None => DependencyInjection::new(),
+        };
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        if let Some(config) = database_config {
+            match get_sql_connect() {
+                // the connection is process-wide and can't be replaced once opened
+                Some(connection) if !connection.opened_with(&config) => {
+                    return Err(PyRuntimeError::new_err(
+                        "Another database is already connected in this process, it can't be replaced",
+                    ));
+                }
+                Some(_) => {}
+                None => {
+                    let connection = py.allow_threads(|| runtime.block_on(DatabaseConnection::new(config)));
+                    set_sql_connect(connection);
+                }
+            }
+        }
+
+        let event_loop = py.import("asyncio")?.call_method0("new_event_loop")?;
+        let kwargs = [
+            ("target", event_loop.getattr("run_forever")?),
+            ("daemon", true.into_py(py).into_ref(py)),
+        ]
+        .into_py_dict(py);
+        py.import("threading")?
+            .getattr("Thread")?
+            .call((), Some(kwargs))?
+            .call_method0("start")?;
+        let task_locals = TaskLocals::new(event_loop).copy_context(py)?;
+
+        Ok(Self {
+            router: Arc::new(RwLock::new(router)),
+            middlewares: middleware,
+            injected,
+            runtime,
+            event_loop: event_loop.into(),
+            task_locals,
+            cookies: HashMap::new(),
+        })
+    }
+
+    /// Send a request, `json`, `data` (form fields), `files` and `body` set the body
+    #[pyo3(signature = (method, path, headers=None, params=None, body=None, json=None, data=None, files=None, cookies=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn request(
+        &mut self,
+        py: Python,
+        method: &str,
+        path: &str,
+        headers: Option<HashMap<String, String>>,
+        params: Option<HashMap<String, String>>,
+        body: Option<&PyAny>,
+        json: Option<&PyAny>,
+        data: Option<HashMap<String, String>>,
+        files: Option<HashMap<String, TestFile>>,
+        cookies: Option<HashMap<String, String>>,
+    ) -> PyResult<TestResponse> {
+        let mut uri = path.to_string();
+        if let Some(params) = params.filter(|params| !params.is_empty()) {
+            uri.push(if uri.contains('?') { '&' } else { '?' });
+            uri.push_str(&urlencode(&params));
+        }
+
+        let (content, content_type) = match (body, json, data, files) {
+            (_, _, data, Some(files)) => (
+                multipart_body(&data.unwrap_or_default(), files),
+                Some(format!("multipart/form-data; boundary={}", MULTIPART_BOUNDARY)),
+            ),
+            (_, _, Some(data), None) => (
+                urlencode(&data).into_bytes(),
+                Some("application/x-www-form-urlencoded".to_string()),
+            ),
+            (_, Some(json), None, None) => (
+                to_json_vec(json, &JsonOptions::default())?,
+                Some("application/json".to_string()),
+            ),
+            (Some(body), None, None, None) => match body.downcast::<PyBytes>() {
+                Ok(bytes) => (bytes.as_bytes().to_vec(), None),
+                Err(_) => (body.extract::<String>()?.into_bytes(), None),
+            },
+            (None, None, None, None) => (Vec::new(), None),
+        };
+
+        let mut builder = HttpRequest::builder()
+            .method(method.to_uppercase().as_str())
+            .uri(uri)
+            .header(header::HOST, "testserver");
+        if let Some(content_type) = content_type {
+            builder = builder.header(header::CONTENT_TYPE, content_type);
+        }
+        let mut jar = self.cookies.clone();
+        jar.extend(cookies.unwrap_or_default());
+        if !jar.is_empty() {
+            let cookie = jar
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<_>>()
+                .join("; ");
+            builder = builder.header(header::COOKIE, cookie);
+        }
+        for (name, value) in headers.unwrap_or_default() {
+            builder = builder.header(name, value);
+        }
+        let mut req = builder
+            .body(Body::from(content))
+            .map_err(|e| PyValueError::new_err(format!("Invalid request: {}", e)))?;
+        req.extensions_mut().insert(self.injected.clone());
+        req.extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0))));
+
+        let router = self.router.clone();
+        let task_locals = self.task_locals.clone();
+        let middlewares = self.middlewares.clone();
+        let runtime = &self.runtime;
+        let (parts, content) = py.allow_threads(|| {
+            runtime.block_on(async move {
+                let response = dispatch(req, router, task_locals, middlewares, DashMap::new()).await;
+                let (parts, body) = response.into_parts();
+                (parts, axum::body::to_bytes(body, usize::MAX).await)
+            })
+        });
+        let content = content.map_err(|e| PyValueError::new_err(format!("Failed to read response: {}", e)))?;
+
+        let headers = Header::from_hyper_headers(&parts.headers);
+        self.update_cookies(&headers);
+        Ok(TestResponse {
+            status_code: parts.status.as_u16(),
+            headers,
+            content: content.to_vec(),
+        })
+    }
+
+    #[pyo3(signature = (path, **kwargs))]
+    pub fn get(&mut self, py: Python, path: &str, kwargs: Option<&PyDict>) -> PyResult<TestResponse> {
+        self.send(py, "GET", path, kwargs)
+    }
+
+    #[pyo3(signature = (path, **kwargs))]
+    pub fn post(&mut self, py: Python, path: &str, kwargs: Option<&PyDict>) -> PyResult<TestResponse> {
+        self.send(py, "POST", path, kwargs)
+    }
+
+    #[pyo3(signature = (path, **kwargs))]
+    pub fn put(&mut self, py: Python, path: &str, kwargs: Option<&PyDict>) -> PyResult<TestResponse> {
+        self.send(py, "PUT", path, kwargs)
+    }
+
+    #[pyo3(signature = (path, **kwargs))]
+    pub fn patch(&mut self, py: Python, path: &str, kwargs: Option<&PyDict>) -> PyResult<TestResponse> {
+        self.send(py, "PATCH", path, kwargs)
+    }
+
+    #[pyo3(signature = (path, **kwargs))]
+    pub fn delete(&mut self, py: Python, path: &str, kwargs: Option<&PyDict>) -> PyResult<TestResponse> {
+        self.send(py, "DELETE", path, kwargs)
+    }
+
+    /// Stop the event loop running async handlers
+    pub fn close(&self, py: Python) -> PyResult<()> {
+        let event_loop = self.event_loop.as_ref(py);
+        event_loop.call_method1("call_soon_threadsafe", (event_loop.getattr("stop")?,))?;
+        Ok(())
+    }
+
+    fn __enter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __exit__(&self, py: Python, _exc_type: &PyAny, _exc_val: &PyAny, _exc_tb: &PyAny) -> PyResult<bool> {
+        self.close(py)?;
+        Ok(false)
+    }
+}
+
+// a client that is never closed must not leave its loop thread running
+impl Drop for TestClient {
+    fn drop(&mut self) {
+        Python::with_gil(|py| {
+            if let Err(e) = self.close(py) {
+                warn!("Failed to stop the test client event loop: {}", e);
+            }
+        });
+    }
+}
+
+impl TestClient {
+    // the method helpers take the keyword arguments of `request`
+    fn send(&mut self, py: Python, method: &str, path: &str, kwargs: Option<&PyDict>) -> PyResult<TestResponse> {
+        let kwargs = kwargs.unwrap_or_else(|| PyDict::new(py));
+        for key in kwargs.keys() {
+            let key: &str = key.extract()?;
+            if !REQUEST_OPTIONS.contains(&key) {
+                return Err(PyTypeError::new_err(format!("unexpected keyword argument '{}'", key)));
+            }
+        }
+        let option = |name: &str| -> PyResult<Option<&PyAny>> {
+            Ok(kwargs.get_item(name)?.filter(|value| !value.is_none()))
+        };
+        let extract = |name: &str| -> PyResult<Option<HashMap<String, String>>> {
+            option(name)?.map(|value| value.extract()).transpose()
+        };
+        self.request(
+            py,
+            method,
+            path,
+            extract("headers")?,
+            extract("params")?,
+            option("body")?,
+            option("json")?,
+            extract("data")?,
+            option("files")?.map(|value| value.extract()).transpose()?,
+            extract("cookies")?,
+        )
+    }
+}
+
+/// Status, headers and body of a response returned by `TestClient`
+#[pyclass]
+#[derive(Clone)]
+pub struct TestResponse {
+    #[pyo3(get)]
+    pub status_code: u16,
+    #[pyo3(get)]
+    pub headers: Header,
+    pub content: Vec<u8>,
+}
+
+#[pymethods]
+impl TestResponse {
+    #[getter]
+    fn content<'p>(&self, py: Python<'p>) -> &'p PyBytes {
+        PyBytes::new(py, &self.content)
+    }
+
+    #[getter]
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.content).into_owned()
+    }
+
+    #[getter]
+    fn ok(&self) -> bool {
+        self.status_code < 400
+    }
+
+    /// Cookies set by this response
+    #[getter]
+    fn cookies(&self) -> HashMap<String, String> {
+        let values: Vec<String> = self
+            .headers
+            .get_all("set-cookie".to_string())
+            .into_iter()
+            .map(|value| value.split(';').next().unwrap_or_default().to_string())
+            .collect();
+        parse_cookies(values.iter())
+    }
+
+    /// The body parsed as JSON
+    fn json(&self, py: Python) -> PyResult<PyObject> {
+        from_json_slice(py, &self.content, "Response body is not JSON")
+    }
+
+    /// Raise AssertionError showing the body when the status is not `expected`
+    fn assert_status(&self, expected: u16) -> PyResult<()> {
+        if self.status_code != expected {
+            return Err(PyAssertionError::new_err(format!(
+                "expected status {}, got {}: {}",
+                expected,
+                self.status_code,
+                self.text()
+            )));
+        }
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<TestResponse [{}]>", self.status_code)
+    }
+}
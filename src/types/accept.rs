@@ -0,0 +1,121 @@
+// Parsing and matching of Accept and Accept-Language headers
+
+// `value;q=0.5, other` into (value, q) pairs, entries with an invalid q are dropped
+fn parse_quality_list(header: &str) -> Vec<(String, f32)> {
+    header
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let value = parts.next()?.trim();
+            if value.is_empty() {
+                return None;
+            }
+            let mut quality = 1.0;
+            for param in parts {
+                if let Some((key, q)) = param.split_once('=') {
+                    if key.trim().eq_ignore_ascii_case("q") {
+                        quality = q.trim().parse::<f32>().ok().filter(|q| (0.0..=1.0).contains(q))?;
+                    }
+                }
+            }
+            Some((value.to_string(), quality))
+        })
+        .collect()
+}
+
+// A missing or unusable header accepts anything
+pub fn parse_accept(header: &str) -> Vec<(String, f32)> {
+    let ranges: Vec<_> = parse_quality_list(header)
+        .into_iter()
+        .filter(|(range, _)| range.contains('/'))
+        .collect();
+    if ranges.is_empty() {
+        vec![("*/*".to_string(), 1.0)]
+    } else {
+        ranges
+    }
+}
+
+pub fn parse_accept_language(header: &str) -> Vec<(String, f32)> {
+    let ranges = parse_quality_list(header);
+    if ranges.is_empty() {
+        vec![("*".to_string(), 1.0)]
+    } else {
+        ranges
+    }
+}
+
+// q of the most specific media range matching `offer`, `text/html` beats `text/*` beats `*/*`
+fn media_quality(ranges: &[(String, f32)], offer: &str) -> Option<f32> {
+    let offer = offer.to_lowercase();
+    let (offer_type, offer_subtype) = offer.split_once('/')?;
+    ranges
+        .iter()
+        .filter_map(|(range, quality)| {
+            let range = range.to_lowercase();
+            let (range_type, range_subtype) = range.split_once('/')?;
+            let specificity = if range_type == offer_type && range_subtype == offer_subtype {
+                2
+            } else if range_type == offer_type && range_subtype == "*" {
+                1
+            } else if range_type == "*" && range_subtype == "*" {
+                0
+            } else {
+                return None;
+            };
+            Some((specificity, *quality))
+        })
+        .max_by_key(|(specificity, _)| *specificity)
+        .map(|(_, quality)| quality)
+}
+
+// q of the closest language range matching `offer`: an exact match, then a range covering the
+// offer (`en` for `en-US`), then an offer covering the range (`vi` for `vi-VN`), then `*`
+fn language_quality(ranges: &[(String, f32)], offer: &str) -> Option<f32> {
+    let offer = offer.to_lowercase();
+    let covers = |general: &str, specific: &str| {
+        specific.len() > general.len()
+            && specific.starts_with(general)
+            && specific[general.len()..].starts_with('-')
+    };
+    ranges
+        .iter()
+        .filter_map(|(range, quality)| {
+            let range = range.to_lowercase();
+            let specificity = if range == offer {
+                3
+            } else if covers(&range, &offer) {
+                2
+            } else if covers(&offer, &range) {
+                1
+            } else if range == "*" {
+                0
+            } else {
+                return None;
+            };
+            Some((specificity, *quality))
+        })
+        .max_by_key(|(specificity, _)| *specificity)
+        .map(|(_, quality)| quality)
+}
+
+// Offer with the highest q, earlier offers win ties and q=0 means not acceptable
+fn best_match(offers: &[String], quality: impl Fn(&str) -> Option<f32>) -> Option<String> {
+    let mut best: Option<(&String, f32)> = None;
+    for offer in offers {
+        if let Some(q) = quality(offer) {
+            if q > 0.0 && best.map_or(true, |(_, best_q)| q > best_q) {
+                best = Some((offer, q));
+            }
+        }
+    }
+    best.map(|(offer, _)| offer.clone())
+}
+
+pub fn best_media_type(ranges: &[(String, f32)], offers: &[String]) -> Option<String> {
+    best_match(offers, |offer| media_quality(ranges, offer))
+}
+
+pub fn best_language(ranges: &[(String, f32)], offers: &[String]) -> Option<String> {
+    best_match(offers, |offer| language_quality(ranges, offer))
+}
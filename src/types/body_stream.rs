@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use axum::body::{Body, Bytes};
+use dashmap::DashMap;
+use futures::StreamExt;
+use lazy_static::lazy_static;
+use pyo3::{
+    exceptions::{PyIOError, PyRuntimeError, PyStopAsyncIteration},
+    prelude::*,
+    types::PyBytes,
+};
+use tokio::sync::{mpsc, Mutex};
+
+// chunks read ahead of the handler, the client is slowed down past that
+const READ_AHEAD: usize = 8;
+
+type Chunk = Result<Bytes, String>;
+
+enum BodyStreamState {
+    Pending(mpsc::Receiver<Chunk>),
+    Taken,
+}
+
+lazy_static! {
+    // bodies of the requests to routes added with `stream_body`, by request context id
+    static ref BODY_STREAMS: DashMap<String, BodyStreamState> = DashMap::new();
+}
+
+// Marks the requests whose body is handed to the handler unread
+#[derive(Clone, Copy, Debug)]
+pub struct StreamBody;
+
+// Start reading `body` in the background for `request.streaming_body()`
+pub fn register_body_stream(context_id: &str, body: Body) {
+    let (tx, rx) = mpsc::channel(READ_AHEAD);
+    tokio::spawn(async move {
+        let mut chunks = body.into_data_stream();
+        while let Some(chunk) = chunks.next().await {
+            let failed = chunk.is_err();
+            // the handler is done with the body once the receiver is dropped
+            if tx.send(chunk.map_err(|e| e.to_string())).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+    BODY_STREAMS.insert(context_id.to_string(), BodyStreamState::Pending(rx));
+}
+
+pub fn is_body_streamed(context_id: &str) -> bool {
+    matches!(
+        BODY_STREAMS.get(context_id).as_deref(),
+        Some(BodyStreamState::Taken)
+    )
+}
+
+pub fn take_body_stream(context_id: &str) -> PyResult<BodyStream> {
+    let mut state = BODY_STREAMS.get_mut(context_id).ok_or_else(|| {
+        PyRuntimeError::new_err(
+            "The body was already read, add the route with stream_body=True to stream it",
+        )
+    })?;
+    match std::mem::replace(&mut *state, BodyStreamState::Taken) {
+        BodyStreamState::Pending(rx) => Ok(BodyStream {
+            rx: Arc::new(Mutex::new(rx)),
+        }),
+        BodyStreamState::Taken => Err(PyRuntimeError::new_err(
+            "streaming_body() can only be called once",
+        )),
+    }
+}
+
+// Drops the body of a request once it is handled, whether it was read or not
+pub struct BodyStreamGuard(pub String);
+
+impl Drop for BodyStreamGuard {
+    fn drop(&mut self) {
+        BODY_STREAMS.remove(&self.0);
+    }
+}
+
+/// Async iterator over the chunks of a request body, as they arrive
+#[pyclass]
+pub struct BodyStream {
+    rx: Arc<Mutex<mpsc::Receiver<Chunk>>>,
+}
+
+#[pymethods]
+impl BodyStream {
+    fn __aiter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __anext__(&self, py: Python) -> PyResult<Option<PyObject>> {
+        let rx = self.rx.clone();
+        let next = pyo3_asyncio::tokio::future_into_py(py, async move {
+            let chunk = rx.lock().await.recv().await;
+            match chunk {
+                Some(Ok(bytes)) => Python::with_gil(|py| Ok(PyBytes::new(py, &bytes).to_object(py))),
+                Some(Err(e)) => Err(PyIOError::new_err(format!("Failed to read the body: {}", e))),
+                None => Err(PyStopAsyncIteration::new_err(())),
+            }
+        })?;
+        Ok(Some(next.into()))
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use chrono::{TimeZone, Utc};
+use pyo3::{
+    exceptions::PyValueError,
+    prelude::*,
+    types::{IntoPyDict, PyString},
+};
+
+const SAME_SITE_VALUES: [&str; 3] = ["Strict", "Lax", "None"];
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+// Parse `cookie` request header values, the first occurrence of a name wins
+pub fn parse_cookies<'a>(values: impl Iterator<Item = &'a String>) -> HashMap<String, String> {
+    let mut cookies = HashMap::new();
+    for value in values {
+        for pair in value.split(';') {
+            let mut split = pair.splitn(2, '=');
+            let name = split.next().unwrap_or("").trim();
+            if name.is_empty() {
+                continue;
+            }
+            let value = split.next().unwrap_or("").trim().trim_matches('"');
+            cookies
+                .entry(name.to_string())
+                .or_insert_with(|| value.to_string());
+        }
+    }
+    cookies
+}
+
+#[derive(Debug, Default)]
+pub struct SetCookie {
+    pub key: String,
+    pub value: String,
+    pub max_age: Option<i64>,
+    pub expires: Option<String>,
+    pub path: Option<String>,
+    pub domain: Option<String>,
+    pub secure: bool,
+    pub httponly: bool,
+    pub samesite: Option<String>,
+}
+
+impl SetCookie {
+    // Value of a Set-Cookie header
+    pub fn build(&self) -> PyResult<String> {
+        if self.key.is_empty()
+            || self
+                .key
+                .chars()
+                .any(|c| c.is_whitespace() || c.is_control() || "()<>@,;:\\\"/[]?={}".contains(c))
+        {
+            return Err(PyValueError::new_err(format!(
+                "Invalid cookie name: {:?}",
+                self.key
+            )));
+        }
+        if self
+            .value
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || c == ';' || c == ',' || c == '"')
+        {
+            return Err(PyValueError::new_err(format!(
+                "Invalid value for cookie {}",
+                self.key
+            )));
+        }
+
+        // a line break or `;` would smuggle extra attributes or headers into the response
+        for (attribute, value) in [
+            ("expires", &self.expires),
+            ("path", &self.path),
+            ("domain", &self.domain),
+        ] {
+            if let Some(value) = value {
+                if value.chars().any(|c| c.is_control() || c == ';') {
+                    return Err(PyValueError::new_err(format!(
+                        "Invalid {} for cookie {}: {:?}",
+                        attribute, self.key, value
+                    )));
+                }
+            }
+        }
+
+        let mut cookie = format!("{}={}", self.key, self.value);
+        if let Some(max_age) = self.max_age {
+            cookie.push_str(&format!("; Max-Age={}", max_age));
+        }
+        if let Some(expires) = &self.expires {
+            cookie.push_str(&format!("; Expires={}", expires));
+        }
+        if let Some(path) = &self.path {
+            cookie.push_str(&format!("; Path={}", path));
+        }
+        if let Some(domain) = &self.domain {
+            cookie.push_str(&format!("; Domain={}", domain));
+        }
+        if self.secure {
+            cookie.push_str("; Secure");
+        }
+        if self.httponly {
+            cookie.push_str("; HttpOnly");
+        }
+        if let Some(samesite) = &self.samesite {
+            let samesite = SAME_SITE_VALUES
+                .iter()
+                .find(|value| value.eq_ignore_ascii_case(samesite))
+                .ok_or_else(|| {
+                    PyValueError::new_err(format!(
+                        "Invalid samesite value: {}, expected one of {:?}",
+                        samesite, SAME_SITE_VALUES
+                    ))
+                })?;
+            cookie.push_str(&format!("; SameSite={}", samesite));
+        }
+        Ok(cookie)
+    }
+}
+
+// `expires` may be an HTTP date string, a unix timestamp or a datetime (naive ones are taken as UTC)
+pub fn format_expires(expires: &PyAny) -> PyResult<String> {
+    if let Ok(expires) = expires.downcast::<PyString>() {
+        return Ok(expires.to_str()?.to_string());
+    }
+    let timestamp = if let Ok(timestamp) = expires.extract::<i64>() {
+        timestamp
+    } else if expires.hasattr("timestamp")? {
+        let expires = match expires.getattr("tzinfo")?.is_none() {
+            true => {
+                let utc = expires.py().import("datetime")?.getattr("timezone")?.getattr("utc")?;
+                let kwargs = [("tzinfo", utc)].into_py_dict(expires.py());
+                expires.call_method("replace", (), Some(kwargs))?
+            }
+            false => expires,
+        };
+        expires.call_method0("timestamp")?.extract::<f64>()? as i64
+    } else {
+        return Err(PyValueError::new_err(
+            "expires must be a string, a unix timestamp or a datetime",
+        ));
+    };
+
+    Utc.timestamp_opt(timestamp, 0)
+        .single()
+        .map(|date| date.format(HTTP_DATE_FORMAT).to_string())
+        .ok_or_else(|| PyValueError::new_err(format!("Invalid expires timestamp: {}", timestamp)))
+}
@@ -7,18 +7,24 @@ pub struct FunctionInfo {
     pub handler: Py<PyAny>,
     #[pyo3(get, set)]
     pub is_async: bool,
+    // handler parameter names after the request, dependencies are injected by matching name
+    #[pyo3(get, set)]
+    pub parameter_names: Option<Vec<String>>,
 }
 
 #[pymethods]
 impl FunctionInfo {
     #[new]
+    #[pyo3(signature = (handler, is_async, parameter_names=None))]
     pub fn new(
         handler: Py<PyAny>,
         is_async: bool,
+        parameter_names: Option<Vec<String>>,
     ) -> Self {
         Self {
             handler,
             is_async,
+            parameter_names,
         }
     }
 
@@ -26,3 +32,22 @@ impl FunctionInfo {
         Ok(format!("Function(handler = {:?}, is_async = {})", self.handler, self.is_async))
     }
 }
+
+impl FunctionInfo {
+    // Qualified name of the handler for logs, its type name when it has none
+    pub fn name(&self, py: Python) -> String {
+        let handler = self.handler.as_ref(py);
+        handler
+            .getattr("__qualname__")
+            .and_then(|name| name.extract::<String>())
+            .unwrap_or_else(|_| handler.get_type().to_string())
+    }
+
+    // Without parameter names, or with an `inject` parameter, the handler gets the whole dict
+    pub fn wants_inject_dict(&self) -> bool {
+        match &self.parameter_names {
+            Some(names) => names.iter().any(|name| name == "inject"),
+            None => true,
+        }
+    }
+}
@@ -2,51 +2,70 @@ use std::collections::HashMap;
 
 use axum::http::HeaderMap;
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyString};
+use pyo3::types::{PyDict, PyIterator, PyList, PyString};
 
 // Custom Multimap class
 #[pyclass(name = "Header")]
 #[derive(Clone, Debug, Default)]
 pub struct Header {
-    pub headers: HashMap<String, String>,
+    // every value is kept so repeated headers such as Set-Cookie survive
+    pub headers: HashMap<String, Vec<String>>,
 }
 
 #[pymethods]
 impl Header {
     #[new]
     pub fn new(default_headers: Option<&PyDict>) -> Self {
-        match default_headers {
-            Some(default_headers) => {
-                let mut headers = HashMap::new();
-                for (key, value) in default_headers {
-                    let key = key.to_string().to_lowercase();
-                    let value = value.to_string();
-                    headers.insert(key, value);
-                }
-                Header { headers }
-            }
-            None => Header {
-                headers: HashMap::new(),
-            },
+        let mut header = Header::default();
+        if let Some(default_headers) = default_headers {
+            header.populate_from_dict(default_headers);
         }
+        header
     }
 
     pub fn set(&mut self, key: String, value: String) {
-        self.headers.insert(key.to_lowercase(), value);
+        self.headers.insert(key.to_lowercase(), vec![value]);
     }
 
+    // first value of the header
     pub fn get(&self, key: String) -> Option<String> {
-        self.headers.get(&key.to_lowercase()).cloned()
+        self.headers
+            .get(&key.to_lowercase())
+            .and_then(|values| values.first().cloned())
+    }
+
+    pub fn get_all(&self, key: String) -> Vec<String> {
+        self.headers
+            .get(&key.to_lowercase())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn set_all(&mut self, key: String, values: Vec<String>) {
+        if values.is_empty() {
+            self.remove(&key);
+        } else {
+            self.headers.insert(key.to_lowercase(), values);
+        }
+    }
+
+    // add a value without replacing the existing ones
+    pub fn append(&mut self, key: String, value: String) {
+        self.headers
+            .entry(key.to_lowercase())
+            .or_default()
+            .push(value);
     }
 
     pub fn get_headers(&self, py: Python) -> Py<PyDict> {
         // return as a dict of lists
         let dict = PyDict::new(py);
-        for (key, value) in &self.headers {
-            let key = PyString::new(py, key);
-            let value = PyString::new(py, value);
-            dict.set_item(key, value).unwrap();
-
+        for (key, values) in &self.headers {
+            if let Some(value) = values.first() {
+                let key = PyString::new(py, key);
+                let value = PyString::new(py, value);
+                dict.set_item(key, value).unwrap();
+            }
         }
         dict.into()
     }
@@ -57,12 +76,14 @@ impl Header {
 
     pub fn populate_from_dict(&mut self, headers: &PyDict) {
         for (key, value) in headers {
-            let key = key.to_string().to_lowercase();
-            let value = value.to_string();
-            self.headers.insert(key, value);
+            self.set(key.to_string(), value.to_string());
         }
     }
 
+    pub fn update_from_dict(&mut self, headers: &PyDict) {
+        self.populate_from_dict(headers);
+    }
+
     pub fn update(&mut self, headers: Py<PyDict>) {
         Python::with_gil(|py| {
             let headers = headers.as_ref(py);
@@ -70,10 +91,49 @@ impl Header {
         });
     }
 
+    // new header with entries of both, `other` wins on duplicate keys
+    pub fn merge(&self, py: Python, other: Py<Header>) -> Header {
+        let mut merged = self.clone();
+        merged.extend(&other.borrow(py));
+        merged
+    }
+
+    pub fn merge_in_place(&mut self, py: Python, other: Py<Header>) {
+        self.extend(&other.borrow(py));
+    }
+
     pub fn is_empty(&self) -> bool {
         self.headers.is_empty()
     }
 
+    pub fn keys(&self) -> Vec<String> {
+        self.headers.keys().cloned().collect()
+    }
+
+    pub fn values(&self) -> Vec<String> {
+        self.headers
+            .values()
+            .filter_map(|values| values.first().cloned())
+            .collect()
+    }
+
+    // one pair per value, so repeated headers appear several times
+    pub fn items(&self) -> Vec<(String, String)> {
+        self.headers
+            .iter()
+            .flat_map(|(key, values)| values.iter().map(move |value| (key.clone(), value.clone())))
+            .collect()
+    }
+
+    pub fn __iter__(&self, py: Python) -> PyResult<Py<PyIterator>> {
+        let keys = PyList::new(py, self.keys());
+        Ok(PyIterator::from_object(keys)?.into())
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.headers.len()
+    }
+
     pub fn __contains__(&self, key: String) -> bool {
         self.contains(key)
     }
@@ -89,6 +149,10 @@ impl Header {
     pub fn __getitem__(&self, key: String) -> Option<String> {
         self.get(key)
     }
+
+    pub fn __delitem__(&mut self, key: String) {
+        self.remove(&key);
+    }
 }
 
 impl Header {
@@ -97,19 +161,18 @@ impl Header {
     }
 
     pub fn extend(&mut self, headers: &Header) {
-        for (key, value) in &headers.headers {
-            self.headers.insert(key.clone(), value.clone());
+        for (key, values) in &headers.headers {
+            self.set_all(key.clone(), values.clone());
         }
     }
 
     pub fn from_hyper_headers(req_headers: &HeaderMap) -> Self {
-        let mut headers = HashMap::new();
+        let mut header = Header::default();
         for (key, value) in req_headers.iter() {
-            headers.insert(
-                key.as_str().to_lowercase(),
-                value.to_str().unwrap().to_string(),
-            );
+            // values that are not valid UTF-8 are decoded lossily instead of failing the request
+            let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+            header.append(key.as_str().to_string(), value);
         }
-        Header { headers }
+        header
     }
 }
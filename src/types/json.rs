@@ -0,0 +1,343 @@
+use pyo3::{
+    exceptions::{PyTypeError, PyValueError},
+    prelude::*,
+    sync::GILOnceCell,
+    types::{
+        PyBool, PyBytes, PyDate, PyDateTime, PyDict, PyFloat, PyFrozenSet, PyInt, PyList, PySet,
+        PyString, PyTime, PyTuple, PyType,
+    },
+};
+use serde_json::{Map, Number, Value};
+
+static UUID_TYPE: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+static DECIMAL_TYPE: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+fn import_type<'py>(
+    py: Python<'py>,
+    cell: &'static GILOnceCell<Py<PyType>>,
+    module: &str,
+    name: &str,
+) -> PyResult<&'py PyType> {
+    cell.get_or_try_init(py, || {
+        py.import(module)?
+            .getattr(name)?
+            .downcast::<PyType>()
+            .map(|t| t.into())
+            .map_err(PyErr::from)
+    })
+    .map(|t| t.as_ref(py))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct JsonOptions {
+    // Decimal is written as a string by default so no precision is lost
+    pub decimal_as_float: bool,
+}
+
+/// Serialize a python object to JSON bytes
+pub fn to_json_vec(value: &PyAny, options: &JsonOptions) -> PyResult<Vec<u8>> {
+    let value = to_json_value(value, options)?;
+    serde_json::to_vec(&value).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Convert a python object graph to a JSON value in a single pass
+pub fn to_json_value(value: &PyAny, options: &JsonOptions) -> PyResult<Value> {
+    let mut path = Vec::new();
+    let mut containers = Vec::new();
+    convert(value, options, &mut path, &mut containers)
+}
+
+// Deeper documents would overflow the stack of the worker converting them
+const MAX_DEPTH: usize = 512;
+
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+fn format_path(path: &[PathSegment]) -> String {
+    let mut formatted = "$".to_string();
+    for segment in path {
+        match segment {
+            PathSegment::Key(key) => {
+                formatted.push('.');
+                formatted.push_str(key);
+            }
+            PathSegment::Index(index) => formatted.push_str(&format!("[{}]", index)),
+        }
+    }
+    formatted
+}
+
+fn unserializable(value: &PyAny, path: &[PathSegment]) -> PyErr {
+    let type_name = value.get_type().name().unwrap_or("unknown");
+    PyTypeError::new_err(format!(
+        "Object of type {} at {} is not JSON serializable",
+        type_name,
+        format_path(path)
+    ))
+}
+
+// `containers` holds the address of every container being converted, like the markers of
+// json.dumps, so one that holds itself is an error instead of endless recursion
+fn enter(container: &PyAny, path: &[PathSegment], containers: &mut Vec<usize>) -> PyResult<()> {
+    let address = container.as_ptr() as usize;
+    if containers.contains(&address) {
+        return Err(PyValueError::new_err(format!(
+            "Circular reference detected at {}",
+            format_path(path)
+        )));
+    }
+    if containers.len() >= MAX_DEPTH {
+        return Err(PyValueError::new_err(format!(
+            "Nesting deeper than {} levels at {}",
+            MAX_DEPTH,
+            format_path(path)
+        )));
+    }
+    containers.push(address);
+    Ok(())
+}
+
+fn convert_items<'a>(
+    container: &PyAny,
+    items: impl Iterator<Item = &'a PyAny>,
+    options: &JsonOptions,
+    path: &mut Vec<PathSegment>,
+    containers: &mut Vec<usize>,
+) -> PyResult<Value> {
+    enter(container, path, containers)?;
+    let mut values = Vec::new();
+    for (index, item) in items.enumerate() {
+        path.push(PathSegment::Index(index));
+        values.push(convert(item, options, path, containers)?);
+        path.pop();
+    }
+    containers.pop();
+    Ok(Value::Array(values))
+}
+
+fn convert_fields<'a>(
+    container: &PyAny,
+    fields: impl Iterator<Item = (String, &'a PyAny)>,
+    options: &JsonOptions,
+    path: &mut Vec<PathSegment>,
+    containers: &mut Vec<usize>,
+) -> PyResult<Value> {
+    enter(container, path, containers)?;
+    let mut map = Map::new();
+    for (key, item) in fields {
+        path.push(PathSegment::Key(key.clone()));
+        let value = convert(item, options, path, containers)?;
+        path.pop();
+        map.insert(key, value);
+    }
+    containers.pop();
+    Ok(Value::Object(map))
+}
+
+// like json.dumps, str, int, float and bool keys are allowed and written as strings
+fn dict_key(key: &PyAny, path: &[PathSegment]) -> PyResult<String> {
+    if let Ok(key) = key.downcast::<PyString>() {
+        Ok(key.to_str()?.to_string())
+    } else if key.is_none() {
+        Ok("null".to_string())
+    } else if let Ok(key) = key.downcast::<PyBool>() {
+        Ok(key.is_true().to_string())
+    } else if key.is_instance_of::<PyInt>() || key.is_instance_of::<PyFloat>() {
+        Ok(key.str()?.to_string())
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "Key of type {} at {} is not a valid JSON key",
+            key.get_type().name()?,
+            format_path(path)
+        )))
+    }
+}
+
+fn float_value(value: f64, path: &[PathSegment]) -> PyResult<Value> {
+    Number::from_f64(value).map(Value::Number).ok_or_else(|| {
+        PyValueError::new_err(format!(
+            "Out of range float value {} at {}",
+            value,
+            format_path(path)
+        ))
+    })
+}
+
+fn convert(
+    value: &PyAny,
+    options: &JsonOptions,
+    path: &mut Vec<PathSegment>,
+    containers: &mut Vec<usize>,
+) -> PyResult<Value> {
+    let py = value.py();
+
+    if value.is_none() {
+        return Ok(Value::Null);
+    }
+    if let Ok(b) = value.downcast::<PyBool>() {
+        return Ok(Value::Bool(b.is_true()));
+    }
+    if value.is_instance_of::<PyInt>() {
+        return match value.extract::<i64>() {
+            Ok(i) => Ok(Value::from(i)),
+            Err(_) => match value.extract::<u64>() {
+                Ok(u) => Ok(Value::from(u)),
+                // integers beyond 64 bits keep their digits as a string
+                Err(_) => Ok(Value::String(value.str()?.to_string())),
+            },
+        };
+    }
+    if let Ok(f) = value.downcast::<PyFloat>() {
+        return float_value(f.value(), path);
+    }
+    if let Ok(s) = value.downcast::<PyString>() {
+        return Ok(Value::String(s.to_str()?.to_string()));
+    }
+    if let Ok(dict) = value.downcast::<PyDict>() {
+        let mut fields = Vec::with_capacity(dict.len());
+        for (key, item) in dict.iter() {
+            fields.push((dict_key(key, path)?, item));
+        }
+        return convert_fields(value, fields.into_iter(), options, path, containers);
+    }
+    if let Ok(list) = value.downcast::<PyList>() {
+        return convert_items(value, list.iter(), options, path, containers);
+    }
+    if let Ok(tuple) = value.downcast::<PyTuple>() {
+        // namedtuple becomes an object keyed by its field names
+        if let Ok(names) = value.getattr("_fields") {
+            let names: Vec<String> = names.extract()?;
+            return convert_fields(
+                value,
+                names.into_iter().zip(tuple.iter()),
+                options,
+                path,
+                containers,
+            );
+        }
+        return convert_items(value, tuple.iter(), options, path, containers);
+    }
+    if let Ok(set) = value.downcast::<PySet>() {
+        return convert_items(value, set.iter(), options, path, containers);
+    }
+    if let Ok(set) = value.downcast::<PyFrozenSet>() {
+        return convert_items(value, set.iter(), options, path, containers);
+    }
+    if value.is_instance_of::<PyDateTime>()
+        || value.is_instance_of::<PyDate>()
+        || value.is_instance_of::<PyTime>()
+    {
+        return Ok(Value::String(
+            value.call_method0("isoformat")?.extract::<String>()?,
+        ));
+    }
+    if value.is_instance(import_type(py, &UUID_TYPE, "uuid", "UUID")?)? {
+        return Ok(Value::String(value.str()?.to_string()));
+    }
+    if value.is_instance(import_type(py, &DECIMAL_TYPE, "decimal", "Decimal")?)? {
+        if options.decimal_as_float {
+            return float_value(value.extract::<f64>()?, path);
+        }
+        return Ok(Value::String(value.str()?.to_string()));
+    }
+    // dataclass instances, not dataclass types themselves
+    if !value.is_instance_of::<PyType>() {
+        if let Ok(dataclass_fields) = value.getattr("__dataclass_fields__") {
+            let names: Vec<String> = dataclass_fields.downcast::<PyDict>()?.keys().extract()?;
+            let mut fields = Vec::with_capacity(names.len());
+            for name in names {
+                let item = value.getattr(name.as_str())?;
+                fields.push((name, item));
+            }
+            return convert_fields(value, fields.into_iter(), options, path, containers);
+        }
+    }
+
+    Err(unserializable(value, path))
+}
+
+// Whether an integer literal outside of strings fits in neither i64 nor u64, serde_json would
+// read it as a float
+fn has_wide_integer(json: &[u8]) -> bool {
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = None;
+    for (index, byte) in json.iter().chain(std::iter::once(&b' ')).enumerate() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (byte, start) {
+            (b'-' | b'0'..=b'9' | b'.' | b'e' | b'E' | b'+', Some(_)) => {}
+            (b'-' | b'0'..=b'9', None) => start = Some(index),
+            (_, Some(from)) => {
+                let literal = std::str::from_utf8(&json[from..index]).unwrap_or_default();
+                if !literal.contains(['.', 'e', 'E'])
+                    && literal.parse::<i64>().is_err()
+                    && literal.parse::<u64>().is_err()
+                {
+                    return true;
+                }
+                start = None;
+                in_string = *byte == b'"';
+            }
+            (b'"', None) => in_string = true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Parse a JSON document to native python objects, integers of any size stay exact.
+/// A syntax error is a ValueError starting with `context`
+pub fn from_json_slice(py: Python, json: &[u8], context: &str) -> PyResult<PyObject> {
+    let value: Value = serde_json::from_slice(json)
+        .map_err(|e| PyValueError::new_err(format!("{}: {}", context, e)))?;
+    if !has_wide_integer(json) {
+        return to_py_object(py, &value);
+    }
+    // the document is valid, python's parser keeps the digits of the wide integers
+    let value = py
+        .import("json")?
+        .call_method1("loads", (PyBytes::new(py, json),))?;
+    Ok(value.into_py(py))
+}
+
+/// Convert a JSON value to native python objects
+pub fn to_py_object(py: Python, value: &Value) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_py(py),
+        Value::Number(number) => {
+            if let Some(i) = number.as_i64() {
+                i.into_py(py)
+            } else if let Some(u) = number.as_u64() {
+                u.into_py(py)
+            } else {
+                number.as_f64().unwrap_or(f64::NAN).into_py(py)
+            }
+        }
+        Value::String(s) => s.into_py(py),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(to_py_object(py, item)?)?;
+            }
+            list.into_py(py)
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (key, item) in map {
+                dict.set_item(key, to_py_object(py, item)?)?;
+            }
+            dict.into_py(py)
+        }
+    })
+}
@@ -1,8 +1,13 @@
+pub mod accept;
+pub mod body_stream;
+pub mod cookie;
 pub mod function_info;
 pub mod header;
+pub mod json;
 pub mod request;
 pub mod response;
 pub mod query;
+pub mod upload;
 pub mod url;
 pub mod http;
 pub mod middleware;
\ No newline at end of file
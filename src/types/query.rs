@@ -1,7 +1,21 @@
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList};
+use pyo3::types::{PyDict, PyIterator, PyList};
 use std::collections::HashMap;
 
+use crate::router::route::percent_decode;
+
+// Split `a=1&b=2` into decoded pairs, a key without `=` gets an empty value.
+// `+` is a space, it is replaced before decoding so `%2B` stays a plus sign
+pub fn parse_urlencoded(input: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    input.split('&').filter(|pair| !pair.is_empty()).map(|pair| {
+        let mut split = pair.splitn(2, '=');
+        let key = percent_decode(&split.next().unwrap_or("").replace('+', " "));
+        let value = percent_decode(&split.next().unwrap_or("").replace('+', " "));
+        (key, value)
+    })
+}
+
 // Custom Multimap class
 #[pyclass(name = "QueryParams")]
 #[derive(Clone, Debug, Default)]
@@ -22,9 +36,10 @@ impl QueryParams {
         self.queries.entry(key).or_default().push(value);
     }
 
+    // repeated keys keep every value, `get` returns the first one
     pub fn get(&self, key: String, default: Option<String>) -> Option<String> {
         match self.queries.get(&key) {
-            Some(values) => values.last().cloned(),
+            Some(values) => values.first().cloned(),
             None => default,
         }
     }
@@ -36,6 +51,58 @@ impl QueryParams {
         }
     }
 
+    pub fn get_int(&self, key: &str, default: Option<i64>) -> PyResult<Option<i64>> {
+        match self.first_value(key) {
+            Some(value) => value.trim().parse::<i64>().map(Some).map_err(|_| {
+                PyValueError::new_err(format!(
+                    "Query parameter '{}' must be an integer, got '{}'",
+                    key, value
+                ))
+            }),
+            None => Ok(default),
+        }
+    }
+
+    pub fn get_float(&self, key: &str, default: Option<f64>) -> PyResult<Option<f64>> {
+        match self.first_value(key) {
+            Some(value) => value.trim().parse::<f64>().map(Some).map_err(|_| {
+                PyValueError::new_err(format!(
+                    "Query parameter '{}' must be a number, got '{}'",
+                    key, value
+                ))
+            }),
+            None => Ok(default),
+        }
+    }
+
+    #[pyo3(signature = (key, default=None))]
+    pub fn get_bool(&self, key: &str, default: Option<bool>) -> PyResult<Option<bool>> {
+        match self.first_value(key) {
+            Some(value) => match value.trim().to_lowercase().as_str() {
+                "true" | "1" | "yes" => Ok(Some(true)),
+                "false" | "0" | "no" => Ok(Some(false)),
+                _ => Err(PyValueError::new_err(format!(
+                    "Query parameter '{}' must be one of true/false, 1/0, yes/no, got '{}'",
+                    key, value
+                ))),
+            },
+            None => Ok(default),
+        }
+    }
+
+    // split a single value such as `?ids=1,2,3` on the separator
+    #[pyo3(signature = (key, sep=","))]
+    pub fn get_list(&self, key: &str, sep: &str) -> Vec<String> {
+        match self.first_value(key) {
+            Some(value) => value
+                .split(sep)
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect(),
+            None => vec![],
+        }
+    }
+
     pub fn empty(&self) -> bool {
         self.queries.is_empty()
     }
@@ -44,8 +111,8 @@ impl QueryParams {
         self.queries.contains_key(&key)
     }
 
-    pub fn get_all(&self, key: String) -> Option<Vec<String>> {
-        self.queries.get(&key).cloned()
+    pub fn get_all(&self, key: String) -> Vec<String> {
+        self.queries.get(&key).cloned().unwrap_or_default()
     }
 
     pub fn extend(&mut self, other: &mut Self) {
@@ -56,15 +123,48 @@ impl QueryParams {
         }
     }
 
-    pub fn to_dict(&self, py: Python) -> PyResult<Py<PyDict>> {
+    // lists of every value, or only the first value of each key when `flat`
+    #[pyo3(signature = (flat=false))]
+    pub fn to_dict(&self, py: Python, flat: bool) -> PyResult<Py<PyDict>> {
         let dict = PyDict::new(py);
         for (key, values) in self.queries.iter() {
-            let values = PyList::new(py, values.iter());
-            dict.set_item(key, values)?;
+            if flat {
+                dict.set_item(key, values.first())?;
+            } else {
+                dict.set_item(key, PyList::new(py, values.iter()))?;
+            }
         }
         Ok(dict.into())
     }
 
+    pub fn keys(&self) -> Vec<String> {
+        self.queries.keys().cloned().collect()
+    }
+
+    // like a dict, each key maps to its first value; use `get_all` for repeated keys
+    pub fn values(&self) -> Vec<String> {
+        self.queries
+            .values()
+            .filter_map(|values| values.first().cloned())
+            .collect()
+    }
+
+    pub fn items(&self) -> Vec<(String, String)> {
+        self.queries
+            .iter()
+            .filter_map(|(key, values)| values.first().map(|value| (key.clone(), value.clone())))
+            .collect()
+    }
+
+    pub fn __iter__(&self, py: Python) -> PyResult<Py<PyIterator>> {
+        let keys = PyList::new(py, self.keys());
+        Ok(PyIterator::from_object(keys)?.into())
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.queries.len()
+    }
+
     pub fn __contains__(&self, key: String) -> bool {
         self.queries.contains_key(&key)
     }
@@ -79,6 +179,18 @@ impl QueryParams {
 }
 
 impl QueryParams {
+    pub fn from_query_string(query: &str) -> Self {
+        let mut multimap = QueryParams::new();
+        for (key, value) in parse_urlencoded(query) {
+            multimap.set(key, value);
+        }
+        multimap
+    }
+
+    fn first_value(&self, key: &str) -> Option<&String> {
+        self.queries.get(key).and_then(|values| values.first())
+    }
+
     pub fn from_hashmap(map: HashMap<String, Vec<String>>) -> Self {
         let mut multimap = QueryParams::new();
         for (key, values) in map {
@@ -1,73 +1,43 @@
-use axum::extract::{ConnectInfo, Multipart};
+use axum::extract::multipart::MultipartRejection;
+use axum::extract::{DefaultBodyLimit, Multipart};
 use axum::extract::{FromRequest, Request as HttpRequest};
 use axum::http::header;
-use axum::response::IntoResponse;
-use axum::Json;
-use pyo3::types::{PyBytes, PyDict, PyList, PyString};
-use pyo3::{exceptions::PyValueError, prelude::*};
-use serde_json::Value;
+use axum::body::Bytes;
+use pyo3::types::{PyBytes, PyDict, PyList};
+use pyo3::{
+    exceptions::PyRuntimeError,
+    prelude::*,
+};
+use std::cell::OnceCell;
 use std::collections::HashMap;
-use std::io::{Read, Write};
-use tempfile::NamedTempFile;
-
-use super::{header::Header, query::QueryParams};
-
-#[derive(Debug, Clone, FromPyObject)]
-pub struct UploadedFile {
-    name: String,
-    content_type: String,
-    path: std::path::PathBuf,
-    size: u64,
-    content: Vec<u8>,
-    filename: String,
-}
-
-impl ToPyObject for UploadedFile {
-    fn to_object(&self, py: Python) -> PyObject {
-        let name = self.name.clone();
-        let content_type = self.content_type.clone();
-        let path = self.path.clone();
-        let size = self.size;
-        let content = PyBytes::new(py, &self.content).into_py(py);
-        let filename = self.filename.clone();
-
-        let uploaded_file = PyUploadedFile {
-            name,
-            content_type,
-            path,
-            size,
-            content,
-            filename,
-        };
-        Py::new(py, uploaded_file).unwrap().as_ref(py).into()
-    }
-}
-
-#[derive(Debug, Clone)]
-#[pyclass]
-pub struct PyUploadedFile {
-    #[pyo3(get)]
-    name: String,
-
-    #[pyo3(get)]
-    content_type: String,
-
-    #[pyo3(get)]
-    path: std::path::PathBuf,
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tower::{service_fn, Layer, ServiceExt};
+use tracing::{error, warn};
 
-    #[pyo3(get)]
-    size: u64,
+use crate::middlewares::proxy::{request_addresses, request_scheme};
 
-    #[pyo3(get)]
-    content: Py<PyBytes>,
+use super::body_stream::{is_body_streamed, take_body_stream, BodyStream};
+use super::{accept::{best_language, best_media_type, parse_accept, parse_accept_language}, cookie::parse_cookies, header::Header, json::from_json_slice, query::{parse_urlencoded, QueryParams}, upload::{max_upload_size, UploadedFile}, url::Url};
 
-    #[pyo3(get)]
-    filename: String,
-}
 #[derive(Debug, Default, Clone, FromPyObject)]
 pub struct BodyData {
     json: Vec<u8>,
     files: Vec<UploadedFile>,
+    // urlencoded fields in body order, repeated keys appear several times
+    form: Vec<(String, String)>,
+    // body as received, empty for multipart requests
+    raw: Vec<u8>,
+}
+
+impl BodyData {
+    // first value of an urlencoded form field
+    pub fn form_value(&self, key: &str) -> Option<String> {
+        self.form
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.clone())
+    }
 }
 
 impl ToPyObject for BodyData {
@@ -78,9 +48,12 @@ impl ToPyObject for BodyData {
         let json = PyBytes::new(py, &json);
         let files: Vec<Py<PyAny>> = files.into_iter().map(|file| file.to_object(py)).collect();
         let files = PyList::new(py, files);
+        let form = PyList::new(py, self.form.iter().map(|field| field.to_object(py)));
         let body = PyBodyData {
             json: json.into(),
             files: files.into(),
+            form: form.into(),
+            raw: PyBytes::new(py, &self.raw).into(),
         };
         Py::new(py, body).unwrap().as_ref(py).into()
     }
@@ -94,22 +67,57 @@ pub struct PyBodyData {
 
     #[pyo3(get)]
     files: Py<PyList>,
+
+    #[pyo3(get)]
+    form: Py<PyList>,
+
+    #[pyo3(get)]
+    raw: Py<PyBytes>,
+}
+
+static MAX_FORM_FIELDS: AtomicUsize = AtomicUsize::new(1000);
+
+pub fn set_max_form_fields(max_fields: usize) {
+    MAX_FORM_FIELDS.store(max_fields, Ordering::Relaxed);
+}
+
+// Parse an application/x-www-form-urlencoded body, fields past the limit are dropped
+fn parse_form(body: &[u8]) -> Vec<(String, String)> {
+    let max_fields = MAX_FORM_FIELDS.load(Ordering::Relaxed);
+    let body = String::from_utf8_lossy(body);
+    let mut fields = Vec::new();
+    for field in parse_urlencoded(&body) {
+        if fields.len() == max_fields {
+            warn!("Form body has more than {} fields, the rest are ignored", max_fields);
+            break;
+        }
+        fields.push(field);
+    }
+    fields
 }
 
 #[derive(Default, Debug, Clone, FromPyObject)]
 pub struct Request {
 
     pub path: String,
+    pub url: Url,
     pub query_params: QueryParams,
     pub headers: Header,
     pub method: String,
     pub path_params: HashMap<String, String>,
     pub body: BodyData,
 
+    // peer of the connection, and the client it forwards for when it is a trusted proxy
     pub remote_addr: String,
+    pub client_ip: String,
     pub timestamp: u32,
     pub context_id: String,
-
+    // filled when sessions are enabled on the server
+    pub session: Option<Py<PyDict>>,
+    // filled when CSRF protection is enabled, forms and scripts send it back
+    pub csrf_token: Option<String>,
+    // values shared by the middlewares and the handler of one request
+    pub state: Option<Py<PyDict>>,
 }
 
 impl ToPyObject for Request {
@@ -121,38 +129,53 @@ impl ToPyObject for Request {
 
         let request = PyRequest {
             path: self.path.clone(),
+            url: self.url.clone(),
             query_params,
             path_params,
             headers,
             body,
             method: self.method.clone(),
             remote_addr: self.remote_addr.clone(),
+            client_ip: self.client_ip.clone(),
             timestamp: self.timestamp.clone(),
             context_id: self.context_id.clone(),
+            session: self.session.clone(),
+            csrf_token: self.csrf_token.clone(),
+            state: self.state.clone(),
+            accept: OnceCell::new(),
+            accept_language: OnceCell::new(),
         };
         Py::new(py, request).unwrap().as_ref(py).into()
     }
 }
 
 impl Request {
-    pub async fn from_request(request: HttpRequest) -> Self {
-        let mut query_params: QueryParams = QueryParams::new();
-
-        // setup query params
-        if let Some(qs) = request.uri().query() {
-            for (key, value) in qs.split('&').filter_map(|s| {
-                let mut split = s.splitn(2, '=');
-                Some((split.next()?, split.next()?))
-            }) {
-                query_params.set(key.to_string(), value.to_string());
-            }
-        }
+    // `path_params` are the `:name` and `*name` segments captured by the matched route
+    pub async fn from_request(request: HttpRequest, path_params: HashMap<String, String>) -> Self {
+        let mut head = Self::from_head(&request, path_params);
+        head.body = Self::read_body(request).await;
+        head
+    }
+
+    // Everything but the body, which is left empty
+    pub fn from_head(request: &HttpRequest, path_params: HashMap<String, String>) -> Self {
+        let query_params = QueryParams::from_query_string(request.uri().query().unwrap_or_default());
 
-        let remote_addr = request
-            .extensions()
-            .get::<ConnectInfo<std::net::SocketAddr>>()
-            .map(|ConnectInfo(addr)| addr.ip().to_string())
+        let (remote_addr, client_ip) = request_addresses(request);
+
+        // HTTP/2 requests carry the host in the URI instead of a Host header
+        let authority = request
+            .headers()
+            .get(header::HOST)
+            .and_then(|host| host.to_str().ok())
+            .or_else(|| request.uri().authority().map(|authority| authority.as_str()))
             .unwrap_or_default();
+        let url = Url::from_parts(
+            &request_scheme(request),
+            authority,
+            request.uri().path(),
+            request.uri().query().unwrap_or_default(),
+        );
 
         // init default current timestamp
         let timestamp = Some(
@@ -167,99 +190,119 @@ impl Request {
         let path = request.uri().path().to_string();
         let headers = Header::from_hyper_headers(request.headers());
         let method = request.method().to_string();
+
+        Self {
+            path,
+            url,
+            query_params,
+            headers: headers.clone(),
+            method,
+            path_params,
+            body: BodyData::default(),
+            remote_addr: remote_addr,
+            client_ip,
+            timestamp,
+            context_id,
+            session: None,
+            csrf_token: None,
+            state: None,
+        }
+    }
+
+    // axum caps extracted bodies at 2 MiB unless a DefaultBodyLimit layer says otherwise, uploads
+    // stream to disk so they get their own limit
+    async fn read_multipart(request: HttpRequest) -> Result<Multipart, MultipartRejection> {
+        let extract = service_fn(|request: HttpRequest| async move {
+            Ok::<_, Infallible>(Multipart::from_request(request, &()).await)
+        });
+        match DefaultBodyLimit::max(max_upload_size())
+            .layer(extract)
+            .oneshot(request)
+            .await
+        {
+            Ok(multipart) => multipart,
+            Err(infallible) => match infallible {},
+        }
+    }
+
+    async fn read_body(request: HttpRequest) -> BodyData {
         let content_type = request
             .headers()
             .get(header::CONTENT_TYPE)
             .and_then(|v| v.to_str().ok())
-            .unwrap_or("");
+            .unwrap_or("")
+            .to_string();
         let default_body = BodyData::default();
-        let body = match content_type {
+        match content_type.as_str() {
+            // parsed lazily by `json()`, handlers that never read the body don't pay for it
             t if t.starts_with("application/json") => {
-                let json = Json::<Value>::from_request(request, &())
-                    .await
-                    .map_err(|e| e.into_response());
-                match json {
-                    Ok(json) => BodyData {
-                        json: json.to_string().as_bytes().to_vec(),
-                        files: vec![],
-                    },
-                    Err(_e) => default_body,
+                let raw = Bytes::from_request(request, &()).await.unwrap_or_default();
+                BodyData {
+                    json: raw.to_vec(),
+                    raw: raw.to_vec(),
+                    ..default_body
+                }
+            }
+            t if t.starts_with("application/x-www-form-urlencoded") => {
+                let raw = Bytes::from_request(request, &()).await.unwrap_or_default();
+                BodyData {
+                    form: parse_form(&raw),
+                    raw: raw.to_vec(),
+                    ..default_body
                 }
             }
             t if t.starts_with("multipart/form-data") => {
-                let mut multipart = Multipart::from_request(request, &())
-                    .await
-                    .map_err(|e| e.into_response());
+                let mut multipart = match Self::read_multipart(request).await {
+                    Ok(multipart) => multipart,
+                    Err(e) => {
+                        warn!("Invalid multipart body: {}", e);
+                        return default_body;
+                    }
+                };
 
                 let mut files = vec![];
                 let mut json = vec![];
 
-                while let Some(field) = multipart
-                    .as_mut()
-                    .unwrap()
-                    .next_field()
-                    .await
-                    .map_err(|e| e.into_response())
-                    .ok()
-                    .flatten()
-                {
+                loop {
+                    let field = match multipart.next_field().await {
+                        Ok(Some(field)) => field,
+                        Ok(None) => break,
+                        Err(e) => {
+                            warn!("Failed to read multipart body: {}", e);
+                            break;
+                        }
+                    };
                     let name = field.name().unwrap_or("").to_string();
-                    let content_type = field
-                        .content_type()
-                        .unwrap_or("application/octet-stream")
-                        .to_string();
 
                     if name == "json" {
-                        let data = field.bytes().await.map_err(|e| e.into_response());
-                        json = match Some(serde_json::from_slice(&data.unwrap()).map_err(|e| e)) {
-                            Some(Ok(json)) => json,
-                            _ => vec![],
-                        }
-                    } else {
-                        let filename = field.file_name().unwrap_or("").to_string();
-                        let data = field.bytes().await.map_err(|e| e.into_response());
-
-                        let mut temp_file = NamedTempFile::new().map_err(|e| e);
-
-                        match temp_file {
-                            Ok(ref mut file) => {
-                                let _ = file.write(&data.unwrap()).map_err(|e| e);
-                                let file_content = file.reopen().map_err(|e| e);
-                                files.push(UploadedFile {
-                                    name,
-                                    content_type,
-                                    path: file.path().to_path_buf(),
-                                    size: file.path().metadata().unwrap().len(),
-                                    content: {
-                                        let mut buffer = Vec::new();
-                                        file_content.unwrap().read_to_end(&mut buffer).unwrap();
-                                        buffer
-                                    },
-                                    filename,
-                                });
-                            }
+                        json = match field.bytes().await {
+                            Ok(data) => serde_json::from_slice(&data).unwrap_or_default(),
                             Err(e) => {
-                                eprintln!("Error: {:?}", e);
+                                warn!("Failed to read multipart field {}: {}", name, e);
+                                vec![]
                             }
                         }
+                    } else {
+                        match UploadedFile::from_field(field).await {
+                            Ok(file) => files.push(file),
+                            Err(e) => error!("Failed to read uploaded file {}: {}", name, e),
+                        }
                     }
                 }
 
-                BodyData { json, files }
+                BodyData {
+                    json,
+                    files,
+                    ..default_body
+                }
             }
-            _ => default_body,
-        };
-
-        Self {
-            path,
-            query_params,
-            headers: headers.clone(),
-            method,
-            path_params: HashMap::new(),
-            body: body,
-            remote_addr: remote_addr,
-            timestamp,
-            context_id,
+            _ => BodyData {
+                raw: Bytes::from_request(request, &())
+                    .await
+                    .unwrap_or_default()
+                    .to_vec(),
+                ..default_body
+            },
         }
     }
 }
@@ -269,22 +312,34 @@ impl Request {
 pub struct PyRequest {
     #[pyo3(get, set)]
     pub path: String,
+    #[pyo3(get)]
+    pub url: Url,
     #[pyo3(get, set)]
     pub query_params: QueryParams,
     #[pyo3(get, set)]
     pub headers: Py<Header>,
     #[pyo3(get, set)]
     pub path_params: Py<PyDict>,
-    #[pyo3(get)]
     pub body: PyBodyData,
     #[pyo3(get)]
     pub method: String,
     #[pyo3(get)]
     pub remote_addr: String,
     #[pyo3(get)]
+    pub client_ip: String,
+    #[pyo3(get)]
     pub timestamp: u32,
     #[pyo3(get)]
     pub context_id: String,
+    #[pyo3(get, set)]
+    pub session: Option<Py<PyDict>>,
+    #[pyo3(get)]
+    pub csrf_token: Option<String>,
+    #[pyo3(get)]
+    pub state: Option<Py<PyDict>>,
+    // Accept and Accept-Language, parsed on first use
+    accept: OnceCell<Vec<(String, f32)>>,
+    accept_language: OnceCell<Vec<(String, f32)>>,
 }
 
 #[pymethods]
@@ -292,6 +347,7 @@ impl PyRequest {
     #[new]
     #[allow(clippy::too_many_arguments)]
     pub fn new(
+        py: Python,
         path: String,
         query_params: QueryParams,
         headers: Py<Header>,
@@ -303,16 +359,101 @@ impl PyRequest {
         timestamp: u32,
     ) -> Self {
         Self {
+            url: Url::default(),
             path,
             query_params,
             headers,
             path_params,
             body,
             method,
+            client_ip: remote_addr.clone(),
             remote_addr,
             timestamp,
             context_id,
+            session: None,
+            csrf_token: None,
+            state: Some(PyDict::new(py).into()),
+            accept: OnceCell::new(),
+            accept_language: OnceCell::new(),
+        }
+    }
+
+    // empty on routes added with `stream_body`, and unusable once the body is streamed
+    #[getter]
+    pub fn body(&self) -> PyResult<PyBodyData> {
+        if is_body_streamed(&self.context_id) {
+            return Err(PyRuntimeError::new_err(
+                "The body is being streamed by streaming_body()",
+            ));
         }
+        Ok(self.body.clone())
+    }
+
+    // chunks of the body as they arrive, for routes added with `stream_body`
+    pub fn streaming_body(&self) -> PyResult<BodyStream> {
+        take_body_stream(&self.context_id)
+    }
+
+    // scheme and host the request was sent to, e.g. `https://example.com/`
+    #[getter]
+    pub fn base_url(&self) -> String {
+        format!("{}://{}/", self.url.scheme, self.url.netloc())
+    }
+
+    #[getter]
+    pub fn full_url(&self) -> String {
+        self.url.__str__()
+    }
+
+    /// The offered content type the client prefers according to its Accept header, None if it takes none of them
+    #[pyo3(signature = (*content_types))]
+    pub fn accepts(&self, py: Python, content_types: Vec<String>) -> Option<String> {
+        let ranges = self.accept.get_or_init(|| {
+            let header = self.headers.borrow(py).get_all("accept".to_string()).join(",");
+            parse_accept(&header)
+        });
+        best_media_type(ranges, &content_types)
+    }
+
+    #[getter]
+    pub fn accepts_json(&self, py: Python) -> bool {
+        self.accepts(py, vec!["application/json".to_string()]).is_some()
+    }
+
+    #[getter]
+    pub fn accepts_html(&self, py: Python) -> bool {
+        self.accepts(py, vec!["text/html".to_string()]).is_some()
+    }
+
+    /// The language of `best_of` the client prefers, or its first choice when `best_of` is not given
+    #[pyo3(signature = (best_of=None))]
+    pub fn accept_language(&self, py: Python, best_of: Option<Vec<String>>) -> Option<String> {
+        let ranges = self.accept_language.get_or_init(|| {
+            let header = self
+                .headers
+                .borrow(py)
+                .get_all("accept-language".to_string())
+                .join(",");
+            parse_accept_language(&header)
+        });
+        match best_of {
+            Some(offers) => best_language(ranges, &offers),
+            None => ranges
+                .iter()
+                .filter(|(range, quality)| range != "*" && *quality > 0.0)
+                .fold(None, |best: Option<&(String, f32)>, range| match best {
+                    Some(best) if best.1 >= range.1 => Some(best),
+                    _ => Some(range),
+                })
+                .map(|(range, _)| range.clone()),
+        }
+    }
+
+    // parsed from the cookie header on access, so header changes made by middlewares are seen
+    #[getter]
+    pub fn cookies(&self, py: Python) -> HashMap<String, String> {
+        let headers = self.headers.borrow(py);
+        parse_cookies(headers.get_all("cookie".to_string()).iter())
     }
 
     #[setter]
@@ -321,27 +462,30 @@ impl PyRequest {
         Ok(())
     }
 
-    pub fn json(&self, py: Python) -> PyResult<PyObject> {
-        let body = self.body.json.clone();
-        let body_bytes: &[u8] = &body.as_ref(py).as_bytes();
-        let body = PyString::new(py, &String::from_utf8_lossy(body_bytes));
-        match serde_json::from_str(body.extract()?) {
-            Ok(Value::Object(map)) => {
-                let dict = PyDict::new(py);
-
-                for (key, value) in map.iter() {
-                    let py_key = key.to_string().into_py(py);
-                    let py_value = match value {
-                        Value::String(s) => s.as_str().into_py(py),
-                        _ => value.to_string().into_py(py),
-                    };
-
-                    dict.set_item(py_key, py_value)?;
-                }
-
-                Ok(dict.into_py(py))
+    // urlencoded fields, a key sent several times maps to a list of its values
+    pub fn form(&self, py: Python) -> PyResult<Py<PyDict>> {
+        let form = PyDict::new(py);
+        for field in self.body.form.as_ref(py).iter() {
+            let (key, value): (String, String) = field.extract()?;
+            match form.get_item(key.as_str())? {
+                Some(existing) => match existing.downcast::<PyList>() {
+                    Ok(values) => values.append(value)?,
+                    Err(_) => form.set_item(key, PyList::new(py, [existing, value.into_py(py).into_ref(py)]))?,
+                },
+                None => form.set_item(key, value)?,
             }
-            _ => Err(PyValueError::new_err("Invalid JSON object")),
         }
+        Ok(form.into())
+    }
+
+    // the body bytes exactly as sent, e.g. to verify a webhook signature
+    pub fn raw_body(&self, py: Python) -> Py<PyBytes> {
+        self.body.raw.clone_ref(py)
+    }
+
+    // any JSON document, objects become dicts, arrays lists and numbers int or float
+    pub fn json(&self, py: Python) -> PyResult<PyObject> {
+        let body = self.body.json.as_ref(py).as_bytes();
+        from_json_slice(py, body, "Invalid JSON body")
     }
 }
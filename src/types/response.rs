@@ -1,14 +1,22 @@
 use axum::{
     body::Body,
-    http::{HeaderMap, HeaderName, Response as ServerResponse, StatusCode},
+    http::{header::{CONTENT_LENGTH, CONTENT_TYPE}, HeaderMap, HeaderName, HeaderValue, Response as ServerResponse, StatusCode},
 };
 use dashmap::DashMap;
 use pyo3::{
+    exceptions::PyValueError,
     prelude::*,
     types::{PyBytes, PyDict, PyString},
 };
+use tracing::error;
 
-use super::header::Header;
+use crate::middlewares::range::FileBody;
+
+use super::{
+    cookie::{format_expires, SetCookie},
+    header::Header,
+    json::{to_json_vec, JsonOptions},
+};
 
 fn get_description_from_pyobject(description: &PyAny) -> PyResult<Vec<u8>> {
     if let Ok(s) = description.downcast::<PyString>() {
@@ -20,6 +28,81 @@ fn get_description_from_pyobject(description: &PyAny) -> PyResult<Vec<u8>> {
     }
 }
 
+const RESPONSE_TYPES: [&str; 4] = ["text", "html", "json", "binary"];
+
+fn starts_with_ignore_case(text: &[u8], prefix: &[u8]) -> bool {
+    text.get(..prefix.len())
+        .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+}
+
+// response_type of a description given without one
+fn sniff_response_type(description: &PyAny) -> &'static str {
+    let (text, is_bytes) = if let Ok(bytes) = description.downcast::<PyBytes>() {
+        (bytes.as_bytes(), true)
+    } else if let Ok(text) = description.downcast::<PyString>() {
+        (text.to_str().unwrap_or_default().as_bytes(), false)
+    } else {
+        return "text";
+    };
+    let start = text
+        .iter()
+        .position(|byte| !byte.is_ascii_whitespace())
+        .map_or(&text[text.len()..], |position| &text[position..]);
+
+    if starts_with_ignore_case(start, b"<!doctype html") || starts_with_ignore_case(start, b"<html") {
+        return "html";
+    }
+    // scalars such as b"1" are more likely plain data than JSON
+    if is_bytes
+        && matches!(start.first(), Some(b'{') | Some(b'['))
+        && serde_json::from_slice::<serde_json::Value>(text).is_ok()
+    {
+        return "json";
+    }
+    if is_bytes {
+        "binary"
+    } else {
+        "text"
+    }
+}
+
+// Content-Type sent when the application didn't set one
+fn default_content_type(response_type: &str) -> Option<&'static str> {
+    match response_type {
+        "text" => Some("text/plain; charset=utf-8"),
+        "html" => Some("text/html; charset=utf-8"),
+        "json" => Some("application/json"),
+        "binary" => Some("application/octet-stream"),
+        _ => None,
+    }
+}
+
+// Percent-encode what may not appear in a Location header, reserved characters and
+// existing escapes are kept so an already encoded URL is not encoded twice
+fn encode_location(url: &str) -> String {
+    const KEEP: &[u8] = b"-._~:/?#[]@!$&'()*+,;=%";
+    let mut encoded = String::with_capacity(url.len());
+    for byte in url.bytes() {
+        if byte.is_ascii_alphanumeric() || KEEP.contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+// A header the application set, dropped with an error when it can't be sent as is
+fn header_pair(key: &str, value: &str) -> Option<(HeaderName, HeaderValue)> {
+    match (HeaderName::from_bytes(key.as_bytes()), HeaderValue::from_str(value)) {
+        (Ok(name), Ok(value)) => Some((name, value)),
+        _ => {
+            error!("Dropping invalid response header {:?}: {:?}", key, value);
+            None
+        }
+    }
+}
+
 #[derive(Debug, Clone, FromPyObject)]
 pub struct Response {
     pub status_code: u16,
@@ -31,35 +114,65 @@ pub struct Response {
     pub file_path: Option<String>,
 
     pub context_id: String,
+    // `request.state` of the request being answered
+    pub state: Option<Py<PyDict>>,
 }
 
 impl Response {
 
     pub fn to_axum_response(&self, extra_headers: DashMap<String, String>) -> axum::http::Response<axum::body::Body> {
+        // StatusCode accepts up to 999, anything past 599 is an application bug
+        if !(100..=599).contains(&self.status_code) {
+            error!("Invalid status code {}, responding with 500", self.status_code);
+            return ServerResponse::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::from("Internal Server Error"))
+                .unwrap();
+        }
+
         let mut headers = HeaderMap::new();
-        for (key, value) in self.headers.headers.clone() {
-            let header_name = HeaderName::from_bytes(key.as_bytes()).unwrap();
-            headers.insert(header_name, value.parse().unwrap());
+        // repeated headers such as Set-Cookie are emitted once per value
+        for (key, values) in self.headers.headers.iter() {
+            for value in values {
+                if let Some((name, value)) = header_pair(key, value) {
+                    headers.append(name, value);
+                }
+            }
+        }
+
+        // the body may have changed since a Content-Length was set, hyper computes the real one
+        headers.remove(CONTENT_LENGTH);
+
+        // so browsers don't have to sniff the body
+        if !headers.contains_key(CONTENT_TYPE) && self.file_path.is_some() {
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/octet-stream"));
+        } else if !headers.contains_key(CONTENT_TYPE) && !self.description.is_empty() {
+            if let Some(content_type) = default_content_type(&self.response_type) {
+                headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+            }
         }
 
         // Add extra headers
         for (key, value) in extra_headers {
-            let header_name = HeaderName::from_bytes(key.as_bytes()).unwrap();
-            headers.insert(header_name, value.parse().unwrap());
-        }
-    
-       
-
-        let mut response_builder =
-            ServerResponse::builder().status(StatusCode::from_u16(self.status_code).unwrap());
-        for (key, value) in headers {
-            if let Some(k) = key {
-                response_builder = response_builder.header(k, value);
+            if let Some((name, value)) = header_pair(&key, &value) {
+                headers.insert(name, value);
             }
         }
-        response_builder
-            .body(Body::from(self.description.clone()))
-            .unwrap()
+
+        // the file is opened by the server, which knows the Range of the request
+        let body = match &self.file_path {
+            Some(_) => Body::empty(),
+            None => Body::from(self.description.clone()),
+        };
+        let mut response = ServerResponse::builder()
+            .status(StatusCode::from_u16(self.status_code).unwrap())
+            .body(body)
+            .unwrap();
+        *response.headers_mut() = headers;
+        if let Some(file_path) = &self.file_path {
+            response.extensions_mut().insert(FileBody(file_path.into()));
+        }
+        response
     }
 }
 
@@ -80,6 +193,7 @@ impl ToPyObject for Response {
             description,
             file_path: self.file_path.clone(),
             context_id: self.context_id.clone(),
+            state: self.state.clone(),
         };
         Py::new(py, response).unwrap().as_ref(py).into()
     }
@@ -96,22 +210,28 @@ pub struct PyResponse {
     pub headers: Py<Header>,
     #[pyo3(get)]
     pub description: Py<PyAny>,
-    #[pyo3(get)]
+    #[pyo3(get, set)]
     pub file_path: Option<String>,
 
     #[pyo3(get)]
     pub context_id: String,
+    #[pyo3(get)]
+    pub state: Option<Py<PyDict>>,
 }
 
 #[pymethods]
 impl PyResponse {
-    // To do: Add check for content-type in header and change response_type accordingly
+    // `response_type` picks the Content-Type used when `headers` has none, by default it
+    // follows the description: HTML documents are "html", bytes holding a JSON object or array
+    // are "json", other str is "text" and other bytes are "binary"
     #[new]
+    #[pyo3(signature = (status_code, headers, description, response_type=None))]
     pub fn new(
         py: Python,
         status_code: u16,
         headers: &PyAny,
         description: Py<PyAny>,
+        response_type: Option<&str>,
     ) -> PyResult<Self> {
         let headers_output: Py<Header> = if let Ok(headers_dict) = headers.downcast::<PyDict>() {
             // Here you'd have logic to create a Headers instance from a PyDict
@@ -127,14 +247,110 @@ impl PyResponse {
             ));
         };
 
+        let response_type = match response_type {
+            Some(response_type) if RESPONSE_TYPES.contains(&response_type) => response_type,
+            Some(response_type) => {
+                return Err(PyValueError::new_err(format!(
+                    "Invalid response type: {}, expected one of {:?}",
+                    response_type, RESPONSE_TYPES
+                )))
+            }
+            // an explicit Content-Type is kept as it is
+            None if headers_output.borrow(py).contains("content-type".to_string()) => {
+                if description.as_ref(py).is_instance_of::<PyBytes>() {
+                    "binary"
+                } else {
+                    "text"
+                }
+            }
+            None => sniff_response_type(description.as_ref(py)),
+        };
+
         Ok(Self {
             status_code,
-            // we should be handling based on headers but works for now
-            response_type: "text".to_string(),
+            response_type: response_type.to_string(),
             headers: headers_output,
             description,
             file_path: None,
             context_id: "".to_string(),
+            state: None,
+        })
+    }
+
+    /// Build a JSON response, serializing `obj` in Rust
+    #[staticmethod]
+    #[pyo3(signature = (obj, status=200, decimal_as_float=false))]
+    pub fn json(py: Python, obj: &PyAny, status: u16, decimal_as_float: bool) -> PyResult<Self> {
+        let body = to_json_vec(obj, &JsonOptions { decimal_as_float })?;
+        let mut headers = Header::default();
+        headers.set("content-type".to_string(), "application/json".to_string());
+
+        Ok(Self {
+            status_code: status,
+            response_type: "json".to_string(),
+            headers: Py::new(py, headers)?,
+            description: PyString::new(py, &String::from_utf8_lossy(&body)).into(),
+            file_path: None,
+            context_id: "".to_string(),
+            state: None,
+        })
+    }
+
+    /// Same as `json`, named like the other alternate constructors
+    #[staticmethod]
+    #[pyo3(signature = (data, status_code=200))]
+    pub fn from_json(py: Python, data: &PyAny, status_code: u16) -> PyResult<Self> {
+        Self::json(py, data, status_code, false)
+    }
+
+    /// Send the file at `path`, read when the response is written. Single byte ranges are
+    /// answered with 206
+    #[staticmethod]
+    #[pyo3(signature = (path, status_code=200, content_type=None))]
+    pub fn file(py: Python, path: String, status_code: u16, content_type: Option<&str>) -> PyResult<Self> {
+        let mut headers = Header::default();
+        if let Some(content_type) = content_type {
+            headers.set("content-type".to_string(), content_type.to_string());
+        }
+
+        Ok(Self {
+            status_code,
+            response_type: "binary".to_string(),
+            headers: Py::new(py, headers)?,
+            description: PyBytes::new(py, b"").into(),
+            file_path: Some(path),
+            context_id: "".to_string(),
+            state: None,
+        })
+    }
+
+    /// Redirect to `url` with an empty body. 302 and 307 are temporary, 301 and 308 permanent;
+    /// 307 and 308 make clients repeat the same method and body, 301 and 302 may turn a POST into a GET
+    #[staticmethod]
+    #[pyo3(signature = (url, status=None, permanent=false))]
+    pub fn redirect(py: Python, url: &str, status: Option<u16>, permanent: bool) -> PyResult<Self> {
+        let status = match status {
+            Some(status) if (300..=399).contains(&status) => status,
+            Some(status) => {
+                return Err(PyValueError::new_err(format!(
+                    "Redirect status must be 3xx, got {}",
+                    status
+                )))
+            }
+            None if permanent => 308,
+            None => 302,
+        };
+        let mut headers = Header::default();
+        headers.set("location".to_string(), encode_location(url));
+
+        Ok(Self {
+            status_code: status,
+            response_type: "text".to_string(),
+            headers: Py::new(py, headers)?,
+            description: PyString::new(py, "").into(),
+            file_path: None,
+            context_id: "".to_string(),
+            state: None,
         })
     }
 
@@ -144,13 +360,59 @@ impl PyResponse {
         Ok(())
     }
 
-    pub fn set_cookie(&mut self, py: Python, key: &str, value: &str) -> PyResult<()> {
-        let headers = self.headers.as_ref(py).to_object(py);
-        let key = PyString::new(py, key);
-        let value = PyString::new(py, value);
-        let headers_dict: &PyDict = headers.downcast::<PyDict>(py)?;
-        headers_dict.set_item(key, value)?;
-        self.headers = headers.extract(py)?;
+    /// Add a Set-Cookie header, each cookie gets its own header line
+    #[pyo3(signature = (key, value, max_age=None, expires=None, path="/", domain=None, secure=false, httponly=false, samesite=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_cookie(
+        &mut self,
+        py: Python,
+        key: &str,
+        value: &str,
+        max_age: Option<i64>,
+        expires: Option<&PyAny>,
+        path: Option<&str>,
+        domain: Option<&str>,
+        secure: bool,
+        httponly: bool,
+        samesite: Option<&str>,
+    ) -> PyResult<()> {
+        let cookie = SetCookie {
+            key: key.to_string(),
+            value: value.to_string(),
+            max_age,
+            expires: expires.map(format_expires).transpose()?,
+            path: path.map(|p| p.to_string()),
+            domain: domain.map(|d| d.to_string()),
+            secure,
+            httponly,
+            samesite: samesite.map(|s| s.to_string()),
+        };
+        self.headers
+            .borrow_mut(py)
+            .append("set-cookie".to_string(), cookie.build()?);
+        Ok(())
+    }
+
+    /// Expire a cookie on the client, `path` and `domain` must match the ones it was set with
+    #[pyo3(signature = (key, path="/", domain=None))]
+    pub fn delete_cookie(
+        &mut self,
+        py: Python,
+        key: &str,
+        path: Option<&str>,
+        domain: Option<&str>,
+    ) -> PyResult<()> {
+        let cookie = SetCookie {
+            key: key.to_string(),
+            max_age: Some(0),
+            expires: Some("Thu, 01 Jan 1970 00:00:00 GMT".to_string()),
+            path: path.map(|p| p.to_string()),
+            domain: domain.map(|d| d.to_string()),
+            ..Default::default()
+        };
+        self.headers
+            .borrow_mut(py)
+            .append("set-cookie".to_string(), cookie.build()?);
         Ok(())
     }
 }
@@ -0,0 +1,249 @@
+use std::{
+    fs,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use axum::extract::multipart::Field;
+use pyo3::{exceptions::PyIOError, prelude::*, types::PyBytes};
+use tempfile::{NamedTempFile, TempPath};
+use tokio::io::AsyncWriteExt;
+
+// uploads up to this size stay in memory, larger ones are spooled to a temp file
+static SPOOL_MAX_SIZE: AtomicUsize = AtomicUsize::new(1024 * 1024);
+
+pub fn set_upload_spool_size(max_size: usize) {
+    SPOOL_MAX_SIZE.store(max_size, Ordering::Relaxed);
+}
+
+// the largest multipart body accepted, instead of axum's 2 MiB default for extracted bodies
+static MAX_UPLOAD_SIZE: AtomicUsize = AtomicUsize::new(1024 * 1024 * 1024);
+
+pub fn set_max_upload_size(max_size: usize) {
+    MAX_UPLOAD_SIZE.store(max_size, Ordering::Relaxed);
+}
+
+pub fn max_upload_size() -> usize {
+    MAX_UPLOAD_SIZE.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone)]
+enum FileStorage {
+    Memory(Arc<Vec<u8>>),
+    // the temp file is removed once the request and every python reference to it are gone
+    Spooled(Arc<TempPath>),
+    // moved by `save`, the file now belongs to the application
+    Saved(PathBuf),
+}
+
+impl FileStorage {
+    fn path(&self) -> Option<&Path> {
+        match self {
+            FileStorage::Memory(_) => None,
+            FileStorage::Spooled(path) => {
+                let path: &Path = path;
+                Some(path)
+            }
+            FileStorage::Saved(path) => Some(path),
+        }
+    }
+
+    // `size` bytes from `offset`, or everything after it
+    fn read(&self, offset: u64, size: Option<usize>) -> io::Result<Vec<u8>> {
+        match (self, self.path()) {
+            (FileStorage::Memory(data), _) => {
+                let start = (offset as usize).min(data.len());
+                let end = size.map_or(data.len(), |size| (start + size).min(data.len()));
+                Ok(data[start..end].to_vec())
+            }
+            (_, Some(path)) => {
+                let mut file = fs::File::open(path)?;
+                file.seek(SeekFrom::Start(offset))?;
+                let mut buffer = Vec::new();
+                match size {
+                    Some(size) => file.take(size as u64).read_to_end(&mut buffer)?,
+                    None => file.read_to_end(&mut buffer)?,
+                };
+                Ok(buffer)
+            }
+            _ => unreachable!("files on disk always have a path"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UploadedFile {
+    name: String,
+    content_type: String,
+    filename: String,
+    size: u64,
+    storage: FileStorage,
+}
+
+impl UploadedFile {
+    // Read a multipart field chunk by chunk, it moves to a temp file once it outgrows the spool size
+    pub async fn from_field(mut field: Field<'_>) -> io::Result<Self> {
+        let name = field.name().unwrap_or("").to_string();
+        let filename = field.file_name().unwrap_or("").to_string();
+        let content_type = field
+            .content_type()
+            .unwrap_or("application/octet-stream")
+            .to_string();
+
+        let spool_max_size = SPOOL_MAX_SIZE.load(Ordering::Relaxed);
+        let mut buffer = Vec::new();
+        let mut spooled: Option<(tokio::fs::File, TempPath)> = None;
+        let mut size = 0;
+        while let Some(chunk) = field.chunk().await.map_err(io::Error::other)? {
+            size += chunk.len() as u64;
+            match spooled.as_mut() {
+                Some((file, _)) => file.write_all(&chunk).await?,
+                None => {
+                    buffer.extend_from_slice(&chunk);
+                    if buffer.len() > spool_max_size {
+                        let (file, path) = NamedTempFile::new()?.into_parts();
+                        let mut file = tokio::fs::File::from_std(file);
+                        file.write_all(&buffer).await?;
+                        buffer = Vec::new();
+                        spooled = Some((file, path));
+                    }
+                }
+            }
+        }
+
+        let storage = match spooled {
+            Some((mut file, path)) => {
+                file.flush().await?;
+                FileStorage::Spooled(Arc::new(path))
+            }
+            None => FileStorage::Memory(Arc::new(buffer)),
+        };
+        Ok(Self {
+            name,
+            content_type,
+            filename,
+            size,
+            storage,
+        })
+    }
+}
+
+impl ToPyObject for UploadedFile {
+    fn to_object(&self, py: Python) -> PyObject {
+        let uploaded_file = PyUploadedFile {
+            name: self.name.clone(),
+            content_type: self.content_type.clone(),
+            filename: self.filename.clone(),
+            size: self.size,
+            storage: self.storage.clone(),
+            position: 0,
+        };
+        Py::new(py, uploaded_file).unwrap().as_ref(py).into()
+    }
+}
+
+impl<'a> FromPyObject<'a> for UploadedFile {
+    fn extract(ob: &'a PyAny) -> PyResult<Self> {
+        let file: PyRef<PyUploadedFile> = ob.extract()?;
+        Ok(Self {
+            name: file.name.clone(),
+            content_type: file.content_type.clone(),
+            filename: file.filename.clone(),
+            size: file.size,
+            storage: file.storage.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct PyUploadedFile {
+    #[pyo3(get)]
+    name: String,
+
+    #[pyo3(get)]
+    content_type: String,
+
+    #[pyo3(get)]
+    filename: String,
+
+    #[pyo3(get)]
+    size: u64,
+
+    storage: FileStorage,
+    // offset of the next `read`
+    position: u64,
+}
+
+#[pymethods]
+impl PyUploadedFile {
+    // None while the file is kept in memory
+    #[getter]
+    fn path(&self) -> Option<PathBuf> {
+        self.storage.path().map(Path::to_path_buf)
+    }
+
+    // the whole file, read when accessed
+    #[getter]
+    fn content(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        let storage = &self.storage;
+        let data = py
+            .allow_threads(|| storage.read(0, None))
+            .map_err(|e| PyIOError::new_err(format!("Failed to read {}: {}", self.filename, e)))?;
+        Ok(PyBytes::new(py, &data).into())
+    }
+
+    // Read up to `size` bytes from the current position, or the rest of the file when negative
+    #[pyo3(signature = (size=-1))]
+    fn read(&mut self, py: Python, size: i64) -> PyResult<Py<PyBytes>> {
+        let storage = &self.storage;
+        let position = self.position;
+        let size = usize::try_from(size).ok();
+        let data = py
+            .allow_threads(|| storage.read(position, size))
+            .map_err(|e| PyIOError::new_err(format!("Failed to read {}: {}", self.filename, e)))?;
+        self.position += data.len() as u64;
+        Ok(PyBytes::new(py, &data).into())
+    }
+
+    // Write the file to `dest`, spooled files are moved rather than copied when possible
+    fn save(&mut self, py: Python, dest: PathBuf) -> PyResult<()> {
+        let storage = &self.storage;
+        let saved = py
+            .allow_threads(|| -> io::Result<bool> {
+                match (storage, storage.path()) {
+                    (FileStorage::Memory(data), _) => {
+                        fs::write(&dest, data.as_slice())?;
+                        Ok(false)
+                    }
+                    (_, Some(path)) => {
+                        // rename fails across filesystems
+                        if fs::rename(path, &dest).is_err() {
+                            fs::copy(path, &dest)?;
+                            if let FileStorage::Saved(_) = storage {
+                                fs::remove_file(path)?;
+                            }
+                        }
+                        Ok(true)
+                    }
+                    _ => unreachable!("files on disk always have a path"),
+                }
+            })
+            .map_err(|e| {
+                PyIOError::new_err(format!(
+                    "Failed to save {} to {}: {}",
+                    self.filename,
+                    dest.display(),
+                    e
+                ))
+            })?;
+        if saved {
+            self.storage = FileStorage::Saved(dest);
+        }
+        Ok(())
+    }
+}
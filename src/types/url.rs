@@ -1,24 +1,136 @@
 use pyo3::prelude::*;
 
+use super::request::PyRequest;
+
+fn default_port(scheme: &str) -> Option<u16> {
+    match scheme {
+        "http" | "ws" => Some(80),
+        "https" | "wss" => Some(443),
+        _ => None,
+    }
+}
+
+// Split a Host header value into host and explicit port, `[::1]:8080` gives ("::1", Some(8080))
+fn split_host_port(authority: &str) -> (String, Option<u16>) {
+    if let Some(rest) = authority.strip_prefix('[') {
+        if let Some((host, rest)) = rest.split_once(']') {
+            let port = rest.strip_prefix(':').and_then(|port| port.parse().ok());
+            return (host.to_string(), port);
+        }
+    }
+    match authority.rsplit_once(':') {
+        // more than one colon is a bare IPv6 literal without a port
+        Some((host, port)) if !host.contains(':') => (host.to_string(), port.parse().ok()),
+        _ => (authority.to_string(), None),
+    }
+}
+
 #[pyclass]
 #[derive(Default, Debug, Clone)]
 pub struct Url {
     #[pyo3(get)]
     pub scheme: String,
+    // without the brackets of an IPv6 literal
     #[pyo3(get)]
     pub host: String,
+    // only set when the request named it explicitly
+    #[pyo3(get)]
+    pub port: Option<u16>,
     #[pyo3(get)]
     pub path: String,
+    // raw query string, without the `?`
+    #[pyo3(get)]
+    pub query: String,
+    // without the `#`, clients never send it so it is empty for requests
+    #[pyo3(get)]
+    pub fragment: String,
 }
 
 #[pymethods]
 impl Url {
     #[new]
-    pub fn new(scheme: &str, host: &str, path: &str) -> Self {
+    #[pyo3(signature = (scheme, host, path, port=None, query="", fragment=""))]
+    pub fn new(
+        scheme: &str,
+        host: &str,
+        path: &str,
+        port: Option<u16>,
+        query: &str,
+        fragment: &str,
+    ) -> Self {
         Self {
             scheme: scheme.to_string(),
             host: host.to_string(),
+            port,
+            path: path.to_string(),
+            query: query.to_string(),
+            fragment: fragment.to_string(),
+        }
+    }
+
+    /// The URL a request was sent to, as seen through trusted proxies
+    #[staticmethod]
+    pub fn from_request(request: &PyRequest) -> Self {
+        request.url.clone()
+    }
+
+    // host and port as they appear in a URL
+    #[getter]
+    pub fn netloc(&self) -> String {
+        self.format_netloc(self.port)
+    }
+
+    pub fn __str__(&self) -> String {
+        self.format(self.port)
+    }
+
+    /// The URL without a port that is the default one of its scheme, for comparing URLs
+    #[pyo3(name = "to_string")]
+    pub fn canonical(&self) -> String {
+        let port = self.port.filter(|port| default_port(&self.scheme) != Some(*port));
+        self.format(port)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("Url({:?})", self.__str__())
+    }
+}
+
+impl Url {
+    pub fn from_parts(scheme: &str, authority: &str, path: &str, query: &str) -> Self {
+        let (host, port) = split_host_port(authority);
+        Self {
+            scheme: scheme.to_string(),
+            host,
+            port,
             path: path.to_string(),
+            query: query.to_string(),
+            fragment: String::new(),
         }
     }
-}
\ No newline at end of file
+
+    fn format_netloc(&self, port: Option<u16>) -> String {
+        let host = if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        };
+        match port {
+            Some(port) => format!("{}:{}", host, port),
+            None => host,
+        }
+    }
+
+    fn format(&self, port: Option<u16>) -> String {
+        let mut url = format!("{}://{}{}", self.scheme, self.format_netloc(port), self.path);
+        if !self.query.is_empty() {
+            url.push('?');
+            url.push_str(&self.query);
+        }
+        if !self.fragment.is_empty() {
+            url.push('#');
+            url.push_str(&self.fragment);
+        }
+        url
+    }
+}
@@ -0,0 +1,59 @@
+use std::{fmt, future::Future, time::Duration};
+
+use axum::Router;
+use tokio::net::{unix::UCred, UnixListener};
+use tracing::error;
+
+use crate::http_server::{Connections, HttpOptions};
+
+// Connect info of a unix socket peer, its credentials when the platform reports them
+#[derive(Clone, Copy, Debug)]
+pub struct UnixPeer(Option<UCred>);
+
+impl fmt::Display for UnixPeer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(cred) = self.0 else {
+            return Ok(());
+        };
+        write!(f, "uid={},gid={}", cred.uid(), cred.gid())?;
+        if let Some(pid) = cred.pid() {
+            write!(f, ",pid={}", pid)?;
+        }
+        Ok(())
+    }
+}
+
+// axum 0.7 only serves a TcpListener, so unix connections are driven by hyper directly.
+// Once `shutdown` resolves no connection is accepted and in-flight ones are finished
+pub async fn serve_unix<F>(
+    listener: UnixListener,
+    app: Router,
+    options: HttpOptions,
+    shutdown: F,
+) -> std::io::Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let connections = Connections::new(&options);
+    tokio::pin!(shutdown);
+
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    // e.g. out of file descriptors, give in-flight connections time to close
+                    error!("Failed to accept a unix socket connection: {}", e);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+        let peer = UnixPeer(stream.peer_cred().ok());
+        connections.spawn(stream, &app, peer);
+    }
+
+    connections.shutdown().await;
+    Ok(())
+}
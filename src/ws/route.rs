@@ -1,4 +1,6 @@
-use pyo3::prelude::*;
+use std::time::Duration;
+
+use pyo3::{exceptions::PyValueError, prelude::*};
 
 #[pyclass]
 #[derive(Debug, Clone)]
@@ -8,16 +10,47 @@ pub struct WebsocketRoute {
 
     #[pyo3(get, set)]
     pub handler: PyObject,
+
+    // at most this many messages per window for one connection
+    pub rate_limit: Option<(u64, Duration)>,
+
+    // close the connection instead of dropping the messages over the limit
+    #[pyo3(get, set)]
+    pub close_on_rate_limit: bool,
 }
 
 #[pymethods]
 impl WebsocketRoute {
     #[new]
-    pub fn new(path: &str, handler: PyObject) -> Self {
-        Self {
+    #[pyo3(signature = (path, handler, rate_limit=None, close_on_rate_limit=false))]
+    pub fn new(
+        path: &str,
+        handler: PyObject,
+        rate_limit: Option<(u64, f64)>,
+        close_on_rate_limit: bool,
+    ) -> PyResult<Self> {
+        let rate_limit = rate_limit
+            .map(|(max_messages, window_secs)| match Duration::try_from_secs_f64(window_secs) {
+                Ok(window) if max_messages > 0 && !window.is_zero() => Ok((max_messages, window)),
+                _ => Err(PyValueError::new_err(format!(
+                    "Invalid rate_limit: ({}, {}), expected a positive message count and window",
+                    max_messages, window_secs
+                ))),
+            })
+            .transpose()?;
+        Ok(Self {
             path: path.to_string(),
             handler,
-        }
+            rate_limit,
+            close_on_rate_limit,
+        })
+    }
+
+    // (max messages, window in seconds)
+    #[getter]
+    fn rate_limit(&self) -> Option<(u64, f64)> {
+        self.rate_limit
+            .map(|(max_messages, window)| (max_messages, window.as_secs_f64()))
     }
 
     // Get a formatted string representation of the route
@@ -7,6 +7,8 @@ use std::net::{IpAddr, SocketAddr};
 #[derive(Debug)]
 pub struct SocketHeld {
     pub socket: Socket,
+    // set for unix domain sockets, abstract names start with '@'
+    pub unix_path: Option<String>,
 }
 
 #[pymethods]
@@ -28,12 +30,94 @@ impl SocketHeld {
         socket.bind(&address.into())?;
         socket.listen(1024)?;
 
-        Ok(SocketHeld { socket })
+        Ok(SocketHeld {
+            socket,
+            unix_path: None,
+        })
+    }
+
+    /// Listen on a unix domain socket. A path starting with '@' is an abstract socket (linux only),
+    /// a stale socket file left at `path` is replaced
+    #[staticmethod]
+    #[pyo3(signature = (path, permissions=None))]
+    pub fn unix(path: String, permissions: Option<u32>) -> PyResult<SocketHeld> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::{
+                fs::{FileTypeExt, PermissionsExt},
+                net::UnixListener,
+            };
+
+            let listener = match path.strip_prefix('@') {
+                Some(name) => {
+                    if permissions.is_some() {
+                        return Err(pyo3::exceptions::PyValueError::new_err(
+                            "Abstract sockets have no file to set permissions on",
+                        ));
+                    }
+                    #[cfg(target_os = "linux")]
+                    {
+                        use std::os::linux::net::SocketAddrExt;
+                        let address = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+                        UnixListener::bind_addr(&address)?
+                    }
+                    #[cfg(not(target_os = "linux"))]
+                    {
+                        let _ = name;
+                        return Err(pyo3::exceptions::PyNotImplementedError::new_err(
+                            "Abstract sockets are only supported on linux",
+                        ));
+                    }
+                }
+                None => {
+                    if let Ok(metadata) = std::fs::symlink_metadata(&path) {
+                        if !metadata.file_type().is_socket() {
+                            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                                "{} exists and is not a socket",
+                                path
+                            )));
+                        }
+                        // only a socket nobody listens on anymore is left over from an earlier run
+                        match std::os::unix::net::UnixStream::connect(&path) {
+                            Ok(_) => {
+                                return Err(pyo3::exceptions::PyOSError::new_err(format!(
+                                    "{} is in use by another server",
+                                    path
+                                )))
+                            }
+                            Err(e) if e.kind() == std::io::ErrorKind::ConnectionRefused => {
+                                std::fs::remove_file(&path)?
+                            }
+                            Err(e) => return Err(e.into()),
+                        }
+                    }
+                    let listener = UnixListener::bind(&path)?;
+                    if let Some(mode) = permissions {
+                        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode))?;
+                    }
+                    listener
+                }
+            };
+            Ok(SocketHeld {
+                socket: Socket::from(listener),
+                unix_path: Some(path),
+            })
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = (path, permissions);
+            Err(pyo3::exceptions::PyNotImplementedError::new_err(
+                "Unix domain sockets are not supported on this platform",
+            ))
+        }
     }
 
     pub fn try_clone(&self) -> PyResult<SocketHeld> {
         let copied = self.socket.try_clone()?;
-        Ok(SocketHeld { socket: copied })
+        Ok(SocketHeld {
+            socket: copied,
+            unix_path: self.unix_path.clone(),
+        })
     }
 }
 
@@ -1,4 +1,7 @@
-use std::sync::{Arc, Mutex as StdMutex};
+use std::{
+    sync::{Arc, Mutex as StdMutex},
+    time::Duration,
+};
 
 use axum::{
     extract::{
@@ -7,11 +10,23 @@ use axum::{
     },
     response::Response,
 };
+use dashmap::DashMap;
+use futures::{SinkExt, StreamExt};
 use pyo3::{
+    exceptions::PyValueError,
     prelude::*,
-    types::{PyDict, PyTuple},
+    types::{PyBytes, PyDict, PyTuple},
+};
+use tokio::{
+    sync::{mpsc, Mutex},
+    time::Instant,
 };
-use tokio::sync::{mpsc, Mutex};
+use tracing::error;
+
+use super::route::WebsocketRoute;
+use crate::types::json::{to_json_value, JsonOptions};
+
+const RATE_LIMIT_ERROR: &str = r#"{"error":"rate limit exceeded"}"#;
 
 #[derive(Debug, Clone)]
 pub enum WebSocketMessage {
@@ -23,13 +38,42 @@ pub enum WebSocketMessage {
 #[pyclass]
 pub struct WebSocketSession {
     tx_send: StdMutex<mpsc::Sender<WebSocketMessage>>,
+    // messages from the client, read by `receive`
+    rx_recv: Arc<Mutex<mpsc::Receiver<WebSocketMessage>>>,
+    // user id, claims... kept for the whole connection
+    metadata: Arc<DashMap<String, String>>,
     is_closed: StdMutex<bool>,
 }
 
 impl WebSocketSession {
-    pub fn from_sender(sender: mpsc::Sender<WebSocketMessage>) -> Self {
+    fn send_message(&self, msg: WebSocketMessage) -> PyResult<()> {
+        // check socket is closed
+        if *self.is_closed.lock().unwrap() {
+            return Err(PyErr::new::<pyo3::exceptions::PyConnectionError, _>(
+                "WebSocket closed",
+            ));
+        }
+
+        let tx = self.tx_send.lock().unwrap().clone();
+
+        tokio::task::spawn_blocking(move || {
+            let _ = tokio::runtime::Runtime::new().unwrap().block_on(async {
+                tx.send(msg).await.map_err(|_| {
+                    PyErr::new::<pyo3::exceptions::PyConnectionError, _>("Failed to send message")
+                })
+            });
+        });
+        Ok(())
+    }
+
+    pub fn from_channels(
+        sender: mpsc::Sender<WebSocketMessage>,
+        receiver: mpsc::Receiver<WebSocketMessage>,
+    ) -> Self {
         WebSocketSession {
             tx_send: StdMutex::new(sender),
+            rx_recv: Arc::new(Mutex::new(receiver)),
+            metadata: Arc::new(DashMap::new()),
             is_closed: StdMutex::new(false),
         }
     }
@@ -40,21 +84,12 @@ impl WebSocketSession {
     #[new]
     fn new() -> Self {
         let (tx_send, _) = mpsc::channel(100);
+        let (_, rx_recv) = mpsc::channel(100);
 
-        WebSocketSession {
-            tx_send: StdMutex::new(tx_send),
-            is_closed: StdMutex::new(false),
-        }
+        WebSocketSession::from_channels(tx_send, rx_recv)
     }
 
     fn send(&self, message: &PyAny) -> PyResult<()> {
-        // check socket is closed
-        if *self.is_closed.lock().unwrap() {
-            return Err(PyErr::new::<pyo3::exceptions::PyConnectionError, _>(
-                "WebSocket closed",
-            ));
-        }
-
         // send message
         let msg = if let Ok(text) = message.extract::<String>() {
             WebSocketMessage::Text(text)
@@ -65,17 +100,65 @@ impl WebSocketSession {
                 "Unsupported message type",
             ));
         };
+        self.send_message(msg)
+    }
 
-        let tx = self.tx_send.lock().unwrap().clone();
+    // serialize `data` to JSON and send it as a text message,
+    // anything that can't be serialized (NaN included) is a TypeError
+    fn send_json(&self, py: Python, data: &PyAny) -> PyResult<()> {
+        let value = to_json_value(data, &JsonOptions::default()).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyTypeError, _>(e.value(py).to_string())
+        })?;
+        let text = serde_json::to_string(&value)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyTypeError, _>(e.to_string()))?;
+        self.send_message(WebSocketMessage::Text(text))
+    }
 
-        tokio::task::spawn_blocking(move || {
-            let _ = tokio::runtime::Runtime::new().unwrap().block_on(async {
-                tx.send(msg).await.map_err(|_| {
-                    PyErr::new::<pyo3::exceptions::PyConnectionError, _>("Failed to send message")
+    // wait for the next message of the client, text as str and binary as bytes.
+    // Resolves to None on timeout or once the connection is closed
+    #[pyo3(signature = (timeout_secs=None))]
+    fn receive<'p>(&self, py: Python<'p>, timeout_secs: Option<f64>) -> PyResult<&'p PyAny> {
+        let timeout = timeout_secs
+            .map(|secs| {
+                Duration::try_from_secs_f64(secs).map_err(|_| {
+                    PyValueError::new_err(format!("Invalid timeout_secs: {}", secs))
                 })
-            });
-        });
-        Ok(())
+            })
+            .transpose()?;
+        let rx_recv = self.rx_recv.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let mut rx = rx_recv.lock().await;
+            let msg = match timeout {
+                Some(timeout) => tokio::time::timeout(timeout, rx.recv())
+                    .await
+                    .unwrap_or(None),
+                None => rx.recv().await,
+            };
+            Python::with_gil(|py| {
+                Ok(match msg {
+                    Some(WebSocketMessage::Text(text)) => text.into_py(py),
+                    Some(WebSocketMessage::Binary(bytes)) => PyBytes::new(py, &bytes).into_py(py),
+                    Some(WebSocketMessage::Close) | None => py.None(),
+                })
+            })
+        })
+    }
+
+    fn set_metadata(&self, key: &str, value: &str) {
+        self.metadata.insert(key.to_string(), value.to_string());
+    }
+
+    fn get_metadata(&self, key: &str) -> Option<String> {
+        self.metadata.get(key).map(|value| value.clone())
+    }
+
+    fn get_all_metadata(&self, py: Python) -> PyResult<Py<PyDict>> {
+        let dict = PyDict::new(py);
+        for entry in self.metadata.iter() {
+            dict.set_item(entry.key(), entry.value())?;
+        }
+        Ok(dict.into())
     }
 
     // close connection
@@ -94,29 +177,99 @@ impl WebSocketSession {
     }
 }
 
-pub async fn websocket_handler(handler: PyObject, ws: WebSocketUpgrade) -> Response {
-    ws.on_upgrade(move |socket| handle_socket(handler, socket))
+pub async fn websocket_handler(route: WebsocketRoute, ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(move |socket| handle_socket(route, socket))
+}
+
+// Run the handler to completion, coroutine functions on their own event loop
+fn call_handler(
+    py: Python,
+    handler: &PyObject,
+    args: &PyTuple,
+    kwargs: Option<&PyDict>,
+) -> PyResult<PyObject> {
+    // Check if the handler is a coroutine function
+    let inspect = py.import("inspect")?;
+    let is_coroutine = inspect
+        .call_method1("iscoroutinefunction", (handler.as_ref(py),))?
+        .is_true()?;
+
+    if is_coroutine {
+        // Handle async function
+        let asyncio = py.import("asyncio")?;
+        let coro = handler.call(py, args, kwargs)?;
+
+        // Create a new event loop in the current thread
+        let loop_obj = asyncio.call_method0("new_event_loop")?;
+
+        // Run the coroutine and get result
+        let result = loop_obj.call_method1("run_until_complete", (coro,));
+
+        // Close the loop
+        loop_obj.call_method0("close")?;
+
+        Ok(result?.into())
+    } else {
+        // Handle sync function
+        handler.call(py, args, kwargs)
+    }
+}
+
+// Handlers taking neither a `message` parameter nor `**kwargs` are called once per
+// connection and pull messages with `session.receive()`
+fn is_pull_handler(py: Python, handler: &PyObject) -> PyResult<bool> {
+    let inspect = py.import("inspect")?;
+    let var_keyword = inspect.getattr("Parameter")?.getattr("VAR_KEYWORD")?;
+    let parameters = inspect
+        .call_method1("signature", (handler.as_ref(py),))?
+        .getattr("parameters")?;
+    if parameters.contains("message")? {
+        return Ok(false);
+    }
+    for parameter in parameters.call_method0("values")?.iter()? {
+        if parameter?.getattr("kind")?.eq(var_keyword)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
 }
 
-async fn handle_socket(python_handler: PyObject, socket: WebSocket) {
+async fn handle_socket(route: WebsocketRoute, socket: WebSocket) {
+    let WebsocketRoute {
+        handler: python_handler,
+        rate_limit,
+        close_on_rate_limit,
+        ..
+    } = route;
     let (tx_send, mut rx_send) = mpsc::channel(100);
-    let (tx_recv, _) = mpsc::channel(100);
+    let (tx_recv, rx_recv) = mpsc::channel(100);
 
-    let is_closed = Arc::new(Mutex::new(false));
-    let is_closed_clone = is_closed.clone();
+    let prepared = Python::with_gil(|py| -> PyResult<(Py<WebSocketSession>, bool)> {
+        let session = Py::new(
+            py,
+            WebSocketSession::from_channels(tx_send.clone(), rx_recv),
+        )?;
+        Ok((session, is_pull_handler(py, &python_handler)?))
+    });
+    let (session, pull) = match prepared {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            error!("Failed to prepare websocket handler: {}", e);
+            return;
+        }
+    };
 
-    let socket = Arc::new(Mutex::new(socket));
-    let socket_send = socket.clone();
+    // the sink is split off so messages can be sent while waiting for the client
+    let (mut sink, mut stream) = socket.split();
 
     // Send message handler
     tokio::spawn(async move {
         while let Some(msg) = rx_send.recv().await {
-            let mut socket = socket_send.lock().await;
             let send_result = match msg {
-                WebSocketMessage::Text(text) => socket.send(Message::Text(text)).await,
-                WebSocketMessage::Binary(bytes) => socket.send(Message::Binary(bytes)).await,
+                WebSocketMessage::Text(text) => sink.send(Message::Text(text)).await,
+                WebSocketMessage::Binary(bytes) => sink.send(Message::Binary(bytes)).await,
                 WebSocketMessage::Close => {
-                    println!("Closing connection");
+                    let _ = sink.send(Message::Close(None)).await;
                     break;
                 }
             };
@@ -127,83 +280,90 @@ async fn handle_socket(python_handler: PyObject, socket: WebSocket) {
         }
     });
 
+    if pull {
+        let handler = python_handler.clone();
+        let session = session.clone();
+        tokio::task::spawn_blocking(move || {
+            Python::with_gil(|py| {
+                let args = PyTuple::new(py, &[session]);
+                if let Err(e) = call_handler(py, &handler, args, None) {
+                    error!("Websocket handler failed: {}", e);
+                }
+            });
+        });
+    }
+
     // Receive message handler
-    let socket_recv = socket.clone();
     tokio::spawn(async move {
-        let mut socket = socket_recv.lock().await;
-
-        while let Some(msg) = socket.recv().await {
-            match msg {
-                Ok(Message::Text(text)) => {
-                    let handler_result = Python::with_gil(|py| -> PyResult<PyObject> {
-                        let session = WebSocketSession::from_sender(tx_send.clone());
-                        
-                        // Check if the handler is a coroutine function
-                        let inspect = py.import("inspect")?;
-                        let is_coroutine = inspect
-                            .call_method1("iscoroutinefunction", (python_handler.as_ref(py),))?
-                            .is_true()?;
-
-                        let kwargs = PyDict::new(py);
-                        kwargs.set_item("message", text.clone())?;
-
-                        let args = PyTuple::new(py, &[PyCell::new(py, session)?]);
-                        
-                        if is_coroutine {
-                            // Handle async function
-                            let asyncio = py.import("asyncio")?;
-                            let coro = python_handler.call(py, args, Some(kwargs))?;
-                            
-                            // Create a new event loop in the current thread
-                            let loop_obj = asyncio.call_method0("new_event_loop")?;
-                            
-                            // Run the coroutine and get result
-                            let result = loop_obj.call_method1("run_until_complete", (coro,))?;
-                            
-                            // Close the loop
-                            loop_obj.call_method0("close")?;
-                            
-                            Ok(result.into())
-                        } else {
-                            // Handle sync function
-                            let result = python_handler.call(py, args, Some(kwargs))?;
-                            Ok(result)
-                        }
-                    });
-
-                    match handler_result {
-                        Ok(_) => {
-                            if tx_recv.send(WebSocketMessage::Text(text)).await.is_err() {
-                                break;
-                            }
-                        }
-                        Err(e) => {
-                            let error_msg = format!("{{\"error\": \"{}\"}}", e.to_string());
-                            if tx_send.send(WebSocketMessage::Text(error_msg)).await.is_err() {
-                                break;
-                            }
-                        }
-                    }
+        // rate limit window of this connection
+        let mut window_start = Instant::now();
+        let mut message_count: u64 = 0;
+
+        while let Some(msg) = stream.next().await {
+            let msg = match msg {
+                Ok(Message::Text(text)) => WebSocketMessage::Text(text),
+                Ok(Message::Binary(bytes)) => WebSocketMessage::Binary(bytes),
+                // pings are answered by the websocket protocol layer
+                Ok(Message::Ping(_)) | Ok(Message::Pong(_)) => continue,
+                Ok(Message::Close(_)) | Err(_) => break,
+            };
+
+            if let Some((max_messages, window)) = rate_limit {
+                if window_start.elapsed() >= window {
+                    window_start = Instant::now();
+                    message_count = 0;
                 }
-                Ok(Message::Binary(bytes)) => {
-                    if tx_recv.send(WebSocketMessage::Binary(bytes)).await.is_err() {
+                message_count += 1;
+                if message_count > max_messages {
+                    // one error per window, the client is not flooded back
+                    if message_count == max_messages + 1
+                        && tx_send
+                            .send(WebSocketMessage::Text(RATE_LIMIT_ERROR.to_string()))
+                            .await
+                            .is_err()
+                    {
                         break;
                     }
-                }
-                Ok(Message::Ping(ping)) => {
-                    if socket.send(Message::Pong(ping)).await.is_err() {
+                    if close_on_rate_limit {
+                        let _ = tx_send.send(WebSocketMessage::Close).await;
                         break;
                     }
+                    continue;
                 }
-                Ok(Message::Pong(_)) => {
-                    // Handle pong messages if needed
+            }
+
+            if pull {
+                // the handler is gone once the receiver is dropped
+                if tx_recv.send(msg).await.is_err() {
+                    break;
+                }
+                continue;
+            }
+
+            let handler_result = Python::with_gil(|py| -> PyResult<PyObject> {
+                let kwargs = PyDict::new(py);
+                match msg {
+                    WebSocketMessage::Text(text) => kwargs.set_item("message", text)?,
+                    WebSocketMessage::Binary(bytes) => {
+                        kwargs.set_item("message", PyBytes::new(py, &bytes))?
+                    }
+                    WebSocketMessage::Close => return Ok(py.None()),
                 }
-                Ok(Message::Close(_)) | Err(_) => {
-                    let mut closed = is_closed_clone.lock().await;
-                    *closed = true;
+
+                let args = PyTuple::new(py, &[session.clone_ref(py)]);
+                call_handler(py, &python_handler, args, Some(kwargs))
+            });
+
+            if let Err(e) = handler_result {
+                let error_msg = format!("{{\"error\": \"{}\"}}", e);
+                if tx_send.send(WebSocketMessage::Text(error_msg)).await.is_err() {
                     break;
                 }
             }
         }
+
+        Python::with_gil(|py| {
+            *session.borrow(py).is_closed.lock().unwrap() = true;
+        });
     });
-}
\ No newline at end of file
+}
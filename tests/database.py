@@ -0,0 +1,11 @@
+import os
+import tempfile
+
+from hypern.hypern import DatabaseConfig, DatabaseType
+
+# The connection is process-wide, the first config used wins, so every database test shares this one
+SQLITE_URL = f"sqlite://{tempfile.gettempdir()}/hypern-tests-{os.getpid()}.db?mode=rwc"
+
+
+def sqlite_config() -> DatabaseConfig:
+    return DatabaseConfig(DatabaseType.Sqlite, SQLITE_URL, 2, 2, 30, warmup=True)
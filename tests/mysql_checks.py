@@ -0,0 +1,103 @@
+"""
+Checks against a real MySQL server, run by test_mysql.py in a subprocess of their own since
+the database connection is process-wide.
+"""
+
+import base64
+import datetime
+import os
+import sys
+
+from hypern import Hypern, Request
+from hypern.datastructures import HTTPMethod
+from hypern.hypern import DatabaseConfig, DatabaseType, get_session_database
+from hypern.response import JSONResponse
+
+MYSQL_URL = os.environ.get("HYPERN_TEST_MYSQL_URL", "")
+
+BLOB = b"\x00\xff\x10binary\x00"
+WHEN = datetime.datetime(2024, 2, 29, 23, 59, 58, 123456)
+DAY = datetime.date(1999, 12, 31)
+AT = datetime.time(7, 8, 9, 654321)
+
+app = Hypern()
+
+
+def temporal(request: Request, **kwargs):
+    tx = get_session_database(request.context_id)
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS typed_items (at_datetime DATETIME(6), on_date DATE, at_time TIME(6), "
+        "price DECIMAL(20, 6), data BLOB)",
+        [],
+    )
+    tx.execute("DELETE FROM typed_items", [])
+    tx.execute(
+        "INSERT INTO typed_items (at_datetime, on_date, at_time, price, data) VALUES (?, ?, ?, ?, ?)",
+        [WHEN, DAY, AT, "12345678901234.000001", BLOB],
+    )
+    row = tx.fetch_one("SELECT at_datetime, on_date, at_time, price, data FROM typed_items", [])
+    # bound values are compared by the server, not only read back
+    matched = tx.fetch_one(
+        "SELECT COUNT(*) AS matched FROM typed_items WHERE at_datetime = ? AND on_date = ? AND at_time = ? AND data = ?",
+        [WHEN, DAY, AT, BLOB],
+    )
+    return JSONResponse(
+        {
+            "types": [type(row[column]).__name__ for column in ("at_datetime", "on_date", "at_time", "price", "data")],
+            "at_datetime": row["at_datetime"].isoformat(),
+            "on_date": row["on_date"].isoformat(),
+            "at_time": row["at_time"].isoformat(),
+            "price": row["price"],
+            "data": base64.b64encode(row["data"]).decode(),
+            "matched": matched["matched"],
+        }
+    )
+
+
+def json_columns(request: Request, **kwargs):
+    tx = get_session_database(request.context_id)
+    tx.execute("CREATE TABLE IF NOT EXISTS json_items (doc JSON, tags JSON, note TEXT)", [])
+    tx.execute("DELETE FROM json_items", [])
+    tx.execute(
+        "INSERT INTO json_items (doc, tags, note) VALUES (?, ?, ?)",
+        [{"name": "widget", "sizes": [1, 2], "meta": {"ok": True, "none": None}}, ["a", 1, 2.5], '{"kept": "as text"}'],
+    )
+    row = tx.fetch_one("SELECT doc, tags, note FROM json_items", [])
+    # a bound dict compares as JSON on the server
+    matched = tx.fetch_one("SELECT COUNT(*) AS matched FROM json_items WHERE JSON_CONTAINS(doc, ?)", [{"name": "widget"}])
+    return JSONResponse({"doc": row["doc"], "tags": row["tags"], "note": row["note"], "matched": matched["matched"]})
+
+
+app.add_route(HTTPMethod.POST, "/temporal", temporal)
+app.add_route(HTTPMethod.POST, "/json", json_columns)
+
+
+def client():
+    return app.test_client(database_config=DatabaseConfig(DatabaseType.Mysql, MYSQL_URL, 2, 1, 30))
+
+
+def check_temporal_decimal_and_blob():
+    with client() as test_client:
+        row = test_client.post("/temporal").json()
+    assert row["types"] == ["datetime", "date", "time", "str", "bytes"], row
+    assert row["at_datetime"] == WHEN.isoformat()
+    assert row["on_date"] == DAY.isoformat()
+    assert row["at_time"] == AT.isoformat()
+    # DECIMAL keeps every digit
+    assert row["price"] == "12345678901234.000001"
+    assert base64.b64decode(row["data"]) == BLOB
+    assert row["matched"] == 1
+
+
+def check_json_columns():
+    with client() as test_client:
+        row = test_client.post("/json").json()
+    assert row["doc"] == {"name": "widget", "sizes": [1, 2], "meta": {"ok": True, "none": None}}
+    assert row["tags"] == ["a", 1, 2.5]
+    # TEXT is not parsed, even when it holds JSON
+    assert row["note"] == '{"kept": "as text"}'
+    assert row["matched"] == 1
+
+
+if __name__ == "__main__":
+    globals()[sys.argv[1]]()
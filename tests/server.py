@@ -1,5 +1,14 @@
 # -*- coding: utf-8 -*-
+import asyncio
+import hashlib
+import os
+import tempfile
+
 from hypern import Hypern, Request, Response
+from hypern.caching import ResponseCache
+from hypern.datastructures import HTTPMethod
+from hypern.hypern import MetricsCounter, Url
+from hypern.middleware import JWTAuthMiddleware, Middleware, MiddlewareConfig
 from hypern.routing import HTTPEndpoint, Route
 from hypern.response import JSONResponse, HTMLResponse, PlainTextResponse, RedirectResponse, FileResponse
 
@@ -86,6 +95,11 @@ class TestFileResponse(HTTPEndpoint):
         return FileResponse(b"Hello", "hello.txt")
 
 
+class RequestHeaders(HTTPEndpoint):
+    def get(self, request: Request):
+        return JSONResponse(dict(request.headers))
+
+
 functional_route = Route("/functional")
 sync_route = Route("/sync")
 
@@ -110,8 +124,18 @@ def delete(request: Request):
     return JSONResponse({"message": MESSAGE})
 
 
+class TrailingSlash(HTTPEndpoint):
+    def get(self, request: Request):
+        return PlainTextResponse("trailing slash")
+
+    def post(self, request: Request):
+        return PlainTextResponse("trailing slash")
+
+
 routes = [
     Route(f"{__base_route__}/default", DefaultRoute),
+    Route(f"{__base_route__}/slash/merge", TrailingSlash, trailing_slash="merge"),
+    Route(f"{__base_route__}/slash/redirect", TrailingSlash, trailing_slash="redirect"),
     Route(f"{__base_route__}/file", RequestFile),
     Route(f"{__base_route__}/sync", SyncQuery),
     Route(f"{__base_route__}/async", AsyncQuery),
@@ -122,24 +146,437 @@ routes = [
     Route(f"{__base_route__}/plain_text", TestPlainTextResponse),
     Route(f"{__base_route__}/redirect", TestRedirectResponse),
     Route(f"{__base_route__}/file", TestFileResponse),
+    Route(f"{__base_route__}/headers", RequestHeaders),
     functional_route,
 ]
 
 app = Hypern(routes=routes)
 app.inject("global_dependencies", "global_dependencies")
 app.inject("router_dependencies", "router_dependencies")
+app.enable_metrics("/metrics")
+app.enable_health(liveness="/healthz", readiness="/readyz")
+app.add_readiness_check("warm", lambda: True, interval=1.0)
+MetricsCounter("server_custom_total", "Counter registered by the test server").inc()
+app.enable_sessions(secret_key="test-secret", old_keys=["old-secret"])
+# the test client connects from loopback, which plays the load balancer
+app.set_trusted_proxies(["127.0.0.0/8", "::1/128"])
+response_cache = ResponseCache()
+app.set_response_cache(response_cache)
+app.set_static_dir("/static", os.path.join(os.path.dirname(__file__), "static"))
+app.set_debug_headers()
+app.enable_security_headers(csp="default-src 'self'")
+app.enable_rate_limit(limit=100000, window_secs=60, exempt_paths=[f"{__base_route__}/ratelimit/exempt"])
+
+
+# registered without the python dispatcher so the exception reaches the server
+def raise_exception(request: Request, **kwargs):
+    raise RuntimeError("handler failed")
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/raise", raise_exception)
+
+
+def dynamic_route(request: Request, **kwargs):
+    return PlainTextResponse("dynamic")
+
+
+# registers another route while the server is running
+def register_route(request: Request, **kwargs):
+    try:
+        app.add_route(HTTPMethod.GET, f"{__base_route__}/dynamic", dynamic_route)
+    except ValueError:
+        pass
+    return PlainTextResponse("registered")
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/register", register_route)
+
+
+def return_dict(request: Request, **kwargs):
+    return {"ok": True, "items": [1, 2]}
+
+
+def return_tuple(request: Request, **kwargs):
+    return "created", 201, {"X-Custom": "custom"}
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/direct/dict", return_dict)
+app.add_route(HTTPMethod.GET, f"{__base_route__}/direct/tuple", return_tuple)
+
+
+# receives a single dependency by parameter name instead of the inject dict
+def named_dependency(request: Request, global_dependencies):
+    return PlainTextResponse(global_dependencies)
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/inject/named", named_dependency)
+
+
+# every appended value is sent as its own header line
+def multi_header(request: Request, **kwargs):
+    response = PlainTextResponse("ok")
+    response.headers.append("Set-Cookie", "a=1")
+    response.headers.append("Set-Cookie", "b=2")
+    return response
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/headers/multi", multi_header)
+
+
+def cookies(request: Request, **kwargs):
+    response = PlainTextResponse(request.cookies.get("theme", ""))
+    response.set_cookie("session", "abc", max_age=60, httponly=True, samesite="lax")
+    response.set_cookie("theme", "dark")
+    response.delete_cookie("old")
+    return response
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/cookies", cookies)
+
+
+def session_counter(request: Request, **kwargs):
+    request.session["visits"] = request.session.get("visits", 0) + 1
+    return PlainTextResponse(str(request.session["visits"]))
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/session", session_counter)
+
+
+def form_echo(request: Request, **kwargs):
+    return {"form": request.form(), "raw": request.body.raw.decode()}
+
+
+app.add_route(HTTPMethod.POST, f"{__base_route__}/form", form_echo)
+
+
+def raw_body_digest(request: Request, **kwargs):
+    return PlainTextResponse(hashlib.sha256(request.raw_body()).hexdigest())
+
+
+app.add_route(HTTPMethod.POST, f"{__base_route__}/raw-body", raw_body_digest)
+
+
+async def stream_body_digest(request: Request, **kwargs):
+    digest = hashlib.sha256()
+    chunks = 0
+    async for chunk in request.streaming_body():
+        digest.update(chunk)
+        chunks += 1
+    try:
+        request.body
+        body_error = ""
+    except RuntimeError:
+        body_error = "RuntimeError"
+    return JSONResponse({"digest": digest.hexdigest(), "chunks": chunks, "body_error": body_error})
+
+
+async def stream_body_buffered(request: Request, **kwargs):
+    try:
+        request.streaming_body()
+    except RuntimeError:
+        return PlainTextResponse("RuntimeError")
+    return PlainTextResponse("streamed")
+
+
+app.add_route(HTTPMethod.POST, f"{__base_route__}/stream-body", stream_body_digest, stream_body=True)
+app.add_route(HTTPMethod.POST, f"{__base_route__}/stream-body/buffered", stream_body_buffered)
+
+
+def json_repr(request: Request, **kwargs):
+    return PlainTextResponse(repr(request.json()))
+
+
+app.add_route(HTTPMethod.POST, f"{__base_route__}/json/repr", json_repr)
+
+
+def query_echo(request: Request, **kwargs):
+    query = request.query_params
+    return {
+        "a": query.get_all("a"),
+        "first": query.get("a"),
+        "flag": query.get("flag"),
+        "q": query.get("q"),
+        "n": query.get_int("n", 0),
+        "debug": query.get_bool("debug", False),
+        "flat": query.to_dict(flat=True),
+    }
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/query/echo", query_echo)
+
+
+def upload_info(request: Request, **kwargs):
+    files = {}
+    for file in request.body.files:
+        head = file.read(4)
+        rest = file.read()
+        spooled = file.path is not None
+        dest = os.path.join(tempfile.mkdtemp(), file.filename)
+        file.save(dest)
+        with open(dest, "rb") as saved:
+            saved_digest = hashlib.sha256(saved.read()).hexdigest()
+        files[file.name] = {
+            "filename": file.filename,
+            "size": file.size,
+            "spooled": spooled,
+            "digest": hashlib.sha256(file.content).hexdigest(),
+            "read_digest": hashlib.sha256(head + rest).hexdigest(),
+            "saved_digest": saved_digest,
+        }
+    return {"files": files}
+
+
+app.add_route(HTTPMethod.POST, f"{__base_route__}/upload", upload_info)
+
+
+def client_address(request: Request, **kwargs):
+    return {"remote_addr": request.remote_addr, "client_ip": request.client_ip}
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/client-ip", client_address)
+
+
+def state_echo(request: Request, **kwargs):
+    request.state["seen_by"] = "handler"
+    return {"user": request.state["user"]}
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/state", state_echo)
+
+
+def url_info(request: Request, **kwargs):
+    url = request.url
+    return {
+        "scheme": url.scheme,
+        "host": url.host,
+        "port": url.port,
+        "path": url.path,
+        "query": url.query,
+        "base_url": request.base_url,
+        "full_url": request.full_url,
+        "canonical_url": Url.from_request(request).to_string(),
+    }
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/url", url_info)
+
+
+def negotiate(request: Request, **kwargs):
+    return {
+        "best": request.accepts("application/json", "text/html"),
+        "json": request.accepts_json,
+        "html": request.accepts_html,
+        "language": request.accept_language(best_of=["en", "vi"]),
+        "first_language": request.accept_language(),
+    }
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/negotiate", negotiate)
+
+
+def default_content_type(request: Request, **kwargs):
+    kind = request.query_params.get("kind")
+    if kind == "bytes":
+        return Response(status_code=200, headers={}, description=b"\x00\x01")
+    if kind == "html":
+        return Response(status_code=200, headers={}, description="<p>hi</p>", response_type="html")
+    if kind == "custom":
+        return Response(status_code=200, headers={"Content-Type": "text/csv"}, description="a,b")
+    if kind == "json-bytes":
+        return Response(status_code=200, headers={}, description=b'{"ok": true}')
+    if kind == "html-document":
+        return Response(status_code=200, headers={}, description="<!DOCTYPE html><html><body>hi</body></html>")
+    if kind == "json-bytes-custom":
+        return Response(status_code=200, headers={"Content-Type": "text/csv"}, description=b'{"ok": true}')
+    if kind == "bad-status":
+        return Response(status_code=700, headers={}, description="oops")
+    return Response(status_code=200, headers={}, description="hi")
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/content-type", default_content_type)
+
+
+def redirect_to(request: Request, **kwargs):
+    if request.query_params.get("permanent"):
+        return Response.redirect("/v2/items", permanent=True)
+    if request.query_params.get("invalid"):
+        return Response.redirect("/nowhere", status=200)
+    return Response.redirect("/target path?q=café")
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/redirect-to", redirect_to)
+
+
+def legacy_items(request: Request, **kwargs):
+    return Response(status_code=200, headers={}, description="legacy")
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/legacy-items", legacy_items, deprecated=True, sunset_date="Wed, 31 Dec 2025 23:59:59 GMT")
+app.add_route(HTTPMethod.GET, f"{__base_route__}/legacy-users", legacy_items, deprecated=True)
+
+
+def etag_catalog(request: Request, **kwargs):
+    return Response(status_code=200, headers={}, description="catalog")
+
+
+def etag_versioned(request: Request, **kwargs):
+    headers = {"ETag": '"v2"', "Last-Modified": "Wed, 21 Oct 2015 07:28:00 GMT"}
+    return Response(status_code=200, headers=headers, description="versioned")
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/etag-catalog", etag_catalog, etag=True)
+app.add_route(HTTPMethod.GET, f"{__base_route__}/etag-versioned", etag_versioned, etag=True)
+app.add_route(HTTPMethod.GET, f"{__base_route__}/etag-off", etag_catalog)
+
+
+
+def range_file(request: Request, **kwargs):
+    return Response.file(os.path.join(os.path.dirname(__file__), "static", "hello.txt"), content_type="text/plain")
+
+
+def range_memory(request: Request, **kwargs):
+    return Response(status_code=200, headers={}, description="0123456789")
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/range/file", range_file)
+app.add_route(HTTPMethod.GET, f"{__base_route__}/range/memory", range_memory)
+
+
+
+def ipfilter_local(request: Request, **kwargs):
+    return Response(status_code=200, headers={}, description="local")
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/ipfilter/private", ipfilter_local, ip_filter={"allow": ["10.0.0.0/8"], "status": 404})
+app.add_route(HTTPMethod.GET, f"{__base_route__}/ipfilter/denied", ipfilter_local, ip_filter={"allow": ["127.0.0.0/8", "::1"], "deny": ["127.0.0.1", "::1"]})
+app.add_route(HTTPMethod.GET, f"{__base_route__}/ipfilter/local", ipfilter_local, ip_filter={"allow": ["127.0.0.0/8", "::1/128"]})
+
+
+
+def openapi_item(request: Request, **kwargs):
+    """
+    Fetch one item
+    ---
+    summary: Fetch one item
+    responses:
+      200:
+        description: The item
+        content:
+          application/json:
+            schema:
+              $ref: Item
+    """
+    return PlainTextResponse("item")
+
+
+app.enable_openapi(path="/openapi-native.json", title="Test API", version="1.0.0", servers=[{"url": "http://localhost:5005"}])
+app.add_schema_component("Item", {"type": "object", "properties": {"id": {"type": "string"}}})
+app.add_security_scheme("bearer", {"type": "http", "scheme": "bearer"})
+app.add_route(HTTPMethod.GET, f"{__base_route__}/openapi/items/:id", openapi_item)
+app.add_route(HTTPMethod.DELETE, f"{__base_route__}/openapi/items/:id", openapi_item, deprecated=True)
+app.add_route(HTTPMethod.GET, f"{__base_route__}/openapi/hidden", openapi_item, include_in_schema=False)
+app.add_route(
+    HTTPMethod.POST,
+    f"{__base_route__}/openapi/items",
+    openapi_item,
+    tags=["admin"],
+    summary="Create an item",
+    operation_id="createItem",
+)
+
+cached_calls = {"count": 0}
+
+
+def cached_products(request: Request, **kwargs):
+    cached_calls["count"] += 1
+    language = request.headers.get("accept-language") or "en"
+    return Response(status_code=200, headers={"X-Language": language}, description=f"products {cached_calls['count']}")
+
+
+def invalidate_products(request: Request, **kwargs):
+    removed = response_cache.invalidate_prefix(f"{__base_route__}/cached-products")
+    return Response(status_code=200, headers={}, description=str(removed))
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/cached-products", cached_products, cache={"ttl": 60, "vary_headers": ["accept-language"]})
+app.add_route(HTTPMethod.POST, f"{__base_route__}/cached-products/invalidate", invalidate_products)
+
+
+class ScopedMiddleware(Middleware):
+    async def after_request(self, response):
+        response.headers.set("x-scoped", "applied")
+        return response
+
+
+def scoped_echo(request: Request, **kwargs):
+    return {"path": request.path}
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/scoped/private", scoped_echo)
+app.add_route(HTTPMethod.GET, f"{__base_route__}/scoped/public/info", scoped_echo)
+app.add_middleware(
+    ScopedMiddleware(MiddlewareConfig(path_patterns=[f"{__base_route__}/scoped/*"], exclude_patterns=[f"{__base_route__}/scoped/public/*"]))
+)
+
+
+class SlowMiddleware(Middleware):
+    async def before_request(self, request):
+        await asyncio.sleep(1)
+        request.headers.set("x-slow", "finished")
+        return request
+
+
+def slow_echo(request: Request, **kwargs):
+    return {"finished": request.headers.get("x-slow") == "finished"}
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/slow/skipped", slow_echo)
+app.add_route(HTTPMethod.GET, f"{__base_route__}/slow/required", slow_echo)
+app.add_middleware(SlowMiddleware(MiddlewareConfig(is_conditional=False, path_patterns=[f"{__base_route__}/slow/skipped"], timeout_ms=100)))
+app.add_middleware(SlowMiddleware(MiddlewareConfig(is_conditional=True, path_patterns=[f"{__base_route__}/slow/required"], timeout_ms=100)))
+
+
+def rate_limited(request: Request, **kwargs):
+    return PlainTextResponse("ok")
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/ratelimit/strict", rate_limited, rate_limit={"limit": 3, "window_secs": 60})
+app.add_route(HTTPMethod.GET, f"{__base_route__}/ratelimit/exempt", rate_limited, rate_limit={"limit": 1, "window_secs": 60})
+
+
+def not_found(request: Request):
+    return {"error": "not_found", "path": request.path}
+
+
+def method_not_allowed(request: Request):
+    return Response(status_code=405, headers={}, description=f"{request.method} not allowed")
+
+
+app.set_404_handler(not_found)
+app.set_405_handler(method_not_allowed)
+
+
+def jwt_me(request: Request, **kwargs):
+    return {"sub": request.state["claims"]["sub"]}
+
+
+app.add_route(HTTPMethod.GET, f"{__base_route__}/jwt/me", jwt_me)
+app.add_middleware(JWTAuthMiddleware("jwt-secret", config=MiddlewareConfig(path_patterns=[f"{__base_route__}/jwt/*"])))
 
 
 # --- Global ---
 @app.before_request()
 def global_before_request(request: Request):
     request.headers.set("global_before", "global_before_request")
+    request.state["user"] = "alice"
     return request
 
 
 @app.after_request()
 def global_after_request(response: Response):
     response.headers.set("global_after", "global_after_request")
+    if response.state is not None and "seen_by" in response.state:
+        response.headers.set("x-state-seen-by", response.state["seen_by"])
     return response
 
 
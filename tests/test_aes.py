@@ -0,0 +1,32 @@
+import os
+
+import pytest
+
+from hypern.security import decrypt_aes_gcm, encrypt_aes_gcm
+
+
+def test_roundtrip():
+    key = os.urandom(32)
+    encrypted = encrypt_aes_gcm(b"secret data", key)
+    assert len(encrypted) == 12 + len(b"secret data") + 16
+    assert decrypt_aes_gcm(encrypted, key) == b"secret data"
+
+
+def test_nonce_is_random():
+    key = os.urandom(32)
+    assert encrypt_aes_gcm(b"same", key) != encrypt_aes_gcm(b"same", key)
+
+
+def test_short_key():
+    with pytest.raises(ValueError):
+        encrypt_aes_gcm(b"data", b"too short")
+
+
+def test_tampered_data():
+    key = os.urandom(32)
+    encrypted = bytearray(encrypt_aes_gcm(b"secret data", key))
+    encrypted[-1] ^= 1
+    with pytest.raises(ValueError):
+        decrypt_aes_gcm(bytes(encrypted), key)
+    with pytest.raises(ValueError):
+        decrypt_aes_gcm(encrypt_aes_gcm(b"secret data", key), os.urandom(32))
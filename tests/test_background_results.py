@@ -0,0 +1,43 @@
+import time
+
+from hypern.background import BackgroundTask, BackgroundTasks
+
+
+def double(value):
+    return value * 2
+
+
+def fail():
+    raise ValueError("boom")
+
+
+def slow():
+    time.sleep(2)
+    return "late"
+
+
+def test_get_all_results():
+    tasks = BackgroundTasks()
+    doubled = tasks.add_task(BackgroundTask(double, [21], {}, None))
+    failed = tasks.add_task(BackgroundTask(fail, [], {}, None))
+    tasks.execute_all()
+
+    deadline = time.time() + 5
+    while not (tasks.is_task_complete(doubled) and tasks.is_task_complete(failed)) and time.time() < deadline:
+        time.sleep(0.05)
+
+    results = tasks.get_all_results()
+    assert results[doubled] == {"success": True, "result": 42, "error": None}
+    assert results[failed]["success"] is False
+    assert "boom" in results[failed]["error"]
+
+    # collected results are only returned once
+    assert tasks.get_all_results() == {}
+
+
+def test_unfinished_tasks_are_left_out():
+    tasks = BackgroundTasks()
+    pending = tasks.add_task(BackgroundTask(slow, [], {}, None))
+    tasks.execute_all()
+
+    assert pending not in tasks.get_all_results()
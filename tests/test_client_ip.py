@@ -0,0 +1,32 @@
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+def client_address(function_type: str, headers: dict) -> dict:
+    res = requests.get(f"{BASE_URL}/{function_type}/client-ip", headers=headers)
+    assert res.status_code == 200
+    return res.json()
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_client_ip_without_proxy_headers(function_type: str, session):
+    address = client_address(function_type, {})
+    assert address["remote_addr"] in ("127.0.0.1", "::1", "::ffff:127.0.0.1")
+    assert address["client_ip"] == "127.0.0.1" or address["client_ip"] == "::1"
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_client_ip_from_x_forwarded_for(function_type: str, session):
+    # the leftmost entry was sent by the client and can't be trusted
+    address = client_address(function_type, {"X-Forwarded-For": "1.1.1.1, 203.0.113.7, 127.0.0.2"})
+    assert address["client_ip"] == "203.0.113.7"
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_client_ip_from_forwarded(function_type: str, session):
+    address = client_address(function_type, {"Forwarded": 'for="[2001:db8:cafe::17]:4711";proto=https'})
+    assert address["client_ip"] == "2001:db8:cafe::17"
@@ -0,0 +1,88 @@
+import pytest
+import requests
+
+from hypern import Hypern, Request
+from hypern.datastructures import HTTPMethod
+from hypern.hypern import Server
+from hypern.response import JSONResponse, PlainTextResponse, Response
+
+
+def test_valid_compression_config():
+    server = Server()
+    server.set_compression_config(["gzip"], 9)
+    server.set_compression_config(["br"], 11)
+    server.set_compression_config(["gzip", "brotli", "deflate"])
+    server.set_compression_config(["zstd"], 22)
+    server.set_compression_config(["br", "gzip"], {"br": 11, "gzip": 6})
+    server.set_compression_config()
+    server.set_compression_config(min_size=0, content_types=["*/*"])
+
+
+@pytest.mark.parametrize(
+    "algorithms,level",
+    [
+        ([], None),
+        (["lz4"], None),
+        (["gzip"], 10),
+        (["br", "deflate"], 11),
+        (["br"], 12),
+        (["zstd"], 23),
+        (["br"], {"gzip": 6}),
+        (["br", "gzip"], {"gzip": 10}),
+    ],
+)
+def test_invalid_compression_config(algorithms, level):
+    with pytest.raises(ValueError):
+        Server().set_compression_config(algorithms, level)
+
+
+def test_invalid_content_type():
+    with pytest.raises(ValueError):
+        Server().set_compression_config(content_types=["json"])
+
+
+app = Hypern(auto_compression=True)
+app.set_compression_config(algorithms=["gzip", "deflate"], min_size=1024, content_types=["application/json", "text/*"])
+
+
+def large_json(request: Request, **kwargs):
+    return JSONResponse({"items": ["x" * 32] * 100})
+
+
+def small_text(request: Request, **kwargs):
+    return PlainTextResponse("tiny")
+
+
+def large_binary(request: Request, **kwargs):
+    return Response(b"\0" * 4096, headers={"Content-Type": "application/octet-stream"})
+
+
+app.add_route(HTTPMethod.GET, "/large-json", large_json)
+app.add_route(HTTPMethod.GET, "/small-text", small_text)
+app.add_route(HTTPMethod.GET, "/large-binary", large_binary)
+
+
+@pytest.fixture(scope="module")
+def base_url():
+    handle = app.serve(port=5108)
+    yield "http://127.0.0.1:5108"
+    handle.stop()
+
+
+def test_compresses_allowed_types(base_url):
+    response = requests.get(f"{base_url}/large-json", headers={"Accept-Encoding": "deflate, gzip"}, timeout=5)
+    # the order of algorithms wins over the order of the client
+    assert response.headers["Content-Encoding"] == "gzip"
+    assert len(response.json()["items"]) == 100
+
+
+def test_skips_small_bodies(base_url):
+    response = requests.get(f"{base_url}/small-text", headers={"Accept-Encoding": "gzip"}, timeout=5)
+    assert "Content-Encoding" not in response.headers
+    assert response.text == "tiny"
+
+
+def test_skips_other_types(base_url):
+    response = requests.get(f"{base_url}/large-binary", headers={"Accept-Encoding": "gzip"}, timeout=5)
+    assert "Content-Encoding" not in response.headers
+    assert len(response.content) == 4096
@@ -0,0 +1,44 @@
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+def negotiate(function_type: str, headers: dict) -> dict:
+    res = requests.get(f"{BASE_URL}/{function_type}/negotiate", headers=headers)
+    assert res.status_code == 200
+    return res.json()
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_accept_prefers_highest_quality(function_type: str, session):
+    result = negotiate(function_type, {"Accept": "text/html, application/json;q=0.9, */*;q=0.1"})
+    assert result["best"] == "text/html"
+    assert result["json"] is True
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_accept_excluded_type(function_type: str, session):
+    result = negotiate(function_type, {"Accept": "text/*, text/html;q=0"})
+    assert result["best"] is None
+    assert result["html"] is False
+    assert result["json"] is False
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_malformed_accept_accepts_anything(function_type: str, session):
+    result = negotiate(function_type, {"Accept": "garbage;q=nope"})
+    assert result["best"] == "application/json"
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_accept_language(function_type: str, session):
+    result = negotiate(function_type, {"Accept-Language": "vi-VN, en;q=0.8"})
+    assert result["language"] == "vi"
+    assert result["first_language"] == "vi-VN"
+
+    result = negotiate(function_type, {"Accept-Language": "fr, en-GB;q=0.5"})
+    assert result["language"] == "en"
@@ -0,0 +1,43 @@
+import pytest
+from tests.utils import get
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_request_cookies(function_type: str, session):
+    res = get(f"/{function_type}/cookies", headers={"Cookie": "theme=blue; lang=en"})
+    assert res.status_code == 200
+    assert res.text == "blue"
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_set_cookie(function_type: str, session):
+    res = get(f"/{function_type}/cookies")
+    assert res.raw.headers.getlist("Set-Cookie") == [
+        "session=abc; Max-Age=60; Path=/; HttpOnly; SameSite=Lax",
+        "theme=dark; Path=/",
+        "old=; Max-Age=0; Expires=Thu, 01 Jan 1970 00:00:00 GMT; Path=/",
+    ]
+
+
+@pytest.mark.parametrize(
+    "attributes",
+    [
+        {"path": "/\r\nX-Injected: 1"},
+        {"path": "/; Secure"},
+        {"domain": "example.com\n"},
+        {"domain": "example.com; HttpOnly"},
+        {"expires": "Thu, 01 Jan 1970 00:00:00 GMT\r\n"},
+        {"expires": "Thu, 01 Jan 1970 00:00:00 GMT; Path=/admin"},
+    ],
+)
+def test_set_cookie_rejects_injected_attributes(attributes):
+    from hypern.response import PlainTextResponse
+
+    response = PlainTextResponse("")
+    with pytest.raises(ValueError):
+        response.set_cookie("session", "abc", **attributes)
+    if "expires" not in attributes:
+        with pytest.raises(ValueError):
+            response.delete_cookie("session", **attributes)
@@ -0,0 +1,85 @@
+import pytest
+import requests
+
+from hypern import Hypern, Request
+from hypern.datastructures import HTTPMethod
+from hypern.hypern import CsrfConfig
+from hypern.response import PlainTextResponse
+
+BASE = "http://127.0.0.1:5112"
+
+app = Hypern()
+app.set_csrf_config(CsrfConfig(secret="csrf-test-secret"))
+
+
+def token(request: Request, **kwargs):
+    return PlainTextResponse(request.csrf_token)
+
+
+def submit(request: Request, **kwargs):
+    return PlainTextResponse("accepted")
+
+
+app.add_route(HTTPMethod.GET, "/token", token)
+app.add_route(HTTPMethod.POST, "/submit", submit)
+app.add_route(HTTPMethod.GET, "/submit", submit)
+
+
+@pytest.fixture(scope="module")
+def client():
+    handle = app.serve(port=5112)
+    try:
+        client = requests.Session()
+        res = client.get(f"{BASE}/token", timeout=5)
+        assert res.status_code == 200
+        assert client.cookies["csrftoken"] == res.text
+        yield client
+    finally:
+        handle.stop()
+
+
+def test_safe_method_needs_no_token(client):
+    res = requests.get(f"{BASE}/submit", timeout=5)
+    assert res.status_code == 200
+    assert res.text == "accepted"
+    # a request without the cookie is issued a token
+    assert "csrftoken" in res.cookies
+
+
+def test_missing_token_is_rejected(client):
+    res = client.post(f"{BASE}/submit", timeout=5)
+    assert res.status_code == 403
+
+
+def test_missing_cookie_is_rejected(client):
+    res = requests.post(
+        f"{BASE}/submit",
+        headers={"X-CSRF-Token": client.cookies["csrftoken"]},
+        timeout=5,
+    )
+    assert res.status_code == 403
+
+
+def test_bad_token_is_rejected(client):
+    res = client.post(f"{BASE}/submit", headers={"X-CSRF-Token": "forged"}, timeout=5)
+    assert res.status_code == 403
+
+
+def test_valid_header_token_is_accepted(client):
+    res = client.post(
+        f"{BASE}/submit",
+        headers={"X-CSRF-Token": client.cookies["csrftoken"]},
+        timeout=5,
+    )
+    assert res.status_code == 200
+    assert res.text == "accepted"
+
+
+def test_valid_form_token_is_accepted(client):
+    res = client.post(
+        f"{BASE}/submit",
+        data={"csrf_token": client.cookies["csrftoken"]},
+        timeout=5,
+    )
+    assert res.status_code == 200
+    assert res.text == "accepted"
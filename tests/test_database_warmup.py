@@ -0,0 +1,14 @@
+from hypern import Hypern
+from hypern.hypern import database_pool_status
+
+from tests.database import sqlite_config
+
+app = Hypern()
+
+
+def test_warmup_opens_min_connections():
+    # sqlite_config() asks for 2 connections with warmup
+    with app.test_client(database_config=sqlite_config()):
+        status = database_pool_status()
+        assert status["size"] >= 2
+        assert status["idle"] <= status["size"]
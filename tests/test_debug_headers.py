@@ -0,0 +1,13 @@
+import pytest
+from tests.utils import get
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_timing_headers(function_type: str, session):
+    res = get(f"/{function_type}/state")
+    # the global before and after hooks
+    assert float(res.headers["x-middleware-1-time-ms"]) >= 0
+    assert float(res.headers["x-middleware-2-time-ms"]) >= 0
+    assert "x-middleware-3-time-ms" not in res.headers
+    assert float(res.headers["x-handler-time-ms"]) >= 0
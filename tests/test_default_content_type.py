@@ -0,0 +1,39 @@
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize(
+    "kind, content_type",
+    [
+        ("text", "text/plain; charset=utf-8"),
+        ("bytes", "application/octet-stream"),
+        ("html", "text/html; charset=utf-8"),
+        ("custom", "text/csv"),
+        ("json-bytes", "application/json"),
+        ("html-document", "text/html; charset=utf-8"),
+        ("json-bytes-custom", "text/csv"),
+    ],
+)
+def test_default_content_type(kind: str, content_type: str, session):
+    res = requests.get(f"{BASE_URL}/benchmark/content-type", params={"kind": kind})
+    assert res.status_code == 200
+    assert res.headers["content-type"] == content_type
+
+
+@pytest.mark.benchmark
+def test_invalid_status_code(session):
+    res = requests.get(f"{BASE_URL}/benchmark/content-type", params={"kind": "bad-status"})
+    assert res.status_code == 500
+
+
+def test_response_type_detection():
+    from hypern.hypern import Response
+
+    assert Response(200, {}, b'[1, 2]').response_type == "json"
+    assert Response(200, {}, b"1").response_type == "binary"
+    assert Response(200, {}, b"{not json").response_type == "binary"
+    assert Response(200, {}, "  <html><body></body></html>").response_type == "html"
+    assert Response(200, {}, '{"a": 1}').response_type == "text"
+    assert Response(200, {"Content-Type": "text/plain"}, b'{"a": 1}').response_type == "binary"
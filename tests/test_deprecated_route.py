@@ -0,0 +1,25 @@
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+@pytest.mark.benchmark
+def test_deprecated_route_headers(session):
+    res = requests.get(f"{BASE_URL}/benchmark/legacy-items")
+    assert res.status_code == 200
+    assert res.text == "legacy"
+    assert res.headers["deprecation"] == "true"
+    assert res.headers["sunset"] == "Wed, 31 Dec 2025 23:59:59 GMT"
+
+
+@pytest.mark.benchmark
+def test_deprecated_route_without_sunset(session):
+    res = requests.get(f"{BASE_URL}/benchmark/legacy-users")
+    assert res.headers["deprecation"] == "true"
+    assert "sunset" not in res.headers
+
+
+@pytest.mark.benchmark
+def test_route_not_deprecated(session):
+    res = requests.get(f"{BASE_URL}/benchmark/redirect-to", allow_redirects=False)
+    assert "deprecation" not in res.headers
@@ -0,0 +1,21 @@
+import pytest
+from tests.utils import get
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_return_dict(function_type: str, session):
+    res = get(f"/{function_type}/direct/dict")
+    assert res.status_code == 200
+    assert res.headers["content-type"] == "application/json"
+    assert res.json() == {"ok": True, "items": [1, 2]}
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_return_tuple(function_type: str, session):
+    res = get(f"/{function_type}/direct/tuple")
+    assert res.status_code == 201
+    assert res.headers["content-type"] == "text/plain"
+    assert res.headers["x-custom"] == "custom"
+    assert res.text == "created"
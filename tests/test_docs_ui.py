@@ -0,0 +1,76 @@
+import pathlib
+
+import pytest
+import requests
+
+from hypern import Hypern
+from hypern.openapi import ReDocUI, SwaggerUI
+
+BUNDLES = pathlib.Path(__file__).resolve().parent.parent / "src" / "openapi" / "bundles"
+# the bundles are only compiled in after scripts/fetch_docs_assets.py
+needs_bundles = pytest.mark.skipif(not BUNDLES.is_dir(), reason="docs bundles not fetched")
+
+
+def test_swagger_cdn_assets():
+    html = SwaggerUI(title="Docs", openapi_url="/openapi.json").get_html_content()
+    assert "https://cdn.jsdelivr.net/npm/swagger-ui-dist@5.9.0/swagger-ui-bundle.js" in html
+    assert "url: '/openapi.json'" in html
+    assert "oauth2RedirectUrl" not in html
+
+
+def test_swagger_static_url_assets():
+    swagger = SwaggerUI(title="Docs", openapi_url="/openapi.json", assets_mode="static_url", assets_url="https://assets.example.com/swagger/")
+    html = swagger.get_html_content()
+    assert swagger.assets_mode == "static_url"
+    assert 'href="https://assets.example.com/swagger/swagger-ui.css"' in html
+    assert "cdn.jsdelivr.net" not in html
+    assert "cloudinary" not in html
+
+
+@needs_bundles
+def test_embedded_assets():
+    swagger = SwaggerUI(title="Docs", openapi_url="/openapi.json", assets_mode="embedded")
+    redoc = ReDocUI(title="Docs", openapi_url="/openapi.json", assets_mode="embedded")
+    assert f"{SwaggerUI.EMBEDDED_ASSETS_URL}/swagger-ui-bundle.js" in swagger.get_html_content()
+    assert f"{ReDocUI.EMBEDDED_ASSETS_URL}/redoc.standalone.js" in redoc.get_html_content()
+
+
+def test_invalid_assets_mode():
+    with pytest.raises(ValueError):
+        SwaggerUI(title="Docs", openapi_url="/openapi.json", assets_mode="local")
+    with pytest.raises(ValueError):
+        ReDocUI(title="Docs", openapi_url="/openapi.json", assets_mode="static_url")
+
+
+def test_redoc_html():
+    html = ReDocUI(title="Docs", openapi_url="/openapi.json").get_html_content()
+    assert '<redoc spec-url="/openapi.json"></redoc>' in html
+    assert "https://cdn.jsdelivr.net/npm/redoc@2.1.3/bundles/redoc.standalone.js" in html
+
+
+def test_swagger_oauth2_redirect():
+    swagger = SwaggerUI(title="Docs", openapi_url="/openapi.json", oauth2_redirect_url="/docs/oauth2-redirect")
+    assert "oauth2RedirectUrl: window.location.origin + '/docs/oauth2-redirect'" in swagger.get_html_content()
+    assert "swaggerUIRedirectOauth2" in swagger.get_oauth2_redirect_html()
+
+
+@needs_bundles
+def test_embedded_assets_served():
+    app = Hypern(openapi_url="/openapi.json", docs_url="/docs", docs_assets_mode="embedded")
+    handle = app.serve(port=5109)
+    try:
+        docs = requests.get("http://127.0.0.1:5109/docs", timeout=5)
+        assert f"{SwaggerUI.EMBEDDED_ASSETS_URL}/swagger-ui-bundle.js" in docs.text
+
+        for name, content_type in (
+            ("swagger-ui.css", "text/css"),
+            ("swagger-ui-bundle.js", "text/javascript"),
+            ("redoc.standalone.js", "text/javascript"),
+        ):
+            res = requests.get(f"http://127.0.0.1:5109{SwaggerUI.EMBEDDED_ASSETS_URL}/{name}", timeout=5)
+            assert res.status_code == 200
+            assert res.headers["content-type"].startswith(content_type)
+            assert len(res.content) > 1000
+        assert requests.get(f"http://127.0.0.1:5109{SwaggerUI.EMBEDDED_ASSETS_URL}/missing.js", timeout=5).status_code == 404
+    finally:
+        handle.stop()
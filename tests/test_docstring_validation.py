@@ -0,0 +1,64 @@
+import pytest
+
+from hypern.hypern import FunctionInfo, Route
+from hypern.openapi import SchemaGenerator
+
+
+def valid(request):
+    """
+    Fetch one item
+    ---
+    summary: Fetch one item
+    responses:
+      200:
+        description: The item
+    """
+
+
+def broken_yaml(request):
+    """
+    Broken
+    ---
+    summary: a: b
+    """
+
+
+def bad_shape(request):
+    """
+    Bad shape
+    ---
+    parameters: id
+    responses:
+      ok:
+        description: The item
+    """
+
+
+def route(handler) -> Route:
+    return Route(path="/items", function=FunctionInfo(handler=handler, is_async=False), method="GET")
+
+
+def test_lenient_mode_skips_invalid_docstrings():
+    generator = SchemaGenerator({})
+    assert generator.parse_docstring(broken_yaml) == ""
+    # valid YAML that is not shaped like an operation is left out as well
+    assert generator.parse_docstring(bad_shape) == ""
+    assert '"200"' in generator.parse_docstring(valid)
+
+
+def test_strict_mode_reports_location():
+    generator = SchemaGenerator({}, strict=True)
+    assert '"200"' in generator.parse_docstring(valid)
+    with pytest.raises(ValueError) as error:
+        generator.parse_docstring(broken_yaml)
+    message = str(error.value)
+    assert message.startswith("broken_yaml: line 4 column")
+    assert message.endswith("summary: a: b")
+
+
+def test_validate_all():
+    problems = SchemaGenerator({}).validate_all([route(valid), route(broken_yaml), route(bad_shape)])
+    assert len(problems) == 3
+    assert problems[0].startswith("broken_yaml: line 4")
+    assert "bad_shape: responses: ok is not a status code" in problems
+    assert "bad_shape: parameters must be a list" in problems
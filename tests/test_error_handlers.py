@@ -0,0 +1,20 @@
+import pytest
+from tests.utils import delete, get
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_custom_404(function_type: str, session):
+    res = get(f"/{function_type}/does-not-exist", expected_status_code=404)
+    assert res.status_code == 404
+    assert res.json() == {"error": "not_found", "path": f"/{function_type}/does-not-exist"}
+    # global after hooks run on error pages too
+    assert res.headers["global_after"] == "global_after_request"
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_custom_405(function_type: str, session):
+    res = delete(f"/{function_type}/state", expected_status_code=405)
+    assert res.status_code == 405
+    assert res.text == "DELETE not allowed"
@@ -0,0 +1,63 @@
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+@pytest.mark.benchmark
+def test_etag_computed(session):
+    res = requests.get(f"{BASE_URL}/benchmark/etag-catalog")
+    assert res.status_code == 200
+    assert res.text == "catalog"
+    etag = res.headers["etag"]
+    assert etag.startswith('"') and etag.endswith('"')
+
+    again = requests.get(f"{BASE_URL}/benchmark/etag-catalog")
+    assert again.headers["etag"] == etag
+
+
+@pytest.mark.benchmark
+def test_if_none_match_returns_304(session):
+    etag = requests.get(f"{BASE_URL}/benchmark/etag-catalog").headers["etag"]
+    res = requests.get(f"{BASE_URL}/benchmark/etag-catalog", headers={"If-None-Match": etag})
+    assert res.status_code == 304
+    assert res.content == b""
+    assert res.headers["etag"] == etag
+
+    res = requests.get(f"{BASE_URL}/benchmark/etag-catalog", headers={"If-None-Match": f"W/{etag}"})
+    assert res.status_code == 304
+
+    res = requests.get(f"{BASE_URL}/benchmark/etag-catalog", headers={"If-None-Match": '"stale"'})
+    assert res.status_code == 200
+    assert res.text == "catalog"
+
+
+@pytest.mark.benchmark
+def test_head_returns_304(session):
+    etag = requests.get(f"{BASE_URL}/benchmark/etag-catalog").headers["etag"]
+    res = requests.head(f"{BASE_URL}/benchmark/etag-catalog", headers={"If-None-Match": etag})
+    assert res.status_code == 304
+
+
+@pytest.mark.benchmark
+def test_handler_etag_kept(session):
+    res = requests.get(f"{BASE_URL}/benchmark/etag-versioned")
+    assert res.headers["etag"] == '"v2"'
+
+    res = requests.get(f"{BASE_URL}/benchmark/etag-versioned", headers={"If-None-Match": '"v1", "v2"'})
+    assert res.status_code == 304
+
+
+@pytest.mark.benchmark
+def test_if_modified_since(session):
+    url = f"{BASE_URL}/benchmark/etag-versioned"
+    res = requests.get(url, headers={"If-Modified-Since": "Thu, 22 Oct 2015 07:28:00 GMT"})
+    assert res.status_code == 304
+
+    res = requests.get(url, headers={"If-Modified-Since": "Tue, 20 Oct 2015 07:28:00 GMT"})
+    assert res.status_code == 200
+
+
+@pytest.mark.benchmark
+def test_etag_not_enabled(session):
+    res = requests.get(f"{BASE_URL}/benchmark/etag-off")
+    assert "etag" not in res.headers
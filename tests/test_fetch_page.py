@@ -0,0 +1,75 @@
+from hypern import Hypern, Request
+from hypern.datastructures import HTTPMethod
+from hypern.hypern import get_session_database
+from hypern.response import JSONResponse, PlainTextResponse
+
+from tests.database import sqlite_config
+
+app = Hypern()
+
+
+def setup(request: Request, **kwargs):
+    tx = get_session_database(request.context_id)
+    tx.execute("CREATE TABLE IF NOT EXISTS page_items (id INTEGER NOT NULL, kind TEXT NOT NULL)", [])
+    tx.execute("DELETE FROM page_items", [])
+    for id in range(1, 6):
+        tx.execute("INSERT INTO page_items (id, kind) VALUES (?, ?)", [id, "odd" if id % 2 else "even"])
+    return PlainTextResponse("ok")
+
+
+def page(request: Request, **kwargs):
+    tx = get_session_database(request.context_id)
+    params = request.query_params
+    try:
+        result = tx.fetch_page(
+            "SELECT id FROM page_items WHERE kind LIKE ? ORDER BY id;",
+            [params.get("kind", "%")],
+            int(params.get("page")),
+            int(params.get("size")),
+        )
+    except ValueError as e:
+        return PlainTextResponse(str(e), status_code=400)
+    result["items"] = [row["id"] for row in result["items"]]
+    return JSONResponse(result)
+
+
+app.add_route(HTTPMethod.POST, "/setup", setup)
+app.add_route(HTTPMethod.GET, "/page", page)
+
+
+def test_pages():
+    with app.test_client(database_config=sqlite_config()) as client:
+        client.post("/setup").assert_status(200)
+
+        first = client.get("/page", params={"page": "0", "size": "2"}).json()
+        assert first == {"items": [1, 2], "total": 5, "page": 0, "page_size": 2, "total_pages": 3}
+        last = client.get("/page", params={"page": "2", "size": "2"}).json()
+        assert last["items"] == [5]
+        assert last["total"] == 5
+
+        # params apply to the count as well
+        odd = client.get("/page", params={"page": "0", "size": "10", "kind": "odd"}).json()
+        assert odd["items"] == [1, 3, 5]
+        assert odd["total"] == 3
+        assert odd["total_pages"] == 1
+
+
+def test_page_past_the_end_is_empty():
+    with app.test_client(database_config=sqlite_config()) as client:
+        client.post("/setup").assert_status(200)
+        beyond = client.get("/page", params={"page": "3", "size": "2"}).json()
+        assert beyond["items"] == []
+        assert beyond["total"] == 5
+        assert beyond["total_pages"] == 3
+
+        none = client.get("/page", params={"page": "0", "size": "2", "kind": "none"}).json()
+        assert none["items"] == []
+        assert none["total"] == 0
+        assert none["total_pages"] == 0
+
+
+def test_page_bounds():
+    with app.test_client(database_config=sqlite_config()) as client:
+        client.post("/setup").assert_status(200)
+        client.get("/page", params={"page": "-1", "size": "2"}).assert_status(400)
+        client.get("/page", params={"page": "0", "size": "0"}).assert_status(400)
@@ -0,0 +1,19 @@
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_form_urlencoded(function_type: str, session):
+    body = "name=John+Doe&scope=read&scope=write&plus=a%2Bb"
+    res = requests.post(
+        f"{BASE_URL}/{function_type}/form",
+        data=body,
+        headers={"Content-Type": "application/x-www-form-urlencoded"},
+    )
+    assert res.status_code == 200
+    assert res.json() == {
+        "form": {"name": "John Doe", "scope": ["read", "write"], "plus": "a+b"},
+        "raw": body,
+    }
@@ -0,0 +1,11 @@
+import pytest
+from tests.utils import get
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_unhandled_exception(function_type: str, session):
+    res = get(f"/{function_type}/raise")
+    assert res.status_code == 500
+    assert res.text == "Internal Server Error"
+    assert "handler failed" not in res.text
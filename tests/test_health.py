@@ -0,0 +1,107 @@
+import os
+import subprocess
+import sys
+
+import pytest
+import requests
+from hypern import Hypern, Request
+from hypern.datastructures import HTTPMethod
+from hypern.hypern import Server
+from hypern.response import PlainTextResponse
+from tests.utils import get
+
+
+@pytest.mark.benchmark
+def test_liveness(session):
+    res = get("/healthz")
+    assert res.status_code == 200
+    assert res.json() == {"status": "ok"}
+
+
+@pytest.mark.benchmark
+def test_readiness(session):
+    res = get("/readyz")
+    assert res.status_code == 200
+    assert res.json() == {"status": "ok", "checks": {"warm": "ok"}}
+
+
+def test_failed_startup_stays_unready():
+    app = Hypern()
+
+    def hello(request: Request, **kwargs):
+        return PlainTextResponse("hello")
+
+    def startup():
+        raise RuntimeError("no upstream")
+
+    app.add_route(HTTPMethod.GET, "/hello", hello)
+    app.on_startup(startup)
+    app.enable_health()
+
+    handle = app.serve(port=5111)
+    try:
+        assert requests.get("http://127.0.0.1:5111/healthz", timeout=5).status_code == 200
+        assert requests.get("http://127.0.0.1:5111/readyz", timeout=5).status_code == 503
+        assert requests.get("http://127.0.0.1:5111/hello", timeout=5).text == "hello"
+    finally:
+        handle.stop()
+
+
+def test_database_health_without_database():
+    # the database connection is process-wide, other tests may have opened one
+    script = """
+from hypern.hypern import check_database_health
+try:
+    check_database_health()
+except RuntimeError as e:
+    print(e)
+"""
+    root = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
+    result = subprocess.run([sys.executable, "-c", script], cwd=root, capture_output=True, text=True, timeout=60)
+    assert result.returncode == 0, result.stderr
+    assert result.stdout.strip() == "Database connection is not configured"
+
+
+@pytest.mark.parametrize("path", ["healthz", ""])
+def test_health_path_needs_leading_slash(path: str):
+    with pytest.raises(ValueError):
+        Hypern().enable_health(liveness=path)
+    with pytest.raises(ValueError):
+        Server().enable_health(path, "/readyz")
+    with pytest.raises(ValueError):
+        Server().set_health_check(path)
+
+
+def test_endpoint_paths_must_differ():
+    with pytest.raises(ValueError):
+        Hypern().enable_health(liveness="/health", readiness="/health")
+
+    server = Server()
+    server.enable_health("/healthz", "/readyz")
+    with pytest.raises(ValueError, match="already served by the Readiness endpoint"):
+        server.set_metrics_path("/readyz")
+    with pytest.raises(ValueError, match="already served by the Liveness endpoint"):
+        server.enable_openapi("/healthz", "API", "1.0", None)
+    # enabling the probes again replaces their paths
+    server.enable_health("/readyz", "/healthz")
+
+    server.enable_metrics("/metrics")
+    with pytest.raises(ValueError, match="already served by the Metrics endpoint"):
+        server.enable_health("/metrics", "/readyz")
+    with pytest.raises(ValueError, match="already served by the Metrics endpoint"):
+        server.set_health_check("/metrics")
+
+
+def test_set_health_check_serves_readiness():
+    app = Hypern()
+    app.set_health_check("/status")
+
+    handle = app.serve(port=5114)
+    try:
+        res = requests.get("http://127.0.0.1:5114/status", timeout=5)
+        assert res.status_code == 200
+        assert res.json()["status"] == "ok"
+        # no liveness probe next to it
+        assert requests.get("http://127.0.0.1:5114/healthz", timeout=5).status_code == 404
+    finally:
+        handle.stop()
@@ -0,0 +1,45 @@
+import pytest
+import requests
+
+from hypern import Hypern, Request
+from hypern.datastructures import HTTPMethod
+from hypern.hypern import Server
+from hypern.response import PlainTextResponse
+
+app = Hypern()
+app.set_http_options(max_header_bytes=8192, keep_alive_secs=5, tcp_nodelay=True)
+
+
+def hello(request: Request, **kwargs):
+    return PlainTextResponse("hello")
+
+
+app.add_route(HTTPMethod.GET, "/hello", hello)
+
+
+@pytest.fixture(scope="module")
+def base_url():
+    handle = app.serve(port=5107)
+    yield "http://127.0.0.1:5107"
+    handle.stop()
+
+
+def test_headers_within_limit(base_url):
+    response = requests.get(f"{base_url}/hello", headers={"X-Data": "a" * 1024}, timeout=5)
+    assert response.status_code == 200
+    assert response.text == "hello"
+
+
+def test_oversized_headers(base_url):
+    response = requests.get(f"{base_url}/hello", headers={"X-Data": "a" * 16384}, timeout=5)
+    assert response.status_code == 431
+
+
+def test_invalid_options():
+    server = Server()
+    with pytest.raises(ValueError):
+        server.set_http_options(max_header_bytes=1024)
+    with pytest.raises(ValueError):
+        server.set_http_options(h2_max_concurrent_streams=0)
+    with pytest.raises(ValueError):
+        server.set_http_options(backlog=0)
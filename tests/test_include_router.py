@@ -0,0 +1,54 @@
+import pytest
+import requests
+from hypern import Hypern, Request
+from hypern.hypern import FunctionInfo, Route, Router
+from hypern.response import PlainTextResponse
+
+
+def list_users(request: Request, **kwargs):
+    return PlainTextResponse("users")
+
+
+def get_user(request: Request, **kwargs):
+    return PlainTextResponse(f"user {request.path_params['id']}")
+
+
+def make_users_router():
+    router = Router("/")
+    router.add_route(Route("/users", FunctionInfo(handler=list_users, is_async=False), "GET"))
+    router.add_route(Route("/users/:id", FunctionInfo(handler=get_user, is_async=False), "GET"))
+    return router
+
+
+@pytest.mark.parametrize("prefix", ["/v1", "/v1/", "v1", "//v1//"])
+def test_nested_prefix(prefix: str):
+    v1 = Router("/")
+    v1.include_router(make_users_router(), prefix)
+    api = Router("/api/")
+    api.include_router(v1, "/")
+
+    assert sorted(api.route_summary()) == [("GET", "/api/v1/users", False), ("GET", "/api/v1/users/:id", True)]
+    assert api.find_matching_route("/api/v1/users/7", "GET").path == "/api/v1/users/:id"
+
+
+def test_mount_rejects_colliding_routes():
+    router = Router("/")
+    router.include_router(make_users_router(), "/v1")
+    with pytest.raises(ValueError):
+        router.include_router(make_users_router(), "/v1/")
+    assert router.route_count() == 2
+
+
+def test_mounted_parameterized_route_served():
+    app = Hypern()
+    app.router.include_router(make_users_router(), "/api/v1/")
+
+    handle = app.serve(port=5115)
+    try:
+        assert requests.get("http://127.0.0.1:5115/api/v1/users", timeout=5).text == "users"
+        res = requests.get("http://127.0.0.1:5115/api/v1/users/42", timeout=5)
+        assert res.status_code == 200
+        assert res.text == "user 42"
+        assert requests.get("http://127.0.0.1:5115/users/42", timeout=5).status_code == 404
+    finally:
+        handle.stop()
@@ -0,0 +1,41 @@
+import pytest
+import requests
+
+from hypern.middleware import IpFilterConfig
+from tests.utils import BASE_URL
+
+
+def test_invalid_cidr_rejected():
+    with pytest.raises(ValueError):
+        IpFilterConfig(["10.0.0.0/33"])
+
+
+def test_invalid_mode_rejected():
+    with pytest.raises(ValueError):
+        IpFilterConfig(["10.0.0.0/8"], mode="denylist")
+
+
+def test_invalid_status_rejected():
+    with pytest.raises(ValueError):
+        IpFilterConfig(allow=["10.0.0.0/8"], status=200)
+
+
+@pytest.mark.benchmark
+def test_route_ip_filter(session):
+    # the test server sees requests from localhost
+    res = requests.get(f"{BASE_URL}/benchmark/ipfilter/private")
+    assert res.status_code == 404
+
+    res = requests.get(f"{BASE_URL}/benchmark/ipfilter/denied")
+    assert res.status_code == 403
+
+    res = requests.get(f"{BASE_URL}/benchmark/ipfilter/local")
+    assert res.status_code == 200
+    assert res.text == "local"
+
+
+@pytest.mark.benchmark
+def test_ip_filter_rejections_counted(session):
+    requests.get(f"{BASE_URL}/benchmark/ipfilter/denied")
+    metrics = requests.get(f"{BASE_URL}/metrics").text
+    assert 'hypern_ip_filter_rejections_total{path="/benchmark/ipfilter/denied"}' in metrics
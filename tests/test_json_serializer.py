@@ -0,0 +1,31 @@
+import pytest
+
+from hypern.hypern import Response
+
+
+def test_self_referencing_list():
+    items = []
+    items.append(items)
+    with pytest.raises(ValueError, match=r"Circular reference detected at \$\[0\]"):
+        Response.json(items)
+
+
+def test_self_referencing_dict():
+    order = {"id": 1, "lines": [{}]}
+    order["lines"][0]["order"] = order
+    with pytest.raises(ValueError, match=r"Circular reference detected at \$\.lines\[0\]\.order"):
+        Response.json(order)
+
+
+def test_shared_value_is_not_circular():
+    tags = ["a", "b"]
+    response = Response.json({"first": tags, "second": tags})
+    assert response.description == '{"first":["a","b"],"second":["a","b"]}'
+
+
+def test_nesting_limit():
+    nested = []
+    for _ in range(1000):
+        nested = [nested]
+    with pytest.raises(ValueError, match="Nesting deeper than 512 levels"):
+        Response.json(nested)
@@ -0,0 +1,51 @@
+import time
+
+import pytest
+
+from hypern.security import ExpiredSignatureError, InvalidSignatureError, InvalidTokenError, create_token, verify_token
+
+
+def test_roundtrip():
+    token = create_token({"sub": "42", "roles": ["admin"], "n": 1}, "secret", "HS512")
+    assert verify_token(token, "secret", "HS512") == {"sub": "42", "roles": ["admin"], "n": 1}
+
+
+def test_standard_claims():
+    claims = verify_token(create_token({"sub": "42"}, "secret", expires_in_secs=60), "secret")
+    assert claims["exp"] - claims["iat"] == 60
+
+
+def test_expired_token():
+    token = create_token({"sub": "42"}, "secret", expires_in_secs=0)
+    time.sleep(1.1)
+    with pytest.raises(ValueError, match="expired"):
+        verify_token(token, "secret")
+
+
+def test_invalid_signature():
+    token = create_token({"sub": "42"}, "secret")
+    with pytest.raises(RuntimeError, match="signature"):
+        verify_token(token, "other-secret")
+
+
+def test_unsupported_algorithm():
+    with pytest.raises(ValueError):
+        create_token({"sub": "42"}, "secret", "none")
+
+
+def test_typed_errors():
+    token = create_token({"sub": "42"}, "secret", expires_in_secs=0)
+    time.sleep(1.1)
+    with pytest.raises(ExpiredSignatureError):
+        verify_token(token, "secret")
+    with pytest.raises(InvalidSignatureError):
+        verify_token(create_token({"sub": "42"}, "secret"), "other-secret")
+    with pytest.raises(InvalidTokenError):
+        verify_token("not-a-token", "secret")
+
+
+def test_leeway_and_verify_exp():
+    token = create_token({"sub": "42"}, "secret", expires_in_secs=0)
+    time.sleep(1.1)
+    assert verify_token(token, "secret", leeway=30)["sub"] == "42"
+    assert verify_token(token, "secret", verify_exp=False)["sub"] == "42"
@@ -0,0 +1,24 @@
+import pytest
+from hypern.security import create_token
+from tests.utils import get
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_valid_bearer_token(function_type: str, session):
+    token = create_token({"sub": "42"}, "jwt-secret", expires_in_secs=60)
+    res = get(f"/{function_type}/jwt/me", headers={"Authorization": f"Bearer {token}"})
+    assert res.json() == {"sub": "42"}
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_missing_or_invalid_token(function_type: str, session):
+    res = get(f"/{function_type}/jwt/me", expected_status_code=401)
+    assert res.status_code == 401
+    assert res.headers["www-authenticate"] == 'Bearer error="invalid_request"'
+
+    token = create_token({"sub": "42"}, "other-secret")
+    res = get(f"/{function_type}/jwt/me", headers={"Authorization": f"Bearer {token}"}, expected_status_code=401)
+    assert res.status_code == 401
+    assert res.headers["www-authenticate"] == 'Bearer error="invalid_token"'
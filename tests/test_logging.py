@@ -0,0 +1,11 @@
+import pytest
+from hypern.hypern import Server
+
+
+def test_set_logging():
+    server = Server()
+    server.set_logging()
+    server.set_logging(level="warn,hypern=debug", json=True, access_log=False)
+
+    with pytest.raises(ValueError):
+        server.set_logging(level="hypern=verbose")
@@ -0,0 +1,108 @@
+import asyncio
+from concurrent.futures import ThreadPoolExecutor
+
+import pytest
+
+from hypern.caching import MemoryBackend
+from hypern.hypern import MemoryStore
+
+
+def test_get_set_delete():
+    backend = MemoryBackend()
+    assert asyncio.run(backend.get("missing")) is None
+    asyncio.run(backend.set("user:1", {"name": "alice"}))
+    assert asyncio.run(backend.get("user:1")) == {"name": "alice"}
+    assert asyncio.run(backend.exists("user:1"))
+    assert asyncio.run(backend.delete("user:1"))
+    assert not asyncio.run(backend.exists("user:1"))
+
+
+def test_ttl():
+    store = MemoryStore(default_ttl=60)
+    store.set("default", 1)
+    store.set("short", 1, 10)
+    assert store.get_ttl("default") == 60
+    assert store.get_ttl("short") == 10
+    assert store.get_ttl("missing") == -2
+    assert MemoryStore().get_ttl("missing") == -2
+    forever = MemoryStore()
+    forever.set("key", 1)
+    assert forever.get_ttl("key") == -1
+
+
+def test_set_nx():
+    store = MemoryStore()
+    assert store.set_nx("lock", "a")
+    assert not store.set_nx("lock", "b")
+    assert store.get("lock") == "a"
+
+
+def test_delete_prefix_and_pattern():
+    backend = MemoryBackend()
+    for key in ["users:1", "users:2", "orders:1"]:
+        asyncio.run(backend.set(key, key))
+    assert asyncio.run(backend.delete_startswith("users:")) == 2
+    asyncio.run(backend.set("users:3", "users:3"))
+    assert asyncio.run(backend.delete_pattern("*:1")) == 1
+    assert sorted(backend.store.keys()) == ["users:3"]
+
+
+def test_incr():
+    store = MemoryStore()
+    assert store.incr("hits") == 1
+    assert store.incr("hits", 5) == 6
+    store.set("name", "alice")
+    assert asyncio.run(MemoryBackend().incr("missing")) == 1
+    with pytest.raises(TypeError):
+        store.incr("name")
+
+
+def test_lru_eviction():
+    store = MemoryStore(max_entries=3)
+    for key in ["a", "b", "c"]:
+        store.set(key, key)
+    # reading a makes b the least recently used entry
+    store.get("a")
+    store.set("d", "d")
+    assert len(store) == 3
+    assert store.get("b") is None
+    assert sorted(store.keys()) == ["a", "c", "d"]
+
+
+def test_invalid_config():
+    with pytest.raises(ValueError):
+        MemoryStore(max_entries=0)
+    with pytest.raises(ValueError):
+        MemoryStore(default_ttl=0)
+
+
+def test_concurrent_set_get():
+    store = MemoryStore(max_entries=500)
+
+    def worker(thread: int):
+        for i in range(2000):
+            key = f"{thread}:{i % 700}"
+            store.set(key, i)
+            value = store.get(key)
+            assert value is None or isinstance(value, int)
+            store.set_nx(f"shared:{i % 50}", thread)
+            store.incr("counter")
+
+    with ThreadPoolExecutor(max_workers=8) as executor:
+        list(executor.map(worker, range(8)))
+
+    assert len(store) <= 500
+    assert store.get("counter") is None or store.get("counter") <= 8 * 2000
+
+
+def test_concurrent_incr_is_atomic():
+    store = MemoryStore()
+
+    def worker(_):
+        for _ in range(1000):
+            store.incr("counter")
+
+    with ThreadPoolExecutor(max_workers=8) as executor:
+        list(executor.map(worker, range(8)))
+
+    assert store.get("counter") == 8000
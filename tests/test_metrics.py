@@ -0,0 +1,49 @@
+import pytest
+from hypern import Hypern
+from hypern.hypern import MetricsCounter, Server
+from tests.utils import get
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_metrics_endpoint(function_type: str, session):
+    get(f"/{function_type}/direct/dict")
+    get(f"/{function_type}/no-such-route")
+
+    res = get("/metrics")
+    assert res.status_code == 200
+    assert res.headers["content-type"].startswith("text/plain")
+    assert 'hypern_requests_total{method="GET",path="/benchmark/direct/dict",status="200"}' in res.text
+    assert 'hypern_requests_total{method="GET",path="",status="404"}' in res.text
+    assert "hypern_request_duration_milliseconds_bucket" in res.text
+    assert "hypern_active_connections" in res.text
+    assert 'path="/metrics"' not in res.text
+    assert 'hypern_responses_total{class="2xx",method="GET",path="/benchmark/direct/dict"}' in res.text
+    # requests that never reach a handler are counted too
+    assert 'hypern_responses_total{class="4xx",method="GET",path=""}' in res.text
+    assert "hypern_requests_in_flight" in res.text
+    assert "hypern_db_sessions" in res.text
+    assert "server_custom_total 1" in res.text
+
+
+def test_metrics_counter():
+    counter = MetricsCounter("test_counter_total", "A test counter", labels=["kind"])
+    counter.inc(labels={"kind": "a"})
+    counter.inc(2.5, labels={"kind": "a"})
+    assert counter.get(labels={"kind": "a"}) == 3.5
+    assert counter.get(labels={"kind": "b"}) == 0
+
+    with pytest.raises(ValueError):
+        counter.inc(labels={})
+    with pytest.raises(ValueError):
+        counter.inc(-1, labels={"kind": "a"})
+    with pytest.raises(ValueError):
+        MetricsCounter("test_counter_total", "Registered twice")
+
+
+@pytest.mark.parametrize("path", ["metrics", ""])
+def test_metrics_path_needs_leading_slash(path: str):
+    with pytest.raises(ValueError):
+        Hypern().enable_metrics(path)
+    with pytest.raises(ValueError):
+        Server().set_metrics_path(path)
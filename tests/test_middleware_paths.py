@@ -0,0 +1,23 @@
+import pytest
+from tests.utils import get
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_middleware_runs_on_included_path(function_type: str, session):
+    res = get(f"/{function_type}/scoped/private")
+    assert res.headers["x-scoped"] == "applied"
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_middleware_skips_excluded_path(function_type: str, session):
+    res = get(f"/{function_type}/scoped/public/info")
+    assert "x-scoped" not in res.headers
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_middleware_skips_other_paths(function_type: str, session):
+    res = get(f"/{function_type}/state")
+    assert "x-scoped" not in res.headers
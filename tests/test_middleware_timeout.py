@@ -0,0 +1,16 @@
+import pytest
+from tests.utils import get
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_timed_out_middleware_is_skipped(function_type: str, session):
+    res = get(f"/{function_type}/slow/skipped")
+    assert res.json() == {"finished": False}
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_timed_out_conditional_middleware_returns_503(function_type: str, session):
+    res = get(f"/{function_type}/slow/required", expected_status_code=503)
+    assert res.status_code == 503
@@ -0,0 +1,10 @@
+import pytest
+from tests.utils import get
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_repeated_set_cookie(function_type: str, session):
+    res = get(f"/{function_type}/headers/multi")
+    assert res.status_code == 200
+    assert res.raw.headers.getlist("Set-Cookie") == ["a=1", "b=2"]
@@ -0,0 +1,10 @@
+import pytest
+from tests.utils import get
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_named_dependency(function_type: str, session):
+    res = get(f"/{function_type}/inject/named")
+    assert res.status_code == 200
+    assert res.text == "global_dependencies"
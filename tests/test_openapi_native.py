@@ -0,0 +1,80 @@
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+def native_spec() -> dict:
+    res = requests.get(f"{BASE_URL}/openapi-native.json")
+    assert res.status_code == 200
+    assert res.headers["content-type"] == "application/json"
+    return res.json()
+
+
+@pytest.mark.benchmark
+def test_openapi_document(session):
+    spec = native_spec()
+    assert spec["openapi"] == "3.0.0"
+    assert spec["info"] == {"title": "Test API", "version": "1.0.0"}
+
+    item = spec["paths"]["/benchmark/openapi/items/{id}"]
+    assert item["get"]["summary"] == "Fetch one item"
+    assert item["get"]["parameters"] == [{"name": "id", "in": "path", "required": True, "schema": {"type": "string"}}]
+    assert item["delete"]["deprecated"] is True
+    assert "responses" in item["delete"]
+
+
+@pytest.mark.benchmark
+def test_openapi_excluded_routes(session):
+    paths = native_spec()["paths"]
+    assert "/benchmark/openapi/hidden" not in paths
+    assert "/openapi.json" not in paths
+    assert "/docs" not in paths
+
+
+@pytest.mark.benchmark
+def test_openapi_route_metadata(session):
+    operation = native_spec()["paths"]["/benchmark/openapi/items"]["post"]
+    assert operation["tags"] == ["admin"]
+    assert operation["summary"] == "Create an item"
+    assert operation["operationId"] == "createItem"
+
+
+def test_route_metadata():
+    from hypern.hypern import FunctionInfo, Route, Router
+
+    def handler(request):
+        pass
+
+    function = FunctionInfo(handler=handler, is_async=False)
+    route = Route("/admin/users", function, "GET", tags=["admin"], summary="List users")
+    route.operation_id = "listUsers"
+    assert route.tags == ["admin"]
+    assert "operation_id='listUsers'" in repr(route)
+
+    router = Router("/")
+    router.add_route(route)
+    router.add_route(Route("/public", function, "GET", tags=["public"]))
+    assert [r.path for r in router.get_routes_by_tag("admin")] == ["/admin/users"]
+    assert router.get_routes_by_tag("missing") == []
+
+
+@pytest.mark.benchmark
+def test_openapi_components(session):
+    spec = native_spec()
+    assert spec["servers"] == [{"url": "http://localhost:5005"}]
+    assert spec["components"]["schemas"]["Item"]["type"] == "object"
+    assert spec["components"]["securitySchemes"]["bearer"] == {"type": "http", "scheme": "bearer"}
+
+    response = spec["paths"]["/benchmark/openapi/items/{id}"]["get"]["responses"]["200"]
+    assert response["content"]["application/json"]["schema"] == {"$ref": "#/components/schemas/Item"}
+
+
+@pytest.mark.parametrize("path", ["openapi.json", "", "docs/openapi.json"])
+def test_openapi_path_needs_leading_slash(path: str):
+    from hypern import Hypern
+    from hypern.hypern import Server
+
+    with pytest.raises(ValueError):
+        Hypern().enable_openapi(path=path)
+    with pytest.raises(ValueError):
+        Server().enable_openapi(path, "API", "1.0", None)
@@ -0,0 +1,32 @@
+import pytest
+
+from hypern.security import hash_password, verify_password
+
+
+@pytest.mark.parametrize("algorithm", ["argon2", "bcrypt"])
+def test_hash_and_verify(algorithm: str):
+    hashed = hash_password("correct horse", algorithm)
+    assert hashed != "correct horse"
+    assert verify_password("correct horse", hashed)
+    assert not verify_password("wrong horse", hashed)
+
+
+def test_bcrypt_cost():
+    assert hash_password("pw", "bcrypt", cost=4).startswith("$2b$04$")
+
+
+def test_unknown_algorithm():
+    with pytest.raises(ValueError):
+        hash_password("pw", "md5")
+
+
+def test_unknown_hash_format():
+    with pytest.raises(ValueError):
+        verify_password("pw", "plain-text")
+
+
+def test_argon2_salts_differ():
+    first, second = hash_password("pw"), hash_password("pw")
+    # $argon2id$v=19$m=...,t=...,p=...$<salt>$<hash>
+    assert first.split("$")[4] != second.split("$")[4]
+    assert verify_password("pw", first) and verify_password("pw", second)
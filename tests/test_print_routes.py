@@ -0,0 +1,33 @@
+from hypern.hypern import FunctionInfo, Route, Router
+
+
+def list_users(request):
+    pass
+
+
+def get_user(request):
+    pass
+
+
+def make_router():
+    router = Router("/api")
+    router.add_route(Route("/users", FunctionInfo(handler=list_users, is_async=False), "GET"))
+    router.add_route(Route("/users/:id", FunctionInfo(handler=get_user, is_async=False), "GET"))
+    return router
+
+
+def test_route_summary():
+    summary = sorted(make_router().route_summary())
+    assert summary == [("GET", "/api/users", False), ("GET", "/api/users/:id", True)]
+
+
+def test_print_routes(capsys):
+    router = make_router()
+    table = router.print_routes()
+    assert capsys.readouterr().out == table + "\n"
+
+    lines = table.splitlines()
+    assert lines[1].split("|")[1:-1] == [" Method ", " Path           ", " Params ", " Handler    "]
+    assert len({len(line) for line in lines}) == 1
+    assert any("get_user" in line and "yes" in line for line in lines)
+    assert str(router) == table
@@ -0,0 +1,44 @@
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+def query(function_type: str, query_string: str) -> dict:
+    res = requests.get(f"{BASE_URL}/{function_type}/query/echo?{query_string}")
+    assert res.status_code == 200
+    return res.json()
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_repeated_keys(function_type: str, session):
+    body = query(function_type, "a=1&a=2")
+    assert body["a"] == ["1", "2"]
+    assert body["first"] == "1"
+    assert body["flat"] == {"a": "1"}
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_key_without_value(function_type: str, session):
+    assert query(function_type, "flag")["flag"] == ""
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_decoding(function_type: str, session):
+    assert query(function_type, "q=hello%20world")["q"] == "hello world"
+    assert query(function_type, "q=a+b%2Bc")["q"] == "a b+c"
+    # malformed escapes are passed through
+    assert query(function_type, "q=100%zz")["q"] == "100%zz"
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_typed_accessors(function_type: str, session):
+    body = query(function_type, "n=42&debug=yes")
+    assert body["n"] == 42
+    assert body["debug"] is True
+    defaults = query(function_type, "")
+    assert defaults["n"] == 0
+    assert defaults["debug"] is False
@@ -0,0 +1,55 @@
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+@pytest.mark.benchmark
+def test_file_response_whole(session):
+    res = requests.get(f"{BASE_URL}/benchmark/range/file")
+    assert res.status_code == 200
+    assert res.text == "hello static\n"
+    assert res.headers["accept-ranges"] == "bytes"
+    assert res.headers["content-type"] == "text/plain"
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize(
+    "range_header, body, content_range",
+    [
+        ("bytes=0-4", "hello", "bytes 0-4/13"),
+        ("bytes=6-", "static\n", "bytes 6-12/13"),
+        ("bytes=-7", "static\n", "bytes 6-12/13"),
+        ("bytes=6-100", "static\n", "bytes 6-12/13"),
+    ],
+)
+def test_file_range(session, range_header, body, content_range):
+    res = requests.get(f"{BASE_URL}/benchmark/range/file", headers={"Range": range_header})
+    assert res.status_code == 206
+    assert res.text == body
+    assert res.headers["content-range"] == content_range
+    assert res.headers["content-length"] == str(len(body))
+
+
+@pytest.mark.benchmark
+def test_memory_range(session):
+    res = requests.get(f"{BASE_URL}/benchmark/range/memory", headers={"Range": "bytes=2-5"})
+    assert res.status_code == 206
+    assert res.text == "2345"
+    assert res.headers["content-range"] == "bytes 2-5/10"
+    assert res.headers["accept-ranges"] == "bytes"
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("path, size", [("file", 13), ("memory", 10)])
+@pytest.mark.parametrize("range_header", ["bytes=0-1,4-5", "bytes=100-"])
+def test_range_not_satisfiable(session, path, size, range_header):
+    res = requests.get(f"{BASE_URL}/benchmark/range/{path}", headers={"Range": range_header})
+    assert res.status_code == 416
+    assert res.headers["content-range"] == f"bytes */{size}"
+
+
+@pytest.mark.benchmark
+def test_non_bytes_range_ignored(session):
+    res = requests.get(f"{BASE_URL}/benchmark/range/memory", headers={"Range": "items=0-1"})
+    assert res.status_code == 200
+    assert res.text == "0123456789"
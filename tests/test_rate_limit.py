@@ -0,0 +1,30 @@
+import pytest
+from tests.utils import get
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_route_limit_overrides_global(function_type: str, session):
+    for remaining in [2, 1, 0]:
+        res = get(f"/{function_type}/ratelimit/strict")
+        assert res.headers["x-ratelimit-limit"] == "3"
+        assert res.headers["x-ratelimit-remaining"] == str(remaining)
+
+    res = get(f"/{function_type}/ratelimit/strict", expected_status_code=429)
+    assert res.headers["x-ratelimit-remaining"] == "0"
+    assert 1 <= int(res.headers["retry-after"]) <= 60
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_global_limit_headers(function_type: str, session):
+    res = get(f"/{function_type}/state")
+    assert res.headers["x-ratelimit-limit"] == "100000"
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_exempt_path(function_type: str, session):
+    for _ in range(3):
+        res = get(f"/{function_type}/ratelimit/exempt")
+        assert "x-ratelimit-limit" not in res.headers
@@ -0,0 +1,19 @@
+import hashlib
+
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_raw_json_body_is_unchanged(function_type: str, session):
+    # key order and whitespace a re-serialization would lose
+    body = b'{ "z": 1,\n  "a": [1, 2.50],  "m": "caf\\u00e9" }' + b" " * (1024 * 1024)
+    res = requests.post(
+        f"{BASE_URL}/{function_type}/raw-body",
+        data=body,
+        headers={"Content-Type": "application/json"},
+    )
+    assert res.status_code == 200
+    assert res.text == hashlib.sha256(body).hexdigest()
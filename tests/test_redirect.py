@@ -0,0 +1,24 @@
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+@pytest.mark.benchmark
+def test_redirect_encodes_location(session):
+    res = requests.get(f"{BASE_URL}/benchmark/redirect-to", allow_redirects=False)
+    assert res.status_code == 302
+    assert res.headers["location"] == "/target%20path?q=caf%C3%A9"
+    assert res.content == b""
+
+
+@pytest.mark.benchmark
+def test_permanent_redirect(session):
+    res = requests.get(f"{BASE_URL}/benchmark/redirect-to", params={"permanent": "1"}, allow_redirects=False)
+    assert res.status_code == 308
+    assert res.headers["location"] == "/v2/items"
+
+
+@pytest.mark.benchmark
+def test_redirect_rejects_non_3xx(session):
+    res = requests.get(f"{BASE_URL}/benchmark/redirect-to", params={"invalid": "1"}, allow_redirects=False)
+    assert res.status_code == 500
@@ -0,0 +1,12 @@
+import pytest
+from tests.utils import get
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_headers_to_dict(function_type: str, session):
+    res = get(f"/{function_type}/headers", headers={"X-Custom": "custom-value"})
+    assert res.status_code == 200
+    headers = res.json()
+    assert headers["x-custom"] == "custom-value"
+    assert headers["global_before"] == "global_before_request"
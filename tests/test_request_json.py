@@ -0,0 +1,34 @@
+import json
+
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+def post_json(function_type: str, body: str) -> requests.Response:
+    return requests.post(
+        f"{BASE_URL}/{function_type}/json/repr",
+        data=body.encode(),
+        headers={"Content-Type": "application/json"},
+    )
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+@pytest.mark.parametrize(
+    "body",
+    [
+        '{"count": 3, "ratio": 0.5, "ok": true, "none": null, "tags": ["a"], "nested": {"x": 1}}',
+        '{"a": {"b": {"c": {"d": {"e": [1, [2, [3, {"f": false}]]]}}}}}',
+        '{"clé": "valeur", "名前": "値"}',
+        '{"big": 123456789012345678901234567890, "neg": -98765432109876543210, "u64": 18446744073709551615}',
+        '{"quoted": "12345678901234567890123 \\" 1", "big": [-12345678901234567890123], "float": 1.5e3}',
+        "[1, 2, 3]",
+        '"text"',
+        "42",
+    ],
+)
+def test_json_types(function_type: str, body: str, session):
+    res = post_json(function_type, body)
+    assert res.status_code == 200
+    assert res.text == repr(json.loads(body))
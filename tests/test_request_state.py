@@ -0,0 +1,12 @@
+import pytest
+from tests.utils import get
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_state_flows_through_middlewares(function_type: str, session):
+    res = get(f"/{function_type}/state")
+    # set by the global before hook
+    assert res.json() == {"user": "alice"}
+    # set by the handler, read by the global after hook
+    assert res.headers["x-state-seen-by"] == "handler"
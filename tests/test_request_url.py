@@ -0,0 +1,58 @@
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+def url_info(function_type: str, headers: dict = {}) -> dict:
+    res = requests.get(f"{BASE_URL}/{function_type}/url?page=2&q=a%20b", headers=headers)
+    assert res.status_code == 200
+    return res.json()
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_url_from_host_header(function_type: str, session):
+    url = url_info(function_type, {"Host": "example.com:8443"})
+    assert url["scheme"] == "http"
+    assert url["host"] == "example.com"
+    assert url["port"] == 8443
+    assert url["path"] == f"/{function_type}/url"
+    assert url["query"] == "page=2&q=a%20b"
+    assert url["base_url"] == "http://example.com:8443/"
+    assert url["full_url"] == f"http://example.com:8443/{function_type}/url?page=2&q=a%20b"
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_url_ipv6_host(function_type: str, session):
+    url = url_info(function_type, {"Host": "[::1]:8080"})
+    assert url["host"] == "::1"
+    assert url["port"] == 8080
+    assert url["base_url"] == "http://[::1]:8080/"
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_url_scheme_from_trusted_proxy(function_type: str, session):
+    url = url_info(function_type, {"Host": "example.com", "X-Forwarded-Proto": "https"})
+    assert url["port"] is None
+    assert url["base_url"] == "https://example.com/"
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_url_to_string_drops_default_port(function_type: str, session):
+    url = url_info(function_type, {"Host": "example.com:80"})
+    assert url["full_url"] == f"http://example.com:80/{function_type}/url?page=2&q=a%20b"
+    assert url["canonical_url"] == f"http://example.com/{function_type}/url?page=2&q=a%20b"
+
+    url = url_info(function_type, {"Host": "example.com:8443"})
+    assert url["canonical_url"] == f"http://example.com:8443/{function_type}/url?page=2&q=a%20b"
+
+
+def test_url_fragment():
+    from hypern.hypern import Url
+
+    url = Url("https", "example.com", "/docs", port=443, fragment="intro")
+    assert str(url) == "https://example.com:443/docs#intro"
+    assert url.to_string() == "https://example.com/docs#intro"
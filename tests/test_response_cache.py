@@ -0,0 +1,61 @@
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+URL = f"{BASE_URL}/benchmark/cached-products"
+
+
+def calls(res):
+    return int(res.text.split()[1])
+
+
+@pytest.mark.benchmark
+def test_cache_hit_skips_handler(session):
+    requests.post(f"{URL}/invalidate")
+    first = requests.get(URL, params={"page": "1"})
+    assert first.status_code == 200
+    second = requests.get(URL, params={"page": "1"})
+    assert second.text == first.text
+    assert second.headers["x-language"] == first.headers["x-language"]
+
+    other_query = requests.get(URL, params={"page": "2"})
+    assert calls(other_query) > calls(first)
+
+
+@pytest.mark.benchmark
+def test_cache_varies_on_headers(session):
+    requests.post(f"{URL}/invalidate")
+    english = requests.get(URL, headers={"Accept-Language": "en"})
+    french = requests.get(URL, headers={"Accept-Language": "fr"})
+    assert french.headers["x-language"] == "fr"
+    assert calls(french) > calls(english)
+    assert requests.get(URL, headers={"Accept-Language": "en"}).text == english.text
+
+
+@pytest.mark.benchmark
+def test_no_cache_bypasses(session):
+    requests.post(f"{URL}/invalidate")
+    first = requests.get(URL)
+    fresh = requests.get(URL, headers={"Cache-Control": "no-cache"})
+    assert calls(fresh) > calls(first)
+    # the refreshed response replaces the stored one
+    assert requests.get(URL).text == fresh.text
+
+
+@pytest.mark.benchmark
+def test_invalidate_prefix(session):
+    requests.post(f"{URL}/invalidate")
+    first = requests.get(URL)
+    assert requests.post(f"{URL}/invalidate").text == "1"
+    assert calls(requests.get(URL)) > calls(first)
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("credentials", [{"Cookie": "session=abc"}, {"Authorization": "Bearer abc"}])
+def test_credentials_bypass(credentials, session):
+    requests.post(f"{URL}/invalidate")
+    anonymous = requests.get(URL)
+    personal = requests.get(URL, headers=credentials)
+    assert calls(personal) > calls(anonymous)
+    # nor is the personal response stored for anonymous clients
+    assert requests.get(URL).text == anonymous.text
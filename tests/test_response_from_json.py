@@ -0,0 +1,15 @@
+import pytest
+
+from hypern import Response
+
+
+def test_from_json():
+    response = Response.from_json({"id": 1, "tags": ["a"]}, 201)
+    assert response.status_code == 201
+    assert response.headers.get("content-type") == "application/json"
+    assert response.description == '{"id":1,"tags":["a"]}'
+
+
+def test_from_json_unserializable():
+    with pytest.raises(TypeError):
+        Response.from_json({"value": object()})
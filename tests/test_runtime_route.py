@@ -0,0 +1,13 @@
+import pytest
+from tests.utils import get
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_register_route_at_runtime(function_type: str, session):
+    res = get(f"/{function_type}/register")
+    assert res.status_code == 200
+
+    res = get(f"/{function_type}/dynamic")
+    assert res.status_code == 200
+    assert res.text == "dynamic"
@@ -0,0 +1,82 @@
+import threading
+import time
+
+from hypern.scheduler import Scheduler
+
+
+def wait_for(condition, timeout: float = 10.0):
+    deadline = time.monotonic() + timeout
+    while time.monotonic() < deadline:
+        if condition():
+            return True
+        time.sleep(0.05)
+    return False
+
+
+def test_skip_if_running_overrides_concurrency():
+    lock = threading.Lock()
+    state = {"running": 0, "max_running": 0, "runs": 0}
+
+    def slow_task():
+        with lock:
+            state["running"] += 1
+            state["runs"] += 1
+            state["max_running"] = max(state["max_running"], state["running"])
+        time.sleep(2.5)
+        with lock:
+            state["running"] -= 1
+
+    scheduler = Scheduler()
+    job_id = scheduler.add_job(
+        "interval", "1", slow_task, "UTC", [], max_concurrency=3, misfire_policy="queue", skip_if_running=True
+    )
+    scheduler.start()
+    try:
+        assert wait_for(lambda: state["runs"] >= 2)
+        # runs due while the first one sleeps are skipped, not started next to it or queued
+        assert state["max_running"] == 1
+        assert scheduler.get_job_status(job_id)[5] > 0
+    finally:
+        scheduler.stop()
+
+
+def run_blocked_job(misfire_policy: str):
+    # the first run holds the only concurrency slot until released, every run due meanwhile misfires
+    release = threading.Event()
+    state = {"runs": 0}
+
+    def slow_task():
+        state["runs"] += 1
+        release.wait(10)
+
+    scheduler = Scheduler()
+    job_id = scheduler.add_job("interval", "1", slow_task, "UTC", [], misfire_policy=misfire_policy)
+    scheduler.start()
+    try:
+        assert wait_for(lambda: state["runs"] == 1)
+        time.sleep(3.5)
+    finally:
+        scheduler.stop()
+    # let the loop see the stop, so no run after this point is a newly due one
+    time.sleep(1.5)
+    assert state["runs"] == 1
+    skipped = scheduler.get_job_status(job_id)[5]
+    release.set()
+    return state, skipped
+
+
+def test_skip_policy_drops_misfired_runs():
+    state, skipped = run_blocked_job("skip")
+    assert skipped >= 2
+    time.sleep(1.0)
+    assert state["runs"] == 1
+
+
+def test_queue_policy_runs_misfired_runs_later():
+    state, skipped = run_blocked_job("queue")
+    assert skipped == 0
+    # the queued runs drain one after another once the slot frees up
+    assert wait_for(lambda: state["runs"] >= 3)
+    time.sleep(1.0)
+    queued = state["runs"] - 1
+    assert 2 <= queued <= 5
@@ -0,0 +1,61 @@
+import time
+from datetime import datetime, timedelta, timezone
+
+from hypern.scheduler import Scheduler
+
+
+def wait_for(condition, timeout: float = 10.0):
+    deadline = time.monotonic() + timeout
+    while time.monotonic() < deadline:
+        if condition():
+            return True
+        time.sleep(0.05)
+    return False
+
+
+def due_now() -> tuple[str, float]:
+    run_at = datetime.now(timezone.utc).replace(microsecond=0) - timedelta(seconds=1)
+    return run_at.strftime("%Y-%m-%dT%H:%M:%S"), run_at.timestamp()
+
+
+def test_date_job_runs_once():
+    runs = []
+    failures = []
+
+    def succeed():
+        runs.append("ok")
+
+    def fail():
+        runs.append("fail")
+        raise RuntimeError("boom")
+
+    scheduler = Scheduler()
+    run_at, timestamp = due_now()
+    succeeding = scheduler.add_job("date", run_at, succeed, "UTC", [])
+    failing = scheduler.add_job(
+        "date",
+        run_at,
+        fail,
+        "UTC",
+        [],
+        retry_policy=(1, 1, False),
+        on_failure=lambda job_id, error: failures.append(error),
+    )
+    assert scheduler.get_next_run(succeeding) == timestamp
+    assert scheduler.get_next_run(failing) == timestamp
+
+    scheduler.start()
+    try:
+        assert wait_for(lambda: runs.count("ok") == 1)
+        assert wait_for(lambda: scheduler.get_next_run(succeeding) is None)
+
+        # the first failure schedules the one retry, the second exhausts it
+        assert wait_for(lambda: len(failures) == 2)
+        assert wait_for(lambda: scheduler.get_next_run(failing) is None)
+
+        time.sleep(3)
+        assert runs.count("ok") == 1
+        assert runs.count("fail") == 2
+        assert scheduler.get_next_run(failing) is None
+    finally:
+        scheduler.stop()
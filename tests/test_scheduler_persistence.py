@@ -0,0 +1,93 @@
+import time
+
+from hypern.scheduler import Scheduler
+
+
+def wait_for(condition, timeout: float = 10.0):
+    deadline = time.monotonic() + timeout
+    while time.monotonic() < deadline:
+        if condition():
+            return True
+        time.sleep(0.05)
+    return False
+
+
+def test_restore_schedule_and_retry_state(tmp_path):
+    persist_path = str(tmp_path / "jobs.db")
+    failures = []
+
+    def tick():
+        pass
+
+    def flaky():
+        raise RuntimeError("upstream down")
+
+    def report_failure(job_id, error):
+        failures.append((job_id, error))
+
+    def make_scheduler():
+        scheduler = Scheduler(persist_path=persist_path)
+        scheduler.register_task("tick", tick)
+        scheduler.register_task("flaky", flaky)
+        scheduler.register_task("report_failure", report_failure)
+        return scheduler
+
+    first = make_scheduler()
+    tick_id = first.add_job("interval", "3600", tick, "UTC", [])
+    flaky_id = first.add_job(
+        "interval", "3600", flaky, "UTC", [], retry_policy=(5, 3, False), on_failure=report_failure
+    )
+    first.start()
+    assert wait_for(lambda: len(failures) == 1 and first.get_job_status(tick_id)[1] > 0)
+    first.stop()
+    tick_status = first.get_job_status(tick_id)
+    flaky_status = first.get_job_status(flaky_id)
+    tick_next_run = first.get_next_run(tick_id)
+    retry_at = first.get_next_run(flaky_id)
+    assert flaky_status[3] == 1
+    del first
+
+    second = make_scheduler()
+    assert second.get_job_status(tick_id) is None
+    second.start()
+    try:
+        # the schedule and last runs come back as they were saved
+        assert second.get_job_status(tick_id)[:2] == tick_status[:2]
+        assert second.get_next_run(tick_id) == tick_next_run
+        assert second.get_job_status(flaky_id)[0] == flaky_status[0]
+        assert second.get_job_status(flaky_id)[3] == 1
+        assert second.get_next_run(flaky_id) == retry_at
+        assert second.get_unrestored_jobs() == []
+
+        # the retry resumes at the second attempt and reports through the re-attached callback
+        assert wait_for(lambda: len(failures) == 2)
+        assert failures[1][0] == flaky_id
+        assert "upstream down" in failures[1][1]
+        assert wait_for(lambda: second.get_job_status(flaky_id)[3] == 2)
+    finally:
+        second.stop()
+
+
+def test_unregistered_callback_is_not_restored(tmp_path):
+    persist_path = str(tmp_path / "jobs.db")
+
+    def tick():
+        pass
+
+    def notify(job_id, output):
+        pass
+
+    first = Scheduler(persist_path=persist_path)
+    first.register_task("tick", tick)
+    first.register_task("notify", notify)
+    job_id = first.add_job("interval", "3600", tick, "UTC", [], on_success=notify)
+    del first
+
+    second = Scheduler(persist_path=persist_path)
+    second.register_task("tick", tick)
+    second.start()
+    try:
+        assert second.get_job_status(job_id) is None
+        assert second.get_unrestored_jobs() == [(job_id, "notify")]
+    finally:
+        second.stop()
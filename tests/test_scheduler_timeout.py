@@ -0,0 +1,93 @@
+import threading
+import time
+
+from hypern.scheduler import Scheduler
+
+
+def wait_for(condition, timeout: float = 10.0):
+    deadline = time.monotonic() + timeout
+    while time.monotonic() < deadline:
+        if condition():
+            return True
+        time.sleep(0.05)
+    return False
+
+
+def test_timed_out_run_keeps_its_slot():
+    lock = threading.Lock()
+    state = {"running": 0, "max_running": 0, "runs": 0}
+    failures = []
+
+    def slow_task():
+        with lock:
+            state["running"] += 1
+            state["runs"] += 1
+            state["max_running"] = max(state["max_running"], state["running"])
+        time.sleep(3)
+        with lock:
+            state["running"] -= 1
+
+    scheduler = Scheduler()
+    job_id = scheduler.add_job(
+        "interval",
+        "1",
+        slow_task,
+        "UTC",
+        [],
+        timeout_secs=1,
+        on_failure=lambda job_id, error: failures.append(error),
+    )
+    scheduler.start()
+    try:
+        assert wait_for(lambda: failures)
+        assert failures[0] == "Job timed out after 1 seconds"
+        # the thread still runs the task, so the job is still running and can't start again
+        assert scheduler.get_job_status(job_id)[4] is True
+        assert wait_for(lambda: state["runs"] >= 2)
+        assert state["max_running"] == 1
+        assert scheduler.get_job_status(job_id)[5] > 0
+    finally:
+        scheduler.stop()
+
+
+def test_timed_out_sync_task_is_interrupted():
+    interrupted = threading.Event()
+    finished = threading.Event()
+
+    def busy_task():
+        try:
+            deadline = time.monotonic() + 30
+            while time.monotonic() < deadline:
+                time.sleep(0.05)
+            finished.set()
+        except TimeoutError:
+            interrupted.set()
+
+    scheduler = Scheduler()
+    job_id = scheduler.add_job("interval", "3600", busy_task, "UTC", [], timeout_secs=1)
+    scheduler.start()
+    try:
+        assert interrupted.wait(10)
+        assert not finished.is_set()
+        assert scheduler.get_last_error(job_id) == "Job timed out after 1 seconds"
+        # the slot is given back once the interrupted thread returns
+        assert wait_for(lambda: scheduler.get_job_status(job_id)[4] is False)
+    finally:
+        scheduler.stop()
+
+
+def test_retry_delay_past_the_latest_date():
+    def failing_task():
+        raise ValueError("boom")
+
+    scheduler = Scheduler()
+    # 10**15 seconds is tens of millions of years, past the latest date chrono can represent
+    job_id = scheduler.add_job("interval", str(10**15), failing_task, "UTC", [], retry_policy=(3, 10**15, False))
+    scheduler.start()
+    try:
+        assert wait_for(lambda: scheduler.get_last_error(job_id) is not None)
+        assert wait_for(lambda: scheduler.get_job_status(job_id)[4] is False)
+        assert scheduler.get_job_status(job_id)[3] == 1
+        assert scheduler.get_next_run(job_id) > time.time() + 10**9
+    finally:
+        scheduler.stop()
@@ -0,0 +1,43 @@
+import orjson
+
+from hypern.openapi import SchemaGenerator
+
+
+def documented(request):
+    """
+    List users
+    ---
+    responses:
+      200:
+        description: The users
+        content:
+          application/json:
+            schema:
+              $ref: User
+      404:
+        description: Unknown
+        content:
+          application/json:
+            schema:
+              $ref: '#/components/schemas/Error'
+    """
+
+
+def test_add_component():
+    generator = SchemaGenerator({"openapi": "3.0.0", "info": {"title": "API", "version": "1.0"}})
+    generator.add_component("User", {"type": "object"})
+    generator.add_security_scheme("bearer", {"type": "http", "scheme": "bearer"})
+    assert generator.base_schema["components"] == {
+        "schemas": {"User": {"type": "object"}},
+        "securitySchemes": {"bearer": {"type": "http", "scheme": "bearer"}},
+    }
+
+
+def test_shorthand_ref_and_missing_refs():
+    generator = SchemaGenerator({"openapi": "3.0.0"})
+    generator.add_component("User", {"type": "object"})
+    operation = orjson.loads(generator.parse_docstring(documented))
+    assert operation["responses"]["200"]["content"]["application/json"]["schema"] == {"$ref": "#/components/schemas/User"}
+
+    schema = dict(generator.base_schema, paths={"/users": {"get": operation}})
+    assert generator.missing_refs(schema) == ["#/components/schemas/Error"]
@@ -0,0 +1,23 @@
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("path", ["/benchmark/etag-catalog", "/static/hello.txt", "/benchmark/does-not-exist"])
+def test_security_headers_on_every_response(session, path):
+    res = requests.get(f"{BASE_URL}{path}")
+    assert res.headers["x-frame-options"] == "DENY"
+    assert res.headers["x-content-type-options"] == "nosniff"
+    assert res.headers["referrer-policy"] == "strict-origin-when-cross-origin"
+    assert res.headers["content-security-policy"] == "default-src 'self'"
+
+
+@pytest.mark.benchmark
+def test_hsts_only_on_https(session):
+    res = requests.get(f"{BASE_URL}/benchmark/etag-catalog")
+    assert "strict-transport-security" not in res.headers
+
+    # the test server trusts localhost as a proxy
+    res = requests.get(f"{BASE_URL}/benchmark/etag-catalog", headers={"X-Forwarded-Proto": "https"})
+    assert res.headers["strict-transport-security"] == "max-age=31536000"
@@ -0,0 +1,39 @@
+import pytest
+import requests
+
+from hypern import Hypern, Request
+from hypern.datastructures import HTTPMethod
+from hypern.response import PlainTextResponse
+
+app = Hypern()
+shutdowns = []
+
+
+def hello(request: Request, **kwargs):
+    return PlainTextResponse("hello")
+
+
+app.add_route(HTTPMethod.GET, "/hello", hello)
+app.on_shutdown(lambda: shutdowns.append(True))
+
+
+def test_serve_stop_and_serve_again():
+    handle = app.serve(port=5105)
+    assert handle.is_running()
+    assert requests.get("http://127.0.0.1:5105/hello", timeout=5).text == "hello"
+
+    with pytest.raises(RuntimeError):
+        app.serve(port=5106)
+
+    handle.stop()
+    assert not handle.is_running()
+    assert handle.wait(timeout=0.1)
+    assert shutdowns == [True]
+    with pytest.raises(requests.ConnectionError):
+        requests.get("http://127.0.0.1:5105/hello", timeout=5)
+
+    handle = app.serve(port=5106)
+    assert requests.get("http://127.0.0.1:5106/hello", timeout=5).text == "hello"
+    handle.stop(graceful=False)
+    assert not handle.is_running()
+    assert shutdowns == [True, True]
@@ -0,0 +1,68 @@
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_session_persists(function_type: str, session):
+    client = requests.Session()
+    assert client.get(f"{BASE_URL}/{function_type}/session").text == "1"
+    assert client.get(f"{BASE_URL}/{function_type}/session").text == "2"
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_tampered_session_is_empty(function_type: str, session):
+    client = requests.Session()
+    client.get(f"{BASE_URL}/{function_type}/session")
+    value = client.cookies["session"]
+    client.cookies.set("session", "x" + value)
+    res = client.get(f"{BASE_URL}/{function_type}/session")
+    assert res.status_code == 200
+    assert res.text == "1"
+
+
+def test_session_backend_validation():
+    from hypern.caching import MemoryBackend
+    from hypern.hypern import Server
+
+    Server().enable_sessions(secret_key="test-secret", backend=MemoryBackend())
+    with pytest.raises(ValueError):
+        Server().enable_sessions(secret_key="test-secret", backend=object())
+    with pytest.raises(ValueError):
+        Server().enable_sessions(secret_key="test-secret", backend="memcached")
+
+
+def test_session_in_cache_backend():
+    from hypern import Hypern, Request
+    from hypern.caching import MemoryBackend
+    from hypern.datastructures import HTTPMethod
+    from hypern.response import PlainTextResponse
+
+    backend = MemoryBackend()
+    app = Hypern()
+    app.enable_sessions(secret_key="test-secret", backend=backend)
+
+    def counter(request: Request, **kwargs):
+        request.session["visits"] = request.session.get("visits", 0) + 1
+        return PlainTextResponse(str(request.session["visits"]))
+
+    app.add_route(HTTPMethod.GET, "/session", counter)
+
+    handle = app.serve(port=5113)
+    try:
+        client = requests.Session()
+        assert client.get("http://127.0.0.1:5113/session", timeout=5).text == "1"
+        assert client.get("http://127.0.0.1:5113/session", timeout=5).text == "2"
+
+        # the data lives in the backend, the cookie only carries the signed id
+        keys = backend.store.keys()
+        assert len(keys) == 1
+        assert "visits" not in client.cookies["session"]
+        assert '"visits":2' in backend.store.get(keys[0]).replace(" ", "")
+
+        # another client starts over
+        assert requests.get("http://127.0.0.1:5113/session", timeout=5).text == "1"
+    finally:
+        handle.stop()
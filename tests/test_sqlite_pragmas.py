@@ -0,0 +1,26 @@
+import pytest
+from hypern.hypern import DatabaseConfig, DatabaseType
+
+
+def sqlite_config(**kwargs):
+    return DatabaseConfig(DatabaseType.Sqlite, "sqlite::memory:", 1, 1, 30, **kwargs)
+
+
+def test_known_pragmas():
+    sqlite_config(sqlite_pragmas={"journal_mode": "WAL", "cache_size": "-2000", "Foreign_Keys": "ON"})
+
+
+def test_unknown_pragma():
+    with pytest.raises(ValueError):
+        sqlite_config(sqlite_pragmas={"temp_store": "MEMORY"})
+    sqlite_config(sqlite_pragmas={"temp_store": "MEMORY"}, allow_unknown_pragmas=True)
+
+
+def test_invalid_pragma_value():
+    with pytest.raises(ValueError):
+        sqlite_config(sqlite_pragmas={"journal_mode": "WAL; DROP TABLE users"})
+
+
+def test_pragmas_require_sqlite():
+    with pytest.raises(ValueError):
+        DatabaseConfig(DatabaseType.Postgres, "postgresql://localhost/db", 1, 1, 30, sqlite_pragmas={"journal_mode": "WAL"})
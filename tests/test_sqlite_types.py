@@ -0,0 +1,61 @@
+import base64
+
+from hypern import Hypern, Request
+from hypern.datastructures import HTTPMethod
+from hypern.hypern import get_session_database
+from hypern.response import JSONResponse
+
+from tests.database import sqlite_config
+
+app = Hypern()
+
+BLOB = b"\x00\xff\x10binary\x00"
+
+
+def round_trip(request: Request, **kwargs):
+    tx = get_session_database(request.context_id)
+    tx.execute("CREATE TABLE IF NOT EXISTS typed_items (data BLOB, doc TEXT, tags TEXT, note TEXT)", [])
+    tx.execute("DELETE FROM typed_items", [])
+    tx.execute(
+        "INSERT INTO typed_items (data, doc, tags, note) VALUES (?, ?, ?, ?)",
+        [BLOB, {"name": "widget", "sizes": [1, 2], "meta": {"ok": True}}, ["a", 1, None], "[not json"],
+    )
+    row = tx.fetch_one("SELECT data, doc, tags, note FROM typed_items", [])
+    return JSONResponse(
+        {
+            "data_type": type(row["data"]).__name__,
+            "data": base64.b64encode(row["data"]).decode(),
+            "doc": row["doc"],
+            "tags": row["tags"],
+            "note": row["note"],
+        }
+    )
+
+
+def json_text(request: Request, **kwargs):
+    tx = get_session_database(request.context_id)
+    row = tx.fetch_one("""SELECT '{"big": 123456789012345678901234567890}' AS doc, json_array(1, 'two') AS items""", [])
+    return JSONResponse({"big": str(row["doc"]["big"]), "items": row["items"]})
+
+
+app.add_route(HTTPMethod.POST, "/round-trip", round_trip)
+app.add_route(HTTPMethod.GET, "/json-text", json_text)
+
+
+def test_blob_and_json_round_trip():
+    with app.test_client(database_config=sqlite_config()) as client:
+        row = client.post("/round-trip").json()
+        assert row["data_type"] == "bytes"
+        assert base64.b64decode(row["data"]) == BLOB
+        # dicts and lists are bound as JSON text and read back parsed
+        assert row["doc"] == {"name": "widget", "sizes": [1, 2], "meta": {"ok": True}}
+        assert row["tags"] == ["a", 1, None]
+        # text that only looks like JSON is returned as is
+        assert row["note"] == "[not json"
+
+
+def test_json_text_from_sql():
+    with app.test_client(database_config=sqlite_config()) as client:
+        row = client.get("/json-text").json()
+        assert row["big"] == "123456789012345678901234567890"
+        assert row["items"] == [1, "two"]
@@ -0,0 +1,35 @@
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+@pytest.mark.benchmark
+def test_static_file(session):
+    res = requests.get(f"{BASE_URL}/static/hello.txt")
+    assert res.status_code == 200
+    assert res.text == "hello static\n"
+    assert res.headers["cache-control"] == "public, max-age=3600"
+    assert "etag" in res.headers
+    # middlewares are skipped
+    assert "global_after" not in res.headers
+
+
+@pytest.mark.benchmark
+def test_static_file_not_modified(session):
+    etag = requests.get(f"{BASE_URL}/static/hello.txt").headers["etag"]
+    res = requests.get(f"{BASE_URL}/static/hello.txt", headers={"If-None-Match": etag})
+    assert res.status_code == 304
+    assert res.headers["etag"] == etag
+
+
+@pytest.mark.benchmark
+def test_static_file_range(session):
+    res = requests.get(f"{BASE_URL}/static/hello.txt", headers={"Range": "bytes=0-4"})
+    assert res.status_code == 206
+    assert res.text == "hello"
+
+
+@pytest.mark.benchmark
+def test_static_path_traversal(session):
+    res = requests.get(f"{BASE_URL}/static/%2e%2e/server.py")
+    assert res.status_code == 403
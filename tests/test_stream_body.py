@@ -0,0 +1,29 @@
+import hashlib
+
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_streaming_body(function_type: str, session):
+    body = b"0123456789abcdef" * (256 * 1024)
+
+    def chunks():
+        for i in range(0, len(body), 64 * 1024):
+            yield body[i : i + 64 * 1024]
+
+    res = requests.post(f"{BASE_URL}/{function_type}/stream-body", data=chunks())
+    assert res.status_code == 200
+    data = res.json()
+    assert data["digest"] == hashlib.sha256(body).hexdigest()
+    assert data["chunks"] > 1
+    assert data["body_error"] == "RuntimeError"
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_streaming_body_requires_stream_route(function_type: str, session):
+    res = requests.post(f"{BASE_URL}/{function_type}/stream-body/buffered", data=b"payload")
+    assert res.text == "RuntimeError"
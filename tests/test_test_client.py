@@ -0,0 +1,131 @@
+import os
+import subprocess
+import sys
+
+import pytest
+
+from hypern import Hypern, Request, Response
+from hypern.datastructures import HTTPMethod
+from hypern.response import JSONResponse, PlainTextResponse
+
+app = Hypern()
+
+
+def echo_json(request: Request, **kwargs):
+    return JSONResponse({"received": request.json(), "query": request.query_params.get("q")})
+
+
+async def async_hello(request: Request, **kwargs):
+    return PlainTextResponse("hello")
+
+
+def upload(request: Request, **kwargs):
+    return {"form": request.form(), "files": {file.name: file.read().decode() for file in request.body.files}}
+
+
+def login(request: Request, **kwargs):
+    response = PlainTextResponse("ok")
+    response.set_cookie("session", "abc")
+    return response
+
+
+def whoami(request: Request, **kwargs):
+    return PlainTextResponse(request.cookies.get("session", ""))
+
+
+def greeting(request: Request, greeting: str, **kwargs):
+    return PlainTextResponse(greeting)
+
+
+app.add_route(HTTPMethod.POST, "/echo", echo_json)
+app.add_route(HTTPMethod.GET, "/async", async_hello)
+app.add_route(HTTPMethod.POST, "/upload", upload)
+app.add_route(HTTPMethod.GET, "/login", login)
+app.add_route(HTTPMethod.GET, "/whoami", whoami)
+app.add_route(HTTPMethod.GET, "/greeting", greeting)
+app.inject("greeting", "hi there")
+
+
+@app.after_request()
+def tag_response(response: Response):
+    response.headers.set("x-tested", "yes")
+    return response
+
+
+def test_json_body():
+    with app.test_client() as client:
+        res = client.post("/echo", json={"name": "hypern"}, params={"q": "a b"})
+        res.assert_status(200)
+        assert res.json() == {"received": {"name": "hypern"}, "query": "a b"}
+        assert res.headers.get("x-tested") == "yes"
+
+
+def test_async_handler_and_injection():
+    with app.test_client() as client:
+        assert client.get("/async").text == "hello"
+        assert client.get("/greeting").text == "hi there"
+
+
+def test_multipart_body():
+    with app.test_client() as client:
+        res = client.post("/upload", data={"title": "report"}, files={"doc": ("doc.txt", b"content", "text/plain")})
+        assert res.json() == {"form": {"title": "report"}, "files": {"doc": "content"}}
+
+
+def test_cookies_persist():
+    with app.test_client() as client:
+        assert client.get("/whoami").text == ""
+        res = client.get("/login")
+        assert res.cookies == {"session": "abc"}
+        assert client.cookies == {"session": "abc"}
+        assert client.get("/whoami").text == "abc"
+
+
+def test_not_found_and_assertions():
+    with app.test_client() as client:
+        res = client.get("/missing")
+        assert res.status_code == 404
+        assert not res.ok
+        with pytest.raises(AssertionError):
+            res.assert_status(200)
+        with pytest.raises(TypeError):
+            client.get("/missing", unknown=True)
+
+
+# the database connection is process-wide, so these run in a process of their own
+DATABASE_SCRIPT = """
+import tempfile, threading
+from hypern import Hypern
+from hypern.hypern import DatabaseConfig, DatabaseType
+
+directory = tempfile.mkdtemp()
+first = DatabaseConfig(DatabaseType.Sqlite, f"sqlite://{directory}/first.db?mode=rwc", 1, 1, 30)
+other = DatabaseConfig(DatabaseType.Sqlite, f"sqlite://{directory}/other.db?mode=rwc", 1, 1, 30)
+app = Hypern()
+with app.test_client(database_config=first):
+    pass
+# the same database is reused
+app.test_client(database_config=first).close()
+try:
+    app.test_client(database_config=other)
+except RuntimeError:
+    print("refused")
+
+# a client dropped without close() stops its loop thread
+before = threading.active_count()
+client = app.test_client()
+assert threading.active_count() == before + 1
+del client
+for _ in range(50):
+    if threading.active_count() == before:
+        break
+    threading.Event().wait(0.1)
+print("threads", threading.active_count() - before)
+"""
+
+
+def test_database_config_and_loop_lifetime():
+    root = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
+    result = subprocess.run([sys.executable, "-c", DATABASE_SCRIPT], cwd=root, capture_output=True, text=True, timeout=60)
+    assert result.returncode == 0, result.stderr
+    assert result.stdout.split("\n")[:2] == ["refused", "threads 0"]
@@ -0,0 +1,30 @@
+import re
+
+import pytest
+
+from hypern.security import generate_hex_token, generate_token
+
+
+def test_generate_token():
+    token = generate_token(40)
+    assert len(token) == 40
+    assert re.fullmatch(r"[A-Za-z0-9_-]+", token)
+    assert generate_token(40) != token
+
+
+def test_generate_token_alphabet():
+    assert set(generate_token(100, alphabet="ab")) <= {"a", "b"}
+
+
+def test_generate_hex_token():
+    token = generate_hex_token(16)
+    assert re.fullmatch(r"[0-9a-f]{32}", token)
+
+
+def test_zero_length_rejected():
+    with pytest.raises(ValueError):
+        generate_token(0)
+    with pytest.raises(ValueError):
+        generate_hex_token(0)
+    with pytest.raises(ValueError):
+        generate_token(8, alphabet="")
@@ -0,0 +1,38 @@
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_trailing_slash_merge(function_type: str, session):
+    res = requests.get(f"{BASE_URL}/{function_type}/slash/merge/")
+    assert res.status_code == 200
+    assert res.text == "trailing slash"
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_trailing_slash_redirect(function_type: str, session):
+    res = requests.get(f"{BASE_URL}/{function_type}/slash/redirect/?page=2", allow_redirects=False)
+    assert res.status_code == 301
+    assert res.headers["location"] == f"/{function_type}/slash/redirect?page=2"
+
+    res = requests.post(f"{BASE_URL}/{function_type}/slash/redirect/", allow_redirects=False)
+    assert res.status_code == 308
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_trailing_slash_strict(function_type: str, session):
+    res = requests.get(f"{BASE_URL}/{function_type}/sync/")
+    assert res.status_code == 404
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_trailing_slash_redirect_stays_on_host(function_type: str, session):
+    # `//benchmark/...` as a Location would send the browser to a host named benchmark
+    res = requests.get(f"{BASE_URL}//{function_type}/slash/redirect/", allow_redirects=False)
+    assert res.status_code == 301
+    assert res.headers["location"] == f"/{function_type}/slash/redirect"
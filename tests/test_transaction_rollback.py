@@ -0,0 +1,74 @@
+from hypern import Hypern, Request
+from hypern.datastructures import HTTPMethod
+from hypern.hypern import get_session_database
+from hypern.response import JSONResponse, PlainTextResponse
+
+from tests.database import sqlite_config
+
+app = Hypern()
+
+
+def setup(request: Request, **kwargs):
+    tx = get_session_database(request.context_id)
+    tx.execute("CREATE TABLE IF NOT EXISTS rollback_items (name TEXT NOT NULL)", [])
+    tx.execute("DELETE FROM rollback_items", [])
+    return PlainTextResponse("ok")
+
+
+def insert_and_raise(request: Request, **kwargs):
+    tx = get_session_database(request.context_id)
+    try:
+        with tx:
+            tx.execute("INSERT INTO rollback_items (name) VALUES (?)", ["lost"])
+            raise ValueError("boom")
+    except ValueError:
+        pass
+    return PlainTextResponse("ok")
+
+
+def insert_and_commit(request: Request, **kwargs):
+    tx = get_session_database(request.context_id)
+    with tx:
+        tx.execute("INSERT INTO rollback_items (name) VALUES (?)", ["kept"])
+    return PlainTextResponse("ok")
+
+
+def handler_raises(request: Request, **kwargs):
+    tx = get_session_database(request.context_id)
+    tx.execute("INSERT INTO rollback_items (name) VALUES (?)", ["failed"])
+    raise ValueError("boom")
+
+
+def names(request: Request, **kwargs):
+    tx = get_session_database(request.context_id)
+    rows = tx.fetch_all("SELECT name FROM rollback_items ORDER BY name", [])
+    return JSONResponse([row["name"] for row in rows])
+
+
+app.add_route(HTTPMethod.POST, "/setup", setup)
+app.add_route(HTTPMethod.POST, "/raise", insert_and_raise)
+app.add_route(HTTPMethod.POST, "/commit", insert_and_commit)
+app.add_route(HTTPMethod.POST, "/fail", handler_raises)
+app.add_route(HTTPMethod.GET, "/names", names)
+
+
+def test_raised_block_rolls_back():
+    with app.test_client(database_config=sqlite_config()) as client:
+        client.post("/setup").assert_status(200)
+        client.post("/raise").assert_status(200)
+        assert client.get("/names").json() == []
+
+
+def test_block_commits():
+    with app.test_client(database_config=sqlite_config()) as client:
+        client.post("/setup").assert_status(200)
+        client.post("/raise").assert_status(200)
+        client.post("/commit").assert_status(200)
+        assert client.get("/names").json() == ["kept"]
+
+
+def test_raising_handler_rolls_back():
+    with app.test_client(database_config=sqlite_config()) as client:
+        client.post("/setup").assert_status(200)
+        client.post("/fail").assert_status(500)
+        assert client.get("/names").json() == []
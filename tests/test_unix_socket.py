@@ -0,0 +1,97 @@
+import os
+import socket
+import sys
+
+import pytest
+
+from hypern import Hypern, Request
+from hypern.datastructures import HTTPMethod
+from hypern.hypern import SocketHeld
+from hypern.response import PlainTextResponse
+
+pytestmark = pytest.mark.skipif(sys.platform == "win32", reason="unix sockets only")
+
+app = Hypern()
+
+
+def peer(request: Request, **kwargs):
+    return PlainTextResponse(request.remote_addr)
+
+
+def client_ip(request: Request, **kwargs):
+    return PlainTextResponse(f"{request.client_ip}|{request.url.scheme}")
+
+
+app.add_route(HTTPMethod.GET, "/peer", peer)
+app.add_route(HTTPMethod.GET, "/client-ip", client_ip)
+
+
+def get(path: str, socket_path: str, headers: str = "") -> bytes:
+    with socket.socket(socket.AF_UNIX, socket.SOCK_STREAM) as client:
+        client.connect(socket_path)
+        client.sendall(f"GET {path} HTTP/1.1\r\nHost: localhost\r\n{headers}Connection: close\r\n\r\n".encode())
+        response = b""
+        while chunk := client.recv(4096):
+            response += chunk
+    return response
+
+
+def test_serve_on_unix_socket(tmp_path):
+    socket_path = str(tmp_path / "app.sock")
+    # a stale file from a previous run is replaced
+    SocketHeld.unix(socket_path)
+    assert os.path.exists(socket_path)
+
+    handle = app.serve(unix_socket=socket_path)
+    response = get("/peer", socket_path)
+    assert response.startswith(b"HTTP/1.1 200")
+    assert f"uid={os.getuid()}".encode() in response
+
+    handle.stop()
+    assert not os.path.exists(socket_path)
+
+
+def test_refuses_to_replace_live_socket(tmp_path):
+    socket_path = str(tmp_path / "live.sock")
+    handle = app.serve(unix_socket=socket_path)
+    try:
+        with pytest.raises(OSError):
+            SocketHeld.unix(socket_path)
+        assert get("/peer", socket_path).startswith(b"HTTP/1.1 200")
+    finally:
+        handle.stop()
+
+
+def test_client_ip_from_forwarding_headers(tmp_path):
+    socket_path = str(tmp_path / "proxy.sock")
+    handle = app.serve(unix_socket=socket_path)
+    try:
+        response = get("/client-ip", socket_path)
+        assert response.endswith(b"\r\n\r\n|http")
+        response = get(
+            "/client-ip",
+            socket_path,
+            "X-Forwarded-For: 203.0.113.7\r\nX-Forwarded-Proto: https\r\n",
+        )
+        assert response.endswith(b"203.0.113.7|https")
+    finally:
+        handle.stop()
+
+
+def test_socket_permissions(tmp_path):
+    socket_path = str(tmp_path / "perm.sock")
+    SocketHeld.unix(socket_path, permissions=0o600)
+    assert os.stat(socket_path).st_mode & 0o777 == 0o600
+
+
+def test_refuses_to_replace_regular_file(tmp_path):
+    path = tmp_path / "data.txt"
+    path.write_text("keep me")
+    with pytest.raises(ValueError):
+        SocketHeld.unix(str(path))
+    assert path.read_text() == "keep me"
+
+
+def test_abstract_socket_rejects_permissions():
+    with pytest.raises(ValueError):
+        SocketHeld.unix("@hypern-test", permissions=0o600)
@@ -0,0 +1,38 @@
+import hashlib
+
+import pytest
+import requests
+from tests.utils import BASE_URL
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_upload_small_and_spooled_files(function_type: str, session):
+    small = b"hello world"
+    large = bytes(range(256)) * (12 * 1024)  # 3 MiB, over the spool size and axum's 2 MiB body limit
+    res = requests.post(
+        f"{BASE_URL}/{function_type}/upload",
+        files={"small": ("small.txt", small), "large": ("large.bin", large)},
+    )
+    assert res.status_code == 200
+    files = res.json()["files"]
+
+    for name, data, spooled in (("small", small, False), ("large", large, True)):
+        digest = hashlib.sha256(data).hexdigest()
+        assert files[name]["size"] == len(data)
+        assert files[name]["spooled"] is spooled
+        assert files[name]["digest"] == digest
+        assert files[name]["read_digest"] == digest
+        assert files[name]["saved_digest"] == digest
+
+
+@pytest.mark.benchmark
+@pytest.mark.parametrize("function_type", ["benchmark"])
+def test_upload_without_boundary(function_type: str, session):
+    res = requests.post(
+        f"{BASE_URL}/{function_type}/upload",
+        data=b"--x\r\n",
+        headers={"Content-Type": "multipart/form-data"},
+    )
+    assert res.status_code == 200
+    assert res.json() == {"files": {}}
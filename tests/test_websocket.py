@@ -0,0 +1,114 @@
+import pytest
+import websocket
+
+from hypern import Hypern
+from hypern.ws import WebsocketRoute, WebSocketSession
+
+BASE_URL = "ws://127.0.0.1:5110"
+
+ws = WebsocketRoute()
+
+
+@ws.on("/ws/push")
+def push(session: WebSocketSession, message):
+    if isinstance(message, bytes):
+        session.send(b"binary:" + message)
+    else:
+        session.send("text:" + message)
+
+
+@ws.on("/ws/kwargs")
+def push_kwargs(session: WebSocketSession, **kwargs):
+    session.send("kwargs:" + kwargs["message"])
+
+
+@ws.on("/ws/pull")
+async def pull(session: WebSocketSession):
+    while True:
+        message = await session.receive(timeout_secs=5)
+        if message is None:
+            break
+        session.send(message)
+
+
+@ws.on("/ws/limited", rate_limit=(2, 60))
+def limited(session: WebSocketSession, message: str):
+    session.send("ok:" + message)
+
+
+@ws.on("/ws/limited-close", rate_limit=(1, 60), close_on_rate_limit=True)
+def limited_close(session: WebSocketSession, message: str):
+    session.send("ok:" + message)
+
+
+app = Hypern()
+app.add_websocket(ws)
+
+
+@pytest.fixture(scope="module")
+def server():
+    handle = app.serve(port=5110)
+    yield
+    handle.stop()
+
+
+def test_push_text_and_binary(server):
+    client = websocket.create_connection(f"{BASE_URL}/ws/push", timeout=5)
+    try:
+        client.send("hello")
+        assert client.recv() == "text:hello"
+        client.send_binary(b"\x00\x01")
+        assert client.recv() == b"binary:\x00\x01"
+    finally:
+        client.close()
+
+
+def test_push_with_kwargs(server):
+    client = websocket.create_connection(f"{BASE_URL}/ws/kwargs", timeout=5)
+    try:
+        client.send("hello")
+        assert client.recv() == "kwargs:hello"
+    finally:
+        client.close()
+
+
+def test_pull_receive(server):
+    client = websocket.create_connection(f"{BASE_URL}/ws/pull", timeout=5)
+    try:
+        for message in ("first", "second"):
+            client.send(message)
+            assert client.recv() == message
+        client.send_binary(b"\xff")
+        assert client.recv() == b"\xff"
+    finally:
+        client.close()
+
+
+def test_rate_limit_drops_messages(server):
+    client = websocket.create_connection(f"{BASE_URL}/ws/limited", timeout=5)
+    try:
+        for message in ("1", "2"):
+            client.send(message)
+            assert client.recv() == "ok:" + message
+        client.send("3")
+        assert client.recv() == '{"error":"rate limit exceeded"}'
+        # one error per window, later messages are dropped silently
+        client.send("4")
+        client.settimeout(0.5)
+        with pytest.raises(websocket.WebSocketTimeoutException):
+            client.recv()
+    finally:
+        client.close()
+
+
+def test_rate_limit_closes_connection(server):
+    client = websocket.create_connection(f"{BASE_URL}/ws/limited-close", timeout=5)
+    try:
+        client.send("1")
+        assert client.recv() == "ok:1"
+        client.send("2")
+        assert client.recv() == '{"error":"rate limit exceeded"}'
+        opcode, _ = client.recv_data(control_frame=True)
+        assert opcode == websocket.ABNF.OPCODE_CLOSE
+    finally:
+        client.close()
@@ -0,0 +1,28 @@
+import pytest
+
+from hypern.hypern import WebsocketRoute
+from hypern.ws import WebsocketRoute as WebsocketRoutes
+
+
+def handler(session, message):
+    pass
+
+
+def test_rate_limit():
+    route = WebsocketRoute("/ws", handler, rate_limit=(100, 1), close_on_rate_limit=True)
+    assert route.rate_limit == (100, 1.0)
+    assert route.close_on_rate_limit is True
+    assert WebsocketRoute("/ws", handler).rate_limit is None
+
+
+@pytest.mark.parametrize("rate_limit", [(0, 1), (10, 0), (10, -1)])
+def test_invalid_rate_limit(rate_limit):
+    with pytest.raises(ValueError):
+        WebsocketRoute("/ws", handler, rate_limit=rate_limit)
+
+
+def test_decorator_rate_limit():
+    routes = WebsocketRoutes()
+    routes.on("/ws/feed", rate_limit=(5, 0.5))(handler)
+    assert routes.routes[0].rate_limit == (5, 0.5)
+    assert routes.routes[0].close_on_rate_limit is False
@@ -0,0 +1,23 @@
+import pytest
+
+from hypern.hypern import WebSocketSession
+
+
+def test_session_metadata():
+    session = WebSocketSession()
+    assert session.get_metadata("user_id") is None
+    assert session.get_all_metadata() == {}
+
+    session.set_metadata("user_id", "42")
+    session.set_metadata("role", "admin")
+    session.set_metadata("role", "viewer")
+    assert session.get_metadata("user_id") == "42"
+    assert session.get_all_metadata() == {"user_id": "42", "role": "viewer"}
+
+
+def test_send_json_unserializable():
+    session = WebSocketSession()
+    with pytest.raises(TypeError):
+        session.send_json({"handler": object()})
+    with pytest.raises(TypeError):
+        session.send_json({"value": float("nan")})